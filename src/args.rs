@@ -2,41 +2,156 @@
 //!
 //! Purpose:
 //!   Define the command-line interface (CLI) for Dirust using `clap`'s derive API.
-//!   This struct describes all the flags and positional arguments the binary accepts,
-//!   and `Args::parse()` will populate it from `std::env::args()` at runtime.
+//!   Every mode is a subcommand with its own flags (`dirust scan`, `dirust fuzz`, ...);
+//!   `Cli::try_parse_with_presets(cli_args())` populates them from `std::env::args()` at
+//!   runtime.
+//!
+//! Presets:
+//!   `scan --polite` and `scan --fast` (also `bench --fast`) stand for several flags at once.
+//!   Presets are applied after parsing (`Cli::try_parse_with_presets`) and only to the flags
+//!   the command line did not set, so `--polite -c 5` keeps every polite setting except the
+//!   concurrency.
+//!
+//! Backward compatibility:
+//!   The original syntax `dirust <url> -w list` still works: `cli_args()` rewrites it to
+//!   `dirust scan <url> -w list` before clap sees it.
 //!
 //! Notes:
 //!   - We keep the code explicit and add detailed comments for learning clarity.
 //!   - No `anyhow` is used anywhere in the project, per your preference.
+//!   - Client settings shared by all network modes live in `HttpArgs`, flattened into each
+//!     subcommand that sends HTTP requests.
 
-use clap::Parser;
-use std::time::Duration;
+use crate::severity::Severity;
+use clap::{
+    error::ErrorKind,
+    parser::ValueSource,
+    ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum,
+};
+use std::{
+    ffi::OsString,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    time::Duration,
+};
 
 /// Top-level CLI configuration for Dirust.
 ///
 /// The `#[derive(Parser)]` attribute instructs `clap` to generate the argument
-/// parsing logic for this struct. The field names and `#[arg(...)]` attributes
-/// become your command-line flags and positional arguments.
+/// parsing logic for this struct. The subcommand enum below holds the actual flags.
 ///
-/// `author`, `version`, and `about` are used by `--help` and `--version`.
+/// `author`, `version`, and `about` are used by `--help` and `--version` (and by the man page
+/// from `dirust completions man`, whose NAME line is the `about` text).
 #[derive(Parser, Debug, Clone)]
-#[command(author, version, about)]
-pub struct Args {
-    /// Base URL to scan (must start with http:// or https://).
+#[command(
+    author,
+    version,
+    about = "Fast, async web content discovery (directories, files, parameters, vhosts)",
+    long_about = None,
+    arg_required_else_help = true
+)]
+pub struct Cli {
+    /// What to do (`scan` is implied when the first argument is not a subcommand).
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// Print plain text: no status colors in result lines.
     ///
-    /// This is a *positional* argument — no flag is required. Example:
-    ///     dirust https://example.com/ -w words.txt
+    /// Long form:
+    ///     --no-color
     ///
-    /// The program will later normalize this to ensure it ends with a trailing `/`.
-    pub base: String,
+    /// Colors are also off when stdout is not a terminal or `NO_COLOR` is set.
+    #[arg(long, global = true, default_value_t = false)]
+    pub no_color: bool,
 
-    /// Path to the wordlist file (e.g., rockyou-like list of endpoints).
+    /// Print findings only: no banner, progress or summary lines.
     ///
-    /// Short form:  -w <PATH>
-    /// Long form:   --wordlist <PATH>
-    #[arg(short, long)]
-    pub wordlist: String,
+    /// Long form:
+    ///     --quiet
+    #[arg(short, long, global = true, default_value_t = false)]
+    pub quiet: bool,
+
+    /// Print nothing on stdout but the machine-readable form of each finding (scans: one
+    /// URL per line), for shell pipelines.
+    ///
+    /// Long form:
+    ///     --silent
+    ///
+    /// Example:
+    ///     dirust --silent https://example.com/ -w words.txt | httpx
+    #[arg(long, global = true, default_value_t = false, conflicts_with = "quiet")]
+    pub silent: bool,
 
+    /// Timestamp at the start of result lines: rfc3339 (UTC, milliseconds), unix (seconds)
+    /// or none.
+    ///
+    /// Long form:
+    ///     --time-format <FORMAT>
+    ///
+    /// Example:
+    ///     --time-format unix
+    #[arg(long, global = true, value_enum, default_value_t = TimeFormat::Rfc3339, value_name = "FORMAT")]
+    pub time_format: TimeFormat,
+
+    /// Write credentials as they are: `Authorization` and cookie values, URL passwords.
+    ///
+    /// Long form only (boolean flag):
+    ///     --no-redact
+    ///
+    /// By default they are replaced with `<redacted>` in transcripts, printed lines, error
+    /// messages and the `--stream-to` / `--syslog` forwarders.
+    #[arg(long, global = true, default_value_t = false)]
+    pub no_redact: bool,
+}
+
+/// Timestamp style for result lines (`--time-format`).
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// Seconds since 1970 (`[1712344878]`).
+    Unix = 0,
+    /// UTC date and time with milliseconds (`[2024-04-05T19:21:18.042Z]`).
+    Rfc3339 = 1,
+    /// No timestamp.
+    None = 2,
+}
+
+/// Dirust's modes.
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Brute-force paths under a base URL (the default: `dirust <url> -w list`).
+    Scan(Box<ScanArgs>),
+    /// Keep one URL fixed and fuzz query parameter names or request headers.
+    Fuzz(FuzzArgs),
+    /// Discover virtual hosts by fuzzing the Host header against one server.
+    Vhost(VhostArgs),
+    /// Brute-force subdomains through DNS resolution.
+    Dns(DnsArgs),
+    /// Print the findings stored in a results file.
+    Report(ReportArgs),
+    /// Review the findings of a results file: mark each interesting or false positive, with a note.
+    Triage(TriageArgs),
+    /// Compare two results files: new, gone and changed findings.
+    Diff(DiffArgs),
+    /// Continue an interrupted scan from its `--state` file.
+    Resume(ResumeArgs),
+    /// Enumerate cloud storage buckets (S3/GCS/Azure) from a wordlist of names.
+    Storage(StorageArgs),
+    /// Measure achievable requests/sec for several concurrency levels and print a table.
+    Bench(BenchArgs),
+    /// Crawl a live target and print a wordlist built from its paths and filenames.
+    Wordgen(WordgenArgs),
+    /// Enumerate IIS 8.3 short names (tilde technique) and suggest full names from a wordlist.
+    Iis(IisArgs),
+    /// Print a shell completion script or the man page to stdout.
+    Completions(CompletionsArgs),
+    /// Run an HTTP API to submit, watch, stream and cancel scan jobs.
+    Serve(ServeArgs),
+    /// Run the scans described in a YAML job file, sharing one concurrency budget.
+    Run(RunArgs),
+}
+
+/// HTTP client settings shared by every subcommand that sends requests.
+#[derive(clap::Args, Debug, Clone)]
+pub struct HttpArgs {
     /// Maximum number of in-flight requests (concurrency cap).
     ///
     /// Short form:  -c <N>
@@ -66,6 +181,218 @@ pub struct Args {
     #[arg(long, default_value_t = 10)]
     pub timeout: u64,
 
+    /// User-Agent sent with every request (default: `dirust/0.1.1`).
+    ///
+    /// Long form:
+    ///     --user-agent <UA>
+    ///
+    /// `--evade` keeps this value; only the other headers are randomized.
+    #[arg(long, value_parser = parse_header_text, value_name = "UA")]
+    pub user_agent: Option<String>,
+
+    /// Contact address sent as the `From` header with every request.
+    ///
+    /// Long form:
+    ///     --from <EMAIL>
+    ///
+    /// Lets the operators of a scanned site reach whoever runs the scan (see `--polite`).
+    #[arg(long, value_parser = parse_header_text, value_name = "EMAIL")]
+    pub from: Option<String>,
+
+    /// Ask servers for compressed responses (`Accept-Encoding: gzip, deflate`).
+    ///
+    /// Long form only (boolean flag):
+    ///     --compressed
+    ///
+    /// Bodies that are read (fuzzing, `--forms`) are decompressed; sizes are compared on the
+    /// decompressed body and the on-the-wire size is shown as `wire=`. Note that `len=` in
+    /// scan lines is the server's Content-Length, i.e. the compressed size.
+    #[arg(long, default_value_t = false, conflicts_with = "no_compression")]
+    pub compressed: bool,
+
+    /// Ask servers not to compress responses (`Accept-Encoding: identity`).
+    ///
+    /// Long form only (boolean flag):
+    ///     --no-compression
+    ///
+    /// Makes `len=` (Content-Length) and measured body sizes directly comparable.
+    #[arg(long, default_value_t = false)]
+    pub no_compression: bool,
+
+    /// Shape the TLS ClientHello like a browser's (cipher suite and key-exchange order).
+    ///
+    /// Long form:
+    ///     --tls-impersonate <chrome|firefox|random>
+    ///
+    /// For targets behind CDNs or bot filters that block clients by TLS fingerprint
+    /// (JA3/JA4). `random` picks a different order on every run. This is an approximation:
+    /// GREASE values and extension order cannot be changed, see `tls.rs`.
+    #[arg(long, value_enum, value_name = "PROFILE")]
+    pub tls_impersonate: Option<TlsProfile>,
+
+    /// Send every request through a proxy (`http://`, `https://`, `socks5://`, `socks5h://`).
+    ///
+    /// Long form:
+    ///     --proxy <URL>
+    ///
+    /// Example:
+    ///     --proxy socks5://127.0.0.1:1080
+    ///
+    /// Replaces the `HTTP_PROXY` / `HTTPS_PROXY` / `ALL_PROXY` environment variables.
+    #[arg(long, value_name = "URL")]
+    pub proxy: Option<String>,
+
+    /// Connect directly even when `HTTP_PROXY` / `HTTPS_PROXY` / `ALL_PROXY` are set.
+    ///
+    /// Long form only (boolean flag):
+    ///     --no-env-proxy
+    ///
+    /// Without it, those variables are used (hosts in `NO_PROXY` excepted), and scans name
+    /// the proxy they go through in their header.
+    #[arg(long, default_value_t = false)]
+    pub no_env_proxy: bool,
+
+    /// Where target host names are resolved when the proxy (`--proxy` or an `*_PROXY`
+    /// variable) is a SOCKS5 proxy.
+    ///
+    /// Long form:
+    ///     --proxy-dns local
+    #[arg(long, value_enum, default_value_t = ProxyDns::Remote, value_name = "WHERE")]
+    pub proxy_dns: ProxyDns,
+
+    /// Connect over IPv4 only.
+    ///
+    /// Short form:  -4
+    /// Long form:   --ipv4
+    ///
+    /// Without `-4` / `-6`, hosts with both A and AAAA records are connected to by racing
+    /// both families (Happy Eyeballs: the second one starts 300ms after the first).
+    #[arg(short = '4', long, default_value_t = false, conflicts_with = "ipv6")]
+    pub ipv4: bool,
+
+    /// Connect over IPv6 only.
+    ///
+    /// Short form:  -6
+    /// Long form:   --ipv6
+    #[arg(short = '6', long, default_value_t = false)]
+    pub ipv6: bool,
+
+    /// Tune the client for HTTP/2 multiplexing (many requests over one connection).
+    ///
+    /// Long form only (boolean flag):
+    ///     --http2
+    ///
+    /// HTTPS targets that offer HTTP/2 already get it (negotiated with ALPN); this sizes the
+    /// flow-control windows adaptively and keeps idle connections alive with pings, which is
+    /// what carries hundreds of concurrent streams. HTTP/1.1 servers are unaffected. Not
+    /// used by `--engine fast` or with `--tls-impersonate` (both speak HTTP/1.1 only).
+    #[arg(long, default_value_t = false)]
+    pub http2: bool,
+
+    /// Speak HTTP/2 without negotiating it first (prior knowledge; `h2c` for http://).
+    ///
+    /// Long form only (boolean flag):
+    ///     --http2-prior-knowledge
+    ///
+    /// For cleartext HTTP/2 servers (gRPC gateways, some internal services). Servers that
+    /// only speak HTTP/1.1 fail every request.
+    #[arg(long, default_value_t = false)]
+    pub http2_prior_knowledge: bool,
+}
+
+/// Permutation settings shared by `scan` and `dns` (`--permute`).
+#[derive(clap::Args, Debug, Clone)]
+pub struct PermuteArgs {
+    /// Probe variants of what was found: environment words attached (`admin-dev`,
+    /// `stage-vpn`) and numbers swapped (`v1` → `v2`), altdns-style.
+    ///
+    /// Long form only (boolean flag):
+    ///     --permute
+    ///
+    /// Scans vary the last segment of directory findings (files are left alone); `dirust dns`
+    /// varies the first label of resolved names and also inserts a label (`dev.vpn`).
+    #[arg(long, default_value_t = false)]
+    pub permute: bool,
+
+    /// How many generations of variants to follow: 1 varies wordlist hits only, 2 also the
+    /// variants that were found, and so on.
+    ///
+    /// Long form:
+    ///     --permute-depth <N>
+    #[arg(long, default_value_t = 1, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub permute_depth: u32,
+
+    /// Words to attach instead of the built-in list (dev, stage, test, ...), one per line.
+    ///
+    /// Long form:
+    ///     --permute-words <PATH>
+    #[arg(long, value_name = "PATH")]
+    pub permute_words: Option<String>,
+}
+
+/// Where SOCKS5 proxy connections resolve host names (`--proxy-dns`). HTTP proxies always
+/// resolve names themselves.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProxyDns {
+    /// The proxy resolves names (socks5h): no DNS query leaves this machine
+    Remote,
+    /// Names are resolved here; the proxy gets IP addresses (socks5)
+    Local,
+}
+
+/// Browser profile for `--tls-impersonate`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TlsProfile {
+    /// Chrome's cipher suite and key-exchange group order
+    Chrome,
+    /// Firefox's cipher suite and key-exchange group order
+    Firefox,
+    /// A fresh random order on every run
+    Random,
+}
+
+/// Flags for `dirust scan` (and the legacy `dirust <url> -w list` form).
+#[derive(clap::Args, Debug, Clone)]
+pub struct ScanArgs {
+    /// Client settings (concurrency, method, timeout, compression).
+    #[command(flatten)]
+    pub http: HttpArgs,
+
+    /// Base URL to scan (http:// or https://; a bare host like `example.com` means https://,
+    /// falling back to http:// if the TLS handshake fails or the connection is refused).
+    ///
+    /// This is a *positional* argument — no flag is required. Example:
+    ///     dirust scan https://example.com/ -w words.txt
+    ///
+    /// The program will later normalize this to ensure it ends with a trailing `/`.
+    /// Required unless `--self-test` is given (or this is a `--role worker`).
+    #[arg(required_unless_present_any = ["self_test", "coordinator"])]
+    pub base: Option<String>,
+
+    /// Path to the wordlist file (e.g., rockyou-like list of endpoints; `.gz` / `.zst` files
+    /// are decompressed while reading), `-` for stdin, or a source expression (see
+    /// `scanner/source.rs`):
+    ///     -w 'mutate(case, file("list.txt"))'
+    ///     -w 'range(001, 500)'
+    ///     -w 'url(https://example.com/words.txt)'
+    ///
+    /// Lines may end with a weight (`admin 10`): higher weights are probed first.
+    ///
+    /// Short form:  -w <PATH>
+    /// Long form:   --wordlist <PATH>
+    ///
+    /// Required unless `--self-test` is given (or this is a `--role worker`).
+    #[arg(short, long, required_unless_present_any = ["self_test", "coordinator"])]
+    pub wordlist: Option<String>,
+
+    /// Words to probe before all others (one per line, optionally weighted like `admin 10`),
+    /// when the wordlist has them.
+    ///
+    /// Long form only:
+    ///     --priority high-value.txt
+    #[arg(long, value_name = "PATH")]
+    pub priority: Option<String>,
+
     /// Extra extensions to try for plain names (comma-separated).
     ///
     /// Example:
@@ -79,14 +406,1642 @@ pub struct Args {
     ///     *no* extra extensions are appended.
     #[arg(long, default_value = "")]
     pub exts: String,
-}
 
-impl Args {
-    /// Convert the numeric `timeout` into a `Duration`.
+    /// After the first few hundred extension probes, drop (or defer to the end of the scan)
+    /// the `--exts` extensions that never produced a finding.
     ///
-    /// We keep this as a method to make call sites (client builder) explicit and readable.
-    pub fn request_timeout(&self) -> Duration {
-        Duration::from_secs(self.timeout)
+    /// Long form:
+    ///     --adaptive-exts             same as --adaptive-exts=drop
+    ///     --adaptive-exts=defer       probe them after the rest of the wordlist instead
+    ///
+    /// On a PHP site, `.asp`/`.aspx`/`.jsp` targets only cost time; this cuts them once the
+    /// first 300 extension targets show which extensions hit. The decision is printed.
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "drop",
+        value_name = "MODE"
+    )]
+    pub adaptive_exts: Option<AdaptiveExts>,
+
+    /// Learn extensions from findings and try them on the plain-name words after them.
+    ///
+    /// Long form only (boolean flag):
+    ///     --learn-exts
+    ///
+    /// A finding such as `login.php` (or a `--forms` action) adds `.php` to the extensions,
+    /// even without `--exts`; page and script extensions only (.php, .aspx, .jsp, .html, ...),
+    /// at most 5 per base URL. Each learned extension is printed.
+    #[arg(long, default_value_t = false)]
+    pub learn_exts: bool,
+
+    /// Print findings that only differ by extension or trailing slash (`admin`, `admin.php`,
+    /// `admin/`) and got identical responses as one line listing the variants.
+    ///
+    /// Long form only (boolean flag):
+    ///     --group-variants
+    ///
+    /// Identical means same status, Content-Length, Location and ETag. Findings are then
+    /// printed per wave (`--chunk-size` targets) instead of as they arrive; result files
+    /// still hold every URL.
+    #[arg(long, default_value_t = false)]
+    pub group_variants: bool,
+
+    /// Fingerprint the target's technology (headers, cookies, page markers) and pick the
+    /// extensions that fit it.
+    ///
+    /// Long form only (boolean flag):
+    ///     --smart-exts
+    ///
+    /// PHP → .php; IIS/ASP.NET → .aspx,.ashx,.asmx,.asp; Java → .jsp,.do,.action;
+    /// ColdFusion → .cfm. `--exts` entries of technologies that were not detected are
+    /// skipped, neutral ones (.html, .txt, .bak, ...) kept. The decision is printed in the
+    /// scan header; when nothing is recognized, `--exts` is used unchanged.
+    #[arg(long, default_value_t = false)]
+    pub smart_exts: bool,
+
+    /// Measure bodies that arrive without Content-Length (chunked), reading at most N bytes.
+    ///
+    /// Long form:
+    ///     --measure-chunked <MAX_BYTES>
+    ///
+    /// Interesting responses without Content-Length are fetched again with GET and their
+    /// wire size is reported instead of `len=-` (shown as `N+` when the cap was reached).
+    #[arg(long, value_name = "MAX_BYTES")]
+    pub measure_chunked: Option<u64>,
+
+    /// Flag archives and dumps of at least MIN_BYTES directly under the site root.
+    ///
+    /// Long form:
+    ///     --flag-archives <MIN_BYTES>
+    ///
+    /// A 200 finding such as `/backup.zip`, `/site.tar.gz` or `/db.sql` whose size is known
+    /// (Content-Length, or `--measure-chunked`) and at least MIN_BYTES is raised to high
+    /// severity and tagged `[ARCHIVE]` (`large-archive` in result files).
+    #[arg(long, value_name = "MIN_BYTES")]
+    pub flag_archives: Option<u64>,
+
+    /// After the scan, request every finding once more with GET at a low rate.
+    ///
+    /// Long form:
+    ///     --verify                  2 requests per second
+    ///     --verify=<REQ_PER_SEC>    e.g. --verify=5
+    ///
+    /// Confirms that findings are reproducible (same status) and records the page title,
+    /// SHA-256 and length of the body in result files; findings that are not reproduced are
+    /// printed and tagged `unverified`.
+    #[arg(
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "2",
+        value_parser = clap::value_parser!(u32).range(1..),
+        value_name = "REQ_PER_SEC"
+    )]
+    pub verify: Option<u32>,
+
+    /// In the `--verify` pass, also send every finding with a poisoned `Host` and
+    /// `X-Forwarded-Host` header and report when the value comes back.
+    ///
+    /// Long form only (boolean flag):
+    ///     --verify --host-injection
+    ///
+    /// A reflection in `Location`, another header or the body is printed as a `HOSTINJECT`
+    /// line (a cache-poisoning / password-reset-poisoning lead), tagged `host-injection` and
+    /// raises the finding to medium severity. Two extra requests per finding, at the
+    /// `--verify` rate.
+    #[arg(long, default_value_t = false, requires = "verify")]
+    pub host_injection: bool,
+
+    /// Path to a JSON results database remembering status + `ETag`/`Last-Modified` per URL.
+    ///
+    /// Long form:
+    ///     --results-db <PATH>
+    ///
+    /// The file is created on the first run and rewritten at the end of every scan.
+    #[arg(long, value_name = "PATH")]
+    pub results_db: Option<String>,
+
+    /// Send conditional requests based on the results database.
+    ///
+    /// Long form only (boolean flag, requires `--results-db`):
+    ///     --incremental
+    ///
+    /// Stored validators are sent as `If-None-Match` / `If-Modified-Since`, so unchanged
+    /// endpoints answer `304 Not Modified` and only new or changed content is reported.
+    /// Changes of the `Server`, auth and security headers since the stored run are printed
+    /// as `[~] header` lines.
+    #[arg(long, default_value_t = false, requires = "results_db")]
+    pub incremental: bool,
+
+    /// Write findings as a DefectDojo "Generic Findings Import" JSON file.
+    ///
+    /// Long form:
+    ///     --export-defectdojo <PATH>
+    ///
+    /// Each finding carries a title, severity (from the `--rules` classification, "Info" when
+    /// untagged), description and endpoint URL, ready for import into DefectDojo.
+    #[arg(long, value_name = "PATH")]
+    pub export_defectdojo: Option<String>,
+
+    /// Write just the discovered URLs to a file, one per line (for nuclei, httpx, ...).
+    ///
+    /// Long form:
+    ///     --export-urls <PATH>
+    #[arg(long, value_name = "PATH")]
+    pub export_urls: Option<String>,
+
+    /// Write findings as a JSON tree mirroring the path hierarchy (origin → directories →
+    /// files), with finding counts per directory.
+    ///
+    /// Long form:
+    ///     --export-tree <PATH>
+    ///
+    /// Every node counts the findings at or below it, per status, and gives their highest
+    /// severity; easier to review than a flat list after a large recursive scan.
+    #[arg(long, value_name = "PATH")]
+    pub export_tree: Option<String>,
+
+    /// Only export URLs with these status codes (comma-separated; default: all findings).
+    ///
+    /// Long form (requires `--export-urls`):
+    ///     --export-status 200,403
+    #[arg(long, value_delimiter = ',', value_name = "CODES", requires = "export_urls")]
+    pub export_status: Vec<u16>,
+
+    /// Write one result file per scanned host into this directory, plus an `index.json`.
+    ///
+    /// Long form:
+    ///     --output-dir <DIR>
+    ///
+    /// With `--ports` / `--both-schemes` every base URL gets its own file, named after its
+    /// host and port (`example.com_443.json`), so results can be handed off per asset.
+    #[arg(long, value_name = "DIR")]
+    pub output_dir: Option<String>,
+
+    /// Format of the per-host files written by `--output-dir`.
+    ///
+    /// Long form:
+    ///     --output-format <json|urls|defectdojo|tree>
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json, requires = "output_dir")]
+    pub output_format: OutputFormat,
+
+    /// Only report findings that are not present in a previous result set.
+    ///
+    /// Long form:
+    ///     --new-only <PATH>
+    ///
+    /// `PATH` is a results file from an earlier run (e.g., a `--results-db` file).
+    /// A finding is suppressed when the same URL was already recorded with the same status.
+    #[arg(long, value_name = "PATH")]
+    pub new_only: Option<String>,
+
+    /// Run a scan against a built-in mock server and verify the results, then exit.
+    ///
+    /// Long form only (boolean flag):
+    ///     --self-test
+    ///
+    /// Useful to validate an install and the effect of your flags without touching a real target.
+    #[arg(long, default_value_t = false)]
+    pub self_test: bool,
+
+    /// With `--self-test`, answer the scan's requests in memory instead of through the mock
+    /// server: no socket is opened, and every run sees exactly the same responses.
+    ///
+    /// Long form only (boolean flag):
+    ///     --self-test --in-memory
+    ///
+    /// Checks the scanner logic alone; the plain `--self-test` also checks the network side.
+    #[arg(long, default_value_t = false, requires = "self_test")]
+    pub in_memory: bool,
+
+    /// Parse HTML forms on 200 responses and report their actions and parameter names.
+    ///
+    /// Long form only (boolean flag):
+    ///     --forms
+    ///
+    /// This downloads the body of every 200 HTML page (one extra GET per hit).
+    #[arg(long, default_value_t = false)]
+    pub forms: bool,
+
+    /// Also probe discovered form actions that live under the base URL.
+    ///
+    /// Long form only (boolean flag, requires `--forms`):
+    ///     --follow-forms
+    #[arg(long, default_value_t = false, requires = "forms")]
+    pub follow_forms: bool,
+
+    /// Send unique canary values to the parameters of discovered forms and report the
+    /// ones the response echoes back (in the body or a header).
+    ///
+    /// Long form only (boolean flag, requires `--forms`):
+    ///     --reflect
+    ///
+    /// Only form actions under the base URL (and inside `--scope`) are tested, one request
+    /// per form. Reflections found with `'"<>` intact are marked `unescaped` (an XSS lead).
+    #[arg(long, default_value_t = false, requires = "forms")]
+    pub reflect: bool,
+
+    /// Record which security headers each 200 finding sends (HSTS, X-Frame-Options, CSP,
+    /// Cache-Control).
+    ///
+    /// Long form only (boolean flag):
+    ///     --header-audit
+    ///
+    /// Stored as `"headers"` on the finding in result files; `dirust report` and the DefectDojo
+    /// export list the missing ones. Cache-Control only counts on sensitive findings (medium
+    /// severity or higher, `[AUTH]`), which should send `no-store` or `private`.
+    #[arg(long, default_value_t = false)]
+    pub header_audit: bool,
+
+    /// Skip the request to the base URL that aborts the scan early when the host does not
+    /// answer (DNS failure, refused connection, TLS error).
+    ///
+    /// Long form only (boolean flag):
+    ///     --no-preflight
+    #[arg(long, default_value_t = false)]
+    pub no_preflight: bool,
+
+    /// Named pipe (created with `mkfifo`) to add words to the running scan: every line
+    /// written to it is probed before the next wordlist chunk.
+    ///
+    /// Long form only:
+    ///     --inject-pipe /tmp/dirust.in
+    ///     echo backup-2024 > /tmp/dirust.in
+    ///
+    /// Jobs of `dirust serve` take words through `POST /scans/<id>/words` instead.
+    #[arg(long, value_name = "PATH")]
+    pub inject_pipe: Option<String>,
+
+    /// Send the session cookies responses set with every later probe of the scan.
+    ///
+    /// Long form only (boolean flag):
+    ///     --keep-cookies
+    ///
+    /// Only session-like cookies are kept (`PHPSESSID`, `JSESSIONID`, `*session*`,
+    /// `*token*`, ...); each new one is announced. Pages that need a session handed out by
+    /// an earlier page are then reached in the same scan.
+    #[arg(long, default_value_t = false)]
+    pub keep_cookies: bool,
+
+    /// Keep per-host calibration (random-path baseline, HEAD support, `--smart-exts`
+    /// technologies) in this file, and start later scans of the same base with it.
+    ///
+    /// Long form only:
+    ///     --profile-cache ~/.cache/dirust/profiles.json
+    ///
+    /// Profiles older than a week are measured again.
+    #[arg(long, value_name = "PATH")]
+    pub profile_cache: Option<String>,
+
+    /// Measure the calibration again even when `--profile-cache` has a fresh profile (the
+    /// profile is then refreshed).
+    ///
+    /// Long form only (boolean flag):
+    ///     --recalibrate
+    #[arg(long, default_value_t = false, requires = "profile_cache")]
+    pub recalibrate: bool,
+
+    /// Forward every finding, as it is found, to a collector as NDJSON (one
+    /// `{"target": ..., "result": ...}` object per line).
+    ///
+    /// Long form only:
+    ///     --stream-to tcp://collector.internal:9000
+    ///     --stream-to unix:///run/dirust/results.sock
+    ///
+    /// The collector must be listening when the scan starts; a dropped connection is
+    /// reopened for the next finding.
+    #[arg(long, value_name = "URL")]
+    pub stream_to: Option<String>,
+
+    /// Only send findings of this severity or above to `--stream-to` and `--syslog`.
+    ///
+    /// Long form:
+    ///     --notify-severity high
+    ///
+    /// Severities come from the `--rules` classification (raised by `--secrets`); unclassified
+    /// findings count as info. Result files and the terminal still get every finding.
+    #[arg(long, value_enum, value_name = "SEVERITY")]
+    pub notify_severity: Option<Severity>,
+
+    /// Exit with status 2 when the scan reports a finding (of this severity or above).
+    ///
+    /// Long form:
+    ///     --fail-on-findings          any finding
+    ///     --fail-on-findings=high     only findings classified high or critical
+    ///
+    /// For CI gates. The scan still runs to the end and writes its outputs; unclassified
+    /// findings count as info. Errors keep exiting with status 1.
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "info",
+        value_name = "SEVERITY"
+    )]
+    pub fail_on_findings: Option<Severity>,
+
+    /// Send every finding to syslog as an RFC 5424 message, with the finding's severity
+    /// mapped to the syslog severity (critical -> crit ... info -> info).
+    ///
+    /// Long form:
+    ///     --syslog                    the local daemon (/dev/log)
+    ///     --syslog=udp://siem:514     a remote collector over UDP
+    ///     --syslog=tcp://siem:601     a remote collector over TCP (octet-counted frames)
+    ///     --syslog=unix:///path       another local datagram socket
+    #[arg(
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "local",
+        value_name = "TARGET"
+    )]
+    pub syslog: Option<String>,
+
+    /// Run this command for every finding, in the background (e.g., a headless-browser
+    /// screenshot tool). `{url}`, `{status}`, `{length}` and `{severity}` are replaced by the
+    /// finding's values, shell-quoted.
+    ///
+    /// Long form:
+    ///     --on-hit 'gowitness single {url}'
+    ///     --on-hit 'echo {status} {url} >> hits.txt'
+    ///
+    /// The command runs through the shell (`sh -c`; `cmd /C` on Windows) with its output
+    /// discarded; the values are also in `DIRUST_URL`, `DIRUST_STATUS`, `DIRUST_LENGTH` and
+    /// `DIRUST_SEVERITY`. Failures and timeouts are reported on stderr and never stop the scan.
+    #[arg(long, value_name = "CMD")]
+    pub on_hit: Option<String>,
+
+    /// Most `--on-hit` commands running at once; further findings wait their turn.
+    ///
+    /// Long form:
+    ///     --on-hit-concurrency <N>
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..), value_name = "N")]
+    pub on_hit_concurrency: u32,
+
+    /// Kill an `--on-hit` command that is still running after this long.
+    ///
+    /// Long form:
+    ///     --on-hit-timeout <DURATION>     e.g. 30s, 2m (plain numbers are seconds)
+    #[arg(long, default_value = "30s", value_parser = parse_duration, value_name = "DURATION")]
+    pub on_hit_timeout: Duration,
+
+    /// Scan the base URL over both https:// and http:// (one scan per scheme).
+    ///
+    /// Long form only (boolean flag):
+    ///     --both-schemes
+    ///
+    /// Useful for hosts that serve different content on each scheme; every printed URL
+    /// carries its scheme, and the results database keeps both sets.
+    #[arg(long, default_value_t = false)]
+    pub both_schemes: bool,
+
+    /// Scan the same host on several ports (comma-separated), one scan per port.
+    ///
+    /// Long form:
+    ///     --ports 80,443,8080,8443
+    ///
+    /// The scheme is guessed per port (443, 8443, 9443 → https; anything else → http);
+    /// combine with `--both-schemes` to try both on every port. Ports that do not answer
+    /// are skipped with a warning.
+    #[arg(long, value_delimiter = ',', value_name = "PORTS")]
+    pub ports: Vec<u16>,
+
+    /// Scan several bases one after another instead of side by side.
+    ///
+    /// Long form only (boolean flag):
+    ///     --no-interleave
+    ///
+    /// By default the bases of `--ports` / `--both-schemes` are scanned at the same time and take turns for the `-c` budget, so
+    /// early results cover all of them. `--rate` is per host, shared by all its bases. With
+    /// this flag, base B starts when base A is finished.
+    #[arg(long, default_value_t = false)]
+    pub no_interleave: bool,
+
+    /// Scope file: allowed hosts / path prefixes and denied patterns. Targets outside the
+    /// scope are dropped (and counted) before they are requested.
+    ///
+    /// Long form:
+    ///     --scope <PATH>
+    ///
+    /// One rule per line: `example.com`, `*.example.com`, `example.com/app/`,
+    /// `https://example.com:8443/` allow; `!/logout`, `!/api/*/delete` deny.
+    /// See `src/scanner/scope.rs` for the details.
+    #[arg(long, value_name = "PATH")]
+    pub scope: Option<String>,
+
+    /// TOML file with severity/tag rules for findings (replaces the built-in rules).
+    ///
+    /// Long form:
+    ///     --rules <PATH>
+    ///
+    /// Findings matching a rule get a `[SEVERITY tags]` suffix and are stored with their
+    /// severity and tags in result files. See `src/severity.toml` for the format.
+    #[arg(long, value_name = "PATH")]
+    pub rules: Option<String>,
+
+    /// Collapse redirects: once N paths redirected to the same target, the next ones are
+    /// not printed; the scan ends with one summary line counting them (0 = off).
+    ///
+    /// Long form:
+    ///     --redirect-threshold <N>
+    ///
+    /// Redirects are printed as they are found until their target reaches N lines, so a
+    /// long scan shows them live; an application sending every unknown path to its login
+    /// page then costs N lines plus one summary instead of thousands.
+    #[arg(long, default_value_t = 5, value_name = "N")]
+    pub redirect_threshold: usize,
+
+    /// Probe the in-scope targets of redirect findings that were not probed yet.
+    ///
+    /// Long form only (boolean flag):
+    ///     --harvest-redirects
+    ///
+    /// Each distinct Location (resolved, without query or fragment) under the base URL is
+    /// queued once, however many paths redirect to it, and `--scope` still applies. Findings
+    /// reached this way are printed with the redirect that led to them and stored with
+    /// `redirected_from`.
+    #[arg(long, default_value_t = false)]
+    pub harvest_redirects: bool,
+
+    /// `--permute`: probe variants of directory findings (see `permute.rs`).
+    #[command(flatten)]
+    pub permute: PermuteArgs,
+
+    /// Do not show the live status line (progress, findings per status) on the terminal.
+    ///
+    /// Long form:
+    ///     --no-progress
+    ///
+    /// The line is drawn on stderr, and only when stderr is a terminal and neither `-q` nor
+    /// `--silent` is given.
+    #[arg(long, default_value_t = false)]
+    pub no_progress: bool,
+
+    /// Write progress as NDJSON events every `--progress-interval`, for GUIs and orchestrators
+    /// wrapping dirust (instead of the live status line).
+    ///
+    /// Long form:
+    ///     --progress-ndjson                   on stderr
+    ///     --progress-ndjson=progress.ndjson   to a file (or a named pipe)
+    ///
+    /// One object per line: `{"event":"progress","completed":1520,"total":4810,"rps":412.3,
+    /// "errors":2,"eta":8,...}`, and a last one with `"event":"finished"`. `total` and `eta`
+    /// are estimates (`null` while the wordlist size is unknown).
+    #[arg(
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "-",
+        value_name = "PATH"
+    )]
+    pub progress_ndjson: Option<String>,
+
+    /// Time between two `--progress-ndjson` events.
+    ///
+    /// Long form:
+    ///     --progress-interval <DURATION>     e.g. 5s, 500ms, 1m (plain numbers are seconds)
+    #[arg(long, default_value = "5s", value_parser = parse_duration, value_name = "DURATION")]
+    pub progress_interval: Duration,
+
+    /// Start without asking, however many requests the scan is about to send.
+    ///
+    /// Short form:  -y
+    /// Long form:   --yes
+    ///
+    /// Every scan first prints `about to send ~N requests to <host>`; above
+    /// `--confirm-above`, it asks for confirmation on the terminal (and refuses to start
+    /// without `--yes` when there is no terminal to ask on).
+    #[arg(short = 'y', long, default_value_t = false)]
+    pub yes: bool,
+
+    /// Number of requests above which a scan asks for confirmation before starting
+    /// (0: never ask).
+    ///
+    /// Long form:
+    ///     --confirm-above 5000000
+    #[arg(long, default_value_t = 1_000_000, value_name = "N")]
+    pub confirm_above: u64,
+
+    /// Print every probed URL with its status, not only findings (404s dimmed).
+    ///
+    /// Long form:
+    ///     --all-statuses
+    ///
+    /// Useful to see why an expected path does not show up. Responses that are not findings
+    /// are still left out of result files and exports.
+    #[arg(short = 'v', long, default_value_t = false)]
+    pub all_statuses: bool,
+
+    /// Do not confirm directories: report `word` → `word/` redirects as plain redirects.
+    ///
+    /// Long form:
+    ///     --no-dir-confirm
+    ///
+    /// By default, a redirect from `word` to `word/` is followed by a probe of `word/`, and
+    /// the two are reported as a single directory finding (`<status> ... word/ [dir]`).
+    #[arg(long)]
+    pub no_dir_confirm: bool,
+
+    /// Also probe the trailing-slash form of every plain name (`admin` and `admin/`).
+    ///
+    /// Long form only (boolean flag):
+    ///     --add-slash
+    ///
+    /// Many servers only answer on the directory form. A `admin` → `admin/` redirect is
+    /// then not reported (the `admin/` probe reports the directory), and identical
+    /// `admin` / `admin/` answers are printed as one line, as with `--group-variants`.
+    #[arg(long, default_value_t = false)]
+    pub add_slash: bool,
+
+    /// Targets built and scheduled per chunk of the wordlist.
+    ///
+    /// Long form:
+    ///     --chunk-size <N>
+    ///
+    /// Targets, task handles and results are only held for one chunk at a time, so memory
+    /// use does not grow with the wordlist. Larger chunks leave fewer idle moments between
+    /// chunks (the last probes of a chunk finish before the next chunk starts).
+    #[arg(long, default_value_t = 10_000, value_name = "N")]
+    pub chunk_size: usize,
+
+    /// HTTP engine used for probes.
+    ///
+    /// Long form:
+    ///     --engine <reqwest|fast>
+    ///
+    /// `fast` is a lean HTTP/1.1 client built directly on hyper, for very high concurrency
+    /// (`-c 500` and up). It does not support proxies or HTTP/2; `reqwest` (the default)
+    /// does everything. Body downloads (`--forms`, `--measure-chunked`) always use reqwest.
+    #[arg(long, value_enum, default_value_t = EngineKind::Reqwest, value_name = "ENGINE")]
+    pub engine: EngineKind,
+
+    /// With `--engine fast`: write up to N probes on a connection before reading their
+    /// responses (HTTP/1.1 pipelining; 1 = off).
+    ///
+    /// Long form:
+    ///     --pipeline <N>
+    ///
+    /// Each connection then carries N probes per round trip, so `-c 500 --pipeline 10` needs
+    /// 50 connections. Requests are written as raw HTTP/1.1 (see `scanner/pipeline.rs`).
+    /// Servers that close the connection after every response still work, one probe per
+    /// connection.
+    #[arg(long, default_value_t = 1, value_name = "N", value_parser = clap::value_parser!(u16).range(1..=256))]
+    pub pipeline: u16,
+
+    /// Open N keep-alive connections to the target before the scan starts.
+    ///
+    /// Long form:
+    ///     --prewarm <N>
+    ///
+    /// The first burst of probes then finds connections ready instead of paying TCP/TLS
+    /// handshakes at the same time, which improves early req/s and keeps the latency
+    /// summary about the server, not about connection setup. Capped at `-c` (more would
+    /// never be used at once). Costs N HEAD requests to the base URL.
+    #[arg(long, default_value_t = 0, value_name = "N")]
+    pub prewarm: usize,
+
+    /// Ramp concurrency up in stages instead of starting at full `-c`
+    /// (comma-separated `CONCURRENCY:DURATION` stages).
+    ///
+    /// Long form:
+    ///     --ramp <STAGES>
+    ///
+    /// Example:
+    ///     --ramp 10:60s,50:300s,100:0     10 for a minute, 50 for five, then 100 to the end
+    ///
+    /// A duration of 0 holds that stage until the scan ends; after the last timed stage the
+    /// scan runs at `-c`, which also caps every stage. Monitoring teams see the load grow
+    /// gradually instead of an instant burst. Each base URL (host) ramps up from the start.
+    #[arg(long, value_delimiter = ',', value_parser = parse_ramp_stage, value_name = "STAGES")]
+    pub ramp: Vec<RampStage>,
+
+    /// Retry a timed-out probe once with this longer timeout; results that only arrive
+    /// on the retry are tagged `[slow-retry]`.
+    ///
+    /// Long form:
+    ///     --slow-retry-timeout <DURATION>     e.g. 30s, 1500ms, 2m (plain numbers are seconds)
+    ///
+    /// Without it, a timeout is an error, as before. A probe that also times out on the
+    /// retry is still an error.
+    #[arg(long, value_parser = parse_duration, value_name = "DURATION")]
+    pub slow_retry_timeout: Option<Duration>,
+
+    /// Shell command printing the `Authorization` header value for the probe requests
+    /// (e.g. `Bearer eyJ...`); run again when a response says the value expired (401).
+    ///
+    /// Long form:
+    ///     --auth-cmd <COMMAND>
+    ///
+    /// Example:
+    ///     --auth-cmd './login.sh'     the first line it prints is the header value
+    ///
+    /// The command runs before the first probe, then whenever a probe sent with a value
+    /// older than 30s gets a 401; that probe is sent again with the new value. A 401 to a
+    /// fresh value is a real answer (a protected path) and is reported as usual. Replaces an
+    /// `Authorization` header given with `-H`. Only probe requests carry it: the baseline
+    /// and body downloads (`--forms`, `--secrets`) are sent without it.
+    #[arg(long, value_name = "COMMAND")]
+    pub auth_cmd: Option<String>,
+
+    /// Send at most N probe requests per second (retries included), whatever `-c` allows.
+    ///
+    /// Long form:
+    ///     --rate <REQ_PER_SEC>
+    ///
+    /// Requests are spaced evenly (1/N s apart) instead of sent in bursts.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..), value_name = "REQ_PER_SEC")]
+    pub rate: Option<u32>,
+
+    /// Attempts per probe, retries included (1 = never send a probe twice).
+    ///
+    /// Long form:
+    ///     --max-attempts <N>
+    ///
+    /// Retries are the GET after a 405 to HEAD and the `--slow-retry-timeout` retry.
+    #[arg(
+        long,
+        default_value_t = crate::scanner::middleware::MAX_ATTEMPTS,
+        value_parser = clap::value_parser!(u32).range(1..),
+        value_name = "N"
+    )]
+    pub max_attempts: u32,
+
+    /// Preset for scanning production systems with minimal impact.
+    ///
+    /// Long form only (boolean flag):
+    ///     --polite
+    ///
+    /// Stands for `-c 2 --rate 2 --max-attempts 1 --user-agent "dirust/0.1.1 (polite scan)"`;
+    /// add `--from <EMAIL>` so the site's operators can reach you. Flags given explicitly win
+    /// over the preset (`--polite --rate 5`).
+    #[arg(long, default_value_t = false, conflicts_with = "fast")]
+    pub polite: bool,
+
+    /// Preset for throughput: the fastest scan this machine and the target allow.
+    ///
+    /// Long form only (boolean flag):
+    ///     --fast
+    ///
+    /// Stands for `-c 200 --http2 --prewarm 200 --no-progress`, with no `--rate` and no
+    /// `--ramp`: high concurrency, HTTP/2 multiplexing where the target offers it, a pool of
+    /// connections opened before the first probe, and no live status line to redraw. Flags
+    /// given explicitly win over the preset (`--fast -c 500`). Check what the target takes
+    /// with `dirust bench <URL> --fast` first.
+    #[arg(long, default_value_t = false)]
+    pub fast: bool,
+
+    /// Tag findings `[SLOW]` when their response took more than N standard deviations
+    /// longer than the scan's rolling latency average (0 = off).
+    ///
+    /// Long form:
+    ///     --latency-sigma <N>
+    ///
+    /// Slow endpoints often do real work (auth checks, database queries). Outliers must also
+    /// be at least 50ms slower than the average; the first 30 probes only set the baseline.
+    #[arg(long, default_value_t = 3.0, value_name = "N")]
+    pub latency_sigma: f64,
+
+    /// Warn when suddenly N percent or more of the responses are findings (0 = off).
+    ///
+    /// Long form:
+    ///     --hit-rate-alert <PERCENT>
+    ///
+    /// A scan where everything starts answering 200 (or redirecting to a login page) has
+    /// usually hit a captive portal, an expired session or a block page. The last 100 probes
+    /// must reach N percent and five times the scan's usual rate; the first 200 probes only
+    /// set that rate.
+    #[arg(
+        long,
+        default_value_t = 50,
+        value_parser = clap::value_parser!(u8).range(0..=100),
+        value_name = "PERCENT"
+    )]
+    pub hit_rate_alert: u8,
+
+    /// Stop starting probes after a hit rate spike and ask whether to continue.
+    ///
+    /// Long form only (boolean flag):
+    ///     --pause-on-spike
+    ///
+    /// Without a terminal to ask on, the scan stops with an error; with `--state` it can be
+    /// finished later with `dirust resume`.
+    #[arg(long, default_value_t = false)]
+    pub pause_on_spike: bool,
+
+    /// Give up on a host that stopped answering mid-scan after SECS seconds (0 = wait forever).
+    ///
+    /// Long form:
+    ///     --max-downtime <SECS>
+    ///
+    /// After 20 probes in a row get no answer (refused connection, timeout), the host is
+    /// quarantined and re-checked after 1s, 2s, 4s, ... (at most a minute apart); the scan
+    /// resumes when it answers again. Other bases keep scanning meanwhile.
+    #[arg(long, default_value_t = 300, value_name = "SECS")]
+    pub max_downtime: u64,
+
+    /// Vary the shape of every probe request, so the scan is harder to fingerprint.
+    ///
+    /// Long form only (boolean flag):
+    ///     --evade
+    ///
+    /// Each request gets a browser-like Accept and Accept-Language picked at random, a random
+    /// subset of innocuous headers (Cache-Control, DNT, Sec-Fetch-*, ...) and its headers in
+    /// a random order; header names are sent in Title-Case. Applies to scan probes only.
+    #[arg(long, default_value_t = false)]
+    pub evade: bool,
+
+    /// Also probe overlong UTF-8 and Unicode-normalization variants of every target
+    /// (`%c0%af` for `/`, fullwidth letters, ...) to test path-filter bypasses.
+    ///
+    /// Long form only (boolean flag):
+    ///     --unsafe-encodings
+    ///
+    /// Up to 6 extra requests per target, and requests that look like attacks: only use it
+    /// where that is authorized. See `scanner/encodings.rs` for the variant list.
+    #[arg(long, default_value_t = false)]
+    pub unsafe_encodings: bool,
+
+    /// Download the bodies of 200 findings and search them for secrets (AWS keys, JWTs,
+    /// private keys, API tokens, high-entropy strings).
+    ///
+    /// Long form only (boolean flag):
+    ///     --secrets
+    ///
+    /// Matches are printed as `[!!] secret` lines under the finding, listed again at the
+    /// end of the scan, raise the finding's severity and are stored as `secret:<rule>` tags.
+    #[arg(long, default_value_t = false)]
+    pub secrets: bool,
+
+    /// TOML file with secret detection rules (replaces the built-in rules).
+    ///
+    /// Long form:
+    ///     --secret-rules <PATH>
+    ///
+    /// See `src/scanner/secrets.toml` for the format (regex rules plus entropy settings).
+    #[arg(long, value_name = "PATH", requires = "secrets")]
+    pub secret_rules: Option<String>,
+
+    /// Record scan progress in a state file so an interrupted scan can be continued with
+    /// `dirust resume <PATH>`.
+    ///
+    /// Long form:
+    ///     --state <PATH>
+    ///
+    /// The file holds this command line and every URL probed so far; resuming re-runs the
+    /// same command (from the same working directory) and skips those URLs.
+    #[arg(long, value_name = "PATH")]
+    pub state: Option<String>,
+
+    /// Record how far the scan got (wordlist position and queued follow-up URLs per base) in
+    /// a small checkpoint file; running the same command again continues from there.
+    ///
+    /// Long form:
+    ///     --checkpoint scan.ckpt
+    ///
+    /// Lighter than `--state` (no per-URL journal): the wave in flight when the scan stopped
+    /// is probed again. The file is removed once the scan completes.
+    #[arg(long, value_name = "PATH")]
+    pub checkpoint: Option<String>,
+
+    /// Record every probe request and its response (status, headers) as JSON lines.
+    ///
+    /// Long form:
+    ///     --transcript <PATH>
+    ///
+    /// Example:
+    ///     --transcript trace.jsonl
+    ///
+    /// An auditable record of exactly what was sent during an engagement. A resumed scan
+    /// appends to it.
+    #[arg(long, value_name = "PATH")]
+    pub transcript: Option<String>,
+
+    /// Also record the first N bytes of each GET response body in the transcript.
+    ///
+    /// Long form:
+    ///     --transcript-body <BYTES>
+    #[arg(long, default_value_t = 0, value_name = "BYTES", requires = "transcript")]
+    pub transcript_body: usize,
+
+    /// Distributed scanning: run as the coordinator (shards targets to workers and
+    /// aggregates their results) or as a worker (probes the batches it is given).
+    ///
+    /// Long form:
+    ///     --role <coordinator|worker>
+    ///
+    /// Coordinator: `dirust scan <BASE> -w list --role coordinator [--listen ADDR:PORT]`
+    /// Worker:      `dirust scan --role worker --coordinator HOST:PORT [-c N]`
+    ///
+    /// A coordinator rejects the flags of local scans only (`--forms`, `--secrets`,
+    /// `--incremental`, `--state`, `--checkpoint`, `--adaptive-exts`, `--inject-pipe`,
+    /// `--stream-to`, `--syslog`, `--fail-on-findings`, `--ramp`).
+    #[arg(long, value_enum, value_name = "ROLE")]
+    pub role: Option<Role>,
+
+    /// Address the coordinator listens on for workers.
+    ///
+    /// Long form:
+    ///     --listen <ADDR:PORT>
+    ///
+    /// Set `--worker-token` to keep other workers out; the protocol itself is not encrypted,
+    /// so only expose this on a network you trust.
+    #[arg(long, default_value = "0.0.0.0:8790", value_name = "ADDR:PORT")]
+    pub listen: String,
+
+    /// Shared secret of a distributed scan: the coordinator only serves workers that send
+    /// the same token.
+    ///
+    /// Long form:
+    ///     --worker-token <SECRET>
+    ///
+    /// Pass the same value to the coordinator and every worker. It travels in clear text:
+    /// it keeps stray and misconfigured workers out, not an attacker reading the traffic.
+    #[arg(long, value_name = "SECRET", requires = "role")]
+    pub worker_token: Option<String>,
+
+    /// Hand a batch to another worker when its worker has not sent the results back after
+    /// this long (the worker may be stuck while still connected).
+    ///
+    /// Long form:
+    ///     --batch-timeout <DURATION>     e.g. 10m, 90s (plain numbers are seconds)
+    ///
+    /// Results that still arrive from the first worker are used if they come first; the
+    /// second copy of a batch is ignored.
+    #[arg(long, default_value = "5m", value_parser = parse_duration, value_name = "DURATION")]
+    pub batch_timeout: Duration,
+
+    /// Coordinator to fetch work from (required for `--role worker`).
+    ///
+    /// Long form:
+    ///     --coordinator <HOST:PORT>
+    #[arg(long, value_name = "HOST:PORT", required_if_eq("role", "worker"))]
+    pub coordinator: Option<String>,
+}
+
+/// Format of `--output-dir` files.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Result-file JSON (the `--results-db` schema)
+    Json,
+    /// One discovered URL per line
+    Urls,
+    /// DefectDojo generic findings import JSON
+    Defectdojo,
+    /// Findings as a per-directory tree (see `--export-tree`)
+    Tree,
+}
+
+impl OutputFormat {
+    /// File extension used for this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Urls => "txt",
+            OutputFormat::Defectdojo => "dojo.json",
+            OutputFormat::Tree => "tree.json",
+        }
+    }
+}
+
+/// What `--adaptive-exts` does with extensions that never hit.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdaptiveExts {
+    /// Stop probing them
+    Drop,
+    /// Probe them after the rest of the wordlist
+    Defer,
+}
+
+/// HTTP engine for scan probes (`--engine`).
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EngineKind {
+    /// reqwest: proxies, HTTP/2, the full client stack
+    Reqwest,
+    /// Lean hyper-based HTTP/1.1 client for maximum throughput
+    Fast,
+}
+
+/// One stage of `--ramp`: run at `concurrency` for `hold` (`None`: until the scan ends).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RampStage {
+    pub concurrency: usize,
+    pub hold: Option<Duration>,
+}
+
+/// Role of this process in a distributed scan.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Build the target list, hand it out in batches, aggregate and report results
+    Coordinator,
+    /// Probe batches received from a coordinator
+    Worker,
+}
+
+/// Which fixed-target fuzzing mode `dirust fuzz` runs.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FuzzMode {
+    /// Query parameter names (`<URL>?name=1`)
+    Params,
+    /// Request headers (`Name: value` lines, 403-bypass style)
+    Headers,
+    /// A raw request template (`--request`), `FUZZ` replaced by each entry
+    Raw,
+}
+
+/// Flags for `dirust fuzz`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct FuzzArgs {
+    /// Client settings (concurrency, method, timeout, compression).
+    #[command(flatten)]
+    pub http: HttpArgs,
+
+    /// What to fuzz: `params`, `headers` or `raw`.
+    #[arg(value_enum)]
+    pub mode: FuzzMode,
+
+    /// The fixed target URL, used exactly as given (no trailing slash is added).
+    ///
+    /// Example:
+    ///     dirust fuzz params https://example.com/search.php -w names.txt
+    pub url: String,
+
+    /// Wordlist: parameter names (`params`) or `Name: value` / bare `Name` lines (`headers`).
+    ///
+    /// Short form:  -w <PATH>
+    /// Long form:   --wordlist <PATH>
+    #[arg(short, long)]
+    pub wordlist: String,
+
+    /// Value sent for bare header names in `headers` wordlists.
+    ///
+    /// Long form:
+    ///     --header-value <VALUE>
+    ///
+    /// Header values may use `{path}` and `{host}` placeholders taken from `<URL>`, e.g.
+    /// `X-Original-URL: {path}`.
+    #[arg(long, default_value = "127.0.0.1", value_name = "VALUE")]
+    pub header_value: String,
+
+    /// Send a unique canary value to every discovered parameter and report the ones the
+    /// response echoes back (`params` mode).
+    ///
+    /// Long form only (boolean flag):
+    ///     --reflect
+    ///
+    /// Reflections found with `'"<>` intact are marked `unescaped` (an XSS lead).
+    #[arg(long, default_value_t = false)]
+    pub reflect: bool,
+
+    /// Request template for `raw` mode: a full HTTP/1.1 request (request line, headers,
+    /// empty line, body) where every `FUZZ` is replaced by the wordlist entry.
+    ///
+    /// Long form:
+    ///     --request <FILE>
+    ///
+    /// Example:
+    ///     dirust fuzz raw https://example.com/ --request req.txt -w words.txt
+    ///
+    /// `<URL>` only chooses where to connect; the Host header comes from the template.
+    #[arg(long, value_name = "FILE", required_if_eq("mode", "raw"))]
+    pub request: Option<String>,
+
+    /// Send `--request` templates byte for byte over a fresh HTTP/1.1 connection instead of
+    /// re-building them with the HTTP client.
+    ///
+    /// Long form only (boolean flag):
+    ///     --raw-engine
+    ///
+    /// Needed when a template is meant to be malformed (request smuggling tests:
+    /// `Transfer-Encoding : chunked`, two `Content-Length` lines, bare LF line ends), which
+    /// the HTTP client would normalize or refuse. No proxy support.
+    #[arg(long, default_value_t = false, requires = "request")]
+    pub raw_engine: bool,
+}
+
+/// Flags for `dirust vhost`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct VhostArgs {
+    /// Client settings (concurrency, method, timeout, compression).
+    #[command(flatten)]
+    pub http: HttpArgs,
+
+    /// The server to ask (e.g., `https://10.0.0.5/`); every request goes to this URL with a
+    /// different Host header.
+    pub url: String,
+
+    /// Wordlist of host name prefixes (e.g., "dev", "staging", "intranet").
+    ///
+    /// Short form:  -w <PATH>
+    /// Long form:   --wordlist <PATH>
+    #[arg(short, long)]
+    pub wordlist: String,
+
+    /// Domain appended to every word (`dev` → `dev.<DOMAIN>`); defaults to the host of `<URL>`.
+    ///
+    /// Long form:
+    ///     --domain <DOMAIN>
+    #[arg(long, value_name = "DOMAIN")]
+    pub domain: Option<String>,
+}
+
+/// Flags for `dirust dns`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct DnsArgs {
+    /// Parent domain, e.g. `example.com`.
+    pub domain: String,
+
+    /// Wordlist of subdomain labels (e.g., "www", "mail", "vpn").
+    ///
+    /// Short form:  -w <PATH>
+    /// Long form:   --wordlist <PATH>
+    #[arg(short, long)]
+    pub wordlist: String,
+
+    /// Maximum number of lookups in flight.
+    ///
+    /// Short form:  -c <N>
+    /// Long form:   --concurrency <N>
+    #[arg(short, long, default_value_t = 50)]
+    pub concurrency: usize,
+
+    /// Query these DNS resolvers directly instead of the system resolver (comma-separated
+    /// IP addresses, optional port).
+    ///
+    /// Long form:
+    ///     --resolver 1.1.1.1,8.8.8.8,9.9.9.9:53
+    ///
+    /// Lookups take turns across the pool. Resolvers that do not answer before the run are
+    /// left out, ones that keep failing are benched for a while, and ones that answer for
+    /// names that cannot exist (NXDOMAIN rewriting) are dropped.
+    #[arg(long, value_delimiter = ',', value_name = "ADDRS")]
+    pub resolver: Vec<String>,
+
+    /// Add the resolvers listed in this file (one address per line) to the pool.
+    ///
+    /// Long form:
+    ///     --resolver-file <PATH>
+    #[arg(long, value_name = "PATH")]
+    pub resolver_file: Option<String>,
+
+    /// Queries per second sent to each resolver of the pool (0 = unlimited).
+    ///
+    /// Long form:
+    ///     --resolver-rate <N>
+    #[arg(long, default_value_t = 50, value_name = "N")]
+    pub resolver_rate: u32,
+
+    /// Ask another resolver of the pool when a query gets SERVFAIL, REFUSED or no answer,
+    /// up to N times.
+    ///
+    /// Long form:
+    ///     --dns-retries <N>
+    #[arg(long, default_value_t = 2, value_name = "N")]
+    pub dns_retries: usize,
+
+    /// Also write every resolved name to this file as NDJSON, one line per name, in the
+    /// envelope of `--stream-to` lines: `{"target":"<DOMAIN>","result":{"name":...}}`.
+    ///
+    /// Long form:
+    ///     --ndjson <PATH>
+    #[arg(long, value_name = "PATH")]
+    pub ndjson: Option<String>,
+
+    /// `--permute`: resolve variants of the names found (see `permute.rs`).
+    #[command(flatten)]
+    pub permute: PermuteArgs,
+}
+
+/// Flags for `dirust report`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct ReportArgs {
+    /// Results file to print (e.g., a `--results-db` file).
+    pub file: String,
+
+    /// Leave out findings marked as false positives with `dirust triage`.
+    ///
+    /// Long form only (boolean flag):
+    ///     --hide-false-positives
+    #[arg(long, default_value_t = false)]
+    pub hide_false_positives: bool,
+
+    /// Order findings by these keys, first key first (comma-separated; default: url).
+    ///
+    /// Long form:
+    ///     --sort status,length
+    ///
+    /// Keys sort ascending, except `severity` (most severe first); ties fall back to the URL.
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "KEYS")]
+    pub sort: Vec<ReportKey>,
+
+    /// Print findings in groups sharing this key, each under a header with its count.
+    ///
+    /// Long form:
+    ///     --group-by dir
+    ///
+    /// Groups are ordered by the key itself; findings inside a group follow `--sort`.
+    #[arg(long, value_enum, value_name = "KEY")]
+    pub group_by: Option<ReportKey>,
+}
+
+/// What `dirust report --sort` / `--group-by` look at in a finding.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportKey {
+    /// The full URL
+    Url,
+    /// Scheme, host and port (`https://example.com:8443`)
+    Host,
+    /// The directory the finding is in (`https://example.com/admin/`)
+    Dir,
+    /// The file extension of the last path segment (none for directories)
+    Ext,
+    /// The HTTP status code
+    Status,
+    /// The Content-Length (unknown lengths last)
+    Length,
+    /// The severity from `--rules` (unclassified last)
+    Severity,
+}
+
+/// Flags for `dirust triage`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct TriageArgs {
+    /// Results file to review (e.g., a `--results-db` file); decisions are saved into it.
+    pub file: String,
+
+    /// Also review findings that already have a decision.
+    ///
+    /// Long form only (boolean flag):
+    ///     --all
+    #[arg(long, default_value_t = false)]
+    pub all: bool,
+}
+
+/// Flags for `dirust diff`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct DiffArgs {
+    /// The older results file.
+    pub old: String,
+
+    /// The newer results file.
+    pub new: String,
+}
+
+/// Flags for `dirust resume`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct ResumeArgs {
+    /// State file written by `dirust scan --state <PATH>`.
+    pub state: String,
+}
+
+/// Flags for `dirust storage`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct StorageArgs {
+    /// Client settings (concurrency, method, timeout, compression).
+    #[command(flatten)]
+    pub http: HttpArgs,
+
+    /// Which provider(s) to enumerate.
+    ///
+    /// Wordlist entries are used as bucket names (storage account names for Azure) and
+    /// classified as AccessDenied / LISTABLE / Redirect; nonexistent ones are not printed.
+    #[arg(value_enum)]
+    pub provider: StorageProvider,
+
+    /// Wordlist of candidate bucket names.
+    ///
+    /// Short form:  -w <PATH>
+    /// Long form:   --wordlist <PATH>
+    #[arg(short, long)]
+    pub wordlist: String,
+}
+
+/// Which provider(s) to enumerate.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageProvider {
+    /// Amazon S3
+    S3,
+    /// Google Cloud Storage
+    Gcs,
+    /// Azure Blob Storage (names are storage accounts)
+    Azure,
+    /// All of the above
+    All,
+}
+
+/// Flags for `dirust bench`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct BenchArgs {
+    /// Client settings (method, timeout, compression; `-c` is replaced by `--levels`).
+    #[command(flatten)]
+    pub http: HttpArgs,
+
+    /// URL to hammer (requested as-is). When omitted, the built-in mock server is used,
+    /// which measures the client-side ceiling of this machine.
+    pub target: Option<String>,
+
+    /// Concurrency levels to measure (comma-separated).
+    ///
+    /// Long form:
+    ///     --levels 10,50,100,200
+    #[arg(long, value_delimiter = ',', default_value = "10,50,100,200")]
+    pub levels: Vec<usize>,
+
+    /// Number of requests sent per concurrency level.
+    ///
+    /// Long form:
+    ///     --requests <N>
+    #[arg(long, default_value_t = 1000)]
+    pub requests: usize,
+
+    /// Measure with the client options of `scan --fast` (`--http2`), at the levels around
+    /// its `-c 200` unless `--levels` is given (50,100,200,400).
+    ///
+    /// Long form only (boolean flag):
+    ///     --fast
+    #[arg(long, default_value_t = false)]
+    pub fast: bool,
+}
+
+/// Flags for `dirust wordgen`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct WordgenArgs {
+    /// Client settings (concurrency, method, timeout, compression).
+    #[command(flatten)]
+    pub http: HttpArgs,
+
+    /// URL to start crawling from (a bare host means https://). Only links on the same
+    /// origin are followed.
+    pub url: String,
+
+    /// Maximum number of link hops from the start page.
+    ///
+    /// Long form:
+    ///     --depth <N>
+    #[arg(long, default_value_t = 2)]
+    pub depth: usize,
+
+    /// Maximum number of pages to fetch in total.
+    ///
+    /// Long form:
+    ///     --max-pages <N>
+    #[arg(long, default_value_t = 200)]
+    pub max_pages: usize,
+
+    /// Shortest word to keep (shorter path fragments are dropped).
+    ///
+    /// Long form:
+    ///     --min-length <N>
+    #[arg(long, default_value_t = 3)]
+    pub min_length: usize,
+
+    /// Write the wordlist to this file instead of stdout.
+    ///
+    /// Short form:  -o <PATH>
+    /// Long form:   --output <PATH>
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<String>,
+}
+
+/// Flags for `dirust iis`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct IisArgs {
+    /// Client settings (concurrency, method, timeout, compression).
+    #[command(flatten)]
+    pub http: HttpArgs,
+
+    /// Directory to enumerate (a bare host means https://). Short names are per directory:
+    /// run again on each discovered directory.
+    pub url: String,
+
+    /// Wordlist to pick full-name suggestions from (entries matching a found short name).
+    ///
+    /// Short form:  -w <PATH>
+    /// Long form:   --wordlist <PATH>
+    #[arg(short, long)]
+    pub wordlist: Option<String>,
+
+    /// Write the suggestions to this file (a wordlist for `dirust scan -w`) instead of stdout.
+    ///
+    /// Short form:  -o <PATH>
+    /// Long form:   --output <PATH>
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<String>,
+}
+
+/// Flags for `dirust serve`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct ServeArgs {
+    /// Address to listen on.
+    ///
+    /// Long form:
+    ///     --listen <ADDR:PORT>
+    ///
+    /// The API has no authentication: keep it on a loopback address unless the network
+    /// in front of it is trusted.
+    #[arg(long, default_value = "127.0.0.1:8787", value_name = "ADDR:PORT")]
+    pub listen: String,
+}
+
+/// Flags for `dirust run`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct RunArgs {
+    /// YAML job file: targets with their wordlists, flags and output files (see `jobs.rs`).
+    pub file: String,
+
+    /// Only check the job file and print the scan command line of every job.
+    ///
+    /// Long form only (boolean flag):
+    ///     --dry-run
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+}
+
+/// What `dirust completions` generates.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionTarget {
+    /// Bash completion script
+    Bash,
+    /// Zsh completion script
+    Zsh,
+    /// Fish completion script
+    Fish,
+    /// PowerShell completion script
+    Powershell,
+    /// Man page (roff), e.g. `dirust completions man > dirust.1`
+    Man,
+}
+
+/// Flags for `dirust completions`.
+#[derive(clap::Args, Debug, Clone)]
+pub struct CompletionsArgs {
+    /// Shell to generate completions for, or `man` for the man page.
+    ///
+    /// Example:
+    ///     dirust completions bash > /etc/bash_completion.d/dirust
+    #[arg(value_enum)]
+    pub target: CompletionTarget,
+}
+
+/// The process arguments, with the legacy scan syntax rewritten to the `scan` subcommand.
+///
+/// `dirust <url> -w list` (the syntax from before subcommands existed) becomes
+/// `dirust scan <url> -w list`: whenever the first argument is neither a subcommand name
+/// nor `-h`/`--help`/`-V`/`--version`, `scan` is inserted in front of it. Leading global
+/// flags are skipped over, so `dirust -q <url>` works too.
+pub fn cli_args() -> Vec<String> {
+    let mut argv: Vec<String> = std::env::args().collect();
+    let mut index: usize = 1;
+    while let Some(arg) = argv.get(index) {
+        match arg.as_str() {
+            "--no-color" | "-q" | "--quiet" | "--silent" => index += 1,
+            // Global flag with a separate value.
+            "--time-format" => index += 2,
+            other if other.starts_with("--time-format=") => index += 1,
+            _ => break,
+        }
+    }
+    if let Some(first) = argv.get(index)
+        && !is_subcommand_or_meta(first)
+    {
+        argv.insert(index, "scan".to_string());
+    }
+    argv
+}
+
+/// Parse a duration such as `30s`, `1500ms`, `2m` or `45` (seconds) for clap.
+fn parse_duration(raw: &str) -> Result<Duration, String> {
+    let text = raw.trim().to_ascii_lowercase();
+    let (number, unit_ms): (&str, u64) = if let Some(n) = text.strip_suffix("ms") {
+        (n, 1)
+    } else if let Some(n) = text.strip_suffix('s') {
+        (n, 1_000)
+    } else if let Some(n) = text.strip_suffix('m') {
+        (n, 60_000)
+    } else {
+        (text.as_str(), 1_000)
+    };
+    match number.trim().parse::<u64>() {
+        Ok(value) => Ok(Duration::from_millis(value.saturating_mul(unit_ms))),
+        Err(_) => Err(format!("invalid duration '{}' (examples: 30s, 1500ms, 2m)", raw)),
+    }
+}
+
+/// Accept `raw` as a header value for clap (no control characters, no line breaks).
+fn parse_header_text(raw: &str) -> Result<String, String> {
+    match raw.chars().any(|c| c.is_control()) {
+        true => Err(format!("invalid header value '{}' (control characters are not allowed)", raw.escape_debug())),
+        false => Ok(raw.to_string()),
+    }
+}
+
+/// Parse one `--ramp` stage such as `50:300s` or `100:0` for clap.
+fn parse_ramp_stage(raw: &str) -> Result<RampStage, String> {
+    let (concurrency, hold) = match raw.split_once(':') {
+        Some(parts) => parts,
+        None => return Err(format!("invalid ramp stage '{}' (expected CONCURRENCY:DURATION, e.g. 50:300s)", raw)),
+    };
+    let concurrency = match concurrency.trim().parse::<usize>() {
+        Ok(n) if n > 0 => n,
+        _ => return Err(format!("invalid concurrency in ramp stage '{}'", raw)),
+    };
+    let hold = parse_duration(hold)?;
+    Ok(RampStage {
+        concurrency,
+        hold: if hold.is_zero() { None } else { Some(hold) },
+    })
+}
+
+/// Whether `arg` is a subcommand name (or `help`) or a top-level help/version flag.
+fn is_subcommand_or_meta(arg: &str) -> bool {
+    if matches!(arg, "-h" | "--help" | "-V" | "--version" | "help") {
+        return true;
+    }
+    let command = Cli::command();
+    for sub in command.get_subcommands() {
+        if sub.get_name() == arg {
+            return true;
+        }
+    }
+    false
+}
+
+/// User-Agent sent when `--user-agent` is not given.
+pub const DEFAULT_USER_AGENT: &str = "dirust/0.1.1";
+
+/// What `scan --polite` sets (see `ScanArgs::apply_presets`).
+const POLITE_CONCURRENCY: usize = 2;
+const POLITE_RATE: u32 = 2;
+const POLITE_MAX_ATTEMPTS: u32 = 1;
+const POLITE_USER_AGENT: &str = "dirust/0.1.1 (polite scan)";
+
+/// What `scan --fast` sets (see `ScanArgs::apply_presets`); `bench --fast` measures around
+/// `FAST_CONCURRENCY` with `FAST_BENCH_LEVELS`.
+const FAST_CONCURRENCY: usize = 200;
+const FAST_BENCH_LEVELS: &[usize] = &[50, 100, 200, 400];
+
+impl Cli {
+    /// Parse `argv` like `Cli::try_parse_from`, then apply the presets of a `scan` command
+    /// line (see the module docs).
+    pub fn try_parse_with_presets<I, T>(argv: I) -> Result<Cli, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let matches = Cli::command().try_get_matches_from(argv)?;
+        let mut cli = Cli::from_arg_matches(&matches)?;
+        match (&mut cli.command, matches.subcommand()) {
+            (Command::Scan(scan), Some(("scan", sub_matches))) => {
+                scan.check_coordinator(sub_matches)?;
+                scan.check_pipeline()?;
+                scan.apply_presets(sub_matches);
+            }
+            (Command::Bench(bench), Some(("bench", sub_matches))) => bench.apply_presets(sub_matches),
+            _ => {}
+        }
+        Ok(cli)
+    }
+}
+
+impl HttpArgs {
+    /// The `--user-agent`, or `DEFAULT_USER_AGENT`.
+    pub fn user_agent(&self) -> &str {
+        self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT)
+    }
+
+    /// Convert the numeric `timeout` into a `Duration`.
+    ///
+    /// We keep this as a method to make call sites (client builder) explicit and readable.
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout)
+    }
+
+    /// The unspecified local address of the family chosen by `-4` / `-6` (`None`: both).
+    ///
+    /// Binding to one family makes the connector skip addresses of the other one.
+    pub fn local_address(&self) -> Option<IpAddr> {
+        if self.ipv4 {
+            Some(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+        } else if self.ipv6 {
+            Some(IpAddr::V6(Ipv6Addr::UNSPECIFIED))
+        } else {
+            None
+        }
+    }
+}
+
+impl BenchArgs {
+    /// Fill in the `--fast` values of every flag that `matches` did not get from the command
+    /// line.
+    pub fn apply_presets(&mut self, matches: &ArgMatches) {
+        if self.fast {
+            if matches.value_source("levels") != Some(ValueSource::CommandLine) {
+                self.levels = FAST_BENCH_LEVELS.to_vec();
+            }
+            self.http.http2 = true;
+        }
+    }
+}
+
+/// An `ArgumentConflict` error of `dirust scan`, reported like clap's own.
+fn scan_conflict(message: String) -> clap::Error {
+    let mut command = Cli::command();
+    let scan = command.find_subcommand_mut("scan").cloned().unwrap_or_default();
+    scan.bin_name("dirust scan").error(ErrorKind::ArgumentConflict, message)
+}
+
+/// Flags of local scans a coordinator has no use for (`--role coordinator` only hands out
+/// targets and reads back statuses), with what to do instead. `--ramp` is fine on workers.
+const COORDINATOR_CONFLICTS: &[(&str, &str, &str)] = &[
+    ("forms", "--forms", "workers do not download bodies"),
+    ("secrets", "--secrets", "workers do not download bodies"),
+    ("incremental", "--incremental", "run it as a local scan"),
+    ("state", "--state", "a distributed scan cannot be resumed"),
+    ("checkpoint", "--checkpoint", "a distributed scan cannot be resumed"),
+    ("adaptive_exts", "--adaptive-exts", "every target is built before it is handed out"),
+    ("inject_pipe", "--inject-pipe", "run it as a local scan"),
+    ("stream_to", "--stream-to", "run it as a local scan"),
+    ("syslog", "--syslog", "run it as a local scan"),
+    ("fail_on_findings", "--fail-on-findings", "run it as a local scan"),
+    ("ramp", "--ramp", "pass it to the workers, which pace themselves"),
+];
+
+impl ScanArgs {
+    /// Reject the flags a coordinator cannot honour, like `conflicts_with` would (clap has
+    /// no conflicts that depend on another flag's value, here `--role coordinator`).
+    pub fn check_coordinator(&self, matches: &ArgMatches) -> Result<(), clap::Error> {
+        if self.role != Some(Role::Coordinator) {
+            return Ok(());
+        }
+        for (id, flag, instead) in COORDINATOR_CONFLICTS {
+            if matches.value_source(id) == Some(ValueSource::CommandLine) {
+                return Err(scan_conflict(format!(
+                    "the argument '{}' cannot be used with '--role coordinator' ({})",
+                    flag, instead
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject `--pipeline N` (N > 1) without `--engine fast`, the only engine that pipelines.
+    pub fn check_pipeline(&self) -> Result<(), clap::Error> {
+        match self.pipeline > 1 && self.engine != EngineKind::Fast {
+            true => Err(scan_conflict("the argument '--pipeline' requires '--engine fast'".to_string())),
+            false => Ok(()),
+        }
+    }
+
+    /// Fill in the preset values (`--polite`, `--fast`) of every flag that `matches` did not
+    /// get from the command line.
+    pub fn apply_presets(&mut self, matches: &ArgMatches) {
+        let unset = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+        if self.polite {
+            if unset("concurrency") {
+                self.http.concurrency = POLITE_CONCURRENCY;
+            }
+            if unset("rate") {
+                self.rate = Some(POLITE_RATE);
+            }
+            if unset("max_attempts") {
+                self.max_attempts = POLITE_MAX_ATTEMPTS;
+            }
+            if unset("user_agent") {
+                self.http.user_agent = Some(POLITE_USER_AGENT.to_string());
+            }
+        }
+        if self.fast {
+            if unset("concurrency") {
+                self.http.concurrency = FAST_CONCURRENCY;
+            }
+            if unset("prewarm") {
+                self.prewarm = self.http.concurrency;
+            }
+            self.http.http2 = true;
+            self.no_progress = true;
+        }
+    }
+
+    /// The base URL argument as given on the command line.
+    ///
+    /// `clap` guarantees it is present unless `--self-test` was used; an absent value is
+    /// returned as "" which `normalize_base` rejects with a clear error.
+    pub fn base_url(&self) -> &str {
+        match &self.base {
+            Some(b) => b.as_str(),
+            None => "",
+        }
+    }
+
+    /// The wordlist path argument (same presence rules as `base_url`).
+    pub fn wordlist_path(&self) -> &str {
+        match &self.wordlist {
+            Some(w) => w.as_str(),
+            None => "",
+        }
     }
 
     /// Parse the comma-separated `exts` string into a normalized list of extensions.
@@ -96,9 +2051,9 @@ impl Args {
     ///   - Trim whitespace around each token.
     ///   - Ignore empty tokens (e.g., trailing comma).
     ///   - Ensure each extension starts with exactly one dot:
-    ///       "php"   -> ".php"
-    ///       ".html" -> ".html"
-    ///       ""      -> (ignored)
+    ///     "php"   -> ".php"
+    ///     ".html" -> ".html"
+    ///     ""      -> (ignored)
     ///
     /// Returns:
     ///   A `Vec<String>` such as: vec![".php", ".html", ".txt"]
@@ -0,0 +1,180 @@
+//! src/bench.rs
+//!
+//! Purpose:
+//!   Implement `dirust bench`: measure how many requests per second this machine can push
+//!   at a target for several concurrency values, and print the results as a table.
+//!
+//! How it works:
+//!   - For each concurrency level `c`, start `c` workers sharing one request counter.
+//!   - Each worker sends requests (HEAD, or GET with `--get`) until `--requests` have been
+//!     issued in total, recording latency and errors.
+//!   - We report requests/sec, error count and average latency per level.
+//!
+//! Reading the table:
+//!   - Against the built-in mock server (no target given), the numbers are the client-side
+//!     ceiling: if req/s stops growing with `c` there, the bottleneck is local (CPU, sockets).
+//!   - Against a real target, pick the `-c` where req/s flattens or errors/latency start rising.
+//!   - `bench --fast` uses the client options of `scan --fast` and measures around its
+//!     `-c 200`: if req/s at 200 is not clearly above 50/100, the preset buys nothing there.
+
+use crate::{
+    args::BenchArgs,
+    client,
+    error::DirustError,
+    output,
+    selftest::mock,
+    url,
+};
+use reqwest::Client;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::task::JoinHandle;
+
+/// Counters gathered by one worker.
+struct WorkerStats {
+    completed: usize,
+    errors: usize,
+    total_latency: Duration,
+}
+
+/// Run the benchmark described by `bench`.
+pub async fn run(bench: &BenchArgs) -> Result<(), DirustError> {
+    // Pick the target: either the user's URL (as-is) or a freshly started mock server.
+    let mut server: Option<mock::MockServer> = None;
+    let target: String = match &bench.target {
+        Some(t) => {
+            if !url::has_http_scheme(t) {
+                return Err(DirustError::InvalidBaseUrl);
+            }
+            t.clone()
+        }
+        None => {
+            let started = mock::start().await?;
+            let base = started.base_url();
+            server = Some(started);
+            base
+        }
+    };
+
+    let client: Client = client::build_client(&bench.http)?;
+    let method = if bench.http.get { "GET" } else { "HEAD" };
+
+    output::info(&format!("[*] bench: {} {} ({} requests per level)", method, target, bench.requests));
+    if bench.fast {
+        output::info("[*] bench: --fast client options (HTTP/2 tuned), as used by `scan --fast`");
+    }
+    println!(
+        "{:>11}  {:>8}  {:>6}  {:>9}  {:>9}  {:>8}",
+        "concurrency", "requests", "errors", "elapsed_s", "req/s", "avg_ms"
+    );
+
+    // Track the level with the best throughput for a final recommendation.
+    let mut best: Option<(usize, f64)> = None;
+
+    for &level in &bench.levels {
+        // A level of 0 would never issue a request; skip it instead of hanging.
+        if level == 0 {
+            continue;
+        }
+
+        let (stats, elapsed) = run_level(&client, &target, bench.http.get, level, bench.requests).await?;
+
+        let secs = elapsed.as_secs_f64();
+        let rps = if secs > 0.0 { stats.completed as f64 / secs } else { 0.0 };
+        let avg_ms = if stats.completed > 0 {
+            stats.total_latency.as_secs_f64() * 1000.0 / stats.completed as f64
+        } else {
+            0.0
+        };
+
+        println!(
+            "{:>11}  {:>8}  {:>6}  {:>9.2}  {:>9.1}  {:>8.2}",
+            level, stats.completed, stats.errors, secs, rps, avg_ms
+        );
+
+        let better = match best {
+            Some((_, best_rps)) => rps > best_rps,
+            None => true,
+        };
+        if better {
+            best = Some((level, rps));
+        }
+    }
+
+    if let Some((level, rps)) = best {
+        output::info(&format!("[*] best throughput: -c {} ({:.1} req/s)", level, rps));
+    }
+
+    if let Some(s) = server {
+        s.shutdown();
+    }
+
+    Ok(())
+}
+
+/// Send `total` requests to `target` using `concurrency` workers.
+///
+/// Returns the merged worker statistics and the wall-clock time for the whole level.
+async fn run_level(
+    client: &Client,
+    target: &str,
+    use_get: bool,
+    concurrency: usize,
+    total: usize,
+) -> Result<(WorkerStats, Duration), DirustError> {
+    // Shared "tickets issued" counter: workers stop once it reaches `total`.
+    let issued = Arc::new(AtomicUsize::new(0));
+    let started = Instant::now();
+
+    let mut workers: Vec<JoinHandle<WorkerStats>> = Vec::with_capacity(concurrency);
+    for _ in 0..concurrency {
+        let client_clone = client.clone();
+        let url = target.to_string();
+        let issued_clone = issued.clone();
+
+        workers.push(tokio::spawn(async move {
+            let mut stats = WorkerStats {
+                completed: 0,
+                errors: 0,
+                total_latency: Duration::ZERO,
+            };
+
+            while issued_clone.fetch_add(1, Ordering::Relaxed) < total {
+                let request_started = Instant::now();
+                let request = if use_get { client_clone.get(&url) } else { client_clone.head(&url) };
+
+                match request.send().await {
+                    Ok(_) => {
+                        stats.completed += 1;
+                        stats.total_latency += request_started.elapsed();
+                    }
+                    Err(_) => {
+                        stats.errors += 1;
+                    }
+                }
+            }
+
+            stats
+        }));
+    }
+
+    // Merge every worker's counters.
+    let mut merged = WorkerStats {
+        completed: 0,
+        errors: 0,
+        total_latency: Duration::ZERO,
+    };
+    for worker in workers {
+        let stats = worker.await?;
+        merged.completed += stats.completed;
+        merged.errors += stats.errors;
+        merged.total_latency += stats.total_latency;
+    }
+
+    Ok((merged, started.elapsed()))
+}
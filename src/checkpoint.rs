@@ -0,0 +1,177 @@
+//! src/checkpoint.rs
+//!
+//! Purpose:
+//!   Lightweight resumability for `dirust scan --checkpoint <PATH>`. Unlike a `--state` file
+//!   (every probed URL), a checkpoint only records how far the scan got: the number of
+//!   wordlist entries fully probed per base URL, and the follow-up URLs still queued. Running
+//!   the same command again continues approximately where the interrupted run stopped.
+//!
+//! File format (JSON, rewritten after every wave):
+//!   { "version": 1,
+//!     "wordlist": "common.txt",
+//!     "bases": {
+//!       "https://example.com/":  { "words": 4820, "follow_ups": ["https://example.com/login"],
+//!                                  "finished": false },
+//!       "http://example.com/":   { "words": 0, "finished": false } } }
+//!
+//! How:
+//!   - `words` counts the entries taken from the `-w` source whose targets have all been
+//!     probed; a resumed scan skips that many entries (every source kind can be skipped, not
+//!     only files).
+//!   - `follow_ups` is the queue of discovered URLs (`--follow-forms`) not probed yet.
+//!   - A finished base is skipped entirely. The file is removed once every base is finished.
+//!
+//! Notes:
+//!   - "Approximately": the wave in flight when the scan stopped is probed again, and words
+//!     injected at runtime, the `--adaptive-exts` sample and the deferred extension pass are
+//!     not recorded.
+//!   - A checkpoint is tied to its wordlist: resuming with another `-w` is an error.
+
+use crate::error::DirustError;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{BufReader, BufWriter, ErrorKind, Write},
+    sync::Mutex,
+};
+
+/// Version of the checkpoint file. Bump on any incompatible change.
+const CHECKPOINT_VERSION: u32 = 1;
+
+/// How far the scan of one base URL got.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BaseProgress {
+    /// Wordlist entries whose targets have all been probed.
+    pub words: usize,
+    /// Discovered URLs queued for the next wave.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub follow_ups: Vec<String>,
+    /// Whether the scan of this base ran to the end.
+    #[serde(default)]
+    pub finished: bool,
+}
+
+/// The whole file.
+#[derive(Debug, Serialize, Deserialize)]
+struct CheckpointFile {
+    version: u32,
+    wordlist: String,
+    bases: BTreeMap<String, BaseProgress>,
+}
+
+/// The checkpoint of one run, shared by the scans of all its bases.
+pub struct Checkpoint {
+    path: String,
+    wordlist: String,
+    bases: Mutex<BTreeMap<String, BaseProgress>>,
+}
+
+impl Checkpoint {
+    /// Open the checkpoint at `path` for a scan of `wordlist` (missing file: a new scan).
+    ///
+    /// Errors:
+    ///   - `DirustError::Io` / `DirustError::Json` if the file cannot be read or parsed
+    ///   - `DirustError::InvalidCheckpoint` if it is from a newer version or another wordlist
+    pub fn open(path: &str, wordlist: &str) -> Result<Checkpoint, DirustError> {
+        let bases = match File::open(path) {
+            Ok(file) => {
+                let parsed: CheckpointFile = serde_json::from_reader(BufReader::new(file))?;
+                if parsed.version > CHECKPOINT_VERSION {
+                    return Err(DirustError::InvalidCheckpoint(format!("unsupported version {}", parsed.version)));
+                }
+                if parsed.wordlist != wordlist {
+                    return Err(DirustError::InvalidCheckpoint(format!(
+                        "{} was written for -w {}, not {} (remove it to start over)",
+                        path, parsed.wordlist, wordlist
+                    )));
+                }
+                parsed.bases
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(DirustError::Io(e)),
+        };
+        Ok(Checkpoint {
+            path: path.to_string(),
+            wordlist: wordlist.to_string(),
+            bases: Mutex::new(bases),
+        })
+    }
+
+    /// Where the scan of `base` stopped (`None` if it never started or has finished).
+    pub fn resume_point(&self, base: &str) -> Option<BaseProgress> {
+        let bases = match self.bases.lock() {
+            Ok(g) => g,
+            Err(p) => p.into_inner(),
+        };
+        bases
+            .get(base)
+            .filter(|progress| !progress.finished && (progress.words > 0 || !progress.follow_ups.is_empty()))
+            .cloned()
+    }
+
+    /// Whether the scan of `base` already ran to the end.
+    pub fn is_finished(&self, base: &str) -> bool {
+        let bases = match self.bases.lock() {
+            Ok(g) => g,
+            Err(p) => p.into_inner(),
+        };
+        bases.get(base).is_some_and(|progress| progress.finished)
+    }
+
+    /// Record the position of `base` and write the file.
+    pub fn record(&self, base: &str, words: usize, follow_ups: &[String]) -> Result<(), DirustError> {
+        self.write_with(base, |progress| {
+            progress.words = words;
+            progress.follow_ups = follow_ups.to_vec();
+        })
+    }
+
+    /// Mark `base` as scanned to the end and write the file.
+    pub fn finish(&self, base: &str) -> Result<(), DirustError> {
+        self.write_with(base, |progress| {
+            progress.follow_ups.clear();
+            progress.finished = true;
+        })
+    }
+
+    /// Delete the file (the whole run is complete).
+    pub fn remove(&self) -> Result<(), DirustError> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(DirustError::Io(e)),
+        }
+    }
+
+    /// The file path.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Change the progress of `base`, then rewrite the file (through a temporary file, so an
+    /// interruption mid-write leaves the previous checkpoint intact).
+    fn write_with<F>(&self, base: &str, change: F) -> Result<(), DirustError>
+    where
+        F: FnOnce(&mut BaseProgress),
+    {
+        let mut bases = match self.bases.lock() {
+            Ok(g) => g,
+            Err(p) => p.into_inner(),
+        };
+        change(bases.entry(base.to_string()).or_default());
+
+        let file = CheckpointFile {
+            version: CHECKPOINT_VERSION,
+            wordlist: self.wordlist.clone(),
+            bases: bases.clone(),
+        };
+        let temporary = format!("{}.tmp", self.path);
+        let mut out = BufWriter::new(File::create(&temporary)?);
+        serde_json::to_writer_pretty(&mut out, &file)?;
+        out.flush()?;
+        drop(out);
+        fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+}
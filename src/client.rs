@@ -0,0 +1,336 @@
+//! src/client.rs
+//!
+//! Purpose:
+//!   Build the single reusable HTTP client (`reqwest::Client`) from the shared client flags
+//!   (`HttpArgs`).
+//!
+//! Why a separate module?
+//!   - Both the normal scan and auxiliary modes (e.g., `--self-test`) need an identically
+//!     configured client; keeping construction in one place guarantees they match.
+//!
+//! Proxies:
+//!   `--proxy` wins. Otherwise `HTTP_PROXY` / `HTTPS_PROXY` (`ALL_PROXY` for both; lower-case
+//!   names too) are used for `http://` / `https://` targets, except for hosts listed in
+//!   `NO_PROXY`; `--no-env-proxy` ignores them all. They are read here rather than left to
+//!   reqwest, so `describe_proxy` can say in the scan header which proxy a target goes
+//!   through. A SOCKS5 proxy from the environment follows `--proxy-dns` like `--proxy` does
+//!   (`socks5h://` by default: no local DNS query).
+
+use crate::{
+    args::{HttpArgs, ProxyDns},
+    error::DirustError,
+    tls,
+};
+use reqwest::{
+    header::{self, HeaderMap, HeaderValue},
+    Client, ClientBuilder, NoProxy, Proxy, Url,
+};
+use std::{net::IpAddr, time::Duration};
+
+/// How often idle HTTP/2 connections are pinged with `--http2` (keeps the pool warm).
+const HTTP2_PING_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Build the shared HTTP client.
+///
+/// This client is cheap to clone and will share connection pools among tasks. We set:
+///   - a custom User-Agent (helps identify the tool in logs; `--user-agent`), and the
+///     `--from` contact address as `From`
+///   - redirect policy = none (we want to *see* 30x + Location headers)
+///   - a per-request timeout derived from CLI (to avoid hung sockets)
+///   - an explicit `Accept-Encoding` when `--compressed` / `--no-compression` is given
+///     (by default none is sent, and servers usually answer uncompressed)
+///   - a browser-like TLS configuration when `--tls-impersonate` is given
+///   - the `--proxy`, with SOCKS5 name resolution following `--proxy-dns`, or the proxies
+///     of the environment (unless `--no-env-proxy`)
+///   - IPv4 or IPv6 only with `-4` / `-6`
+///   - adaptive HTTP/2 flow control and keep-alive pings with `--http2`, and HTTP/2 without
+///     negotiation with `--http2-prior-knowledge`
+///
+/// Errors:
+///   - Any reqwest build error becomes `DirustError::Http` via `From`.
+pub fn build_client(args: &HttpArgs) -> Result<Client, DirustError> {
+    let client: Client = builder(args)?.build()?;
+    Ok(client)
+}
+
+/// Build a client like `build_client`, but writing HTTP/1.1 header names in Title-Case
+/// (`User-Agent` instead of `user-agent`), as browsers do. Used for `scan --evade` probes.
+pub fn build_title_case_client(args: &HttpArgs) -> Result<Client, DirustError> {
+    let client: Client = builder(args)?.http1_title_case_headers().build()?;
+    Ok(client)
+}
+
+/// The client configuration shared by `build_client` and `build_title_case_client`.
+fn builder(args: &HttpArgs) -> Result<ClientBuilder, DirustError> {
+    let mut headers = HeaderMap::new();
+    if args.compressed {
+        // Only encodings `scanner::http::read_body` can decode.
+        headers.insert(header::ACCEPT_ENCODING, HeaderValue::from_static("gzip, deflate"));
+    } else if args.no_compression {
+        headers.insert(header::ACCEPT_ENCODING, HeaderValue::from_static("identity"));
+    }
+    // (Checked by clap already: `parse_header_text`.)
+    if let Some(from) = &args.from
+        && let Ok(value) = HeaderValue::from_str(from)
+    {
+        headers.insert(header::FROM, value);
+    }
+
+    let mut builder = Client::builder()
+        .user_agent(args.user_agent())
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(args.request_timeout())
+        .default_headers(headers);
+    // reqwest sends a preconfigured TLS configuration as it is: without ALPN, h2 would never
+    // be negotiated and the ClientHello would lack the extension every browser sends.
+    if let Some(profile) = args.tls_impersonate {
+        let mut config = tls::client_config(profile)?;
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        builder = builder.use_preconfigured_tls(config);
+    }
+    // `-4` / `-6`; otherwise both families are raced (hyper's Happy Eyeballs).
+    if let Some(address) = args.local_address() {
+        builder = builder.local_address(address);
+    }
+    // `--http2`: windows grow with the bandwidth-delay product instead of staying at 64 KiB,
+    // so many concurrent streams on one connection do not stall each other.
+    if args.http2 {
+        builder = builder
+            .http2_adaptive_window(true)
+            .http2_keep_alive_interval(HTTP2_PING_INTERVAL)
+            .http2_keep_alive_while_idle(true);
+    }
+    if args.http2_prior_knowledge {
+        builder = builder.http2_prior_knowledge();
+    }
+    // An explicit proxy also turns off the `*_PROXY` environment variables.
+    if let Some(proxy) = proxy_url(args)? {
+        return Ok(builder.proxy(Proxy::all(&proxy)?));
+    }
+    builder = builder.no_proxy();
+    if !args.no_env_proxy {
+        let exceptions: Option<NoProxy> = env_no_proxy().and_then(|list| NoProxy::from_string(&list));
+        if let Some((name, value)) = env_proxy("http") {
+            let proxy = env_proxy_url(name, &value, args.proxy_dns)?;
+            builder = builder.proxy(Proxy::http(&proxy)?.no_proxy(exceptions.clone()));
+        }
+        if let Some((name, value)) = env_proxy("https") {
+            let proxy = env_proxy_url(name, &value, args.proxy_dns)?;
+            builder = builder.proxy(Proxy::https(&proxy)?.no_proxy(exceptions));
+        }
+    }
+    Ok(builder)
+}
+
+/// Where requests to `url` go, for the scan header: `--proxy`, the environment proxy for its
+/// scheme (or why it is bypassed), `None` when no proxy is configured anywhere.
+pub fn describe_proxy(args: &HttpArgs, url: &str) -> Option<String> {
+    if let Ok(Some(proxy)) = proxy_url(args) {
+        return Some(format!("{} (--proxy)", proxy));
+    }
+    let scheme = match url.split_once("://") {
+        Some((scheme, _)) => scheme.to_ascii_lowercase(),
+        None => "https".to_string(),
+    };
+    let (name, value) = env_proxy(&scheme)?;
+    if args.no_env_proxy {
+        return Some(format!("none (--no-env-proxy; {} ignored)", name));
+    }
+    let host = Url::parse(url).ok()?.host_str()?.trim_matches(|c| c == '[' || c == ']').to_ascii_lowercase();
+    if let Some(list) = env_no_proxy()
+        && no_proxy_matches(&list, &host)
+    {
+        return Some(format!("none ({} is in NO_PROXY; {} bypassed)", host, name));
+    }
+    Some(format!("{} ({})", env_proxy_url(name, &value, args.proxy_dns).unwrap_or(value), name))
+}
+
+/// Whether the environment sets a proxy that `--no-env-proxy` would turn off: (variable).
+pub fn env_proxy_in_use(args: &HttpArgs) -> Option<&'static str> {
+    if args.proxy.is_some() || args.no_env_proxy {
+        return None;
+    }
+    env_proxy("https").or_else(|| env_proxy("http")).map(|(name, _)| name)
+}
+
+/// The environment variable setting the proxy for `scheme` URLs, and its value.
+fn env_proxy(scheme: &str) -> Option<(&'static str, String)> {
+    let names: &[&'static str] = match scheme {
+        "http" => &["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"],
+        _ => &["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"],
+    };
+    names.iter().find_map(|&name| match std::env::var(name) {
+        Ok(value) if !value.trim().is_empty() => Some((name, value.trim().to_string())),
+        _ => None,
+    })
+}
+
+/// The `NO_PROXY` list (`no_proxy` too), if set.
+fn env_no_proxy() -> Option<String> {
+    std::env::var("NO_PROXY").or_else(|_| std::env::var("no_proxy")).ok()
+}
+
+/// The proxy URL of environment variable `name` (`http://` added to a bare `host:port`),
+/// its SOCKS scheme adjusted to `dns` like `--proxy`.
+///
+/// Errors:
+///   - `DirustError::InvalidProxy` for a scheme other than http, https, socks5, socks5h
+fn env_proxy_url(name: &str, value: &str, dns: ProxyDns) -> Result<String, DirustError> {
+    let (scheme, rest) = match value.split_once("://") {
+        Some(parts) => parts,
+        None => return Ok(format!("http://{}", value)),
+    };
+    match proxy_scheme(scheme, dns) {
+        Some(scheme) => Ok(format!("{}://{}", scheme, rest)),
+        None => Err(DirustError::InvalidProxy(format!(
+            "{}={}: unsupported scheme {}:// (use http, https, socks5 or socks5h, or --no-env-proxy)",
+            name, value, scheme
+        ))),
+    }
+}
+
+/// The scheme to use for a proxy given as `scheme://`: SOCKS5 as `socks5h` (the proxy
+/// resolves names) or `socks5` (resolved here) after `dns`; `None` if unsupported.
+fn proxy_scheme(scheme: &str, dns: ProxyDns) -> Option<&'static str> {
+    match (scheme.to_ascii_lowercase().as_str(), dns) {
+        ("http", _) => Some("http"),
+        ("https", _) => Some("https"),
+        ("socks5" | "socks5h", ProxyDns::Remote) => Some("socks5h"),
+        ("socks5" | "socks5h", ProxyDns::Local) => Some("socks5"),
+        _ => None,
+    }
+}
+
+/// Whether `host` is exempted by the `NO_PROXY` list `list`: `*`, a domain (matching its
+/// subdomains too; a leading `.` is ignored), an IP address or a network (`10.0.0.0/8`).
+fn no_proxy_matches(list: &str, host: &str) -> bool {
+    let address: Option<IpAddr> = host.parse().ok();
+    list.split(',').map(str::trim).filter(|entry| !entry.is_empty()).any(|entry| {
+        if entry == "*" {
+            return true;
+        }
+        match (address, entry.split_once('/')) {
+            (Some(address), Some((network, bits))) => match (network.parse::<IpAddr>(), bits.parse::<u32>()) {
+                (Ok(network), Ok(bits)) => in_network(address, network, bits),
+                _ => false,
+            },
+            (Some(address), None) => entry.parse::<IpAddr>() == Ok(address),
+            (None, _) => {
+                let domain = entry.trim_start_matches('*').trim_start_matches('.').to_ascii_lowercase();
+                host == domain || host.ends_with(&format!(".{}", domain))
+            }
+        }
+    })
+}
+
+/// Whether `address` lies in `network`/`bits`.
+fn in_network(address: IpAddr, network: IpAddr, bits: u32) -> bool {
+    match (address, network) {
+        (IpAddr::V4(a), IpAddr::V4(n)) if bits <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - bits).unwrap_or(0);
+            u32::from(a) & mask == u32::from(n) & mask
+        }
+        (IpAddr::V6(a), IpAddr::V6(n)) if bits <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - bits).unwrap_or(0);
+            u128::from(a) & mask == u128::from(n) & mask
+        }
+        _ => false,
+    }
+}
+
+/// The `--proxy` URL with its SOCKS scheme adjusted to `--proxy-dns` (`socks5h://` for
+/// remote resolution, `socks5://` for local), or `None` without `--proxy`.
+///
+/// Errors:
+///   - `DirustError::InvalidProxy` for a scheme other than http, https, socks5, socks5h
+fn proxy_url(args: &HttpArgs) -> Result<Option<String>, DirustError> {
+    let proxy = match &args.proxy {
+        Some(p) => p.trim(),
+        None => return Ok(None),
+    };
+    let (scheme, rest) = match proxy.split_once("://") {
+        Some(parts) => parts,
+        None => return Err(DirustError::InvalidProxy(format!("{} (expected <scheme>://host:port)", proxy))),
+    };
+    match proxy_scheme(scheme, args.proxy_dns) {
+        Some(scheme) => Ok(Some(format!("{}://{}", scheme, rest))),
+        None => Err(DirustError::InvalidProxy(format!(
+            "unsupported scheme {}:// (use http, https, socks5 or socks5h)",
+            scheme
+        ))),
+    }
+}
+
+/// Send one request to `base` before a scan and fail with the reason if it gets no answer
+/// at all (any HTTP status is fine).
+///
+/// Without this, a typo'd host or a closed port surfaces as the error of some random probe
+/// after millions of targets were generated.
+///
+/// Errors:
+///   - `DirustError::Unreachable` naming DNS, refused connection, TLS or timeout failures
+pub async fn preflight(client: &Client, base: &str) -> Result<(), DirustError> {
+    match client.head(base).send().await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(DirustError::Unreachable(base.to_string(), describe_failure(&e))),
+    }
+}
+
+/// Name the kind of a connection failure, followed by the innermost error message.
+pub fn describe_failure(error: &reqwest::Error) -> String {
+    let detail = causes(error).pop().unwrap_or_else(|| error.to_string());
+    format!("{} ({})", failure_kind(error), detail)
+}
+
+/// The messages of an error's causes, outermost first: reqwest → hyper → io / TLS.
+fn causes(error: &reqwest::Error) -> Vec<String> {
+    let mut messages: Vec<String> = Vec::new();
+    let mut source: Option<&dyn std::error::Error> = std::error::Error::source(error);
+    while let Some(cause) = source {
+        messages.push(cause.to_string());
+        source = cause.source();
+    }
+    messages
+}
+
+/// The kind of a connection failure, as `describe_failure` names it.
+fn failure_kind(error: &reqwest::Error) -> &'static str {
+    let chain = causes(error).join(": ").to_ascii_lowercase();
+
+    if error.is_timeout() {
+        "no answer before --timeout"
+    } else if chain.contains("dns error") || chain.contains("lookup address") {
+        "DNS lookup failed"
+    } else if chain.contains("connection refused") {
+        "connection refused"
+    } else if chain.contains("tls") || chain.contains("ssl") || chain.contains("certificate") {
+        "TLS handshake failed"
+    } else if chain.contains("unreachable") {
+        "network unreachable"
+    } else {
+        "request failed"
+    }
+}
+
+/// Return `true` if `url` answers at all (any HTTP status), `false` on connect/TLS errors.
+///
+/// Used to skip the `--ports` / `--both-schemes` candidates that do not answer.
+pub async fn is_reachable(client: &Client, url: &str) -> bool {
+    client.head(url).send().await.is_ok()
+}
+
+/// Why `url` cannot be spoken to over its scheme, if that is the reason it gets no answer:
+/// the TLS handshake failed or the TCP handshake was refused (`describe_failure` text).
+///
+/// Used to fall back from `https://` to `http://` for scheme-less base URLs. DNS failures,
+/// timeouts and the like return `None`: the other scheme would not fare better, and the scan
+/// reports them as they are.
+pub async fn handshake_failure(client: &Client, url: &str) -> Option<String> {
+    match client.head(url).send().await {
+        Ok(_) => None,
+        Err(e) => match failure_kind(&e) {
+            "TLS handshake failed" | "connection refused" => Some(describe_failure(&e)),
+            _ => None,
+        },
+    }
+}
@@ -0,0 +1,45 @@
+//! src/completions.rs
+//!
+//! Purpose:
+//!   `dirust completions <SHELL|man>`: generate shell completion scripts (bash, zsh, fish,
+//!   PowerShell) with `clap_complete`, or a roff man page with `clap_mangen`, from the same
+//!   `clap` definition that parses the command line — so they never drift from the real flags.
+//!
+//! Everything is written to stdout; packagers redirect it to the usual locations, e.g.
+//!   dirust completions bash > /usr/share/bash-completion/completions/dirust
+//!   dirust completions zsh  > /usr/share/zsh/site-functions/_dirust
+//!   dirust completions fish > /usr/share/fish/vendor_completions.d/dirust.fish
+//!   dirust completions man  > /usr/share/man/man1/dirust.1
+
+use crate::{
+    args::{Cli, CompletionTarget, CompletionsArgs},
+    error::DirustError,
+};
+use clap::CommandFactory;
+use clap_complete::{generate, Shell};
+use std::io::{self, Write};
+
+/// Name the generated scripts complete (the installed binary name).
+const BIN_NAME: &str = "dirust";
+
+/// Write the requested completion script or man page to stdout.
+pub fn run(args: &CompletionsArgs) -> Result<(), DirustError> {
+    let mut command = Cli::command();
+    let mut out = io::stdout().lock();
+
+    let shell = match args.target {
+        CompletionTarget::Bash => Shell::Bash,
+        CompletionTarget::Zsh => Shell::Zsh,
+        CompletionTarget::Fish => Shell::Fish,
+        CompletionTarget::Powershell => Shell::PowerShell,
+        CompletionTarget::Man => {
+            clap_mangen::Man::new(command).render(&mut out)?;
+            out.flush()?;
+            return Ok(());
+        }
+    };
+
+    generate(shell, &mut command, BIN_NAME, &mut out);
+    out.flush()?;
+    Ok(())
+}
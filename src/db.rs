@@ -0,0 +1,214 @@
+//! src/db.rs
+//!
+//! Purpose:
+//!   A tiny persistent "results database" that remembers, per URL, what the last scan saw:
+//!   the status code plus the cache validators (`ETag`, `Last-Modified`) the server returned.
+//!
+//! Why?
+//!   - With `--incremental`, the scanner sends these validators back as
+//!     `If-None-Match` / `If-Modified-Since`. Unchanged endpoints then answer `304 Not Modified`
+//!     and are not reported again, so repeated monitoring runs only show new or changed content.
+//!
+//! Storage format:
+//!   - A single JSON file (path given by `--results-db <PATH>`) in the versioned
+//!     `ScanResult` schema (see `schema.rs`); `scan` describes the most recent run.
+//!   - A missing file is treated as an empty database (first run).
+//!   - The file is rewritten in full at the end of every scan (through a temporary file in
+//!     the same directory, renamed over it).
+//!
+//! The same file doubles as a "previous result set" for `--new-only <PATH>`, which hides
+//! findings (same URL + same status) that an earlier scan already reported.
+//!
+//! The database also remembers which URLs the running scan reported (not saved): exports
+//! describe this run (`this_run`), not everything earlier runs left in the file.
+
+use crate::{
+    error::DirustError,
+    schema::{ResultEntry, ScanMetadata, ScanResult, SCHEMA_VERSION},
+};
+use reqwest::StatusCode;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{BufReader, BufWriter, ErrorKind, Write},
+};
+
+/// In-memory results database, keyed by URL.
+#[derive(Debug, Default)]
+pub struct ResultsDb {
+    entries: HashMap<String, ResultEntry>,
+    /// URLs reported as findings by the running scan.
+    reported: HashSet<String>,
+}
+
+impl ResultsDb {
+    /// Load the database from `path`.
+    ///
+    /// Behavior:
+    ///   - File does not exist → `Ok(empty database)` (this is simply the first run).
+    ///   - File exists but cannot be read/parsed → `Err(DirustError)`.
+    pub fn load(path: &str) -> Result<ResultsDb, DirustError> {
+        match ResultsDb::read(path) {
+            Ok(db) => Ok(db),
+            Err(DirustError::Io(e)) if e.kind() == ErrorKind::NotFound => Ok(ResultsDb::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Read an existing result set from `path`.
+    ///
+    /// Unlike `load`, a missing file is an error: this is used for `--new-only`, where the
+    /// user explicitly points at a previous scan and a typo should not silently report everything.
+    pub fn read(path: &str) -> Result<ResultsDb, DirustError> {
+        let parsed: ScanResult = read_result_file(path)?;
+
+        let mut entries: HashMap<String, ResultEntry> = HashMap::new();
+        for entry in parsed.results {
+            entries.insert(entry.url.clone(), entry);
+        }
+
+        Ok(ResultsDb {
+            entries,
+            reported: HashSet::new(),
+        })
+    }
+
+    /// Write the whole database to `path` as pretty-printed JSON.
+    ///
+    /// `meta` describes the run that just finished and becomes the `scan` block.
+    /// Entries are sorted by URL so consecutive runs produce stable, diffable files.
+    pub fn save(&self, path: &str, meta: &ScanMetadata) -> Result<(), DirustError> {
+        let list: Vec<ResultEntry> = self.sorted_entries();
+
+        let out = ScanResult {
+            schema_version: SCHEMA_VERSION,
+            scan: meta.clone(),
+            results: list,
+        };
+        write_result_file(path, &out)
+    }
+
+    /// Return a copy of every entry, sorted by URL (stable output for files and exports).
+    pub fn sorted_entries(&self) -> Vec<ResultEntry> {
+        let mut list: Vec<ResultEntry> = self.entries.values().cloned().collect();
+        list.sort_by(|a, b| a.url.cmp(&b.url));
+        list
+    }
+
+    /// Return a database holding only the entries whose URL starts with `prefix`
+    /// (e.g., one base URL of a multi-host scan).
+    pub fn subset(&self, prefix: &str) -> ResultsDb {
+        let entries: HashMap<String, ResultEntry> = self
+            .entries
+            .iter()
+            .filter(|(url, _)| url.starts_with(prefix))
+            .map(|(url, entry)| (url.clone(), entry.clone()))
+            .collect();
+        let reported: HashSet<String> = self.reported.iter().filter(|url| url.starts_with(prefix)).cloned().collect();
+        ResultsDb { entries, reported }
+    }
+
+    /// Replace every entry whose URL starts with `prefix` with the entries of `part` (a
+    /// `subset` that was scanned on its own).
+    pub fn replace_subset(&mut self, prefix: &str, part: ResultsDb) {
+        self.entries.retain(|url, _| !url.starts_with(prefix));
+        self.entries.extend(part.entries);
+        self.reported.extend(part.reported);
+    }
+
+    /// Note that the running scan reported `url` as a finding (call before `apply`).
+    pub fn mark_reported(&mut self, url: &str) {
+        self.reported.insert(url.to_string());
+    }
+
+    /// A database holding only the findings the running scan reported, as stored now:
+    /// entries of earlier runs and findings hidden by `--new-only` are left out.
+    pub fn this_run(&self) -> ResultsDb {
+        let entries: HashMap<String, ResultEntry> = self
+            .entries
+            .iter()
+            .filter(|(url, _)| self.reported.contains(*url))
+            .map(|(url, entry)| (url.clone(), entry.clone()))
+            .collect();
+        ResultsDb {
+            entries,
+            reported: self.reported.clone(),
+        }
+    }
+
+    /// Return the stored entry for `url`, if any.
+    pub fn get(&self, url: &str) -> Option<&ResultEntry> {
+        self.entries.get(url)
+    }
+
+    /// Fold the outcome of one probe into the database.
+    ///
+    /// Rules:
+    ///   - `304 Not Modified`: the stored entry is still accurate → keep it, refreshing only
+    ///     the tracked headers the 304 carried (`response_headers`).
+    ///   - "interesting" status (see `keep`): store/replace the entry with the fresh validators;
+    ///     a `dirust triage` decision survives as long as the status is the same.
+    ///   - anything else (e.g., 404): the content is gone → forget the URL.
+    pub fn apply(&mut self, mut entry: ResultEntry, keep: bool) {
+        if entry.status == StatusCode::NOT_MODIFIED.as_u16() {
+            if let Some(fresh) = entry.response_headers
+                && let Some(stored) = self.entries.get_mut(&entry.url)
+            {
+                stored.response_headers.get_or_insert_with(Default::default).extend(fresh);
+            }
+            return;
+        }
+
+        if keep {
+            if let Some(stored) = self.entries.get(&entry.url)
+                && stored.status == entry.status
+                && entry.triage.is_none()
+            {
+                entry.triage = stored.triage.clone();
+            }
+            self.entries.insert(entry.url.clone(), entry);
+        } else {
+            self.entries.remove(&entry.url);
+        }
+    }
+}
+
+/// Write a whole result file as pretty-printed JSON (replacing `path`).
+///
+/// Used by `ResultsDb::save` and by `dirust triage`, which edits a file in place. The JSON goes
+/// to `<path>.tmp` first and is renamed over `path` once fully written, so a crash or a full
+/// disk leaves the previous file intact instead of a truncated one.
+pub fn write_result_file(path: &str, result: &ScanResult) -> Result<(), DirustError> {
+    let temporary = format!("{}.tmp", path);
+    let written = write_json(&temporary, result);
+    if written.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    written?;
+    fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+/// Serialize `result` to `path`, with write errors surfaced by the final flush.
+fn write_json(path: &str, result: &ScanResult) -> Result<(), DirustError> {
+    let mut out = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut out, result)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Read a whole result file (metadata included) from `path`, checking its schema version.
+///
+/// Used by `ResultsDb::read` and by the commands that work on result files
+/// (`dirust report`, `dirust diff`).
+pub fn read_result_file(path: &str) -> Result<ScanResult, DirustError> {
+    let file = File::open(path)?;
+    let parsed: ScanResult = serde_json::from_reader(BufReader::new(file))?;
+
+    // Files written by a newer, incompatible Dirust cannot be trusted field-by-field.
+    if parsed.schema_version > SCHEMA_VERSION {
+        return Err(DirustError::UnsupportedSchema(parsed.schema_version));
+    }
+
+    Ok(parsed)
+}
@@ -0,0 +1,92 @@
+//! src/diff.rs
+//!
+//! Purpose:
+//!   `dirust diff <OLD> <NEW>`: compare two results files and show what changed between
+//!   the scans — useful for monitoring a target over time.
+//!
+//! Output (new and changed first, then gone; sorted by URL within each group):
+//!   [+] <finding line>                  present only in NEW
+//!   [-] <finding line>                  present only in OLD
+//!   [~] <finding line>  (was <status>)  same URL, different status or Content-Length
+//!   [~] header <url>  <change>            same URL, tracked header changed (see `headerdiff.rs`)
+//!   [*] diff: <a> new, <b> gone, <c> changed (<d> with header changes)
+//!
+//! Finding lines use the `dirust report` format. Headers are compared only when both files
+//! recorded them (`response_headers`).
+
+use crate::{
+    args::DiffArgs,
+    db,
+    error::DirustError,
+    headerdiff,
+    output::{self, Style},
+    report,
+    schema::ResultEntry,
+};
+use std::collections::BTreeMap;
+
+/// Compare `args.old` with `args.new` and print the differences.
+pub fn run(args: &DiffArgs) -> Result<(), DirustError> {
+    let old = by_url(db::read_result_file(&args.old)?.results);
+    let new = by_url(db::read_result_file(&args.new)?.results);
+
+    let mut added: usize = 0;
+    let mut removed: usize = 0;
+    let mut changed: usize = 0;
+    let mut header_changed: usize = 0;
+
+    for (url, entry) in &new {
+        match old.get(url) {
+            None => {
+                let line = format!("[+] {}", report::format_entry(entry));
+                output::finding(&line, Style::Plain, Some(&format!("+ {}", url)));
+                added += 1;
+            }
+            Some(before) => {
+                if before.status != entry.status || before.content_length != entry.content_length {
+                    let was_len = match before.content_length {
+                        Some(len) => len.to_string(),
+                        None => "-".to_string(),
+                    };
+                    let line = format!(
+                        "[~] {}  (was {} len={})",
+                        report::format_entry(entry),
+                        before.status,
+                        was_len
+                    );
+                    output::finding(&line, Style::Plain, Some(&format!("~ {}", url)));
+                    changed += 1;
+                }
+                if let (Some(was), Some(now)) = (&before.response_headers, &entry.response_headers) {
+                    let changes = headerdiff::compare(was, now, false);
+                    if !changes.is_empty() {
+                        headerdiff::print_changes(url, &changes);
+                        header_changed += 1;
+                    }
+                }
+            }
+        }
+    }
+    for (url, entry) in &old {
+        if !new.contains_key(url) {
+            let line = format!("[-] {}", report::format_entry(entry));
+            output::finding(&line, Style::Plain, Some(&format!("- {}", url)));
+            removed += 1;
+        }
+    }
+
+    output::info(&format!(
+        "[*] diff: {} new, {} gone, {} changed ({} with header changes)",
+        added, removed, changed, header_changed
+    ));
+    Ok(())
+}
+
+/// Index entries by URL (sorted, so output order is stable).
+fn by_url(entries: Vec<ResultEntry>) -> BTreeMap<String, ResultEntry> {
+    let mut map: BTreeMap<String, ResultEntry> = BTreeMap::new();
+    for entry in entries {
+        map.insert(entry.url.clone(), entry);
+    }
+    map
+}
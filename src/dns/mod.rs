@@ -0,0 +1,367 @@
+//! src/dns/mod.rs
+//!
+//! Purpose:
+//!   `dirust dns <DOMAIN>`: subdomain brute force through DNS. Every wordlist entry `label`
+//!   becomes the name `label.<DOMAIN>`, which is resolved with the system resolver, or with a
+//!   pool of resolvers queried directly (`--resolver`); names that resolve are printed with
+//!   their addresses.
+//!
+//! Submodules:
+//!   - pool.rs : `--resolver` pool: round robin, per-resolver rate limits, health checks,
+//!     retries on SERVFAIL / timeouts
+//!   - wire.rs : DNS query encoding and answer decoding (A, AAAA, CNAME)
+//!
+//! Wildcard DNS:
+//!   Before enumerating, two random names that cannot exist (`dirust<hex>.<DOMAIN>`) are
+//!   resolved. If they resolve, the zone has a wildcard record and every name "exists";
+//!   the scan then compares instead of filtering on "resolves": names whose addresses are
+//!   all wildcard addresses (or, with `--resolver`, that end in the wildcard's CNAME target)
+//!   are hidden, names with any other answer are reported and tagged `wildcard-zone`.
+//!   With a pool, every resolver is asked: when only some of them answer for the random
+//!   names, those rewrite NXDOMAIN answers (ISP "search" pages) and are dropped instead.
+//!
+//! Permutations:
+//!   With `--permute`, the names found are varied (`vpn` → `vpn-stage`, `vpn2`, `dev.vpn`;
+//!   see `permute.rs`) and the variants resolved in another round, up to `--permute-depth`
+//!   rounds. Found variants name the find they came from.
+//!
+//! Output:
+//!   [2024-04-05T19:21:18.042Z] vpn.example.com                          203.0.113.7
+//!   [2024-04-05T19:21:18.311Z] shop.example.com                         198.51.100.4  (CNAME shops.example.net)
+//!   `--ndjson <PATH>` writes the same names as
+//!   `{"schema_version":1,"target":"example.com","result":{...}}`
+//!   lines, the envelope of `--stream-to` (see `schema::DnsEntry`).
+//!
+//! Notes:
+//!   - Lookups run through the shared bounded-concurrency engine (`engine::run_bounded`),
+//!     at most `-c` at a time.
+//!   - NXDOMAIN simply means "not found" and is not printed. Lookups the pool could not get
+//!     any answer for (after `--dns-retries`) are listed on stderr and counted.
+//!   - Wildcards served from rotating address pools (CDNs) may show more addresses than
+//!     the two samples; such names still get reported unless a CNAME gives them away.
+
+pub mod pool;
+pub mod wire;
+
+use crate::{
+    args::DnsArgs,
+    engine,
+    error::DirustError,
+    fuzz::baseline::random_token,
+    output::{self, Style},
+    permute::Permuter,
+    schema::{DnsEntry, SCHEMA_VERSION},
+    scanner::wordlist,
+};
+use pool::{Lookup, ResolverPool};
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{BufWriter, Write},
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+
+/// What the random names of a wildcard zone resolve to (both empty: no wildcard).
+#[derive(Debug, Default)]
+struct Wildcard {
+    addresses: Vec<IpAddr>,
+    cnames: Vec<String>,
+}
+
+impl Wildcard {
+    fn is_empty(&self) -> bool {
+        self.addresses.is_empty() && self.cnames.is_empty()
+    }
+
+    /// Whether an answer is just the wildcard's.
+    fn matches(&self, addresses: &[IpAddr], cnames: &[String]) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let same_target = match cnames.last() {
+            Some(target) => self.cnames.contains(target),
+            None => false,
+        };
+        same_target || addresses.iter().all(|a| self.addresses.contains(a))
+    }
+}
+
+/// A name that resolved.
+struct Resolved {
+    addresses: Vec<IpAddr>,
+    cnames: Vec<String>,
+    /// The resolver that answered (`None`: the system resolver).
+    resolver: Option<SocketAddr>,
+}
+
+/// How the lookup of one name ended.
+enum Outcome {
+    /// The name resolved and was printed.
+    Reported(String),
+    Hidden,
+    Failed,
+}
+
+/// One `--ndjson` line.
+#[derive(Serialize)]
+struct NdjsonLine<'a> {
+    /// See `schema::SCHEMA_VERSION`.
+    schema_version: u32,
+    target: &'a str,
+    result: &'a DnsEntry,
+}
+
+/// Run subdomain enumeration for `args.domain`.
+pub async fn run(args: &DnsArgs) -> Result<(), DirustError> {
+    let domain: String = args.domain.trim().trim_matches('.').to_ascii_lowercase();
+    let labels = wordlist::read_wordlist(&args.wordlist)?;
+
+    let mut names: Vec<String> = Vec::with_capacity(labels.len());
+    for raw in &labels {
+        let label = raw.trim().trim_matches('.').to_ascii_lowercase();
+        if label.is_empty() {
+            continue;
+        }
+        names.push(format!("{}.{}", label, domain));
+    }
+
+    // `--resolver` / `--resolver-file`: a pool queried directly (none: the system resolver).
+    let addrs = pool::parse_resolvers(&args.resolver, args.resolver_file.as_deref())?;
+    let pool: Option<Arc<ResolverPool>> = match addrs.is_empty() {
+        true => None,
+        false => {
+            let pool = ResolverPool::new(addrs, args.resolver_rate, args.dns_retries);
+            pool.check(&domain).await?;
+            Some(Arc::new(pool))
+        }
+    };
+
+    let ndjson: Option<Arc<Mutex<BufWriter<File>>>> = match &args.ndjson {
+        Some(path) => Some(Arc::new(Mutex::new(BufWriter::new(File::create(path)?)))),
+        None => None,
+    };
+
+    match &pool {
+        Some(pool) => output::info(&format!(
+            "[*] dns: resolving {} name(s) under {} with {} resolver(s), {} retries",
+            names.len(),
+            domain,
+            pool.active_count(),
+            args.dns_retries
+        )),
+        None => output::info(&format!("[*] dns: resolving {} name(s) under {}", names.len(), domain)),
+    }
+
+    // Wildcard zone: "resolves" means nothing, compare against the wildcard answer instead.
+    let wildcard: Arc<Wildcard> = Arc::new(match &pool {
+        Some(pool) => detect_pool_wildcard(pool, &domain).await,
+        None => Wildcard {
+            addresses: detect_wildcard(&domain).await,
+            cnames: Vec::new(),
+        },
+    });
+    if !wildcard.is_empty() {
+        let mut answer: Vec<String> = wildcard.addresses.iter().map(|a| a.to_string()).collect();
+        answer.extend(wildcard.cnames.iter().map(|c| format!("CNAME {}", c)));
+        output::info(&format!(
+            "[*] dns: wildcard DNS detected (*.{} -> {}); names answering only with these are hidden",
+            domain,
+            answer.join(",")
+        ));
+    }
+
+    // `--permute`: after the wordlist, resolve variants of the names found, round by round.
+    let mut permuter = Permuter::from_args(&args.permute)?;
+    // Names already looked up: a variant that is also a wordlist entry is not asked twice.
+    let mut tried: HashSet<String> = names.iter().cloned().collect();
+    let mut queue: Vec<(String, Option<String>)> = names.into_iter().map(|n| (n, None)).collect();
+    let mut outcomes: Vec<Outcome> = Vec::new();
+    while !queue.is_empty() {
+        let round: Vec<Outcome> = engine::run_bounded(queue, args.concurrency, |(name, origin): (String, Option<String>)| {
+            let wildcard = Arc::clone(&wildcard);
+            let pool = pool.clone();
+            let ndjson = ndjson.clone();
+            let domain = domain.clone();
+            async move {
+                let resolved = match &pool {
+                    Some(pool) => match pool.resolve(&name).await {
+                        Lookup::Found {
+                            addresses,
+                            cnames,
+                            resolver,
+                        } => Resolved {
+                            addresses,
+                            cnames,
+                            resolver: Some(resolver),
+                        },
+                        Lookup::NotFound => return Outcome::Hidden,
+                        Lookup::Failed(reason) => {
+                            eprintln!("[!] dns: no answer for {}: {}", name, reason);
+                            return Outcome::Failed;
+                        }
+                    },
+                    None => Resolved {
+                        addresses: resolve(&name).await,
+                        cnames: Vec::new(),
+                        resolver: None,
+                    },
+                };
+                if resolved.addresses.is_empty() || wildcard.matches(&resolved.addresses, &resolved.cnames) {
+                    return Outcome::Hidden;
+                }
+
+                let list: Vec<String> = resolved.addresses.iter().map(|a| a.to_string()).collect();
+                let mut via = match resolved.cnames.last() {
+                    Some(target) => format!("  (CNAME {})", target),
+                    None => String::new(),
+                };
+                if let Some(origin) = &origin {
+                    via.push_str(&format!("  (permutation of {})", origin));
+                }
+                let line = format!("{}{:<40} {}{}", output::timestamp_prefix(), name, list.join(","), via);
+                output::finding(&line, Style::Plain, Some(&name));
+
+                if let Some(out) = &ndjson {
+                    let entry = DnsEntry {
+                        name: name.clone(),
+                        addresses: list,
+                        cnames: resolved.cnames,
+                        resolver: resolved.resolver.map(|r| r.to_string()),
+                        permuted_from: origin,
+                        tags: match wildcard.is_empty() {
+                            true => Vec::new(),
+                            false => vec!["wildcard-zone".to_string()],
+                        },
+                    };
+                    let line = NdjsonLine {
+                        schema_version: SCHEMA_VERSION,
+                        target: &domain,
+                        result: &entry,
+                    };
+                    if let Ok(text) = serde_json::to_string(&line) {
+                        let mut out = match out.lock() {
+                            Ok(guard) => guard,
+                            Err(poisoned) => poisoned.into_inner(),
+                        };
+                        if let Err(e) = writeln!(out, "{}", text) {
+                            eprintln!("[!] dns: could not write --ndjson line: {}", e);
+                        }
+                    }
+                }
+                Outcome::Reported(name)
+            }
+        })
+        .await?;
+
+        queue = Vec::new();
+        if let Some(permuter) = &mut permuter {
+            let mut varied: usize = 0;
+            for outcome in &round {
+                if let Outcome::Reported(name) = outcome {
+                    let variants: Vec<String> = permuter
+                        .name_variants(name, &domain)
+                        .into_iter()
+                        .filter(|v| tried.insert(v.clone()))
+                        .collect();
+                    varied += usize::from(!variants.is_empty());
+                    queue.extend(variants.into_iter().map(|v| (v, Some(name.clone()))));
+                }
+            }
+            if !queue.is_empty() {
+                output::info(&format!("[*] dns: permute: resolving {} variant(s) of {} name(s)", queue.len(), varied));
+            }
+        }
+        outcomes.extend(round);
+    }
+
+    if let Some(out) = &ndjson {
+        let mut out = match out.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        out.flush()?;
+    }
+
+    let found = outcomes.iter().filter(|o| matches!(o, Outcome::Reported(_))).count();
+    let failed = outcomes.iter().filter(|o| matches!(o, Outcome::Failed)).count();
+    match failed {
+        0 => output::info(&format!("[*] dns: {} name(s) resolved", found)),
+        _ => output::info(&format!(
+            "[*] dns: {} name(s) resolved, {} lookup(s) got no answer from any resolver",
+            found, failed
+        )),
+    }
+    if let Some(pool) = &pool {
+        pool.print_summary();
+    }
+
+    Ok(())
+}
+
+/// Resolve two random, certainly unregistered names under `domain`.
+///
+/// Returns the union of their addresses: empty when neither resolves (no wildcard),
+/// otherwise the addresses the wildcard record answers with.
+pub async fn detect_wildcard(domain: &str) -> Vec<IpAddr> {
+    let mut addrs: Vec<IpAddr> = Vec::new();
+    for _ in 0..2 {
+        let probe = format!("{}.{}", random_token(), domain);
+        for addr in resolve(&probe).await {
+            if !addrs.contains(&addr) {
+                addrs.push(addr);
+            }
+        }
+    }
+    addrs
+}
+
+/// Ask every resolver of `pool` for two random names under `domain`. Resolvers answering
+/// where others say the name does not exist are dropped; when all of them answer, the
+/// answers are the wildcard's.
+async fn detect_pool_wildcard(pool: &ResolverPool, domain: &str) -> Wildcard {
+    let mut wildcard = Wildcard::default();
+    for _ in 0..2 {
+        let probe = format!("{}.{}", random_token(), domain);
+        let answers = pool.ask_each(&probe).await;
+        let denying = answers.iter().filter(|(_, a)| matches!(a, Some(r) if r.addresses.is_empty())).count();
+        for (index, answer) in answers {
+            let Some(response) = answer else {
+                continue;
+            };
+            if response.addresses.is_empty() {
+                continue;
+            }
+            if denying > 0 {
+                pool.drop_resolver(index, &format!("answers for {}, which {} other resolver(s) say does not exist", probe, denying));
+                continue;
+            }
+            for addr in response.addresses {
+                if !wildcard.addresses.contains(&addr) {
+                    wildcard.addresses.push(addr);
+                }
+            }
+            if let Some(target) = response.cnames.last()
+                && !wildcard.cnames.contains(target)
+            {
+                wildcard.cnames.push(target.clone());
+            }
+        }
+    }
+    wildcard
+}
+
+/// Resolve `name` to its distinct addresses (empty when it does not resolve).
+async fn resolve(name: &str) -> Vec<IpAddr> {
+    // `lookup_host` wants a socket address; the port is irrelevant for resolution.
+    let mut addrs: Vec<IpAddr> = Vec::new();
+    if let Ok(found) = tokio::net::lookup_host((name, 0)).await {
+        for socket in found {
+            if !addrs.contains(&socket.ip()) {
+                addrs.push(socket.ip());
+            }
+        }
+    }
+    addrs
+}
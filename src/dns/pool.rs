@@ -0,0 +1,373 @@
+//! src/dns/pool.rs
+//!
+//! Purpose:
+//!   `--resolver`: spread the lookups of `dirust dns` over a pool of DNS resolvers, queried
+//!   directly over UDP, instead of the system resolver. A large wordlist then does not hammer
+//!   one resolver into rate-limiting us, and one flaky resolver does not cost findings.
+//!
+//! How:
+//!   - Resolvers take turns (round robin). Each one sends at most `--resolver-rate` queries
+//!     per second, evenly spaced.
+//!   - Before the run, every resolver is asked for the domain itself; the ones that do not
+//!     answer are left out (none answering is an error).
+//!   - An answer that says nothing (SERVFAIL, REFUSED, no answer within `QUERY_TIMEOUT`) is
+//!     retried on another resolver, up to `--dns-retries` times. NXDOMAIN is an answer.
+//!   - `BENCH_AFTER` failures in a row bench a resolver for `BENCH_TIME`; its next query after
+//!     that is its health check. When every resolver is benched, lookups wait for the first
+//!     one to come back.
+//!
+//! Output (stderr, when a resolver is benched):
+//!   [!] dns: resolver 8.8.8.8:53 benched for 30s (5 failures in a row: SERVFAIL)
+
+use super::wire::{self, Rcode, RecordType, Response};
+use crate::{error::DirustError, fuzz::baseline::random_below, output, scanner::wordlist};
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+use tokio::{net::UdpSocket, time::Instant};
+
+/// How long one query waits for its answer.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Failures in a row that bench a resolver.
+const BENCH_AFTER: usize = 5;
+
+/// How long a benched resolver gets no queries.
+const BENCH_TIME: Duration = Duration::from_secs(30);
+
+/// Largest answer read (EDNS is not used, so answers are at most 512 bytes).
+const MAX_ANSWER: usize = 512;
+
+/// What a name resolved to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lookup {
+    /// Addresses (A and AAAA) and CNAME targets, and the resolver that answered.
+    Found {
+        addresses: Vec<IpAddr>,
+        cnames: Vec<String>,
+        resolver: SocketAddr,
+    },
+    /// NXDOMAIN, or no address records.
+    NotFound,
+    /// No resolver gave an answer within the retries (the last reason).
+    Failed(String),
+}
+
+/// Counters and pacing of one resolver.
+#[derive(Debug)]
+struct State {
+    /// When its next query may leave (`--resolver-rate`).
+    next_slot: Instant,
+    /// Failures since its last answer.
+    failures: usize,
+    /// It gets no queries before this.
+    benched_until: Option<Instant>,
+    /// Left out for good (no answer before the run, or it invents answers).
+    dropped: bool,
+    queries: u64,
+    failed: u64,
+}
+
+/// One resolver of the pool.
+#[derive(Debug)]
+struct Resolver {
+    addr: SocketAddr,
+    state: Mutex<State>,
+}
+
+impl Resolver {
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        match self.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// The resolvers of one `dirust dns` run, shared by all lookups.
+#[derive(Debug)]
+pub struct ResolverPool {
+    resolvers: Vec<Resolver>,
+    /// Minimum spacing of one resolver's queries (zero: unlimited).
+    interval: Duration,
+    /// Extra attempts for a query that got no answer.
+    retries: usize,
+    /// Round-robin position.
+    turn: AtomicUsize,
+}
+
+impl ResolverPool {
+    /// A pool of `addrs`, each sending at most `rate` queries per second (0 = unlimited),
+    /// retrying unanswered queries `retries` times.
+    pub fn new(addrs: Vec<SocketAddr>, rate: u32, retries: usize) -> ResolverPool {
+        let now = Instant::now();
+        ResolverPool {
+            resolvers: addrs
+                .into_iter()
+                .map(|addr| Resolver {
+                    addr,
+                    state: Mutex::new(State {
+                        next_slot: now,
+                        failures: 0,
+                        benched_until: None,
+                        dropped: false,
+                        queries: 0,
+                        failed: 0,
+                    }),
+                })
+                .collect(),
+            interval: match rate {
+                0 => Duration::ZERO,
+                r => Duration::from_secs_f64(1.0 / f64::from(r)),
+            },
+            retries,
+            turn: AtomicUsize::new(0),
+        }
+    }
+
+    /// Ask every resolver for `domain`; leave out the ones that do not answer.
+    ///
+    /// Errors:
+    ///   - `DirustError::InvalidResolver` when none answers
+    pub async fn check(&self, domain: &str) -> Result<(), DirustError> {
+        for index in 0..self.resolvers.len() {
+            if let Err(reason) = self.query_with(index, domain, RecordType::A).await {
+                self.drop_resolver(index, &format!("no answer for {}: {}", domain, reason));
+            }
+        }
+        match self.active().is_empty() {
+            true => Err(DirustError::InvalidResolver(format!(
+                "none of the {} resolver(s) answered a query for {}",
+                self.resolvers.len(),
+                domain
+            ))),
+            false => Ok(()),
+        }
+    }
+
+    /// Resolve `name` on each active resolver once (for wildcard checks): per resolver, its
+    /// address, and what it answered (`None`: no answer).
+    pub async fn ask_each(&self, name: &str) -> Vec<(usize, Option<Response>)> {
+        let mut answers = Vec::new();
+        for index in self.active() {
+            answers.push((index, self.query_with(index, name, RecordType::A).await.ok()));
+        }
+        answers
+    }
+
+    /// Leave resolver `index` out for the rest of the run, saying why.
+    pub fn drop_resolver(&self, index: usize, reason: &str) {
+        let resolver = &self.resolvers[index];
+        resolver.lock().dropped = true;
+        eprintln!("[!] dns: resolver {} not used ({})", resolver.addr, reason);
+    }
+
+    /// Resolve the A and AAAA records of `name`.
+    pub async fn resolve(&self, name: &str) -> Lookup {
+        let (a, resolver) = match self.query(name, RecordType::A).await {
+            Ok(answer) => answer,
+            Err(reason) => return Lookup::Failed(reason),
+        };
+        if a.rcode == Rcode::NxDomain {
+            return Lookup::NotFound;
+        }
+        let mut addresses = a.addresses;
+        let mut cnames = a.cnames;
+        // A name without IPv6 is common; a failed AAAA query keeps what A found.
+        if let Ok((aaaa, _)) = self.query(name, RecordType::Aaaa).await {
+            addresses.extend(aaaa.addresses);
+            for cname in aaaa.cnames {
+                if !cnames.contains(&cname) {
+                    cnames.push(cname);
+                }
+            }
+        }
+        match addresses.is_empty() {
+            true => Lookup::NotFound,
+            false => Lookup::Found {
+                addresses,
+                cnames,
+                resolver,
+            },
+        }
+    }
+
+    /// Print queries and failures per resolver.
+    pub fn print_summary(&self) {
+        for resolver in &self.resolvers {
+            let state = resolver.lock();
+            output::info(&format!(
+                "    resolver {:<24} {:>7} queries {:>5} unanswered{}",
+                resolver.addr.to_string(),
+                state.queries,
+                state.failed,
+                if state.dropped { " (not used)" } else { "" }
+            ));
+        }
+    }
+
+    /// Resolvers not left out.
+    pub fn active_count(&self) -> usize {
+        self.active().len()
+    }
+
+    /// Indexes of the resolvers that were not dropped.
+    fn active(&self) -> Vec<usize> {
+        (0..self.resolvers.len()).filter(|&i| !self.resolvers[i].lock().dropped).collect()
+    }
+
+    /// Ask `name` on the next resolvers in turn until one answers (NOERROR or NXDOMAIN).
+    async fn query(&self, name: &str, record: RecordType) -> Result<(Response, SocketAddr), String> {
+        let mut last = String::from("no resolver");
+        for _ in 0..=self.retries {
+            let index = self.pick().await?;
+            match self.query_with(index, name, record).await {
+                Ok(response) => return Ok((response, self.resolvers[index].addr)),
+                Err(reason) => last = format!("{}: {}", self.resolvers[index].addr, reason),
+            }
+        }
+        Err(last)
+    }
+
+    /// The next resolver to ask: the next one in turn that is not benched; when all are, the
+    /// first to come back, after waiting for it.
+    async fn pick(&self) -> Result<usize, String> {
+        let active = self.active();
+        if active.is_empty() {
+            return Err("every resolver was left out".to_string());
+        }
+        let start = self.turn.fetch_add(1, Ordering::Relaxed);
+        let now = Instant::now();
+        let mut soonest: Option<(Instant, usize)> = None;
+        for offset in 0..active.len() {
+            let index = active[(start + offset) % active.len()];
+            match self.resolvers[index].lock().benched_until {
+                Some(until) if until > now => {
+                    if soonest.is_none_or(|(s, _)| until < s) {
+                        soonest = Some((until, index));
+                    }
+                }
+                _ => return Ok(index),
+            }
+        }
+        match soonest {
+            Some((until, index)) => {
+                tokio::time::sleep_until(until).await;
+                Ok(index)
+            }
+            None => Err("every resolver was left out".to_string()),
+        }
+    }
+
+    /// Send one query to resolver `index` (after its rate-limit slot) and read the answer.
+    /// SERVFAIL, REFUSED and other error codes count as failures, like timeouts.
+    async fn query_with(&self, index: usize, name: &str, record: RecordType) -> Result<Response, String> {
+        let resolver = &self.resolvers[index];
+        let slot = {
+            let mut state = resolver.lock();
+            let slot = state.next_slot.max(Instant::now());
+            state.next_slot = slot + self.interval;
+            state.queries += 1;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+
+        let outcome = match tokio::time::timeout(QUERY_TIMEOUT, exchange(resolver.addr, name, record)).await {
+            Ok(Ok(response)) => match response.rcode {
+                Rcode::NoError | Rcode::NxDomain => Ok(response),
+                Rcode::ServFail => Err("SERVFAIL".to_string()),
+                Rcode::Refused => Err("REFUSED".to_string()),
+                Rcode::Other(code) => Err(format!("response code {}", code)),
+            },
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(format!("no answer within {}s", QUERY_TIMEOUT.as_secs())),
+        };
+
+        let mut state = resolver.lock();
+        match &outcome {
+            Ok(_) => {
+                state.failures = 0;
+                state.benched_until = None;
+            }
+            Err(reason) => {
+                state.failed += 1;
+                state.failures += 1;
+                if state.failures >= BENCH_AFTER && !state.dropped {
+                    state.benched_until = Some(Instant::now() + BENCH_TIME);
+                    // Its next query after the bench is the health check: fail again, bench again.
+                    state.failures = BENCH_AFTER - 1;
+                    eprintln!(
+                        "[!] dns: resolver {} benched for {}s ({} failures in a row: {})",
+                        resolver.addr,
+                        BENCH_TIME.as_secs(),
+                        BENCH_AFTER,
+                        reason
+                    );
+                }
+            }
+        }
+        outcome
+    }
+}
+
+/// One UDP round trip: send the query for `name` to `addr` and wait for its answer (stray
+/// datagrams are skipped; the caller bounds the wait).
+async fn exchange(addr: SocketAddr, name: &str, record: RecordType) -> Result<Response, String> {
+    let id = random_below(usize::from(u16::MAX) + 1) as u16;
+    let query = match wire::encode_query(id, name, record) {
+        Some(query) => query,
+        None => return Err(format!("{} is not a valid DNS name", name)),
+    };
+    let local: SocketAddr = match addr {
+        SocketAddr::V4(_) => SocketAddr::from(([0, 0, 0, 0], 0)),
+        SocketAddr::V6(_) => SocketAddr::from(([0u16; 8], 0)),
+    };
+    let socket = UdpSocket::bind(local).await.map_err(|e| e.to_string())?;
+    socket.connect(addr).await.map_err(|e| e.to_string())?;
+    socket.send(&query).await.map_err(|e| e.to_string())?;
+    let mut buffer = [0u8; MAX_ANSWER];
+    loop {
+        let length = socket.recv(&mut buffer).await.map_err(|e| e.to_string())?;
+        if let Some(response) = wire::decode_response(&buffer[..length], id, name, record) {
+            return Ok(response);
+        }
+    }
+}
+
+/// The resolver addresses of `--resolver` entries and `--resolver-file` lines: an IP address
+/// with an optional port (`1.1.1.1`, `9.9.9.9:53`, `2606:4700::1111`, `[2606:4700::1111]:53`).
+///
+/// Errors:
+///   - `DirustError::InvalidResolver` for an entry that is not an address
+pub fn parse_resolvers(entries: &[String], file: Option<&str>) -> Result<Vec<SocketAddr>, DirustError> {
+    let mut raw: Vec<String> = entries.to_vec();
+    if let Some(path) = file {
+        raw.extend(wordlist::read_wordlist(path)?);
+    }
+    let mut addrs: Vec<SocketAddr> = Vec::new();
+    for entry in raw {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let addr = match (entry.parse::<SocketAddr>(), entry.parse::<IpAddr>()) {
+            (Ok(addr), _) => addr,
+            (_, Ok(ip)) => SocketAddr::new(ip, 53),
+            _ => {
+                return Err(DirustError::InvalidResolver(format!(
+                    "'{}' is not an IP address (examples: 1.1.1.1, 9.9.9.9:53, [2606:4700::1111]:53)",
+                    entry
+                )));
+            }
+        };
+        if !addrs.contains(&addr) {
+            addrs.push(addr);
+        }
+    }
+    Ok(addrs)
+}
@@ -0,0 +1,258 @@
+//! src/dns/wire.rs
+//!
+//! Purpose:
+//!   Just enough of the DNS wire format (RFC 1035) to ask a resolver for the A or AAAA records
+//!   of one name and read the answer: the response code, the addresses and the CNAME chain.
+//!
+//! Notes:
+//!   - Queries ask for recursion (RD) and carry one question; answers are matched to their
+//!     query by id and question name.
+//!   - Compressed names (pointers) are followed, with a hop limit against pointer loops.
+//!   - A truncated (TC) answer is used as far as it goes: A/AAAA answers practically always
+//!     fit into a UDP datagram, and retrying over TCP is not worth a second transport.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Record types asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordType {
+    A,
+    Aaaa,
+}
+
+impl RecordType {
+    fn code(self) -> u16 {
+        match self {
+            RecordType::A => 1,
+            RecordType::Aaaa => 28,
+        }
+    }
+}
+
+/// Record type code of CNAME records.
+const TYPE_CNAME: u16 = 5;
+
+/// Class IN.
+const CLASS_IN: u16 = 1;
+
+/// Pointers followed while reading one name.
+const MAX_POINTER_HOPS: usize = 16;
+
+/// The response code of an answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rcode {
+    NoError,
+    /// The name does not exist.
+    NxDomain,
+    /// The resolver could not get an answer (often a broken or rate-limiting upstream).
+    ServFail,
+    /// The resolver does not answer us.
+    Refused,
+    Other(u8),
+}
+
+impl Rcode {
+    fn from_code(code: u8) -> Rcode {
+        match code {
+            0 => Rcode::NoError,
+            2 => Rcode::ServFail,
+            3 => Rcode::NxDomain,
+            5 => Rcode::Refused,
+            other => Rcode::Other(other),
+        }
+    }
+}
+
+/// What a resolver answered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub rcode: Rcode,
+    /// Addresses of the asked type, in answer order.
+    pub addresses: Vec<IpAddr>,
+    /// CNAME targets, in answer order (lower case, no trailing dot).
+    pub cnames: Vec<String>,
+}
+
+/// Encode a recursive query with `id` for the `record` records of `name`.
+///
+/// Returns `None` for names that cannot be encoded (a label empty or longer than 63 bytes).
+pub fn encode_query(id: u16, name: &str, record: RecordType) -> Option<Vec<u8>> {
+    let mut packet: Vec<u8> = Vec::with_capacity(18 + name.len());
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&0x0100u16.to_be_bytes()); // RD
+    packet.extend_from_slice(&1u16.to_be_bytes()); // QDCOUNT
+    packet.extend_from_slice(&[0, 0, 0, 0, 0, 0]); // ANCOUNT, NSCOUNT, ARCOUNT
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return None;
+        }
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&record.code().to_be_bytes());
+    packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Some(packet)
+}
+
+/// Decode the answer to query `id` for `name`.
+///
+/// Returns `None` when `packet` is not that answer (another id or question, not a response,
+/// malformed).
+pub fn decode_response(packet: &[u8], id: u16, name: &str, record: RecordType) -> Option<Response> {
+    if packet.len() < 12 || u16::from_be_bytes([packet[0], packet[1]]) != id {
+        return None;
+    }
+    let flags = u16::from_be_bytes([packet[2], packet[3]]);
+    if flags & 0x8000 == 0 {
+        return None;
+    }
+    let rcode = Rcode::from_code((flags & 0x000f) as u8);
+    let questions = u16::from_be_bytes([packet[4], packet[5]]);
+    let answers = u16::from_be_bytes([packet[6], packet[7]]);
+
+    let mut pos: usize = 12;
+    let wanted = name.trim_end_matches('.').to_ascii_lowercase();
+    for _ in 0..questions {
+        let (asked, next) = read_name(packet, pos)?;
+        if asked != wanted {
+            return None;
+        }
+        pos = next + 4;
+    }
+
+    let mut response = Response {
+        rcode,
+        addresses: Vec::new(),
+        cnames: Vec::new(),
+    };
+    for _ in 0..answers {
+        let (_, next) = read_name(packet, pos)?;
+        let header = packet.get(next..next + 10)?;
+        let kind = u16::from_be_bytes([header[0], header[1]]);
+        let length = u16::from_be_bytes([header[8], header[9]]) as usize;
+        let data_start = next + 10;
+        let data = packet.get(data_start..data_start + length)?;
+        match kind {
+            1 if record == RecordType::A && length == 4 => {
+                response.addresses.push(IpAddr::V4(Ipv4Addr::new(data[0], data[1], data[2], data[3])));
+            }
+            28 if record == RecordType::Aaaa && length == 16 => {
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(data);
+                response.addresses.push(IpAddr::V6(Ipv6Addr::from(octets)));
+            }
+            TYPE_CNAME => {
+                let (target, _) = read_name(packet, data_start)?;
+                response.cnames.push(target);
+            }
+            _ => {}
+        }
+        pos = data_start + length;
+    }
+    Some(response)
+}
+
+/// Read the (possibly compressed) name at `start`: the name in lower case without a trailing
+/// dot, and the position after it in the packet.
+fn read_name(packet: &[u8], start: usize) -> Option<(String, usize)> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut pos = start;
+    // Where reading continues once a pointer was followed.
+    let mut resume: Option<usize> = None;
+    let mut hops: usize = 0;
+    loop {
+        let length = *packet.get(pos)? as usize;
+        match length {
+            0 => {
+                pos += 1;
+                break;
+            }
+            l if l & 0xc0 == 0xc0 => {
+                hops += 1;
+                if hops > MAX_POINTER_HOPS {
+                    return None;
+                }
+                let target = ((l & 0x3f) << 8) | *packet.get(pos + 1)? as usize;
+                if resume.is_none() {
+                    resume = Some(pos + 2);
+                }
+                pos = target;
+            }
+            l => {
+                let label = packet.get(pos + 1..pos + 1 + l)?;
+                labels.push(String::from_utf8_lossy(label).to_ascii_lowercase());
+                pos += 1 + l;
+            }
+        }
+    }
+    Some((labels.join("."), resume.unwrap_or(pos)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_response, encode_query, Rcode, RecordType, Response};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    const ID: u16 = 0x1234;
+    const NAME: &str = "example.com";
+
+    /// Offset of the first answer record: header (12) + "example.com" (13) + type, class (4).
+    const ANSWER_AT: u8 = 29;
+
+    /// The answer to our query with `records` appended (ANCOUNT from `count`).
+    fn packet(count: u16, records: &[u8]) -> Vec<u8> {
+        let mut packet = encode_query(ID, NAME, RecordType::A).expect("query");
+        packet[2..4].copy_from_slice(&0x8180u16.to_be_bytes()); // QR, RD, RA
+        packet[6..8].copy_from_slice(&count.to_be_bytes());
+        packet.extend_from_slice(records);
+        packet
+    }
+
+    /// One resource record: `name` (wire form), type, class IN, TTL 60, RDLENGTH, RDATA.
+    fn record(name: &[u8], kind: u16, rdlength: u16, data: &[u8]) -> Vec<u8> {
+        let mut out = name.to_vec();
+        out.extend_from_slice(&kind.to_be_bytes());
+        out.extend_from_slice(&1u16.to_be_bytes());
+        out.extend_from_slice(&60u32.to_be_bytes());
+        out.extend_from_slice(&rdlength.to_be_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    fn addresses(addresses: &[[u8; 4]]) -> Option<Response> {
+        Some(Response {
+            rcode: Rcode::NoError,
+            addresses: addresses.iter().map(|a| IpAddr::V4(Ipv4Addr::from(*a))).collect(),
+            cnames: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn decode_response_on_malformed_packets() {
+        // A pointer to the question name (offset 12), as resolvers send it.
+        let to_question: &[u8] = &[0xc0, 12];
+        let cases: Vec<(&str, Vec<u8>, Option<Response>)> = vec![
+            ("well-formed", packet(1, &record(to_question, 1, 4, &[192, 0, 2, 1])), addresses(&[[192, 0, 2, 1]])),
+            ("truncated header", packet(1, &[])[..11].to_vec(), None),
+            ("answer count past the end", packet(2, &record(to_question, 1, 4, &[192, 0, 2, 1])), None),
+            ("pointer to itself", packet(1, &record(&[0xc0, ANSWER_AT], 1, 4, &[192, 0, 2, 1])), None),
+            (
+                "two pointers to each other",
+                packet(1, &record(&[0xc0, ANSWER_AT + 2, 0xc0, ANSWER_AT], 1, 4, &[192, 0, 2, 1])),
+                None,
+            ),
+            ("pointer past the end", packet(1, &record(&[0xc0, 0xff], 1, 4, &[192, 0, 2, 1])), None),
+            ("pointer in the CNAME data past the end", packet(1, &record(to_question, 5, 2, &[0xff, 0xff])), None),
+            ("RDLENGTH past the end", packet(1, &record(to_question, 1, 0x0100, &[192, 0, 2, 1])), None),
+            (
+                "oversized A record is skipped",
+                packet(1, &record(to_question, 1, 6, &[192, 0, 2, 1, 0, 0])),
+                addresses(&[]),
+            ),
+        ];
+        for (case, packet, expected) in cases {
+            assert_eq!(decode_response(&packet, ID, NAME, RecordType::A), expected, "{}", case);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! src/engine.rs
+//!
+//! Purpose:
+//!   The bounded-concurrency engine shared by the auxiliary modes (fuzzing, storage
+//!   enumeration, ...): run one async job per item with at most N jobs in flight, and
+//!   return every job's result in input order.
+//!
+//! Design choices (same rules as the directory scanner):
+//!   - A semaphore permit is acquired BEFORE each task is spawned, so the number of active
+//!     tasks never exceeds the limit.
+//!   - The permit lives inside the task and is released when the task finishes.
+//!   - A task that panics or is cancelled surfaces as `DirustError::Join`.
+
+use crate::error::DirustError;
+use std::{future::Future, sync::Arc};
+use tokio::{sync::Semaphore, task::JoinHandle};
+
+/// Run `work(item)` for every item, at most `concurrency` at a time.
+///
+/// Returns:
+///   - Ok(results) in the same order as `items`
+///   - Err(DirustError::Join) if any task panicked or was cancelled
+pub async fn run_bounded<T, R, F, Fut>(
+    items: Vec<T>,
+    concurrency: usize,
+    work: F,
+) -> Result<Vec<R>, DirustError>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = R> + Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    let mut jobs: Vec<JoinHandle<R>> = Vec::with_capacity(items.len());
+
+    for item in items {
+        // Wait for a free slot. `acquire_owned` only fails if the semaphore is closed,
+        // which never happens here because we own it; treat it as a skipped item anyway.
+        let permit = match semaphore.clone().acquire_owned().await {
+            Ok(p) => p,
+            Err(_) => {
+                eprintln!("[!] failed to acquire semaphore permit");
+                continue;
+            }
+        };
+
+        // Build the future outside the task (so `work` itself need not be `Send`/'static),
+        // then run it inside the task while holding the permit.
+        let future = work(item);
+        jobs.push(tokio::spawn(async move {
+            let _permit = permit;
+            future.await
+        }));
+    }
+
+    let mut results: Vec<R> = Vec::with_capacity(jobs.len());
+    for job in jobs {
+        results.push(job.await?);
+    }
+
+    Ok(results)
+}
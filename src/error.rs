@@ -7,6 +7,7 @@
 //! - It lets us print friendly messages (`Display`) while still keeping debug info (`Debug`).
 //! - It allows the `?` operator to convert common error types into `DirustError` via `From`.
 
+use crate::severity::Severity;
 use std::{error::Error, fmt};
 
 /// Top-level error type for the application.
@@ -30,6 +31,85 @@ pub enum DirustError {
 
     /// An async task failed to join (panic/cancellation surfaced as `JoinError`).
     Join(tokio::task::JoinError),
+
+    /// A JSON file (e.g., the results database) could not be parsed or written.
+    Json(serde_json::Error),
+
+    /// A TOML file (e.g., `--rules`) could not be parsed.
+    Toml(toml::de::Error),
+
+    /// A result file was written with a newer, unsupported `schema_version`.
+    UnsupportedSchema(u32),
+
+    /// `--self-test` found this many expectations that did not hold.
+    SelfTestFailed(usize),
+
+    /// A `--state` file cannot be used to resume a scan (bad header or command line).
+    InvalidState(String),
+
+    /// The coordinator of a distributed scan sent something a worker did not expect.
+    Protocol(String),
+
+    /// A request sent by `--engine fast` failed (connect, TLS, protocol).
+    Engine(String),
+
+    /// A request sent by `--engine fast` got no response within its timeout (the URL).
+    Timeout(String),
+
+    /// A request sent by the byte-exact HTTP/1.1 sender (`--raw-engine`) failed (connect,
+    /// TLS, a response that cannot be read).
+    Raw(String),
+
+    /// A `--request` template cannot be used (reason).
+    InvalidTemplate(String),
+
+    /// A new credential for `AuthRefresh` (`--auth-cmd`) could not be obtained (reason).
+    AuthRefresh(String),
+
+    /// The TLS configuration for `--tls-impersonate` could not be built.
+    Tls(rustls::Error),
+
+    /// A `--secret-rules` pattern is not a valid regular expression.
+    Regex(regex::Error),
+
+    /// A `--scope` file has a rule that cannot be parsed (line and reason).
+    InvalidScope(String),
+
+    /// `--proxy` is not a usable proxy URL, or cannot be combined with other flags.
+    InvalidProxy(String),
+
+    /// A `-w` source expression cannot be parsed (reason).
+    InvalidSource(String),
+
+    /// A `--stream-to` sink is not a `tcp://` / `unix://` address or cannot be connected (reason).
+    StreamSink(String),
+
+    /// A scan above `--confirm-above` was not confirmed (estimated requests, threshold).
+    NotConfirmed(u64, u64),
+
+    /// The scan stopped after a hit rate spike (`--pause-on-spike`; current and usual rate, %).
+    HitRateSpike(f64, f64),
+
+    /// A `--checkpoint` file cannot be used for this scan (reason).
+    InvalidCheckpoint(String),
+
+    /// A `--syslog` target cannot be parsed or reached (reason).
+    Syslog(String),
+
+    /// A `dirust run` job file cannot be read or one of its jobs is invalid (reason).
+    InvalidJobFile(String),
+
+    /// Jobs of a `dirust run` job file failed (failed jobs, all jobs).
+    JobsFailed(usize, usize),
+
+    /// `--fail-on-findings`: the scan reported findings at the threshold (count, threshold).
+    FindingsAtSeverity(usize, Severity),
+
+    /// The preflight request to a base URL failed (base URL, reason).
+    Unreachable(String, String),
+
+    /// A `dirust dns --resolver` entry is not an address, or no resolver answers (reason).
+    InvalidResolver(String),
 }
 
 /// Human-readable error messages.
@@ -52,6 +132,121 @@ impl fmt::Display for DirustError {
 
             DirustError::Join(e) =>
                 write!(f, "task join error: {}", e),
+
+            DirustError::Json(e) =>
+                write!(f, "json error: {}", e),
+
+            DirustError::Toml(e) =>
+                write!(f, "toml error: {}", e),
+
+            DirustError::UnsupportedSchema(v) =>
+                write!(f, "unsupported result schema_version {}", v),
+
+            DirustError::SelfTestFailed(n) =>
+                write!(f, "self-test failed: {} check(s) did not match", n),
+
+            DirustError::InvalidState(reason) =>
+                write!(f, "cannot resume from state file: {}", reason),
+
+            DirustError::Protocol(reason) =>
+                write!(f, "distributed scan protocol error: {}", reason),
+
+            DirustError::Engine(reason) =>
+                write!(f, "fast engine error: {}", reason),
+
+            DirustError::Timeout(url) =>
+                write!(f, "request timed out: {}", url),
+
+            DirustError::Raw(reason) =>
+                write!(f, "raw http error: {}", reason),
+
+            DirustError::InvalidTemplate(reason) =>
+                write!(f, "invalid request template: {}", reason),
+
+            DirustError::AuthRefresh(reason) =>
+                write!(f, "auth refresh failed: {}", reason),
+
+            DirustError::Tls(e) =>
+                write!(f, "tls configuration error: {}", e),
+
+            DirustError::Regex(e) =>
+                write!(f, "invalid secret rule pattern: {}", e),
+
+            DirustError::InvalidScope(reason) =>
+                write!(f, "invalid scope file: {}", reason),
+
+            DirustError::InvalidProxy(reason) =>
+                write!(f, "invalid proxy: {}", reason),
+
+            DirustError::InvalidSource(reason) =>
+                write!(f, "invalid wordlist source: {}", reason),
+
+            DirustError::StreamSink(reason) =>
+                write!(f, "--stream-to: {}", reason),
+
+            DirustError::NotConfirmed(requests, threshold) =>
+                write!(f, "not started: ~{} requests is more than --confirm-above {} (pass --yes to start it anyway)", requests, threshold),
+
+            DirustError::HitRateSpike(current, usual) =>
+                write!(f, "stopped after a hit rate spike ({:.0}% findings, usually {:.0}%); fix the target or session, then resume", current, usual),
+
+            DirustError::InvalidCheckpoint(reason) =>
+                write!(f, "cannot resume from checkpoint: {}", reason),
+
+            DirustError::Syslog(reason) =>
+                write!(f, "--syslog: {}", reason),
+
+            DirustError::InvalidJobFile(reason) =>
+                write!(f, "invalid job file: {}", reason),
+
+            DirustError::JobsFailed(failed, total) =>
+                write!(f, "{} of {} job(s) failed", failed, total),
+
+            DirustError::FindingsAtSeverity(count, Severity::Info) =>
+                write!(f, "{} finding(s) reported (--fail-on-findings)", count),
+
+            DirustError::FindingsAtSeverity(count, threshold) =>
+                write!(f, "{} finding(s) at {} or above (--fail-on-findings={})", count, threshold, threshold.to_string().to_ascii_lowercase()),
+
+            DirustError::Unreachable(base, reason) =>
+                write!(f, "cannot reach {}: {} (nothing was scanned; --no-preflight skips this check)", base, reason),
+
+            DirustError::InvalidResolver(reason) =>
+                write!(f, "invalid DNS resolver: {}", reason),
+        }
+    }
+}
+
+impl DirustError {
+    /// Process exit status for this error: 2 for `--fail-on-findings`, 1 for real failures.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            DirustError::FindingsAtSeverity(..) => 2,
+            _ => 1,
+        }
+    }
+
+    /// Whether this is a request timeout (from either HTTP engine).
+    ///
+    /// Used by `--slow-retry-timeout` to retry only timeouts, not refused connections.
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            DirustError::Http(e) => e.is_timeout(),
+            DirustError::Timeout(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the request got no answer at all: a refused, reset or failed connection, DNS
+    /// failure or timeout (from either HTTP engine). HTTP error statuses are answers.
+    ///
+    /// Used to notice a host that went down mid-scan (see `scanner/health.rs`).
+    pub fn is_connection_failure(&self) -> bool {
+        match self {
+            DirustError::Http(e) => e.is_connect() || e.is_timeout() || e.is_request(),
+            DirustError::Timeout(_) => true,
+            DirustError::Engine(reason) | DirustError::Raw(reason) => reason.to_ascii_lowercase().contains("connect"),
+            _ => false,
         }
     }
 }
@@ -89,6 +284,20 @@ impl From<reqwest::header::ToStrError> for DirustError {
     }
 }
 
+/// Convert regex compile errors (secret rules) into `DirustError::Regex`.
+impl From<regex::Error> for DirustError {
+    fn from(e: regex::Error) -> Self {
+        DirustError::Regex(e)
+    }
+}
+
+/// Convert rustls configuration errors into `DirustError::Tls`.
+impl From<rustls::Error> for DirustError {
+    fn from(e: rustls::Error) -> Self {
+        DirustError::Tls(e)
+    }
+}
+
 /// Convert Tokio task join failures into `DirustError::Join`.
 ///
 /// This surfaces panics/cancellations from spawned tasks back to the caller.
@@ -97,3 +306,21 @@ impl From<tokio::task::JoinError> for DirustError {
         DirustError::Join(e)
     }
 }
+
+/// Convert JSON (de)serialization failures into `DirustError::Json`.
+///
+/// Used when loading or saving the results database.
+impl From<serde_json::Error> for DirustError {
+    fn from(e: serde_json::Error) -> Self {
+        DirustError::Json(e)
+    }
+}
+
+/// Convert TOML parse failures into `DirustError::Toml`.
+///
+/// Used when loading a `--rules` file.
+impl From<toml::de::Error> for DirustError {
+    fn from(e: toml::de::Error) -> Self {
+        DirustError::Toml(e)
+    }
+}
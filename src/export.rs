@@ -0,0 +1,468 @@
+//! src/export.rs
+//!
+//! Purpose:
+//!   Write scan findings in formats other tools import directly.
+//!
+//! Formats:
+//!   - DefectDojo "Generic Findings Import" JSON (`--export-defectdojo <PATH>`), a
+//!     `{ "findings": [...] }` document with title, severity, description, date,
+//!     dynamic_finding, unique_id_from_tool and endpoints per finding.
+//!     Only fields that importer accepts are written (it rejects unknown ones); the finding
+//!     URL goes into `endpoints` in its structured form (protocol/host/port/path).
+//!   - Plain URL list (`--export-urls <PATH>`): one discovered URL per line, optionally
+//!     limited to some status codes (`--export-status`), for nuclei, httpx and friends.
+//!   - Result tree (`--export-tree <PATH>`): the findings as a tree mirroring the path
+//!     hierarchy, one root per origin, each node with the number of findings at or below
+//!     it (in all and per status), their highest severity, the finding at the node's own URL
+//!     (if any) and its children (see below). Directories come before files at every
+//!     level, each in name order.
+//!   - Per-target files (`--output-dir <DIR>`): one file per scanned base URL in the
+//!     `--output-format` of choice (result JSON, URL list, DefectDojo or tree), named after
+//!     its host and port (`example.com_443.json`), plus an `index.json` listing them.
+//!
+//! Result tree document:
+//!   { "schema_version": 1, "scan": {...}, "tree": [ { "name": "https://example.com", "findings": 3, ...
+//!     "children": [ { "name": "admin/", "url": "https://example.com/admin/",
+//!       "findings": 2, "statuses": { "200": 1, "403": 1 }, "max_severity": "high",
+//!       "result": {...}, "children": [...] } ] } ] }
+//!
+//! Findings are the ones the scan reported, taken from the results database after the scan
+//! (`ResultsDb::this_run`: without entries of earlier runs or those `--new-only` hid), with
+//! severity and tags from the classification rules (see `severity.rs`).
+
+use crate::{
+    args::OutputFormat,
+    db::ResultsDb,
+    error::DirustError,
+    schema::{ResultEntry, ScanMetadata, SCHEMA_VERSION},
+    severity::Severity,
+};
+use reqwest::Url;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+/// Top-level DefectDojo generic import document.
+#[derive(Debug, Serialize)]
+struct DojoReport {
+    findings: Vec<DojoFinding>,
+}
+
+/// One finding in DefectDojo's generic import format.
+#[derive(Debug, Serialize)]
+struct DojoFinding {
+    title: String,
+    /// One of "Info", "Low", "Medium", "High", "Critical".
+    severity: String,
+    description: String,
+    /// Scan date as YYYY-MM-DD.
+    date: String,
+    dynamic_finding: bool,
+    /// Lets DefectDojo deduplicate the same URL across re-imports.
+    unique_id_from_tool: String,
+    endpoints: Vec<DojoEndpoint>,
+}
+
+/// Structured endpoint, as DefectDojo stores it.
+#[derive(Debug, Serialize)]
+struct DojoEndpoint {
+    protocol: String,
+    host: String,
+    port: Option<u16>,
+    path: String,
+}
+
+/// Write every finding in `db` as a DefectDojo generic findings import file.
+///
+/// Untagged findings are exported with severity "Info".
+pub fn write_defectdojo(path: &str, db: &ResultsDb, meta: &ScanMetadata) -> Result<(), DirustError> {
+    let date = iso_date(meta.started_at);
+    let mut findings: Vec<DojoFinding> = Vec::new();
+
+    for entry in db.sorted_entries() {
+        findings.push(dojo_finding(&entry, &date));
+    }
+
+    let file = File::create(path)?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &DojoReport { findings })?;
+    Ok(())
+}
+
+/// Write the discovered URLs, one per line, sorted.
+///
+/// `statuses` limits the export to these status codes; an empty slice exports everything.
+/// Returns the number of URLs written.
+pub fn write_urls(path: &str, db: &ResultsDb, statuses: &[u16]) -> Result<usize, DirustError> {
+    let mut out = BufWriter::new(File::create(path)?);
+    let mut written: usize = 0;
+
+    for entry in db.sorted_entries() {
+        if !statuses.is_empty() && !statuses.contains(&entry.status) {
+            continue;
+        }
+        writeln!(out, "{}", entry.url)?;
+        written += 1;
+    }
+
+    out.flush()?;
+    Ok(written)
+}
+
+/// The `--export-tree` document.
+#[derive(Debug, Serialize)]
+struct TreeReport {
+    /// See `schema::SCHEMA_VERSION`.
+    schema_version: u32,
+    scan: ScanMetadata,
+    tree: Vec<TreeNode>,
+}
+
+/// One directory, file or origin of the result tree.
+#[derive(Debug, Serialize)]
+struct TreeNode {
+    /// The origin (`https://example.com`) at the root, a path segment below it (`admin/`
+    /// for a directory, `login.php` for a file).
+    name: String,
+    url: String,
+    /// Findings at or below this node.
+    findings: usize,
+    /// The same, per status code.
+    statuses: BTreeMap<u16, usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_severity: Option<Severity>,
+    /// The finding at exactly this URL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<ResultEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<TreeNode>,
+}
+
+/// A tree node while findings are being added.
+#[derive(Default)]
+struct TreeBuilder {
+    result: Option<ResultEntry>,
+    children: BTreeMap<String, TreeBuilder>,
+}
+
+impl TreeBuilder {
+    /// Hang `entry` below this node, one level per segment of `segments`.
+    fn insert(&mut self, segments: &[String], entry: ResultEntry) {
+        match segments.split_first() {
+            Some((first, rest)) => self.children.entry(first.clone()).or_default().insert(rest, entry),
+            None => self.result = Some(entry),
+        }
+    }
+
+    /// The finished node, counts summed up from its children.
+    fn build(self, name: String, url: String) -> TreeNode {
+        let mut findings: usize = 0;
+        let mut statuses: BTreeMap<u16, usize> = BTreeMap::new();
+        let mut max_severity: Option<Severity> = None;
+        if let Some(entry) = &self.result {
+            findings += 1;
+            *statuses.entry(entry.status).or_default() += 1;
+            max_severity = entry.severity;
+        }
+
+        let mut children: Vec<TreeNode> = Vec::with_capacity(self.children.len());
+        for (segment, child) in self.children {
+            let child_url = format!("{}{}", url, segment);
+            children.push(child.build(segment, child_url));
+        }
+        // Directories first, then files; `BTreeMap` already ordered both by name.
+        children.sort_by_key(|child| !child.name.ends_with('/'));
+        for child in &children {
+            findings += child.findings;
+            for (status, count) in &child.statuses {
+                *statuses.entry(*status).or_default() += count;
+            }
+            max_severity = max_severity.max(child.max_severity);
+        }
+
+        TreeNode {
+            name,
+            url,
+            findings,
+            statuses,
+            max_severity,
+            result: self.result,
+            children,
+        }
+    }
+}
+
+/// Write every finding in `db` as a per-directory tree (see the module docs).
+pub fn write_tree(path: &str, db: &ResultsDb, meta: &ScanMetadata) -> Result<(), DirustError> {
+    let mut origins: BTreeMap<String, TreeBuilder> = BTreeMap::new();
+    for entry in db.sorted_entries() {
+        let (origin, segments) = split_url(&entry.url);
+        origins.entry(origin).or_default().insert(&segments, entry);
+    }
+
+    let tree: Vec<TreeNode> = origins
+        .into_iter()
+        .map(|(origin, node)| {
+            let url = format!("{}/", origin);
+            node.build(origin, url)
+        })
+        .collect();
+    let file = File::create(path)?;
+    serde_json::to_writer_pretty(
+        BufWriter::new(file),
+        &TreeReport {
+            schema_version: SCHEMA_VERSION,
+            scan: meta.clone(),
+            tree,
+        },
+    )?;
+    Ok(())
+}
+
+/// `("https://example.com", ["admin/", "login.php"])` from
+/// `https://example.com/admin/login.php`. Directory segments keep their trailing slash, so
+/// `admin` (a file or redirect) and `admin/` are different nodes; a query string stays on
+/// the last segment.
+fn split_url(url: &str) -> (String, Vec<String>) {
+    let (origin, path) = match url.split_once("://") {
+        Some((scheme, rest)) => match rest.find('/') {
+            Some(slash) => (format!("{}://{}", scheme, &rest[..slash]), &rest[slash + 1..]),
+            None => (url.to_string(), ""),
+        },
+        None => (String::new(), url.trim_start_matches('/')),
+    };
+    let (path, query) = match path.find('?') {
+        Some(mark) => (&path[..mark], &path[mark..]),
+        None => (path, ""),
+    };
+
+    let mut segments: Vec<String> = Vec::new();
+    let mut rest = path;
+    while let Some(slash) = rest.find('/') {
+        segments.push(rest[..=slash].to_string());
+        rest = &rest[slash + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(rest.to_string());
+    }
+    if !query.is_empty() {
+        match segments.last_mut() {
+            Some(last) => last.push_str(query),
+            None => segments.push(query.to_string()),
+        }
+    }
+    (origin, segments)
+}
+
+/// `index.json` of an `--output-dir`: the scan, then one line per target file.
+#[derive(Debug, Serialize)]
+struct OutputIndex {
+    /// See `schema::SCHEMA_VERSION`.
+    schema_version: u32,
+    scan: ScanMetadata,
+    targets: Vec<IndexEntry>,
+}
+
+/// One target in `index.json`.
+#[derive(Debug, Serialize)]
+struct IndexEntry {
+    /// The base URL that was scanned.
+    target: String,
+    /// File name inside the output directory.
+    file: String,
+    findings: usize,
+    /// Highest severity among the findings, if any was classified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_severity: Option<Severity>,
+}
+
+/// Write one result file per base URL in `bases` into `dir` (created if missing), plus
+/// `index.json`. Each file holds the findings under its base, with `meta.target` set to it.
+///
+/// Returns the number of target files written.
+pub fn write_output_dir(
+    dir: &str,
+    bases: &[String],
+    db: &ResultsDb,
+    meta: &ScanMetadata,
+    format: OutputFormat,
+) -> Result<usize, DirustError> {
+    fs::create_dir_all(dir)?;
+
+    let mut targets: Vec<IndexEntry> = Vec::with_capacity(bases.len());
+    for base in bases {
+        let subset = db.subset(base);
+        let mut target_meta = meta.clone();
+        target_meta.target = base.clone();
+
+        let file = format!("{}.{}", target_file_stem(base), format.extension());
+        let path = Path::new(dir).join(&file);
+        let path = path.to_string_lossy();
+        match format {
+            OutputFormat::Json => subset.save(&path, &target_meta)?,
+            OutputFormat::Urls => {
+                write_urls(&path, &subset, &[])?;
+            }
+            OutputFormat::Defectdojo => write_defectdojo(&path, &subset, &target_meta)?,
+            OutputFormat::Tree => write_tree(&path, &subset, &target_meta)?,
+        }
+
+        let entries = subset.sorted_entries();
+        targets.push(IndexEntry {
+            target: base.clone(),
+            file,
+            findings: entries.len(),
+            max_severity: entries.iter().filter_map(|e| e.severity).max(),
+        });
+    }
+
+    let written = targets.len();
+    let index = OutputIndex {
+        schema_version: SCHEMA_VERSION,
+        scan: meta.clone(),
+        targets,
+    };
+    let file = File::create(Path::new(dir).join("index.json"))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &index)?;
+    Ok(written)
+}
+
+/// File name (without extension) for a base URL: host and port, e.g. `example.com_8443`.
+/// Characters that are awkward in file names (IPv6 brackets and colons) become `_`.
+fn target_file_stem(base: &str) -> String {
+    let (host, port) = match Url::parse(base) {
+        Ok(url) => (
+            url.host_str().unwrap_or("target").to_string(),
+            url.port_or_known_default().unwrap_or(0),
+        ),
+        Err(_) => ("target".to_string(), 0),
+    };
+    let safe: String = host
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    format!("{}_{}", safe.trim_matches('_'), port)
+}
+
+/// Convert one result entry into a DefectDojo finding.
+fn dojo_finding(entry: &ResultEntry, date: &str) -> DojoFinding {
+    let severity = entry.severity.unwrap_or(Severity::Info);
+
+    // Title: the most specific tag if any, then the path and status.
+    let (endpoint, path) = match Url::parse(&entry.url) {
+        Ok(url) => {
+            let endpoint = DojoEndpoint {
+                protocol: url.scheme().to_string(),
+                host: url.host_str().unwrap_or_default().to_string(),
+                port: url.port_or_known_default(),
+                path: url.path().trim_start_matches('/').to_string(),
+            };
+            (Some(endpoint), url.path().to_string())
+        }
+        Err(_) => (None, entry.url.clone()),
+    };
+    let title = match entry.tags.first() {
+        Some(tag) => format!("{}: {} ({})", tag, path, entry.status),
+        None => format!("Discovered path: {} ({})", path, entry.status),
+    };
+
+    // Description: everything we know about the response, one fact per line.
+    let mut description = format!(
+        "dirust discovered {} answering HTTP {}.",
+        entry.url, entry.status
+    );
+    if let Some(length) = entry.content_length {
+        description.push_str(&format!("\nContent-Length: {}", length));
+    }
+    if let Some(location) = &entry.location {
+        description.push_str(&format!("\nRedirects to: {}", location));
+    }
+    if !entry.tags.is_empty() {
+        description.push_str(&format!("\nTags: {}", entry.tags.join(", ")));
+    }
+    if let Some(audit) = &entry.headers {
+        let missing = audit.missing(entry.is_sensitive());
+        if !missing.is_empty() {
+            description.push_str(&format!("\nMissing security headers: {}", missing.join(", ")));
+        }
+    }
+    let https = entry.url.starts_with("https://");
+    for cookie in &entry.cookies {
+        let missing = cookie.missing(https);
+        if entry.is_sensitive() && !missing.is_empty() {
+            description.push_str(&format!("\nCookie {} lacks: {}", cookie.name, missing.join(", ")));
+        }
+    }
+
+    DojoFinding {
+        title,
+        severity: dojo_severity(severity).to_string(),
+        description,
+        date: date.to_string(),
+        dynamic_finding: true,
+        unique_id_from_tool: entry.url.clone(),
+        endpoints: endpoint.into_iter().collect(),
+    }
+}
+
+/// DefectDojo's capitalized severity names.
+fn dojo_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "Info",
+        Severity::Low => "Low",
+        Severity::Medium => "Medium",
+        Severity::High => "High",
+        Severity::Critical => "Critical",
+    }
+}
+
+/// Format UNIX seconds as a UTC calendar date (YYYY-MM-DD).
+///
+/// Uses the days-to-civil conversion (proleptic Gregorian calendar) so we do not need a
+/// date/time dependency for one field.
+pub fn iso_date(unix_secs: u64) -> String {
+    let days = (unix_secs / 86_400) as i64;
+
+    // Shift the epoch to 0000-03-01 so leap days fall at the end of each 400-year era.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Format a time since the UNIX epoch as RFC 3339 UTC with milliseconds
+/// ("YYYY-MM-DDTHH:MM:SS.mmmZ").
+pub fn rfc3339_millis(since_epoch: std::time::Duration) -> String {
+    let secs = since_epoch.as_secs();
+    let seconds_of_day = secs % 86_400;
+    format!(
+        "{}T{:02}:{:02}:{:02}.{:03}Z",
+        iso_date(secs),
+        seconds_of_day / 3_600,
+        seconds_of_day % 3_600 / 60,
+        seconds_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Format UNIX seconds as a UTC date and time ("YYYY-MM-DD HH:MM:SS UTC").
+pub fn iso_datetime(unix_secs: u64) -> String {
+    let seconds_of_day = unix_secs % 86_400;
+    format!(
+        "{} {:02}:{:02}:{:02} UTC",
+        iso_date(unix_secs),
+        seconds_of_day / 3_600,
+        seconds_of_day % 3_600 / 60,
+        seconds_of_day % 60
+    )
+}
@@ -0,0 +1,188 @@
+//! src/fuzz/baseline.rs
+//!
+//! Purpose:
+//!   Baseline capture and differential comparison, shared by every "fixed target, fuzzed
+//!   request" mode (parameter names, header names, virtual hosts, ...).
+//!
+//! Idea:
+//!   - Send two requests that are guaranteed to be meaningless to the application
+//!     (e.g., a random parameter name). Their responses describe "nothing happened".
+//!   - The size difference between the two samples tells us how much a dynamic page varies
+//!     on its own; that becomes the tolerance for later comparisons.
+//!   - A fuzzed request whose status differs, or whose size is outside the tolerance,
+//!     is reported as interesting.
+//!   - Directory scans capture one at start (random paths under the base) and keep each
+//!     finding's raw difference as `diff_from_baseline` in result files.
+
+use crate::{
+    error::DirustError,
+    profile::BaselineRecord,
+    scanner::{http, transport::FetchedBody},
+    schema::BaselineDiff,
+};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// Minimum size tolerance in bytes, even when both baseline samples are identical.
+/// Absorbs tiny per-request noise such as reflected timestamps or CSRF tokens.
+const MIN_TOLERANCE: u64 = 16;
+
+/// The comparable parts of one response.
+#[derive(Debug, Clone, Copy)]
+pub struct Fingerprint {
+    pub status: StatusCode,
+    /// Decompressed body size in bytes as actually received (independent of Content-Length).
+    /// This is the measure all size comparisons use.
+    pub length: u64,
+    /// Body size on the wire, before decompression (equal to `length` when uncompressed).
+    pub transferred: u64,
+}
+
+impl Fingerprint {
+    /// The fingerprint of a body downloaded through a `ProbeClient`.
+    pub fn of(fetched: &FetchedBody) -> Fingerprint {
+        Fingerprint {
+            status: fetched.status,
+            length: fetched.size.decoded,
+            transferred: fetched.size.transferred,
+        }
+    }
+}
+
+/// What "nothing happened" looks like for a target.
+#[derive(Debug, Clone, Copy)]
+pub struct Baseline {
+    pub status: StatusCode,
+    pub length: u64,
+    /// Allowed absolute size deviation before a response counts as different.
+    pub tolerance: u64,
+}
+
+impl Baseline {
+    /// Build a baseline from two samples of "meaningless" requests.
+    ///
+    /// If the two samples disagree on status, the first one is used and the size tolerance
+    /// still grows with their difference; the caller may want to warn about an unstable target.
+    pub fn from_samples(first: Fingerprint, second: Fingerprint) -> Baseline {
+        let spread = first.length.abs_diff(second.length);
+        Baseline {
+            status: first.status,
+            length: first.length,
+            tolerance: spread.max(MIN_TOLERANCE),
+        }
+    }
+
+    /// The baseline as stored in a calibration profile (`--profile-cache`).
+    pub fn record(&self) -> BaselineRecord {
+        BaselineRecord {
+            status: self.status.as_u16(),
+            length: self.length,
+            tolerance: self.tolerance,
+        }
+    }
+
+    /// A baseline read back from a calibration profile (`None` for an invalid status).
+    pub fn from_record(record: &BaselineRecord) -> Option<Baseline> {
+        Some(Baseline {
+            status: StatusCode::from_u16(record.status).ok()?,
+            length: record.length,
+            tolerance: record.tolerance,
+        })
+    }
+
+    /// Compare a response with the baseline, without applying the tolerance.
+    ///
+    /// `length` is `None` when the response size is unknown (no `Content-Length`).
+    pub fn compare(&self, status: StatusCode, length: Option<u64>) -> BaselineDiff {
+        BaselineDiff {
+            status_changed: status != self.status,
+            size_delta: length.map(|len| len as i64 - self.length as i64),
+        }
+    }
+
+    /// Describe how `observed` differs from the baseline, or `None` if it looks the same.
+    ///
+    /// Examples of returned reasons:
+    ///   "status 200->302"
+    ///   "size +340"
+    pub fn diff(&self, observed: &Fingerprint) -> Option<String> {
+        let delta = self.compare(observed.status, Some(observed.length));
+        if delta.status_changed {
+            return Some(format!(
+                "status {}->{}",
+                self.status.as_u16(),
+                observed.status.as_u16()
+            ));
+        }
+
+        match delta.size_delta {
+            Some(size) if size.unsigned_abs() > self.tolerance => Some(format!("size {:+}", size)),
+            _ => None,
+        }
+    }
+}
+
+/// Send a prepared request and fingerprint the response (downloads the body to measure it).
+pub async fn fingerprint(request: RequestBuilder) -> Result<Fingerprint, DirustError> {
+    let response = request.send().await?;
+    let status = response.status();
+    let (_body, size) = http::read_body(response).await?;
+
+    Ok(Fingerprint {
+        status,
+        length: size.decoded,
+        transferred: size.transferred,
+    })
+}
+
+/// Capture a baseline by fingerprinting two requests produced by `make_request`, each called
+/// with a fresh random token (e.g., used as a parameter name or header value).
+pub async fn capture<F>(client: &Client, make_request: F) -> Result<Baseline, DirustError>
+where
+    F: Fn(&Client, &str) -> RequestBuilder,
+{
+    let first = fingerprint(make_request(client, &random_token())).await?;
+    let second = fingerprint(make_request(client, &random_token())).await?;
+    Ok(from_fingerprints(first, second))
+}
+
+/// Build a baseline from two samples (see `Baseline::from_samples`), warning when they
+/// disagree on status.
+pub fn from_fingerprints(first: Fingerprint, second: Fingerprint) -> Baseline {
+    if first.status != second.status {
+        eprintln!(
+            "[!] unstable baseline: status {} vs {} for two random probes",
+            first.status.as_u16(),
+            second.status.as_u16()
+        );
+    }
+    Baseline::from_samples(first, second)
+}
+
+/// A random lowercase token such as "dirust3f9a1c0b7e2d4a65".
+///
+/// `RandomState` is seeded randomly per instance by the standard library, which is plenty
+/// for "a name the application has never heard of" without pulling in a RNG crate.
+pub fn random_token() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    format!("dirust{:016x}", hasher.finish())
+}
+
+/// A random index in `0..bound` (`bound` must be non-zero), from the same entropy source
+/// as `random_token`.
+pub fn random_below(bound: usize) -> usize {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    (hasher.finish() % bound as u64) as usize
+}
+
+/// Fisher–Yates shuffle driven by `random_below`.
+pub fn shuffle<T>(items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        items.swap(i, random_below(i + 1));
+    }
+}
@@ -0,0 +1,89 @@
+//! src/fuzz/headers.rs
+//!
+//! Purpose:
+//!   `dirust fuzz headers <URL>`: keep the URL fixed and fuzz request *headers*.
+//!   This is the classic 403-bypass check: front-end proxies and frameworks sometimes route
+//!   on headers such as `X-Original-URL`, `X-Rewrite-URL` or `X-Forwarded-Host`, so a
+//!   forbidden page may answer differently when one of them is present.
+//!
+//! Wordlist format (one header per line):
+//!   X-Original-URL: {path}     → header with an explicit value
+//!   X-Forwarded-For            → bare name, sent with the `--header-value` value
+//!
+//! Placeholders expanded in values:
+//!   {path}  → path (and query) of the target URL, e.g. "/admin"
+//!   {host}  → host[:port] of the target URL
+//!
+//! Responses whose status or size differ from the baseline (a random, meaningless header)
+//! are reported.
+
+use super::{baseline, run_variants, Variant};
+use crate::{args::FuzzArgs, error::DirustError, output, scanner::wordlist, url};
+use reqwest::{Client, Url};
+
+/// Run the header fuzzing mode.
+pub async fn run(client: &Client, args: &FuzzArgs) -> Result<(), DirustError> {
+    let target: String = args.url.trim().to_string();
+    if !url::has_http_scheme(&target) {
+        return Err(DirustError::InvalidBaseUrl);
+    }
+    if args.reflect {
+        eprintln!("[!] header-fuzz: --reflect is ignored here; it tests parameters (fuzz params, --forms)");
+    }
+
+    // Placeholder values derived from the target.
+    let (path, host) = match Url::parse(&target) {
+        Ok(parsed) => {
+            let mut p = parsed.path().to_string();
+            if let Some(q) = parsed.query() {
+                p.push('?');
+                p.push_str(q);
+            }
+            let mut h = parsed.host_str().unwrap_or("").to_string();
+            if let Some(port) = parsed.port() {
+                h.push_str(&format!(":{}", port));
+            }
+            (p, h)
+        }
+        Err(_) => return Err(DirustError::InvalidBaseUrl),
+    };
+
+    let lines = wordlist::read_wordlist(&args.wordlist)?;
+
+    // Baseline: the target with a header nobody reads.
+    let baseline = baseline::capture(client, |c, token| {
+        c.get(target.as_str()).header(format!("X-{}", token), "1")
+    })
+    .await?;
+    output::info(&format!(
+        "[*] header-fuzz baseline: {} len={} (±{})",
+        baseline.status.as_u16(),
+        baseline.length,
+        baseline.tolerance
+    ));
+
+    let mut variants: Vec<Variant> = Vec::with_capacity(lines.len());
+    for line in &lines {
+        // "Name: value" or a bare "Name".
+        let (name, raw_value) = match line.split_once(':') {
+            Some((n, v)) => (n.trim().to_string(), v.trim().to_string()),
+            None => (line.trim().to_string(), args.header_value.clone()),
+        };
+        if name.is_empty() {
+            continue;
+        }
+
+        let value = raw_value.replace("{path}", &path).replace("{host}", &host);
+        variants.push(Variant {
+            label: format!("{}: {}", name, value),
+            url: target.clone(),
+            headers: vec![(name, value)],
+        });
+    }
+
+    let total = variants.len();
+    let differing = run_variants(client, args.http.concurrency, baseline, variants, "HEADER").await?.len();
+    output::info(&format!("[*] header-fuzz: {} of {} headers changed the response", differing, total));
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+//! src/fuzz/hostinject.rs
+//!
+//! Purpose:
+//!   Host header injection checks (`--host-injection`, part of the `--verify` pass). Each
+//!   finding is requested again with a poisoned `Host`, then with a poisoned
+//!   `X-Forwarded-Host`; when the made-up host name comes back in the `Location` header,
+//!   another header or the body, the application builds URLs from a header the client
+//!   controls — a lead for web cache poisoning and password-reset poisoning.
+//!
+//! How:
+//!   - One GET per header in `POISONED_HEADERS`, each with its own canary host
+//!     (`dirust<16 hex>.invalid`), so a reflection names the header that caused it.
+//!   - Redirects are not followed: a reflected `Location` is the classic case.
+//!   - For https targets the TLS handshake (SNI) still uses the real host; only the
+//!     HTTP-level headers are poisoned.
+//!
+//! Output:
+//!   HOSTINJECT X-Forwarded-Host  302  https://example.com/reset  (in header location)
+
+use super::baseline::random_token;
+use crate::{
+    error::DirustError,
+    output::{self, Style},
+    scanner::http,
+};
+use reqwest::Client;
+use std::time::Duration;
+
+/// Headers poisoned one request at a time.
+const POISONED_HEADERS: &[&str] = &["Host", "X-Forwarded-Host"];
+
+/// A poisoned header whose value came back in a response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Injection {
+    /// The header that carried the canary (e.g., "X-Forwarded-Host").
+    pub header: &'static str,
+    /// Status of the poisoned response.
+    pub status: u16,
+    /// Where the canary came back ("in header location", "in body").
+    pub places: Vec<String>,
+}
+
+impl Injection {
+    /// Short form for result files, e.g. "X-Forwarded-Host: 302, in header location".
+    pub fn describe(&self) -> String {
+        format!("{}: {}, {}", self.header, self.status, self.places.join(", "))
+    }
+}
+
+/// Send `url` once per poisoned header, waiting `interval` before each request, and print
+/// every reflection.
+///
+/// Returns the reflections found (empty: none). A failed request is an error; the headers
+/// after it are not tried.
+pub async fn check(client: &Client, url: &str, interval: Duration) -> Result<Vec<Injection>, DirustError> {
+    let mut found: Vec<Injection> = Vec::new();
+    for &name in POISONED_HEADERS {
+        tokio::time::sleep(interval).await;
+        let canary = format!("{}.invalid", random_token());
+        let response = client.get(url).header(name, canary.as_str()).send().await?;
+        let status = response.status().as_u16();
+        let headers = response.headers().clone();
+        let body = http::read_text(response).await?;
+
+        let mut places: Vec<String> = Vec::new();
+        for (header_name, value) in &headers {
+            if String::from_utf8_lossy(value.as_bytes()).contains(canary.as_str()) {
+                places.push(format!("in header {}", header_name));
+            }
+        }
+        if body.contains(canary.as_str()) {
+            places.push("in body".to_string());
+        }
+        if places.is_empty() {
+            continue;
+        }
+
+        let line = format!(
+            "{}HOSTINJECT {}  {:>3}  {}  ({})",
+            output::timestamp_prefix(),
+            name,
+            status,
+            url,
+            places.join(", ")
+        );
+        output::finding(&line, Style::Alert, Some(&format!("{} {}", url, name)));
+        found.push(Injection {
+            header: name,
+            status,
+            places,
+        });
+    }
+    Ok(found)
+}
@@ -0,0 +1,114 @@
+//! src/fuzz/mod.rs
+//!
+//! Purpose:
+//!   "Fixed target, fuzzed request" modes. Instead of walking paths like the directory
+//!   scanner, these keep the URL fixed and vary one part of the request per wordlist entry,
+//!   reporting the variants whose response differs from a baseline.
+//!
+//! Submodules:
+//!   - baseline.rs : baseline capture + differential comparison (shared by all modes)
+//!   - params.rs   : `dirust fuzz params`, fuzzing query parameter names (`?FUZZ=1`)
+//!   - headers.rs  : `dirust fuzz headers`, fuzzing request headers (403-bypass style checks)
+//!   - vhost.rs    : `dirust vhost`, fuzzing the Host header (virtual host discovery)
+//!   - raw.rs      : `dirust fuzz raw`, a raw request template with a `FUZZ` marker
+//!     (`--raw-engine` sends it byte for byte)
+//!   - reflect.rs  : `--reflect`, canary values for discovered parameters (reflection leads)
+//!   - hostinject.rs : `--host-injection`, poisoned Host / X-Forwarded-Host per finding
+
+pub mod baseline;
+pub mod headers;
+pub mod hostinject;
+pub mod params;
+pub mod raw;
+pub mod reflect;
+pub mod vhost;
+
+use crate::{
+    engine,
+    error::DirustError,
+    output::{self, Style},
+};
+use baseline::Baseline;
+use reqwest::Client;
+
+/// One fuzzed request to send.
+#[derive(Debug, Clone)]
+pub struct Variant {
+    /// What was fuzzed, for display (e.g., the parameter name).
+    pub label: String,
+    /// Full URL to request.
+    pub url: String,
+    /// Extra request headers (name, value).
+    pub headers: Vec<(String, String)>,
+}
+
+/// Send every variant (GET, at most `concurrency` at a time), compare with `baseline`, and print
+/// the ones that differ.
+///
+/// `kind` is a short upper-case tag printed on each line (e.g., "PARAM", "HEADER").
+///
+/// Per-variant network errors are reported and skipped: a single timeout should not abort
+/// a fuzzing run against a fixed target.
+///
+/// Returns the labels of the variants that differed from the baseline.
+pub async fn run_variants(
+    client: &Client,
+    concurrency: usize,
+    baseline: Baseline,
+    variants: Vec<Variant>,
+    kind: &'static str,
+) -> Result<Vec<String>, DirustError> {
+    let client_owned = client.clone();
+
+    let results: Vec<Option<String>> = engine::run_bounded(variants, concurrency, |variant: Variant| {
+        let client_clone = client_owned.clone();
+        async move {
+            let mut request = client_clone.get(&variant.url);
+            for (name, value) in &variant.headers {
+                request = request.header(name.as_str(), value.as_str());
+            }
+
+            let observed = match baseline::fingerprint(request).await {
+                Ok(f) => f,
+                Err(e) => {
+                    eprintln!("[!] {} {}: {}", kind, variant.label, e);
+                    return None;
+                }
+            };
+
+            match baseline.diff(&observed) {
+                Some(reason) => {
+                    print_difference(kind, &variant.label, &variant.url, &observed, &reason);
+                    Some(variant.label)
+                }
+                None => None,
+            }
+        }
+    })
+    .await?;
+
+    Ok(results.into_iter().flatten().collect())
+}
+
+/// Print one variant that differs from the baseline, e.g.
+/// `PARAM debug  200 len=5120  https://example.com/?debug=1  (size +340)`.
+pub fn print_difference(kind: &str, label: &str, url: &str, observed: &baseline::Fingerprint, reason: &str) {
+    // Compressed responses also show their on-the-wire size.
+    let wire = if observed.transferred != observed.length {
+        format!(" wire={}", observed.transferred)
+    } else {
+        String::new()
+    };
+    let line = format!(
+        "{}{} {}  {:>3} len={}{}  {}  ({})",
+        output::timestamp_prefix(),
+        kind,
+        label,
+        observed.status.as_u16(),
+        observed.length,
+        wire,
+        url,
+        reason
+    );
+    output::finding(&line, Style::for_status(observed.status.as_u16()), Some(label));
+}
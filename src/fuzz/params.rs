@@ -0,0 +1,72 @@
+//! src/fuzz/params.rs
+//!
+//! Purpose:
+//!   `dirust fuzz params <URL>`: keep the URL fixed and fuzz query parameter *names*.
+//!   Each wordlist entry `name` becomes a request to `<URL>?name=1` (or `&name=1` when the
+//!   URL already has a query string). Responses that differ from the baseline (a random,
+//!   meaningless parameter name) reveal parameters the application actually reads.
+//!
+//! With `--reflect`, the parameters that changed the response are then sent once more with
+//! canary values, and the ones echoed back are reported (see `reflect.rs`).
+//!
+//! Note: the URL is used exactly as given (no trailing slash is added), because the
+//! fixed target is usually a page like `https://example.com/search.php`.
+
+use super::{baseline, reflect, run_variants, Variant};
+use crate::{args::FuzzArgs, error::DirustError, output, scanner::wordlist, url};
+use reqwest::Client;
+
+/// Value sent for every fuzzed parameter.
+const PARAM_VALUE: &str = "1";
+
+/// Run the parameter-name fuzzing mode.
+pub async fn run(client: &Client, args: &FuzzArgs) -> Result<(), DirustError> {
+    let target: String = args.url.trim().to_string();
+    if !url::has_http_scheme(&target) {
+        return Err(DirustError::InvalidBaseUrl);
+    }
+
+    let names = wordlist::read_wordlist(&args.wordlist)?;
+
+    // Baseline: what the page looks like with a parameter it certainly ignores.
+    let baseline = baseline::capture(client, |c, token| c.get(with_param(&target, token))).await?;
+    output::info(&format!(
+        "[*] param-fuzz baseline: {} len={} (±{})",
+        baseline.status.as_u16(),
+        baseline.length,
+        baseline.tolerance
+    ));
+
+    let mut variants: Vec<Variant> = Vec::with_capacity(names.len());
+    for name in &names {
+        variants.push(Variant {
+            label: name.clone(),
+            url: with_param(&target, name),
+            headers: Vec::new(),
+        });
+    }
+
+    let total = variants.len();
+    let differing = run_variants(client, args.http.concurrency, baseline, variants, "PARAM").await?;
+    output::info(&format!("[*] param-fuzz: {} of {} parameter names changed the response", differing.len(), total));
+
+    // `--reflect`: do the discovered parameters echo their value back?
+    if args.reflect && !differing.is_empty() {
+        let tested = differing.len();
+        let endpoint = reflect::Endpoint {
+            method: "GET".to_string(),
+            url: target.clone(),
+            params: differing,
+        };
+        let reflected = reflect::run(client, args.http.concurrency, vec![endpoint]).await?;
+        output::info(&format!("[*] reflect: {} of {} parameter(s) reflected their value", reflected, tested));
+    }
+
+    Ok(())
+}
+
+/// Append `name=1` to the URL's query string.
+fn with_param(target: &str, name: &str) -> String {
+    let separator = if target.contains('?') { '&' } else { '?' };
+    format!("{}{}{}={}", target, separator, name, PARAM_VALUE)
+}
@@ -0,0 +1,267 @@
+//! src/fuzz/raw.rs
+//!
+//! Purpose:
+//!   `dirust fuzz raw <URL> --request req.txt`: fuzz a whole request written out by hand (or
+//!   saved from a proxy). The template is a complete HTTP/1.1 request — request line,
+//!   headers, empty line, optional body — and every `FUZZ` in it (anywhere: path, header
+//!   name or value, body) is replaced by the wordlist entry. `<URL>` only says where to
+//!   connect (scheme, host, port); the `Host` header is whatever the template says.
+//!
+//! Two ways to send a template:
+//!   - Default: the template is parsed (method, request target, headers, body) and sent by
+//!     the HTTP client. The client normalizes it: `Content-Length` and `Transfer-Encoding`
+//!     are recomputed from the body, header names must be valid, duplicates may be merged.
+//!   - `--raw-engine`: the bytes are written to a fresh connection exactly as they are after
+//!     the `FUZZ` substitution (see `scanner/raw.rs`). This is what request smuggling tests
+//!     need (`Transfer-Encoding : chunked`, conflicting `Content-Length`s, bare LF line
+//!     ends). Save such templates with the line endings the test needs; nothing is fixed up.
+//!
+//! Reporting is the same as for the other fuzzing modes: two random tokens in place of
+//! `FUZZ` give the baseline, and entries whose response differs from it are printed.
+
+use super::{baseline, print_difference};
+use crate::{
+    args::FuzzArgs,
+    engine,
+    error::DirustError,
+    output,
+    scanner::{
+        http,
+        raw::{RawConnector, RawResponse},
+        wordlist,
+    },
+    url,
+};
+use reqwest::{header, Client, Method, Url};
+use std::sync::Arc;
+
+/// The marker replaced by every wordlist entry.
+pub const MARKER: &[u8] = b"FUZZ";
+
+/// A request template read from `--request`.
+#[derive(Debug, Clone)]
+pub struct Template {
+    bytes: Vec<u8>,
+}
+
+impl Template {
+    /// Read a template file; it must contain at least one `FUZZ`.
+    pub fn load(path: &str) -> Result<Template, DirustError> {
+        let bytes = std::fs::read(path)?;
+        match bytes.windows(MARKER.len()).any(|w| w == MARKER) {
+            true => Ok(Template { bytes }),
+            false => Err(DirustError::InvalidTemplate(format!("{} has no FUZZ marker", path))),
+        }
+    }
+
+    /// The request with every `FUZZ` replaced by `word`, byte for byte.
+    pub fn render(&self, word: &str) -> Vec<u8> {
+        let mut out: Vec<u8> = Vec::with_capacity(self.bytes.len() + word.len());
+        let mut rest: &[u8] = &self.bytes;
+        while let Some(at) = rest.windows(MARKER.len()).position(|w| w == MARKER) {
+            out.extend_from_slice(&rest[..at]);
+            out.extend_from_slice(word.as_bytes());
+            rest = &rest[at + MARKER.len()..];
+        }
+        out.extend_from_slice(rest);
+        out
+    }
+}
+
+/// A rendered template, parsed for the HTTP client.
+#[derive(Debug)]
+struct ParsedRequest {
+    method: String,
+    /// Absolute URL: the request target resolved against `<URL>`.
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+/// Split a rendered request into its parts (LF and CRLF line endings both accepted).
+fn parse(request: &[u8], target: &Url) -> Result<ParsedRequest, DirustError> {
+    let (head, body) = match find(request, b"\r\n\r\n") {
+        Some(at) => (&request[..at], &request[at + 4..]),
+        None => match find(request, b"\n\n") {
+            Some(at) => (&request[..at], &request[at + 2..]),
+            None => (request, &request[request.len()..]),
+        },
+    };
+    let head = String::from_utf8_lossy(head);
+    let mut lines = head.lines();
+    let request_line = lines.next().unwrap_or("");
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path),
+        _ => return Err(DirustError::InvalidTemplate(format!("bad request line {:?}", request_line))),
+    };
+    let url = match url::has_http_scheme(path) {
+        true => path.to_string(),
+        false => match target.join(path) {
+            Ok(joined) => joined.to_string(),
+            Err(e) => return Err(DirustError::InvalidTemplate(format!("request target {:?}: {}", path, e))),
+        },
+    };
+
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    Ok(ParsedRequest {
+        method,
+        url,
+        headers,
+        body: body.to_vec(),
+    })
+}
+
+/// Position of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Whether a rendered request is a HEAD request (its response has no body).
+fn is_head(request: &[u8]) -> bool {
+    request.starts_with(b"HEAD ")
+}
+
+/// How the requests of a run are sent.
+#[derive(Clone)]
+enum Sender {
+    /// Parsed and sent by the HTTP client.
+    Client(Client),
+    /// Written byte for byte (`--raw-engine`).
+    Raw(Arc<RawConnector>),
+}
+
+impl Sender {
+    /// Send one rendered request to `target` and fingerprint the response.
+    async fn fingerprint(&self, request: Vec<u8>, target: &Url) -> Result<baseline::Fingerprint, DirustError> {
+        match self {
+            Sender::Client(client) => {
+                let parsed = parse(&request, target)?;
+                let method = Method::from_bytes(parsed.method.as_bytes())
+                    .map_err(|_| DirustError::InvalidTemplate(format!("bad method {:?}", parsed.method)))?;
+                let mut builder = client.request(method, &parsed.url);
+                for (name, value) in &parsed.headers {
+                    // The client frames the body itself.
+                    if name.eq_ignore_ascii_case("content-length") || name.eq_ignore_ascii_case("transfer-encoding") {
+                        continue;
+                    }
+                    builder = builder.header(name.as_str(), value.as_str());
+                }
+                if !parsed.body.is_empty() {
+                    builder = builder.body(parsed.body);
+                }
+                baseline::fingerprint(builder).await
+            }
+            Sender::Raw(connector) => {
+                let response = send_raw(connector, &request, target).await?;
+                Ok(fingerprint_of(&response))
+            }
+        }
+    }
+}
+
+/// Write `request` to a new connection to `target` and read its response.
+async fn send_raw(connector: &RawConnector, request: &[u8], target: &Url) -> Result<RawResponse, DirustError> {
+    let mut connection = connector.connect(target).await?;
+    connection.write(request).await?;
+    match tokio::time::timeout(connector.timeout(), connection.read_response(is_head(request))).await {
+        Ok(result) => result,
+        Err(_) => Err(DirustError::Timeout(target.to_string())),
+    }
+}
+
+/// The fingerprint of a raw response; the size comparison uses the decompressed body.
+fn fingerprint_of(response: &RawResponse) -> baseline::Fingerprint {
+    let encoding = response
+        .headers
+        .get(header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    let length = match http::decode(&encoding, &response.body) {
+        Some(decoded) => decoded.len() as u64,
+        None => response.transferred,
+    };
+    baseline::Fingerprint {
+        status: response.status,
+        length,
+        transferred: response.transferred,
+    }
+}
+
+/// Run the raw template fuzzing mode.
+pub async fn run(client: &Client, args: &FuzzArgs) -> Result<(), DirustError> {
+    let target_text: String = args.url.trim().to_string();
+    if !url::has_http_scheme(&target_text) {
+        return Err(DirustError::InvalidBaseUrl);
+    }
+    let target = Url::parse(&target_text).map_err(|_| DirustError::InvalidBaseUrl)?;
+    let path = match &args.request {
+        Some(path) => path,
+        None => return Err(DirustError::InvalidTemplate("fuzz raw needs --request <FILE>".to_string())),
+    };
+    let template = Arc::new(Template::load(path)?);
+    if args.reflect {
+        eprintln!("[!] raw-fuzz: --reflect is ignored here; it tests parameters (fuzz params, --forms)");
+    }
+
+    let sender = match args.raw_engine {
+        true => Sender::Raw(Arc::new(RawConnector::new(&args.http)?)),
+        false => {
+            output::info("[*] raw-fuzz: the template is re-built by the HTTP client (use --raw-engine to send it byte for byte)");
+            Sender::Client(client.clone())
+        }
+    };
+    let words = wordlist::read_wordlist(&args.wordlist)?;
+
+    // Baseline: the template with tokens the application has never seen.
+    let first = sender.fingerprint(template.render(&baseline::random_token()), &target).await?;
+    let second = sender.fingerprint(template.render(&baseline::random_token()), &target).await?;
+    let baseline = baseline::from_fingerprints(first, second);
+    output::info(&format!(
+        "[*] raw-fuzz baseline: {} len={} (±{})",
+        baseline.status.as_u16(),
+        baseline.length,
+        baseline.tolerance
+    ));
+
+    let total = words.len();
+    let results: Vec<bool> = engine::run_bounded(words, args.http.concurrency, |word: String| {
+        let sender = sender.clone();
+        let template = Arc::clone(&template);
+        let target = target.clone();
+        async move {
+            let request = template.render(&word);
+            // Shown on the result line: the request target, resolved against `<URL>`.
+            let shown = match parse(&request, &target) {
+                Ok(parsed) => parsed.url,
+                Err(_) => target.to_string(),
+            };
+            let observed = match sender.fingerprint(request, &target).await {
+                Ok(f) => f,
+                Err(e) => {
+                    eprintln!("[!] RAW {}: {}", word, e);
+                    return false;
+                }
+            };
+            match baseline.diff(&observed) {
+                Some(reason) => {
+                    print_difference("RAW", &word, &shown, &observed, &reason);
+                    true
+                }
+                None => false,
+            }
+        }
+    })
+    .await?;
+
+    let differing = results.iter().filter(|&&differs| differs).count();
+    output::info(&format!("[*] raw-fuzz: {} of {} entries changed the response", differing, total));
+    Ok(())
+}
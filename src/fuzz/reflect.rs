@@ -0,0 +1,126 @@
+//! src/fuzz/reflect.rs
+//!
+//! Purpose:
+//!   Canary reflection testing (`--reflect`) for parameters found by `dirust fuzz params`
+//!   or by `--forms`. Every parameter is sent with its own unique value; endpoints that
+//!   echo it back in the body or in a response header are reported — leads for XSS,
+//!   header injection or template injection, found without leaving the discovery workflow.
+//!
+//! How:
+//!   - One request per endpoint: each of its parameters gets a distinct canary
+//!     (`dirust<16 hex>` followed by `'"<>`), so a reflection names the parameter.
+//!   - GET endpoints get the canaries in the query string, POST endpoints in an
+//!     `application/x-www-form-urlencoded` body.
+//!   - A canary found in the body with its `'"<>` intact is marked `unescaped`: the
+//!     application did not HTML-encode it, which is the interesting case for XSS.
+//!
+//! Output:
+//!   REFLECT q  200  https://example.com/search.php  (in body, unescaped)
+//!   REFLECT next  302  https://example.com/login  (in header location)
+
+use super::baseline::random_token;
+use crate::{
+    engine,
+    error::DirustError,
+    output::{self, Style},
+    scanner::http,
+};
+use reqwest::{header, Client};
+
+/// Characters appended to every canary to see whether they come back encoded.
+const PROBE_CHARS: &str = "'\"<>";
+
+/// `PROBE_CHARS`, percent-encoded for the query string / form body.
+const PROBE_CHARS_ENCODED: &str = "%27%22%3C%3E";
+
+/// An endpoint and the parameters to send canaries in.
+#[derive(Debug, Clone)]
+pub struct Endpoint {
+    /// "GET" or "POST" (anything else is sent as GET).
+    pub method: String,
+    pub url: String,
+    pub params: Vec<String>,
+}
+
+/// Send canaries to every endpoint (at most `concurrency` at a time) and print the
+/// parameters that are reflected.
+///
+/// Per-endpoint network errors are reported and skipped.
+///
+/// Returns the number of reflected parameters.
+pub async fn run(client: &Client, concurrency: usize, endpoints: Vec<Endpoint>) -> Result<usize, DirustError> {
+    let client_owned = client.clone();
+
+    let results: Vec<usize> = engine::run_bounded(endpoints, concurrency, |endpoint: Endpoint| {
+        let client_clone = client_owned.clone();
+        async move {
+            match test_endpoint(&client_clone, &endpoint).await {
+                Ok(count) => count,
+                Err(e) => {
+                    eprintln!("[!] REFLECT {}: {}", endpoint.url, e);
+                    0
+                }
+            }
+        }
+    })
+    .await?;
+
+    Ok(results.into_iter().sum())
+}
+
+/// Send one request carrying a canary per parameter and print every reflection.
+async fn test_endpoint(client: &Client, endpoint: &Endpoint) -> Result<usize, DirustError> {
+    let canaries: Vec<(&String, String)> = endpoint.params.iter().map(|p| (p, random_token())).collect();
+    let encoded: Vec<String> = canaries
+        .iter()
+        .map(|(name, canary)| format!("{}={}{}", name, canary, PROBE_CHARS_ENCODED))
+        .collect();
+    let pairs = encoded.join("&");
+
+    let request = match endpoint.method.as_str() {
+        "POST" => client
+            .post(&endpoint.url)
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(pairs),
+        _ => {
+            let separator = if endpoint.url.contains('?') { '&' } else { '?' };
+            client.get(format!("{}{}{}", endpoint.url, separator, pairs))
+        }
+    };
+
+    let response = request.send().await?;
+    let status = response.status().as_u16();
+    let headers = response.headers().clone();
+    let body = http::read_text(response).await?;
+
+    let mut reflected: usize = 0;
+    for (name, canary) in &canaries {
+        let mut places: Vec<String> = Vec::new();
+        if body.contains(canary.as_str()) {
+            match body.contains(&format!("{}{}", canary, PROBE_CHARS)) {
+                true => places.push("in body, unescaped".to_string()),
+                false => places.push("in body".to_string()),
+            }
+        }
+        for (header_name, value) in &headers {
+            if String::from_utf8_lossy(value.as_bytes()).contains(canary.as_str()) {
+                places.push(format!("in header {}", header_name));
+            }
+        }
+        if places.is_empty() {
+            continue;
+        }
+
+        let line = format!(
+            "{}REFLECT {}  {:>3}  {}  ({})",
+            output::timestamp_prefix(),
+            name,
+            status,
+            endpoint.url,
+            places.join(", ")
+        );
+        output::finding(&line, Style::Alert, Some(&format!("{} {}", endpoint.url, name)));
+        reflected += 1;
+    }
+    Ok(reflected)
+}
@@ -0,0 +1,89 @@
+//! src/fuzz/vhost.rs
+//!
+//! Purpose:
+//!   `dirust vhost <URL>`: virtual host discovery. Every request goes to the same server
+//!   (`<URL>`), only the `Host` header changes: `<word>.<domain>` for each wordlist entry.
+//!   A server that hosts several sites answers a known name differently from an unknown one,
+//!   so responses that differ from the baseline (a random, certainly unknown host name)
+//!   reveal configured virtual hosts — including ones without public DNS records.
+//!
+//! Wildcards:
+//!   - DNS: a wildcard record for `*.<domain>` is detected and reported up front; it does
+//!     not change the method (the Host header is sent to `<URL>` regardless of DNS), but it
+//!     explains why every name "exists" for other tools.
+//!   - Catch-all vhosts: a server that answers unknown hosts with a normal page (2xx) is
+//!     reported as such; findings are then exactly the hosts that differ from that page.
+//!
+//! Notes:
+//!   - `<domain>` is `--domain`, or the host of `<URL>` when not given.
+//!   - For https targets the TLS handshake (SNI) still uses the host of `<URL>`; only the
+//!     HTTP-level `Host` header is fuzzed.
+
+use super::{baseline, run_variants, Variant};
+use crate::{args::VhostArgs, dns, error::DirustError, output, scanner::wordlist, url};
+use reqwest::{header, Client, Url};
+
+/// Run the virtual host fuzzing mode.
+pub async fn run(client: &Client, args: &VhostArgs) -> Result<(), DirustError> {
+    let target: String = args.url.trim().to_string();
+    if !url::has_http_scheme(&target) {
+        return Err(DirustError::InvalidBaseUrl);
+    }
+
+    // The domain appended to every word.
+    let domain: String = match &args.domain {
+        Some(d) => d.trim().trim_matches('.').to_string(),
+        None => match Url::parse(&target) {
+            Ok(parsed) => parsed.host_str().unwrap_or("").to_string(),
+            Err(_) => return Err(DirustError::InvalidBaseUrl),
+        },
+    };
+
+    let words = wordlist::read_wordlist(&args.wordlist)?;
+
+    // Wildcard DNS only matters for reporting; the comparison below works either way.
+    let wildcard = dns::detect_wildcard(&domain).await;
+    if !wildcard.is_empty() {
+        let list: Vec<String> = wildcard.iter().map(|a| a.to_string()).collect();
+        output::info(&format!("[*] vhost: wildcard DNS for *.{} -> {}", domain, list.join(",")));
+    }
+
+    // Baseline: the server asked for a host it cannot know.
+    let baseline = baseline::capture(client, |c, token| {
+        c.get(target.as_str()).header(header::HOST, format!("{}.{}", token, domain))
+    })
+    .await?;
+    output::info(&format!(
+        "[*] vhost baseline: {} len={} (±{})",
+        baseline.status.as_u16(),
+        baseline.length,
+        baseline.tolerance
+    ));
+    if baseline.status.is_success() {
+        output::info(&format!(
+            "[*] vhost: unknown hosts get a {} page (catch-all vhost); reporting hosts that differ from it",
+            baseline.status.as_u16()
+        ));
+    }
+
+    let mut variants: Vec<Variant> = Vec::with_capacity(words.len());
+    for word in &words {
+        let label = word.trim().trim_matches('.');
+        if label.is_empty() {
+            continue;
+        }
+
+        let host = format!("{}.{}", label, domain);
+        variants.push(Variant {
+            label: host.clone(),
+            url: target.clone(),
+            headers: vec![("Host".to_string(), host)],
+        });
+    }
+
+    let total = variants.len();
+    let differing = run_variants(client, args.http.concurrency, baseline, variants, "VHOST").await?.len();
+    output::info(&format!("[*] vhost: {} of {} host names changed the response", differing, total));
+
+    Ok(())
+}
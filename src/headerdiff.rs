@@ -0,0 +1,139 @@
+//! src/headerdiff.rs
+//!
+//! Purpose:
+//!   Track the response headers that say something about a target's software and hardening
+//!   (`Server`, `X-Powered-By`, `WWW-Authenticate`, the security headers, ...) per URL, and
+//!   report how they changed between two runs: "Server: nginx/1.18.0 → nginx/1.25.3",
+//!   "WWW-Authenticate appeared", "X-Powered-By gone". Useful for following hardening work
+//!   (version banners removed, HSTS added) over time.
+//!
+//! Where changes are reported:
+//!   - `scan --results-db <PATH> --incremental`: every finding (and every `304 Not Modified`
+//!     of a stored one) is compared with the headers stored by the previous run.
+//!   - `dirust diff <OLD> <NEW>`: URLs present in both files are compared the same way.
+//!
+//! How:
+//!   - Only the headers in `TRACKED` are kept (`response_headers` in result files), so
+//!     volatile ones (`Date`, `Set-Cookie` values, request ids) never show up as changes.
+//!     CSP nonces are masked for the same reason.
+//!   - A finding stored by a Dirust version that did not keep headers has none to compare
+//!     with; the next run stores them.
+//!   - A `304 Not Modified` carries only some headers: those are compared and refreshed, but
+//!     a header missing from a 304 is not reported as gone.
+//!
+//! Output (one line per change):
+//!   [~] header https://example.com/admin  Server: nginx/1.18.0 → nginx/1.25.3
+//!   [~] header https://example.com/admin  WWW-Authenticate appeared: Basic realm="admin"
+
+use crate::output::{self, Style};
+use reqwest::header::HeaderMap;
+use std::collections::BTreeMap;
+
+/// Headers kept per finding and compared between runs (stored under these names).
+const TRACKED: &[&str] = &[
+    "Server",
+    "X-Powered-By",
+    "X-AspNet-Version",
+    "X-AspNetMvc-Version",
+    "X-Generator",
+    "Via",
+    "WWW-Authenticate",
+    "Strict-Transport-Security",
+    "Content-Security-Policy",
+    "X-Frame-Options",
+    "X-Content-Type-Options",
+    "Referrer-Policy",
+    "Permissions-Policy",
+    "Access-Control-Allow-Origin",
+    "Access-Control-Allow-Credentials",
+];
+
+/// How one tracked header differs between two runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderChange {
+    /// Sent now, not before.
+    Appeared { name: String, value: String },
+    /// Sent before, not now.
+    Gone { name: String, value: String },
+    /// Sent both times, with another value.
+    Changed { name: String, from: String, to: String },
+}
+
+impl HeaderChange {
+    /// One line of text, e.g. "Server: nginx/1.18.0 → nginx/1.25.3".
+    pub fn describe(&self) -> String {
+        match self {
+            HeaderChange::Appeared { name, value } => format!("{} appeared: {}", name, value),
+            HeaderChange::Gone { name, value } => format!("{} gone (was {})", name, value),
+            HeaderChange::Changed { name, from, to } => format!("{}: {} → {}", name, from, to),
+        }
+    }
+}
+
+/// The tracked headers of a response (multiple values joined with ", "; non-UTF-8 skipped).
+pub fn capture(headers: &HeaderMap) -> BTreeMap<String, String> {
+    let mut tracked: BTreeMap<String, String> = BTreeMap::new();
+    for &name in TRACKED {
+        let values: Vec<&str> = headers.get_all(name).iter().filter_map(|v| v.to_str().ok()).collect();
+        if values.is_empty() {
+            continue;
+        }
+        let value = values.join(", ");
+        let value = match name {
+            "Content-Security-Policy" => mask_nonces(&value),
+            _ => value,
+        };
+        tracked.insert(name.to_string(), value);
+    }
+    tracked
+}
+
+/// What changed from `before` to `after`, in `TRACKED` order. With `partial` (the headers of
+/// a 304), headers missing from `after` are not reported as gone.
+pub fn compare(before: &BTreeMap<String, String>, after: &BTreeMap<String, String>, partial: bool) -> Vec<HeaderChange> {
+    let mut changes: Vec<HeaderChange> = Vec::new();
+    for &name in TRACKED {
+        match (before.get(name), after.get(name)) {
+            (None, Some(value)) => changes.push(HeaderChange::Appeared {
+                name: name.to_string(),
+                value: value.clone(),
+            }),
+            (Some(value), None) if !partial => changes.push(HeaderChange::Gone {
+                name: name.to_string(),
+                value: value.clone(),
+            }),
+            (Some(from), Some(to)) if from != to => changes.push(HeaderChange::Changed {
+                name: name.to_string(),
+                from: from.clone(),
+                to: to.clone(),
+            }),
+            _ => {}
+        }
+    }
+    changes
+}
+
+/// Print one line per change of `url`.
+pub fn print_changes(url: &str, changes: &[HeaderChange]) {
+    for change in changes {
+        let line = format!("[~] header {}  {}", url, change.describe());
+        output::finding(&line, Style::Plain, Some(&format!("{} {}", url, change.describe())));
+    }
+}
+
+/// Replace `'nonce-…'` sources of a CSP (new on every response) with `'nonce-*'`.
+fn mask_nonces(policy: &str) -> String {
+    let mut masked = String::with_capacity(policy.len());
+    let mut rest = policy;
+    while let Some(start) = rest.find("'nonce-") {
+        masked.push_str(&rest[..start]);
+        masked.push_str("'nonce-*'");
+        let after = &rest[start + "'nonce-".len()..];
+        rest = match after.find('\'') {
+            Some(end) => &after[end + 1..],
+            None => "",
+        };
+    }
+    masked.push_str(rest);
+    masked
+}
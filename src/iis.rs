@@ -0,0 +1,289 @@
+//! src/iis.rs
+//!
+//! Purpose:
+//!   `dirust iis <URL>`: IIS short-name (8.3 "tilde") enumeration. Windows keeps a DOS-style
+//!   short name next to long file names (`ADMINI~1.ASP` for `administration.aspx`), and
+//!   older IIS/.NET setups answer wildcard requests for short names differently depending
+//!   on whether a match exists. That leaks the first 6 characters of every file and
+//!   directory name (and the first 3 of its extension) without knowing the full name.
+//!
+//! How it works:
+//!   1) Calibration: request `/*~1*<suffix>` (matches any short name) and
+//!      `/<random long prefix>*~1*<suffix>` (can never match, short names have 6 characters
+//!      before the tilde). On a vulnerable server the answers differ, classically 404 vs
+//!      400. The first suffix (`/.aspx`, `/~1/.rem`, none) that tells them apart is used;
+//!      the status of the matching request is the "exists" answer.
+//!   2) Name prefixes are grown one character at a time (`/a*~1*`, `/ab*~1*`, ...) with
+//!      every character of the 8.3 alphabet per step, up to 6 characters. Several branches
+//!      can be alive at once (`adm...` and `app...`).
+//!   3) Every prefix found is checked as a complete short name (`/adm~1*`) and for higher
+//!      tilde numbers (`~2`, `~3`, ...), which exist when several long names share a stem.
+//!   4) Each short name is checked for an extension (`/ADMINI~1.*`) and the extension is
+//!      grown the same way, up to 3 characters. No extension means a directory or an
+//!      extension-less file.
+//!
+//! Feeding the main wordlist:
+//!   Short names are only prefixes; with `-w <PATH>` every wordlist entry whose name starts
+//!   with a found prefix (and whose extension, if any, starts with the found extension) is
+//!   suggested. Suggestions are printed, or written to `-o <PATH>` as a wordlist for
+//!   `dirust scan -w`.
+//!
+//! Probes of one step run concurrently through the shared engine (`engine::run_bounded`).
+
+use crate::{
+    args::IisArgs,
+    client, engine,
+    error::DirustError,
+    fuzz::baseline::random_token,
+    output::{self, Style},
+    scanner::wordlist,
+    url,
+};
+use reqwest::Client;
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+/// Characters allowed in 8.3 short names that are worth trying (short names are
+/// case-insensitive; IIS matches lowercase requests against the uppercase names).
+const ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Request suffixes tried during calibration, most reliable first.
+const SUFFIXES: [&str; 3] = ["/.aspx", "/~1/.rem", ""];
+
+/// Short names have at most 6 characters before the tilde and 3 after the dot.
+const MAX_STEM: usize = 6;
+const MAX_EXT: usize = 3;
+
+/// Highest tilde number tried for a stem (`~1` .. `~4`; Windows switches to hashed short
+/// names after the fourth collision).
+const MAX_TILDE: usize = 4;
+
+/// One discovered short name.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct ShortName {
+    stem: String,
+    tilde: usize,
+    /// `None`: a directory or an extension-less file.
+    ext: Option<String>,
+}
+
+impl std::fmt::Display for ShortName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stem = self.stem.to_ascii_uppercase();
+        match &self.ext {
+            Some(ext) => write!(f, "{}~{}.{}", stem, self.tilde, ext.to_ascii_uppercase()),
+            None => write!(f, "{}~{}", stem, self.tilde),
+        }
+    }
+}
+
+/// Answers "does a short name match this pattern?" with one request.
+#[derive(Clone)]
+struct Oracle {
+    client: Client,
+    /// Normalized base URL (ends with '/').
+    base: String,
+    suffix: &'static str,
+    /// Status of a request whose pattern matches.
+    hit: u16,
+}
+
+impl Oracle {
+    async fn matches(&self, pattern: &str) -> bool {
+        status(&self.client, &format!("{}{}{}", self.base, pattern, self.suffix)).await == Some(self.hit)
+    }
+
+    /// Test all `patterns` at once (at most `concurrency` in flight); returns the matching ones.
+    async fn matching(&self, patterns: Vec<String>, concurrency: usize) -> Result<Vec<String>, DirustError> {
+        let oracle = self.clone();
+        let results: Vec<Option<String>> = engine::run_bounded(patterns, concurrency, |pattern: String| {
+            let oracle_clone = oracle.clone();
+            async move {
+                match oracle_clone.matches(&pattern).await {
+                    true => Some(pattern),
+                    false => None,
+                }
+            }
+        })
+        .await?;
+        Ok(results.into_iter().flatten().collect())
+    }
+}
+
+/// Run short-name enumeration against `args.url`.
+pub async fn run(args: &IisArgs) -> Result<(), DirustError> {
+    let base = url::normalize_base(&args.url)?;
+    let client: Client = client::build_client(&args.http)?;
+    let concurrency = args.http.concurrency;
+
+    let oracle = match calibrate(&client, &base).await {
+        Some(o) => o,
+        None => {
+            output::info(&format!("[*] iis: {} does not leak short names (matching and non-matching requests look the same)", base));
+            return Ok(());
+        }
+    };
+    output::info(&format!(
+        "[*] iis: short names leak on {} (suffix {:?}: match answers {})",
+        base, oracle.suffix, oracle.hit
+    ));
+
+    // 2) Grow stems level by level; every prefix found is a candidate complete stem.
+    let mut stems: Vec<String> = Vec::new();
+    let mut frontier: Vec<String> = vec![String::new()];
+    while !frontier.is_empty() {
+        let mut patterns: Vec<String> = Vec::new();
+        for prefix in &frontier {
+            for c in ALPHABET.chars() {
+                patterns.push(format!("{}{}*~1*", prefix, c));
+            }
+        }
+        let found: Vec<String> = oracle
+            .matching(patterns, concurrency)
+            .await?
+            .into_iter()
+            .map(|p| p.trim_end_matches("*~1*").to_string())
+            .collect();
+
+        frontier = found.iter().filter(|p| p.len() < MAX_STEM).cloned().collect();
+        stems.extend(found);
+    }
+
+    // 3) Complete stems and their tilde numbers.
+    let mut names: Vec<ShortName> = Vec::new();
+    let complete: Vec<String> = oracle
+        .matching(stems.iter().map(|s| format!("{}~1*", s)).collect(), concurrency)
+        .await?;
+    for pattern in complete {
+        let stem = pattern.trim_end_matches("~1*").to_string();
+        for tilde in 1..=MAX_TILDE {
+            if tilde > 1 && !oracle.matches(&format!("{}~{}*", stem, tilde)).await {
+                break;
+            }
+            // 4) Extension, one character at a time.
+            let name = format!("{}~{}", stem, tilde);
+            let ext = match oracle.matches(&format!("{}.*", name)).await {
+                true => Some(grow_extension(&oracle, &name, concurrency).await?),
+                false => None,
+            };
+            let short = ShortName { stem: stem.clone(), tilde, ext };
+            output::finding(&format!("[+] {}", short), Style::Plain, None);
+            names.push(short);
+        }
+    }
+    names.sort();
+    output::info(&format!("[*] iis: {} short name(s)", names.len()));
+
+    // Suggestions for the main wordlist.
+    let words: Vec<String> = match &args.wordlist {
+        Some(path) => wordlist::read_wordlist(path)?,
+        None => Vec::new(),
+    };
+    let suggestions = suggest(&names, &words);
+    match &args.output {
+        Some(path) => {
+            let mut out = BufWriter::new(File::create(path)?);
+            for word in &suggestions {
+                writeln!(out, "{}", word)?;
+            }
+            out.flush()?;
+            output::info(&format!("[*] iis: {} suggestion(s) written to {}", suggestions.len(), path));
+        }
+        None => {
+            if !suggestions.is_empty() {
+                output::info(&format!("[*] iis: {} suggestion(s) for the main wordlist:", suggestions.len()));
+            }
+            for word in &suggestions {
+                println!("{}", word);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Find a suffix for which a matching and a non-matching pattern get different statuses.
+async fn calibrate(client: &Client, base: &str) -> Option<Oracle> {
+    for suffix in SUFFIXES {
+        let hit = status(client, &format!("{}*~1*{}", base, suffix)).await;
+        let miss = status(client, &format!("{}{}*~1*{}", base, random_token(), suffix)).await;
+        if let (Some(hit), Some(miss)) = (hit, miss)
+            && hit != miss
+        {
+            return Some(Oracle {
+                client: client.clone(),
+                base: base.to_string(),
+                suffix,
+                hit,
+            });
+        }
+    }
+    None
+}
+
+/// Grow the extension of the short name `name` (`admini~1`); at least one character
+/// exists (`name.*` matched).
+async fn grow_extension(oracle: &Oracle, name: &str, concurrency: usize) -> Result<String, DirustError> {
+    let mut ext = String::new();
+    while ext.len() < MAX_EXT {
+        let patterns: Vec<String> = ALPHABET.chars().map(|c| format!("{}.{}{}*", name, ext, c)).collect();
+        // A short name has exactly one extension, so at most one character matches.
+        match oracle.matching(patterns, concurrency).await?.first() {
+            Some(pattern) => {
+                ext = pattern[name.len() + 1..pattern.len() - 1].to_string();
+            }
+            None => break,
+        }
+    }
+    Ok(ext)
+}
+
+/// Wordlist entries that can be the long name of one of `names`, plus the short stems
+/// themselves when they are shorter than 6 characters (then they are likely the whole name).
+fn suggest(names: &[ShortName], words: &[String]) -> Vec<String> {
+    let mut suggestions: BTreeSet<String> = BTreeSet::new();
+    for name in names {
+        if name.stem.len() < MAX_STEM {
+            match &name.ext {
+                Some(ext) => suggestions.insert(format!("{}.{}", name.stem, ext)),
+                None => suggestions.insert(name.stem.clone()),
+            };
+        }
+
+        for raw in words {
+            let word = raw.trim().trim_start_matches('/').to_ascii_lowercase();
+            let (stem, ext) = match word.rsplit_once('.') {
+                Some((s, e)) => (s.to_string(), Some(e.to_string())),
+                None => (word.clone(), None),
+            };
+            // Short names drop spaces and dots from the stem.
+            let compact: String = stem.chars().filter(|c| *c != ' ' && *c != '.').collect();
+            if !compact.starts_with(&name.stem) {
+                continue;
+            }
+            match (&name.ext, ext) {
+                (Some(short), Some(long)) if long.starts_with(short.as_str()) => {
+                    suggestions.insert(word);
+                }
+                (Some(short), None) => {
+                    suggestions.insert(format!("{}.{}", word, short));
+                }
+                (None, None) => {
+                    suggestions.insert(word);
+                }
+                _ => {}
+            }
+        }
+    }
+    suggestions.into_iter().collect()
+}
+
+/// Status of a GET to `url`, or `None` on a transport error.
+async fn status(client: &Client, url: &str) -> Option<u16> {
+    match client.get(url).send().await {
+        Ok(response) => Some(response.status().as_u16()),
+        Err(_) => None,
+    }
+}
@@ -0,0 +1,266 @@
+//! src/jobs.rs
+//!
+//! Purpose:
+//!   `dirust run <FILE>`: run the scans described in a YAML job file — several targets, each
+//!   with its own wordlist, flags and result file — under one concurrency budget, instead of
+//!   a shell loop around the binary.
+//!
+//! Job file:
+//!   concurrency: 200          # requests in flight across all jobs (default: no shared limit)
+//!   parallel: 2               # jobs running at the same time (default: 1)
+//!   args: [--exts, "php,html"]  # `dirust scan` flags every job gets, before its own
+//!   jobs:
+//!     - name: shop            # label in progress lines (default: the target)
+//!       target: https://shop.example.com/
+//!       wordlist: lists/common.txt
+//!       args: [--scope, shop.scope, --secrets]
+//!       output: results/shop.json    # the job's `--results-db`
+//!     - target: https://api.example.com/
+//!       wordlist: lists/api.txt
+//!       args: [-c, "20"]
+//!
+//! How:
+//!   - Every job becomes the command line `dirust scan <target> -w <wordlist> <args...>
+//!     <job args...> --results-db <output>` and is parsed like one, so a job accepts every scan
+//!     flag. All jobs are checked before the first one starts; `--dry-run` stops there and
+//!     prints the command lines.
+//!   - Jobs start in file order, `parallel` at a time. With `concurrency`, every probe of every
+//!     job also holds a permit of one shared semaphore (on top of its job's own `-c`), so the
+//!     jobs together never have more requests in flight than that.
+//!   - A failed job does not stop the others. At the end, one line per job gives its outcome
+//!     and finding count.
+//!
+//! Notes:
+//!   - The job file is the confirmation: jobs never ask (`--yes`).
+//!   - Paths are relative to the current directory, as on the command line.
+//!   - Output flags (`-q`, `--silent`, `--no-color`, `--time-format`) apply to the whole run:
+//!     give them to `dirust run` itself (`dirust -q run jobs.yaml`), not in the file.
+//!   - Exit status: 1 if a job failed, 2 if none failed but a job's `--fail-on-findings`
+//!     was triggered, 0 otherwise.
+
+use crate::{
+    args::{Cli, Command, RunArgs, ScanArgs},
+    error::DirustError,
+    output,
+    resume::ScanState,
+    scanner::{monitor::ScanMonitor, ScanHooks},
+    severity::Severity,
+};
+use serde::Deserialize;
+use std::{fs, sync::Arc};
+use tokio::{sync::Semaphore, task::JoinHandle};
+
+/// The whole job file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobFile {
+    /// Requests in flight across all jobs.
+    #[serde(default)]
+    concurrency: Option<usize>,
+    /// Jobs running at the same time.
+    #[serde(default)]
+    parallel: Option<usize>,
+    /// Flags every job gets.
+    #[serde(default)]
+    args: Vec<String>,
+    jobs: Vec<JobSpec>,
+}
+
+/// One job as written in the file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobSpec {
+    #[serde(default)]
+    name: Option<String>,
+    target: String,
+    #[serde(default)]
+    wordlist: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    output: Option<String>,
+}
+
+/// A checked job, ready to run.
+struct Job {
+    name: String,
+    /// The equivalent `dirust scan` command line.
+    argv: Vec<String>,
+    args: ScanArgs,
+}
+
+/// How one job ended.
+enum JobOutcome {
+    Finished,
+    /// `--fail-on-findings` was triggered (findings at the threshold, threshold).
+    Gated(usize, Severity),
+    Failed(String),
+}
+
+/// A running job's task: its outcome and the number of findings it reported.
+type JobTask = JoinHandle<(JobOutcome, usize)>;
+
+/// Run every job of `args.file` (see the module docs).
+pub async fn run(args: &RunArgs) -> Result<(), DirustError> {
+    let (file, jobs) = load(&args.file)?;
+    if args.dry_run {
+        for job in &jobs {
+            output::info(&format!("[*] run: {}: {}", job.name, job.argv[1..].join(" ")));
+        }
+        return Ok(());
+    }
+
+    let parallel = file.parallel.unwrap_or(1).max(1);
+    let budget: Option<Arc<Semaphore>> = file.concurrency.map(|n| Arc::new(Semaphore::new(n.max(1))));
+    output::info(&format!(
+        "[*] run: {} job(s) from {}, {} at a time{}",
+        jobs.len(),
+        args.file,
+        parallel,
+        match file.concurrency {
+            Some(n) => format!(", at most {} request(s) in flight overall", n.max(1)),
+            None => String::new(),
+        }
+    ));
+
+    // Jobs start in file order as slots free up.
+    let slots = Arc::new(Semaphore::new(parallel));
+    let mut running: Vec<(String, Option<String>, JobTask)> = Vec::new();
+    for job in jobs {
+        let slot = match slots.clone().acquire_owned().await {
+            Ok(s) => s,
+            Err(_) => break,
+        };
+        let budget = budget.clone();
+        let name = job.name.clone();
+        let result_file = job.args.results_db.clone();
+        let handle = tokio::spawn(async move {
+            let _slot = slot;
+            output::info(&format!("[*] run: {} started ({})", job.name, job.args.base_url()));
+            let (outcome, findings) = run_job(&job, budget).await;
+            match &outcome {
+                JobOutcome::Failed(reason) => eprintln!("[!] run: {} failed: {}", job.name, reason),
+                _ => output::info(&format!("[*] run: {} finished, {} finding(s)", job.name, findings)),
+            }
+            (outcome, findings)
+        });
+        running.push((name, result_file, handle));
+    }
+
+    let mut done: Vec<(String, Option<String>, JobOutcome, usize)> = Vec::new();
+    for (name, result_file, handle) in running {
+        let (outcome, findings) = match handle.await {
+            Ok(d) => d,
+            Err(e) => (JobOutcome::Failed(DirustError::from(e).to_string()), 0),
+        };
+        done.push((name, result_file, outcome, findings));
+    }
+
+    // Summary, one line per job.
+    let total = done.len();
+    let mut failed: usize = 0;
+    let mut gated: Option<(usize, Severity)> = None;
+    output::info(&format!("[*] run: {} job(s) done", total));
+    for (name, result_file, outcome, findings) in done {
+        let saved = match &result_file {
+            Some(path) => format!(" -> {}", path),
+            None => String::new(),
+        };
+        let status = match outcome {
+            JobOutcome::Finished => "finished".to_string(),
+            JobOutcome::Gated(count, threshold) => {
+                gated = Some(match gated {
+                    Some((sum, lowest)) => (sum + count, lowest.min(threshold)),
+                    None => (count, threshold),
+                });
+                format!("finished, {} finding(s) at {} or above", count, threshold)
+            }
+            JobOutcome::Failed(_) => {
+                failed += 1;
+                "failed".to_string()
+            }
+        };
+        output::info(&format!("    {:<32} {}, {} finding(s){}", name, status, findings, saved));
+    }
+
+    if failed > 0 {
+        return Err(DirustError::JobsFailed(failed, total));
+    }
+    if let Some((count, threshold)) = gated {
+        return Err(DirustError::FindingsAtSeverity(count, threshold));
+    }
+    Ok(())
+}
+
+/// Run one job's scan; returns its outcome and the number of findings it reported.
+async fn run_job(job: &Job, budget: Option<Arc<Semaphore>>) -> (JobOutcome, usize) {
+    let monitor = Arc::new(ScanMonitor::new());
+    let mut hooks = ScanHooks {
+        monitor: Some(Arc::clone(&monitor)),
+        budget,
+        ..ScanHooks::default()
+    };
+    if let Some(path) = &job.args.state {
+        match ScanState::create(path, &job.argv) {
+            Ok(state) => hooks.state = Some(Arc::new(state)),
+            Err(e) => return (JobOutcome::Failed(e.to_string()), 0),
+        }
+    }
+    let outcome = match crate::run_scan(&job.args, hooks).await {
+        Ok(()) => JobOutcome::Finished,
+        Err(DirustError::FindingsAtSeverity(count, threshold)) => JobOutcome::Gated(count, threshold),
+        Err(e) => JobOutcome::Failed(e.to_string()),
+    };
+    monitor.finish();
+    (outcome, monitor.findings().len())
+}
+
+/// Read `path` and check every job in it.
+///
+/// Errors:
+///   - `DirustError::Io` if the file cannot be read
+///   - `DirustError::InvalidJobFile` if it is not a job file, has no jobs, or a job's
+///     command line does not parse
+fn load(path: &str) -> Result<(JobFile, Vec<Job>), DirustError> {
+    let text = fs::read_to_string(path)?;
+    let file: JobFile = match serde_yaml::from_str(&text) {
+        Ok(f) => f,
+        Err(e) => return Err(DirustError::InvalidJobFile(format!("{}: {}", path, e))),
+    };
+    if file.jobs.is_empty() {
+        return Err(DirustError::InvalidJobFile(format!("{}: no jobs", path)));
+    }
+
+    let mut jobs: Vec<Job> = Vec::new();
+    for (index, spec) in file.jobs.iter().enumerate() {
+        let name = spec.name.clone().unwrap_or_else(|| spec.target.clone());
+        let invalid = |reason: &str| DirustError::InvalidJobFile(format!("job {} ({}): {}", index + 1, name, reason));
+
+        let mut argv: Vec<String> = vec!["dirust".to_string(), "scan".to_string(), spec.target.clone()];
+        if let Some(wordlist) = &spec.wordlist {
+            argv.extend(["-w".to_string(), wordlist.clone()]);
+        }
+        argv.extend(file.args.iter().cloned());
+        argv.extend(spec.args.iter().cloned());
+        if let Some(output) = &spec.output {
+            argv.extend(["--results-db".to_string(), output.clone()]);
+        }
+
+        let mut args: ScanArgs = match Cli::try_parse_with_presets(&argv) {
+            Ok(Cli { command: Command::Scan(scan), .. }) => *scan,
+            Ok(_) => return Err(invalid("not a scan command line")),
+            // The first line of clap's message says what is wrong; the rest is usage.
+            Err(e) => {
+                let message = e.to_string();
+                let first = message.lines().next().unwrap_or_default();
+                return Err(invalid(first.trim_start_matches("error: ")));
+            }
+        };
+        if args.self_test {
+            return Err(invalid("--self-test is not available as a job"));
+        }
+        args.yes = true;
+        jobs.push(Job { name, argv, args });
+    }
+    Ok((file, jobs))
+}
@@ -0,0 +1,819 @@
+//! src/lib.rs
+//!
+//! The Dirust library: everything the `dirust` binary (`src/main.rs`) does, usable from
+//! other Rust programs.
+//!
+//! Responsibilities of this file:
+//!   1) Declare the modules used by the program (`args`, `error`, `scanner`, `url`, ...).
+//!   2) Parse command-line arguments into a typed `Cli` (via `clap`) and dispatch on the
+//!      subcommand (`scan`, `fuzz`, `vhost`, `dns`, `report`, `triage`, `diff`, `resume`, `run`, ...).
+//!   3) For scans: normalize and validate the base URL (HTTP/HTTPS + trailing slash).
+//!   4) Build a reusable HTTP client (`reqwest::Client`) with sane defaults.
+//!   5) Start the asynchronous scan and return any error to the caller.
+//!   6) Re-export the types an embedding program needs (see "Library use" below).
+//!
+//! Library use:
+//!   - `run_scan(&args, hooks)` runs a directory scan like `dirust scan` does. `args` come
+//!     from a command line (`Cli::try_parse_with_presets`, then `Command::Scan`), so every
+//!     flag means what it means on the terminal.
+//!   - `ScanHooks` is where the caller plugs in: `middleware` takes extra `Middleware`
+//!     layers (see `scanner/middleware.rs`; `AuthRefresh` is one ready to use), run after
+//!     the built-in ones on every probe request.
+//!   - `ScanHooks::transport` replaces the HTTP engine: any `ProbeClient` sends the scan's
+//!     requests (`FakeClient` answers from a function, without sockets; `tests/library.rs`
+//!     runs whole scans that way).
+//!   - Stopping a scan from another task: put a `ScanMonitor` in `ScanHooks::monitor` (built
+//!     with `ScanMonitor::with_cancellation(token)`, or take `monitor.cancellation()`) and
+//!     cancel the `CancellationToken`. No new probe starts, the probes in flight are aborted,
+//!     and `run_scan` returns; `monitor.findings()` holds what was found until then.
+//!
+//! Notes:
+//!   - The modules themselves stay private; only the re-exports below are the library API.
+
+mod args;        // CLI definition and helpers (subcommands, flags, legacy `dirust <url>` form)
+mod bench;       // `dirust bench`: requests/sec per concurrency level
+mod checkpoint;  // `scan --checkpoint`: wordlist position of an interrupted scan
+mod client;      // Shared HTTP client construction
+mod completions; // `dirust completions`: shell completion scripts and man page
+mod db;          // Persistent per-URL results database (validators for `--incremental`)
+mod diff;        // `dirust diff`: compare two results files
+mod dns;         // `dirust dns`: subdomain brute force through DNS resolution
+mod engine;      // Shared bounded-concurrency job runner for auxiliary modes
+mod error;       // Central application error type (`DirustError`)
+mod export;      // Findings export for other tools (DefectDojo import, plain URL list)
+mod headerdiff;  // Tracked response headers per URL and their changes between runs
+mod fuzz;        // Fixed-target fuzzing modes with baseline comparison (params, headers, vhosts)
+mod iis;         // `dirust iis`: IIS 8.3 short-name (tilde) enumeration
+mod jobs;        // `dirust run`: scans from a YAML job file under one concurrency budget
+mod onhit;       // `--on-hit`: an external command per finding (screenshots, notifications)
+mod output;      // Terminal writer for result lines (colors, `-q`/`--silent`, `--time-format`)
+mod permute;     // `--permute`: altdns-style variants of found directories and subdomains
+mod profile;     // Per-host calibration profiles (`--profile-cache`)
+pub mod redact;  // Credential masking for every output (`--no-redact` turns it off)
+mod report;      // `dirust report`: print the findings of a results file
+mod resume;      // Scan state files (`scan --state`, `dirust resume`)
+mod scanner;     // Orchestrates wordlist read, target build, concurrency, probing, and printing
+mod schema;      // Versioned JSON result schema shared by machine-readable outputs
+mod serve;       // `dirust serve`: HTTP API for scan jobs (submit, progress, SSE, cancel)
+mod selftest;    // `--self-test`: embedded mock server + end-to-end checks
+mod severity;    // Rules-based severity/tag classification of findings (`--rules`)
+mod storage;     // `dirust storage`: cloud bucket enumeration (S3/GCS/Azure)
+mod stream;      // `--stream-to`: live NDJSON forwarding of findings to a collector
+mod syslog;      // `--syslog`: RFC 5424 messages for findings (local daemon, UDP, TCP)
+mod tls;         // `--tls-impersonate`: browser-like TLS ClientHello configurations
+mod triage;      // `dirust triage`: mark findings interesting / false positive, with notes
+mod url;         // Base URL validation and normalization
+mod wordgen;     // `dirust wordgen`: crawl a target and build a tailored wordlist
+
+// Library API (see the module docs); the private `use`s below are this file's own.
+pub use args::{Cli, Command, ScanArgs}; // Parsed CLI arguments (from `src/args.rs`)
+pub use error::DirustError;     // Our explicit error type for clean propagation
+pub use scanner::ScanHooks;     // Optional scan observers and extension points
+pub use scanner::http::{summarize_headers, BodySize, HttpSummary}; // What a probe request returned
+pub use scanner::middleware::{AuthRefresh, Chain, LayerFuture, Middleware, Next, ProbeRequest, RateLimit};
+pub use scanner::monitor::{Progress, ScanMonitor}; // Progress, findings and cancellation of a running scan
+pub use scanner::transport::{FakeClient, FakeResponse, FetchFuture, FetchedBody, MeasureFuture, ProbeClient, ProbeFuture};
+pub use schema::ResultEntry;    // One finding (`ScanMonitor::findings`)
+pub use tokio_util::sync::CancellationToken; // Stops a scan from another task
+
+use args::{FuzzMode, Role};     // Fuzzing mode and `--role` of the parsed command
+use checkpoint::Checkpoint;     // `--checkpoint` wordlist position per base
+use db::ResultsDb;              // Results remembered between runs
+use profile::{ProfileCache, TechRecord}; // `--profile-cache` calibration profiles
+use resume::ScanState;          // Progress of a scan, for `--state` / `resume`
+use scanner::middleware::SharedBudget; // Interleaved bases: one `-c` budget, one `--rate` per host
+use scanner::source;            // `-w` payload sources (file, stdin, url, range, mutate)
+use scanner::fingerprint::{self, Detection}; // `--smart-exts` technology detection
+use scanner::events::ProgressEvents; // `--progress-ndjson` events
+use scanner::live::LiveLine;    // Live status line of terminal scans
+use scanner::transcript::Transcript; // `--transcript` request/response log
+use schema::{BaseSummary, ScanMetadata}; // Scan metadata block (and per-base sections) for JSON outputs
+use onhit::OnHitHook;           // `--on-hit` command hook
+use output::Verbosity;          // `-q` / `--silent` output level
+use reqwest::Client;            // HTTP client (connection pooling, TLS, etc.)
+use std::collections::HashMap;  // `--smart-exts` detections per base
+use std::io::{IsTerminal, Write}; // `--confirm-above` question on the terminal
+use std::sync::Arc;             // Shared ownership of the scan state across probe tasks
+use stream::ResultStream;       // `--stream-to` forwarder
+use syslog::SyslogSink;         // `--syslog` forwarder
+use tokio::sync::Semaphore;     // Budget of interleaved bases
+use tokio::task::{JoinHandle, JoinSet}; // `--inject-pipe` reader task, interleaved bases
+
+/// Parse the command line and run the chosen mode; `?` inside turns into `main`'s error exit.
+pub async fn run() -> Result<(), DirustError> {
+    // Parse command-line flags and positional arguments into a strongly-typed struct.
+    // Example CLI (the `scan` subcommand may be omitted):
+    //   dirust scan https://example.com/ -w words.txt --exts php,html -c 100 --get
+    let argv: Vec<String> = args::cli_args();
+    let cli: Cli = match Cli::try_parse_with_presets(&argv) {
+        Ok(cli) => cli,
+        Err(e) => e.exit(),
+    };
+    output::init(cli.no_color, Verbosity::from_flags(cli.quiet, cli.silent), cli.time_format);
+    redact::init(cli.no_redact);
+
+    match cli.command {
+        Command::Scan(scan_args) => {
+            // `--self-test` replaces the normal scan: it scans a built-in mock server instead.
+            if scan_args.self_test {
+                return selftest::run(&scan_args).await;
+            }
+
+            // A worker takes its targets from a coordinator instead of building them.
+            if scan_args.role == Some(Role::Worker) {
+                return scanner::distributed::work(&scan_args).await;
+            }
+
+            // `--state` records this command line and every probed URL for `dirust resume`.
+            let mut hooks = ScanHooks::default();
+            if let Some(path) = &scan_args.state {
+                hooks.state = Some(Arc::new(ScanState::create(path, &argv)?));
+            }
+            run_scan(&scan_args, hooks).await
+        }
+        Command::Resume(resume_args) => {
+            let (scan_args, state) = ScanState::open(&resume_args.state)?;
+            output::info(&format!(
+                "[*] resume: {} ({} target(s) already probed)",
+                scan_args.base_url(),
+                state.done_count()
+            ));
+            let hooks = ScanHooks {
+                state: Some(Arc::new(state)),
+                ..ScanHooks::default()
+            };
+            run_scan(&scan_args, hooks).await
+        }
+        // Fuzzing modes keep the URL fixed (no trailing-slash normalization) and vary
+        // one part of the request per wordlist entry instead of scanning paths.
+        Command::Fuzz(fuzz_args) => {
+            let client: Client = client::build_client(&fuzz_args.http)?;
+            match fuzz_args.mode {
+                FuzzMode::Params => fuzz::params::run(&client, &fuzz_args).await,
+                FuzzMode::Headers => fuzz::headers::run(&client, &fuzz_args).await,
+                FuzzMode::Raw => fuzz::raw::run(&client, &fuzz_args).await,
+            }
+        }
+        Command::Vhost(vhost_args) => {
+            let client: Client = client::build_client(&vhost_args.http)?;
+            fuzz::vhost::run(&client, &vhost_args).await
+        }
+        Command::Dns(dns_args) => dns::run(&dns_args).await,
+        Command::Report(report_args) => report::run(&report_args),
+        Command::Triage(triage_args) => triage::run(&triage_args),
+        Command::Diff(diff_args) => diff::run(&diff_args),
+        // Storage enumeration probes provider URLs built from the wordlist; no base URL.
+        Command::Storage(storage_args) => {
+            let client: Client = client::build_client(&storage_args.http)?;
+            storage::run(&client, &storage_args).await
+        }
+        Command::Bench(bench_args) => bench::run(&bench_args).await,
+        Command::Wordgen(wordgen_args) => wordgen::run(&wordgen_args).await,
+        Command::Iis(iis_args) => iis::run(&iis_args).await,
+        Command::Completions(completions_args) => completions::run(&completions_args),
+        Command::Serve(serve_args) => serve::run(&serve_args).await,
+        Command::Run(run_args) => jobs::run(&run_args).await,
+    }
+}
+
+/// Run a directory scan (`dirust scan`, or `dirust resume` with the stored arguments).
+///
+/// `hooks` carries the `--state` progress file (already probed URLs are skipped, new ones
+/// recorded) and, for `dirust serve` jobs, the scan monitor.
+pub async fn run_scan(args: &ScanArgs, mut hooks: ScanHooks) -> Result<(), DirustError> {
+    // Validate the base URL and ensure it ends with a trailing slash `/`.
+    // This prevents mistakes like "https://x/y" + "admin" → "https://x/yadmin".
+    // Errors here (e.g., non-http scheme) turn into `Err(DirustError::InvalidBaseUrl)`.
+    // Scheme-less input ("example.com") is normalized to https:// here.
+    let base: String = url::normalize_base(args.base_url())?;
+
+    // Build a single reusable HTTP client (shared connection pool, no auto-redirects,
+    // per-request timeout). See `client.rs` for the exact settings.
+    let client: Client = client::build_client(&args.http)?;
+
+    // Decide which base URL(s) to scan (scheme fallback, `--both-schemes`, `--ports`).
+    let bases: Vec<String> = select_bases(&client, args, &base).await?;
+
+    // Fail fast with one clear error when a base does not answer at all (DNS, refused
+    // connection, TLS), instead of one confusing error from the first of many probes.
+    // Coordinators leave this to their workers, which may see a different network; with a
+    // `ScanHooks::transport`, there is no network to check.
+    if !args.no_preflight && args.role != Some(Role::Coordinator) && hooks.transport.is_none() {
+        for scan_base in &bases {
+            client::preflight(&client, scan_base).await?;
+        }
+    }
+
+    // `--profile-cache`: calibration stored by earlier scans of these bases.
+    if let Some(path) = &args.profile_cache {
+        hooks.profiles = Some(Arc::new(ProfileCache::load(path)?));
+    }
+
+    // `--smart-exts`: fingerprint every base and scan with the extensions of its stack.
+    // The detections per base also go into the per-host sections of the metadata.
+    let smart_args: ScanArgs;
+    let mut smart_note: Option<String> = None;
+    let mut technologies: HashMap<String, Vec<String>> = HashMap::new();
+    let args: &ScanArgs = if args.smart_exts {
+        let (chosen, note) =
+            smart_extensions(&client, args, &bases, hooks.profiles.as_deref(), &mut technologies).await;
+        smart_args = chosen;
+        smart_note = Some(note);
+        &smart_args
+    } else {
+        args
+    };
+
+    // Load the results database if one was requested. A missing file simply means
+    // "first run" and yields an empty database.
+    let mut db: ResultsDb = match &args.results_db {
+        Some(path) => ResultsDb::load(path)?,
+        None => ResultsDb::default(),
+    };
+
+    // Load the previous result set for `--new-only`. Here a missing file is an error,
+    // because the user explicitly asked to compare against it.
+    let previous: Option<ResultsDb> = match &args.new_only {
+        Some(path) => Some(ResultsDb::read(path)?),
+        None => None,
+    };
+
+    // Stamp the start of the scan for the metadata block of machine-readable outputs.
+    // With several bases, the target is recorded as given on the command line.
+    let target: String = if bases.len() == 1 {
+        bases[0].clone()
+    } else {
+        args.base_url().trim().to_string()
+    };
+    let mut meta: ScanMetadata = ScanMetadata::start(&target);
+    meta.wordlist = source::label(args.wordlist_path());
+    meta.wordlist_entries = source::count(args.wordlist_path())?;
+    meta.extensions = args.parse_exts();
+    meta.concurrency = Some(args.http.concurrency);
+    // The proxy each base goes through (`--proxy`, `HTTPS_PROXY`, ...), when there is one.
+    let mut proxies: Vec<(String, String)> = Vec::new();
+    for base in &bases {
+        if let Some(proxy) = client::describe_proxy(&args.http, base)
+            && !proxies.iter().any(|(_, p)| *p == proxy)
+        {
+            proxies.push((base.clone(), proxy));
+        }
+    }
+    meta.proxy = match proxies.len() {
+        0 => None,
+        1 => Some(redact::text(&proxies[0].1).into_owned()),
+        _ => Some(
+            proxies
+                .iter()
+                .map(|(base, proxy)| format!("{} for {}", redact::text(proxy), base))
+                .collect::<Vec<_>>()
+                .join("; "),
+        ),
+    };
+
+    // Saved output should say what produced it: print the same header the JSON files carry.
+    for line in meta.header_lines() {
+        output::info(&line);
+    }
+    // `--polite`: say what the preset ended up as (explicit flags may have changed it).
+    if args.polite {
+        output::info(&format!(
+            "[*] polite: concurrency {}, {} req/s, {} attempt(s) per probe, User-Agent \"{}\"",
+            args.http.concurrency,
+            args.rate.map_or("unlimited".to_string(), |rate| rate.to_string()),
+            args.max_attempts,
+            args.http.user_agent()
+        ));
+        if args.http.from.is_none() {
+            eprintln!("[!] polite: no --from <EMAIL> given; add one so the site's operators can reach you");
+        }
+    }
+    // `--fast`: same, for the throughput preset.
+    if args.fast {
+        output::info(&format!(
+            "[*] fast: concurrency {}, {} connection(s) prewarmed, HTTP/2 tuned, {}",
+            args.http.concurrency,
+            args.prewarm.min(args.http.concurrency),
+            args.rate.map_or("no rate limit".to_string(), |rate| format!("{} req/s", rate))
+        ));
+    }
+    if let Some(note) = &smart_note {
+        output::info(note);
+    }
+
+    // Say how big the scan is before it starts, and ask first when it is very big.
+    confirm_launch(args, &bases, meta.wordlist_entries)?;
+
+    // Kick off the scan orchestration. This will:
+    //   - read the wordlist,
+    //   - expand targets (base + word [+ ext]),
+    //   - run bounded-concurrency probes,
+    //   - print "interesting" results (200/301/302/401/403) not already in `previous`,
+    //   - and fold every probe outcome into the results database.
+    //
+    // Any error encountered inside (I/O, HTTP, task join) bubbles up as `Err(DirustError)`.
+    // With several bases, each scan is announced so findings can be attributed to a scheme
+    // (the scheme is also part of every printed URL).
+    // A coordinator hands the targets of all bases to its workers instead of probing them.
+    if bases.len() > 1 && source::reads_stdin(args.wordlist_path()) {
+        eprintln!("[!] wordlist: stdin can only be read once; bases after the first get no words");
+    }
+    if args.role == Some(Role::Coordinator) {
+        let words = source::open(args.wordlist_path(), args.priority.as_deref(), &client).await?;
+        scanner::distributed::coordinate(&bases, args, words, &mut db, previous.as_ref()).await?;
+        if let Some(profiles) = &hooks.profiles {
+            profiles.save()?;
+        }
+    } else {
+        // `--transcript`: a resumed scan appends to the transcript of its first run.
+        if let Some(path) = &args.transcript {
+            let resuming = hooks.state.as_ref().is_some_and(|state| state.done_count() > 0);
+            hooks.transcript = Some(Arc::new(Transcript::open(path, args.transcript_body, resuming)?));
+        }
+
+        // `--checkpoint`: bases finished by an earlier run are skipped, the others continue
+        // from their recorded wordlist position.
+        if let Some(path) = &args.checkpoint {
+            hooks.checkpoint = Some(Arc::new(Checkpoint::open(path, args.wordlist_path())?));
+        }
+
+        // On a terminal, a live status line (progress, findings per status) follows the scan;
+        // it reads a monitor of its own (`dirust serve` jobs bring theirs). `--progress-ndjson`
+        // events replace it.
+        let live: Option<LiveLine> = if hooks.monitor.is_none()
+            && !args.no_progress
+            && args.progress_ndjson.is_none()
+            && output::live_line_supported()
+        {
+                let monitor = Arc::new(ScanMonitor::new());
+                hooks.monitor = Some(Arc::clone(&monitor));
+                Some(LiveLine::start(monitor))
+            } else {
+                None
+            };
+
+        // `--inject-pipe`: words written to the pipe go into the monitor's queue (a scan
+        // without a status line gets a monitor for it).
+        let injector: Option<JoinHandle<()>> = match &args.inject_pipe {
+            Some(path) => {
+                let monitor = Arc::clone(hooks.monitor.get_or_insert_with(|| Arc::new(ScanMonitor::new())));
+                Some(scanner::inject::spawn_pipe_reader(path, monitor)?)
+            }
+            None => None,
+        };
+
+        // `--progress-ndjson`: the same counters as machine-readable events.
+        let progress_events: Option<ProgressEvents> = match &args.progress_ndjson {
+            Some(destination) => {
+                let monitor = Arc::clone(hooks.monitor.get_or_insert_with(|| Arc::new(ScanMonitor::new())));
+                Some(ProgressEvents::start(destination, args.progress_interval, monitor)?)
+            }
+            None => None,
+        };
+
+        // `--stream-to`: findings reported to the monitor are forwarded to the collector.
+        let streamer: Option<ResultStream> = match &args.stream_to {
+            Some(sink) => {
+                let monitor = Arc::clone(hooks.monitor.get_or_insert_with(|| Arc::new(ScanMonitor::new())));
+                Some(ResultStream::start(sink, &bases, args.notify_severity, monitor).await?)
+            }
+            None => None,
+        };
+
+        // `--syslog`: the same findings, as syslog messages.
+        let syslog: Option<SyslogSink> = match &args.syslog {
+            Some(target) => {
+                let monitor = Arc::clone(hooks.monitor.get_or_insert_with(|| Arc::new(ScanMonitor::new())));
+                Some(SyslogSink::start(target, args.notify_severity, monitor).await?)
+            }
+            None => None,
+        };
+
+        // `--on-hit`: a command per finding, in the background.
+        let on_hit: Option<OnHitHook> = match &args.on_hit {
+            Some(command) => {
+                let monitor = Arc::clone(hooks.monitor.get_or_insert_with(|| Arc::new(ScanMonitor::new())));
+                Some(OnHitHook::start(command, args.on_hit_concurrency as usize, args.on_hit_timeout, monitor))
+            }
+            None => None,
+        };
+
+        // `--fail-on-findings` counts the findings the monitor hears about.
+        if args.fail_on_findings.is_some() {
+            hooks.monitor.get_or_insert_with(|| Arc::new(ScanMonitor::new()));
+        }
+
+        // Progress estimates need the number of wordlist entries still to read.
+        if let Some(monitor) = &hooks.monitor
+            && let Some(entries) = meta.wordlist_entries
+        {
+            let pending = bases
+                .iter()
+                .filter(|base| !hooks.checkpoint.as_ref().is_some_and(|checkpoint| checkpoint.is_finished(base)))
+                .count();
+            monitor.plan_words(entries * pending);
+        }
+
+        // Several bases are scanned side by side unless `--no-interleave` is given.
+        let outcome: Result<Vec<BaseSummary>, DirustError> = match bases.len() > 1 && !args.no_interleave {
+            true => scan_interleaved(&client, &bases, args, &mut db, previous.as_ref(), &hooks).await,
+            false => scan_in_turn(&client, &bases, args, &mut db, previous.as_ref(), &hooks).await,
+        };
+
+        if let Some(reader) = injector {
+            reader.abort();
+        }
+        if let Some(streamer) = streamer {
+            streamer.close().await;
+        }
+        if let Some(syslog) = syslog {
+            syslog.close().await;
+        }
+        if let Some(on_hit) = on_hit {
+            on_hit.close().await;
+        }
+        if let Some(events) = progress_events {
+            events.stop().await;
+        }
+        // A checkpoint is only needed until every base has been scanned to the end.
+        if let Some(checkpoint) = &hooks.checkpoint
+            && outcome.is_ok()
+            && !hooks.is_cancelled()
+        {
+            checkpoint.remove()?;
+            output::info(&format!("[*] checkpoint: scan complete, removed {}", checkpoint.path()));
+        }
+        // Erase the status line before anything else is printed (errors included).
+        if let Some(line) = live {
+            line.stop().await;
+        }
+        if let Some(transcript) = &hooks.transcript {
+            transcript.flush()?;
+        }
+        // Calibration measured so far is kept even when the scan failed.
+        if let Some(profiles) = &hooks.profiles {
+            profiles.save()?;
+        }
+        // Per-base sections: what each base was sent and gave back, with `--smart-exts`
+        // detections added to the banners the findings carried.
+        meta.bases = outcome?;
+        for section in &mut meta.bases {
+            if let Some(names) = technologies.get(&section.base) {
+                let mut detected: Vec<String> = names.clone();
+                detected.append(&mut section.technologies);
+                section.technologies = detected;
+            }
+        }
+        if meta.bases.len() > 1 {
+            for line in meta.base_lines() {
+                output::info(&line);
+            }
+        }
+    }
+
+    // Persist the updated database so the next `--incremental` run can send validators.
+    meta.finish();
+    if let Some(path) = &args.results_db {
+        db.save(path, &meta)?;
+    }
+
+    // Hand the findings to other tools if requested. Exports describe this run: the results
+    // database may also hold earlier runs and findings `--new-only` hid.
+    let found = db.this_run();
+    if let Some(path) = &args.export_defectdojo {
+        export::write_defectdojo(path, &found, &meta)?;
+    }
+    if let Some(path) = &args.export_urls {
+        let count = export::write_urls(path, &found, &args.export_status)?;
+        eprintln!("[*] wrote {} URL(s) to {}", count, path);
+    }
+    if let Some(path) = &args.export_tree {
+        export::write_tree(path, &found, &meta)?;
+    }
+    if let Some(dir) = &args.output_dir {
+        let count = export::write_output_dir(dir, &bases, &found, &meta, args.output_format)?;
+        eprintln!("[*] wrote {} per-host file(s) and index.json to {}", count, dir);
+    }
+
+    // `--fail-on-findings`: with everything written, fail the run if it found enough.
+    if let Some(threshold) = args.fail_on_findings
+        && let Some(monitor) = &hooks.monitor
+    {
+        let count = monitor
+            .findings()
+            .iter()
+            .filter(|entry| threshold.is_met_by(entry.severity))
+            .count();
+        if count > 0 {
+            return Err(DirustError::FindingsAtSeverity(count, threshold));
+        }
+    }
+
+    Ok(())
+}
+
+/// `--smart-exts`: detect the technologies behind `bases` and return the arguments with the
+/// extensions chosen for them, plus the header line explaining the choice. The names
+/// detected on each base are added to `per_base`.
+///
+/// A base that cannot be fetched only contributes no detections (the scan reports it).
+/// With `--profile-cache`, bases fingerprinted by an earlier scan are not fetched again.
+async fn smart_extensions(
+    client: &Client,
+    args: &ScanArgs,
+    bases: &[String],
+    profiles: Option<&ProfileCache>,
+    per_base: &mut HashMap<String, Vec<String>>,
+) -> (ScanArgs, String) {
+    let mut detected: Vec<Detection> = Vec::new();
+    for base in bases {
+        let stored: Option<Vec<TechRecord>> = match profiles {
+            Some(cache) if !args.recalibrate => cache.fresh(base).and_then(|profile| profile.technologies),
+            _ => None,
+        };
+        let reused = stored.is_some();
+        let result = match stored {
+            Some(records) => Ok(records
+                .iter()
+                .filter_map(|record| fingerprint::restore(&record.name, &record.evidence))
+                .collect()),
+            None => fingerprint::detect(client, base).await,
+        };
+        if let (Some(cache), Ok(found), false) = (profiles, &result, reused) {
+            cache.update(base, |profile| {
+                profile.technologies = Some(
+                    found
+                        .iter()
+                        .map(|d| TechRecord {
+                            name: d.name.to_string(),
+                            evidence: d.evidence.clone(),
+                        })
+                        .collect(),
+                );
+            });
+        }
+        match result {
+            Ok(found) => {
+                per_base.insert(base.clone(), found.iter().map(|d| d.name.to_string()).collect());
+                for detection in found {
+                    if !detected.iter().any(|d| d.name == detection.name) {
+                        detected.push(detection);
+                    }
+                }
+            }
+            Err(e) => eprintln!("[!] smart-exts: could not fingerprint {}: {}", base, e),
+        }
+    }
+
+    let requested = args.parse_exts();
+    let chosen = fingerprint::select_extensions(&detected, &requested);
+    let extensions = match chosen.is_empty() {
+        true => "none".to_string(),
+        false => chosen.join(","),
+    };
+    let note = match detected.is_empty() {
+        true => format!("[*] smart-exts: no technology recognized, extensions: {}", extensions),
+        false => {
+            let found: Vec<String> = detected.iter().map(|d| format!("{} ({})", d.name, d.evidence)).collect();
+            format!("[*] smart-exts: {} → extensions: {}", found.join(", "), extensions)
+        }
+    };
+
+    let mut smart = args.clone();
+    smart.exts = chosen.join(",");
+    (smart, note)
+}
+
+/// Print the size of the scan (`about to send ~N requests to <host> ...`) and, above
+/// `--confirm-above`, ask for confirmation unless `--yes` was given.
+///
+/// The estimate is an upper bound: words with a dot or a slash get no extensions, duplicates
+/// are dropped. Without a known word count (stdin, `url(...)`), nothing is asked.
+///
+/// Errors:
+///   - `DirustError::NotConfirmed` if the answer is not yes, or there is no terminal to ask on
+fn confirm_launch(args: &ScanArgs, bases: &[String], words: Option<usize>) -> Result<(), DirustError> {
+    let mut hosts: Vec<String> = Vec::new();
+    for base in bases {
+        let host = match reqwest::Url::parse(base) {
+            Ok(parsed) => parsed.host_str().unwrap_or(base).to_string(),
+            Err(_) => base.clone(),
+        };
+        if !hosts.contains(&host) {
+            hosts.push(host);
+        }
+    }
+    let hosts = hosts.join(", ");
+
+    let words = match words {
+        Some(n) => n as u64,
+        None => {
+            output::info(&format!("[*] about to scan {} (number of requests unknown: the wordlist size is not known up front)", hosts));
+            return Ok(());
+        }
+    };
+    let extensions = args.parse_exts().len();
+    let per_word = scanner::targets_per_word(args, extensions) as u64;
+    let requests = words.saturating_mul(per_word).saturating_mul(bases.len() as u64);
+    let bases_note = match bases.len() {
+        1 => String::new(),
+        n => format!(" × {} bases", n),
+    };
+    output::info(&format!(
+        "[*] about to send ~{} requests to {} ({} words × {} per word: as-is + {} extension(s){}{})",
+        requests,
+        hosts,
+        words,
+        per_word,
+        extensions,
+        if args.add_slash { " + slash" } else { "" },
+        bases_note
+    ));
+
+    if args.yes || args.confirm_above == 0 || requests <= args.confirm_above {
+        return Ok(());
+    }
+    // The question needs a terminal, and stdin must not be the wordlist.
+    if !std::io::stdin().is_terminal() || source::reads_stdin(args.wordlist_path()) {
+        return Err(DirustError::NotConfirmed(requests, args.confirm_above));
+    }
+    eprint!("[?] ~{} requests is more than --confirm-above {}. Start the scan? [y/N] ", requests, args.confirm_above);
+    let _ = std::io::stderr().flush();
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    match answer.trim().to_ascii_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => Err(DirustError::NotConfirmed(requests, args.confirm_above)),
+    }
+}
+
+/// Scan `bases` one after another (a single base, or `--no-interleave`).
+///
+/// Returns the summary of every base scanned, in order.
+async fn scan_in_turn(
+    client: &Client,
+    bases: &[String],
+    args: &ScanArgs,
+    db: &mut ResultsDb,
+    previous: Option<&ResultsDb>,
+    hooks: &ScanHooks,
+) -> Result<Vec<BaseSummary>, DirustError> {
+    let mut sections: Vec<BaseSummary> = Vec::new();
+    for scan_base in bases {
+        if hooks.is_cancelled() {
+            break;
+        }
+        if skip_finished(hooks, scan_base) {
+            continue;
+        }
+        if bases.len() > 1 {
+            output::info(&format!("[*] scanning {}", scan_base));
+        }
+        let words = source::open(args.wordlist_path(), args.priority.as_deref(), client).await?;
+        sections.push(scanner::scan(client, scan_base, args, words, db, previous, hooks).await?);
+    }
+    Ok(sections)
+}
+
+/// Scan all `bases` at the same time, taking turns: every probe of every base also holds a
+/// permit of one shared `-c` budget, and permits are handed out in the order probes asked for
+/// them, so each base's next probe waits behind the others' (round-robin across bases).
+/// Partial results then cover every base early. `--rate` applies per host: the bases of one
+/// host (its `--ports` / `--both-schemes` variants) share one limiter.
+///
+/// Each scan works on its own part of `db` (the entries under its base), folded back when
+/// it finishes. The first scan that fails stops the others. Returns the summary of every
+/// base scanned, in the order of `bases`.
+async fn scan_interleaved(
+    client: &Client,
+    bases: &[String],
+    args: &ScanArgs,
+    db: &mut ResultsDb,
+    previous: Option<&ResultsDb>,
+    hooks: &ScanHooks,
+) -> Result<Vec<BaseSummary>, DirustError> {
+    let mut hooks = hooks.clone();
+    let budget = Arc::new(Semaphore::new(args.http.concurrency.max(1)));
+    hooks.middleware.push(Arc::new(SharedBudget(budget)));
+    let mut rate_limits: HashMap<String, Arc<RateLimit>> = HashMap::new();
+
+    let mut scans: JoinSet<(String, ResultsDb, Result<BaseSummary, DirustError>)> = JoinSet::new();
+    for scan_base in bases {
+        if skip_finished(&hooks, scan_base) {
+            continue;
+        }
+        output::info(&format!("[*] scanning {} (interleaved with the other bases)", scan_base));
+        let words = source::open(args.wordlist_path(), args.priority.as_deref(), client).await?;
+        let client = client.clone();
+        let base = scan_base.clone();
+        let args = args.clone();
+        let mut hooks = hooks.clone();
+        if let Some(rate) = args.rate {
+            let host = reqwest::Url::parse(scan_base)
+                .ok()
+                .and_then(|url| url.host_str().map(|host| host.to_string()))
+                .unwrap_or_else(|| scan_base.clone());
+            let limit = rate_limits.entry(host).or_insert_with(|| Arc::new(RateLimit::new(rate)));
+            hooks.rate_limit = Some(Arc::clone(limit));
+        }
+        let mut part = db.subset(scan_base);
+        let previous = previous.map(|set| set.subset(scan_base));
+        scans.spawn(async move {
+            let outcome = scanner::scan(&client, &base, &args, words, &mut part, previous.as_ref(), &hooks).await;
+            (base, part, outcome)
+        });
+    }
+
+    let mut sections: Vec<BaseSummary> = Vec::new();
+    while let Some(joined) = scans.join_next().await {
+        let (base, part, outcome) = joined?;
+        match outcome {
+            Ok(summary) => sections.push(summary),
+            Err(e) => {
+                scans.abort_all();
+                return Err(e);
+            }
+        }
+        db.replace_subset(&base, part);
+    }
+    sections.sort_by_key(|section| bases.iter().position(|base| *base == section.base));
+    Ok(sections)
+}
+
+/// Whether `--checkpoint` says `scan_base` was scanned to the end already (said so if yes).
+fn skip_finished(hooks: &ScanHooks, scan_base: &str) -> bool {
+    match &hooks.checkpoint {
+        Some(checkpoint) if checkpoint.is_finished(scan_base) => {
+            output::info(&format!("[*] checkpoint: {} was already scanned, skipping it", scan_base));
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Expand the normalized base into the list of bases to scan.
+///
+/// Rules:
+///   - `--ports`: one base per port, with the scheme guessed from the port (443/8443 → https)
+///   - `--both-schemes`: every candidate is also tried with the other scheme (on the same
+///     port when it came from `--ports`)
+///   - scheme-less input without either flag: https://, falling back to http:// when the
+///     TLS handshake fails or the connection is refused (not on DNS failures or timeouts)
+///   - otherwise: exactly the base that was given
+///
+/// When several candidates are generated, the ones that do not answer at all (closed port,
+/// no TLS) are skipped with a warning. If none answers, the first candidate is kept so the
+/// scan reports the connection error.
+async fn select_bases(client: &Client, args: &ScanArgs, base: &str) -> Result<Vec<String>, DirustError> {
+    // Only the base itself, possibly with a scheme fallback.
+    if args.ports.is_empty() && !args.both_schemes {
+        if !url::has_explicit_scheme(args.base_url())
+            && let Some(failure) = client::handshake_failure(client, base).await
+        {
+            let fallback = url::swap_scheme(base);
+            eprintln!("[!] {}: {}, falling back to {}", base, failure, fallback);
+            return Ok(vec![fallback]);
+        }
+        return Ok(vec![base.to_string()]);
+    }
+
+    // Build the candidate list.
+    let mut per_port: Vec<String> = Vec::new();
+    if args.ports.is_empty() {
+        per_port.push(base.to_string());
+    } else {
+        for &port in &args.ports {
+            per_port.push(url::with_port(base, port)?);
+        }
+    }
+
+    let mut candidates: Vec<String> = Vec::new();
+    for candidate in per_port {
+        // A port from `--ports` stays: `--ports 80 --both-schemes` means https on port 80 too.
+        let swapped = match args.ports.is_empty() {
+            true => url::swap_scheme(&candidate),
+            false => url::swap_scheme_keep_port(&candidate)?,
+        };
+        if !candidates.contains(&candidate) {
+            candidates.push(candidate);
+        }
+        if args.both_schemes && !candidates.contains(&swapped) {
+            candidates.push(swapped);
+        }
+    }
+
+    // Keep the candidates that answer.
+    let mut reachable: Vec<String> = Vec::new();
+    for candidate in &candidates {
+        if client::is_reachable(client, candidate).await {
+            reachable.push(candidate.clone());
+        } else {
+            eprintln!("[!] {} is not reachable, skipping", candidate);
+        }
+    }
+    if reachable.is_empty() {
+        reachable.push(candidates[0].clone());
+    }
+
+    Ok(reachable)
+}
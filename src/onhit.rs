@@ -0,0 +1,217 @@
+//! src/onhit.rs
+//!
+//! Purpose:
+//!   `--on-hit <CMD>`: run an external command for every finding as it is reported, e.g. a
+//!   headless-browser screenshot tool, a notifier or a second scanner. Commands run in the
+//!   background: the scan never waits for them, however slow they are.
+//!
+//! How:
+//!   - The findings come from the scan monitor, like the `--stream-to` / `--syslog`
+//!     forwarders. `{url}`, `{status}`, `{length}` and `{severity}` in the command are replaced
+//!     by the finding's values, quoted for the shell, and the command line runs through
+//!     `sh -c` (`cmd /C` on Windows). The same values are set as `DIRUST_URL`,
+//!     `DIRUST_STATUS`, `DIRUST_LENGTH` and `DIRUST_SEVERITY`.
+//!   - At most `--on-hit-concurrency` commands run at once; the others queue in order.
+//!   - A command still running after `--on-hit-timeout` is killed. Output is discarded;
+//!     a non-zero exit or a timeout is reported on stderr. The scan's result never depends
+//!     on them.
+//!   - When the scan is over, queued and running commands are waited for (each still
+//!     bounded by the timeout), then counted in one line.
+//!
+//! Notes:
+//!   - Values are passed as they are, without `--no-redact` masking: the command needs the
+//!     real URL. Findings are not filtered by `--notify-severity`; filter on `{severity}`.
+//!   - A timeout kills the shell; programs it started in the background are not tracked.
+//!
+//! Output:
+//!   [!] on-hit: command for https://example.com/admin/ exited with status 1
+//!   [!] on-hit: command for https://example.com/backup/ killed after 30s
+//!   [*] on-hit: 14 command(s) run, 1 failed, 1 timed out
+
+use crate::{
+    output,
+    scanner::monitor::{MonitorEvent, ScanMonitor},
+    schema::ResultEntry,
+};
+use std::{process::Stdio, sync::Arc, time::Duration};
+use tokio::{
+    process::Command,
+    sync::{Semaphore, broadcast::error::RecvError, oneshot},
+    task::{JoinHandle, JoinSet},
+};
+
+/// How one command ended.
+enum Ran {
+    Ok,
+    Failed,
+    TimedOut,
+}
+
+/// Commands that ended, for the closing line.
+#[derive(Default)]
+struct Tally {
+    ran: usize,
+    failed: usize,
+    timed_out: usize,
+}
+
+impl Tally {
+    fn add(&mut self, joined: Result<Ran, tokio::task::JoinError>) {
+        self.ran += 1;
+        match joined {
+            Ok(Ran::Ok) => {}
+            Ok(Ran::TimedOut) => self.timed_out += 1,
+            Ok(Ran::Failed) | Err(_) => self.failed += 1,
+        }
+    }
+}
+
+/// A running `--on-hit` hook; `close` it when the scan is over.
+pub struct OnHitHook {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl OnHitHook {
+    /// Run `command` for every finding `monitor` reports from now on, at most `concurrency`
+    /// at a time, each for at most `timeout`.
+    pub fn start(command: &str, concurrency: usize, timeout: Duration, monitor: Arc<ScanMonitor>) -> OnHitHook {
+        let (stop, mut stopped) = oneshot::channel::<()>();
+        let command = command.to_string();
+
+        let task = tokio::spawn(async move {
+            let slots = Arc::new(Semaphore::new(concurrency.max(1)));
+            let mut running: JoinSet<Ran> = JoinSet::new();
+            let mut tally = Tally::default();
+            let mut started = 0;
+            let mut events = monitor.subscribe();
+            loop {
+                tokio::select! {
+                    _ = &mut stopped => break,
+                    Some(joined) = running.join_next() => tally.add(joined),
+                    event = events.recv() => match event {
+                        Ok(MonitorEvent::Finding(_)) | Err(RecvError::Lagged(_)) => {
+                            started = spawn_new(&mut running, &slots, &command, timeout, &monitor, started);
+                        }
+                        Ok(MonitorEvent::Finished) | Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+            // Whatever arrived since the last event.
+            spawn_new(&mut running, &slots, &command, timeout, &monitor, started);
+
+            if !running.is_empty() {
+                output::info(&format!("[*] on-hit: waiting for {} command(s)", running.len()));
+            }
+            while let Some(joined) = running.join_next().await {
+                tally.add(joined);
+            }
+            if tally.ran > 0 {
+                output::info(&format!(
+                    "[*] on-hit: {} command(s) run, {} failed, {} timed out",
+                    tally.ran, tally.failed, tally.timed_out
+                ));
+            }
+        });
+
+        OnHitHook { stop, task }
+    }
+
+    /// Start the commands of the findings not seen yet, then wait for every command.
+    pub async fn close(self) {
+        let _ = self.stop.send(());
+        let _ = self.task.await;
+    }
+}
+
+/// Queue a command for every finding of `monitor` after the first `started` ones; returns
+/// the new count.
+fn spawn_new(
+    running: &mut JoinSet<Ran>,
+    slots: &Arc<Semaphore>,
+    command: &str,
+    timeout: Duration,
+    monitor: &ScanMonitor,
+    started: usize,
+) -> usize {
+    let entries = monitor.findings_since(started);
+    for entry in &entries {
+        let slots = Arc::clone(slots);
+        let command = command.to_string();
+        let entry = entry.clone();
+        running.spawn(async move {
+            // The semaphore is never closed; without a permit the command runs anyway.
+            let _permit = slots.acquire_owned().await.ok();
+            run(&command, &entry, timeout).await
+        });
+    }
+    started + entries.len()
+}
+
+/// Run `command` for `entry`, killing it after `timeout`.
+async fn run(command: &str, entry: &ResultEntry, timeout: Duration) -> Ran {
+    let values = [
+        ("url", entry.url.clone()),
+        ("status", entry.status.to_string()),
+        ("length", entry.content_length.map(|l| l.to_string()).unwrap_or_default()),
+        ("severity", entry.severity.map(|s| s.to_string()).unwrap_or_default()),
+    ];
+    let mut line = command.to_string();
+    for (name, value) in &values {
+        line = line.replace(&format!("{{{}}}", name), &quote(value));
+    }
+
+    let mut shell = match cfg!(windows) {
+        true => {
+            let mut shell = Command::new("cmd");
+            shell.arg("/C").arg(&line);
+            shell
+        }
+        false => {
+            let mut shell = Command::new("sh");
+            shell.arg("-c").arg(&line);
+            shell
+        }
+    };
+    for (name, value) in &values {
+        shell.env(format!("DIRUST_{}", name.to_ascii_uppercase()), value);
+    }
+    shell.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null()).kill_on_drop(true);
+
+    let mut child = match shell.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("[!] on-hit: could not start the command for {}: {}", entry.url, e);
+            return Ran::Failed;
+        }
+    };
+    match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(Ok(status)) if status.success() => Ran::Ok,
+        Ok(Ok(status)) => {
+            let code = match status.code() {
+                Some(code) => format!("status {}", code),
+                None => "a signal".to_string(),
+            };
+            eprintln!("[!] on-hit: command for {} exited with {}", entry.url, code);
+            Ran::Failed
+        }
+        Ok(Err(e)) => {
+            eprintln!("[!] on-hit: command for {} failed: {}", entry.url, e);
+            Ran::Failed
+        }
+        Err(_) => {
+            let _ = child.kill().await;
+            eprintln!("[!] on-hit: command for {} killed after {}s", entry.url, timeout.as_secs());
+            Ran::TimedOut
+        }
+    }
+}
+
+/// `value` as one shell word: single quotes for `sh`, double quotes (without any inside) for
+/// `cmd`.
+fn quote(value: &str) -> String {
+    match cfg!(windows) {
+        true => format!("\"{}\"", value.replace('"', "")),
+        false => format!("'{}'", value.replace('\'', r"'\''")),
+    }
+}
@@ -0,0 +1,240 @@
+//! src/output.rs
+//!
+//! Purpose:
+//!   The terminal writer for result lines: decides once whether to use color and paints
+//!   lines by what they report, so callers format plain text and never deal with ANSI codes.
+//!   It also decides which lines reach stdout at all (`-q`, `--silent`).
+//!
+//! Verbosity:
+//!   - normal     banner, progress and summaries (`info`) plus findings
+//!   - `-q`       findings and their annotations only (`finding`, `print_styled`)
+//!   - `--silent` only the machine-readable form of each finding (e.g., its bare URL),
+//!     so `dirust --silent <url> -w list | other-tool` needs no filtering
+//!
+//! Warnings and errors go to stderr in every mode.
+//!
+//! Live status line:
+//!   While `scanner/live.rs` shows its status line on stderr, every stdout line first
+//!   erases it (it is redrawn on the next tick), so findings never end up glued to it.
+//!
+//! Timestamps:
+//!   Result lines start with `timestamp_prefix()`, in the `--time-format` chosen:
+//!   `rfc3339` (default, UTC with milliseconds, to line up with server logs), `unix`
+//!   (seconds) or `none` (no prefix).
+//!
+//! Colors:
+//!   - 2xx          green
+//!   - 3xx          yellow
+//!   - 401 / 403    red
+//!   - other        uncolored
+//!   - filtered     dim (e.g., redirects collapsed into one group line)
+//!   - alerts       bold red (e.g., `--secrets` matches)
+//!
+//! Color is used only when stdout is a terminal, `--no-color` was not given and the
+//! `NO_COLOR` environment variable is not set (https://no-color.org). Redirected output
+//! (`> results.txt`, pipes) therefore stays plain text.
+
+use crate::{args::TimeFormat, export, redact};
+use std::{
+    io::IsTerminal,
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, AtomicU8, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Whether lines are painted; set once by `init`.
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/// The current `Verbosity` (as its discriminant); set once by `init`.
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+/// Whether the live status line is on screen (see the module docs).
+static LIVE_LINE: AtomicBool = AtomicBool::new(false);
+
+/// Serializes terminal writes while the live status line is active.
+static TERMINAL: Mutex<()> = Mutex::new(());
+
+/// Erases the current terminal line.
+const CLEAR_LINE: &str = "\r\x1b[2K";
+
+/// The `--time-format` (as its discriminant); set once by `init`.
+static TIME_FORMAT: AtomicU8 = AtomicU8::new(TimeFormat::Rfc3339 as u8);
+
+/// How much goes to stdout (see the module docs).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Silent = 0,
+    Quiet = 1,
+    Normal = 2,
+}
+
+impl Verbosity {
+    /// The level selected by the global `-q` / `--silent` flags.
+    pub fn from_flags(quiet: bool, silent: bool) -> Verbosity {
+        match (quiet, silent) {
+            (_, true) => Verbosity::Silent,
+            (true, false) => Verbosity::Quiet,
+            (false, false) => Verbosity::Normal,
+        }
+    }
+}
+
+/// What a line reports, which decides its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Success,
+    Redirect,
+    Denied,
+    Plain,
+    Dim,
+    Alert,
+}
+
+impl Style {
+    /// The style for a result with HTTP status `status`.
+    pub fn for_status(status: u16) -> Style {
+        match status {
+            200..=299 => Style::Success,
+            300..=399 => Style::Redirect,
+            401 | 403 => Style::Denied,
+            _ => Style::Plain,
+        }
+    }
+
+    /// ANSI SGR parameters, or `None` for unpainted text.
+    fn sgr(self) -> Option<&'static str> {
+        match self {
+            Style::Success => Some("32"),
+            Style::Redirect => Some("33"),
+            Style::Denied => Some("31"),
+            Style::Plain => None,
+            Style::Dim => Some("2"),
+            Style::Alert => Some("1;31"),
+        }
+    }
+}
+
+/// Decide whether to use color, how much to print and how to stamp lines (see the module
+/// docs). Call once, before any output.
+pub fn init(no_color: bool, verbosity: Verbosity, time_format: TimeFormat) {
+    let enabled = !no_color && std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal();
+    COLOR.store(enabled, Ordering::Relaxed);
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+    TIME_FORMAT.store(time_format as u8, Ordering::Relaxed);
+}
+
+/// The level set by `init`.
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Silent,
+        1 => Verbosity::Quiet,
+        _ => Verbosity::Normal,
+    }
+}
+
+/// The format set by `init`.
+fn time_format() -> TimeFormat {
+    match TIME_FORMAT.load(Ordering::Relaxed) {
+        0 => TimeFormat::Unix,
+        2 => TimeFormat::None,
+        _ => TimeFormat::Rfc3339,
+    }
+}
+
+/// The current time as a line prefix: `"[2024-04-05T19:21:18.042Z] "`,
+/// `"[1712344878] "` or `""`, depending on `--time-format`.
+///
+/// Panics if the system clock is set before 1970, which would make every timestamp
+/// meaningless anyway.
+pub fn timestamp_prefix() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time before UNIX_EPOCH");
+    match time_format() {
+        TimeFormat::Unix => format!("[{}] ", now.as_secs()),
+        TimeFormat::None => String::new(),
+        TimeFormat::Rfc3339 => format!("[{}] ", export::rfc3339_millis(now)),
+    }
+}
+
+/// `text` wrapped in the escape codes for `style` (unchanged when color is off).
+pub fn paint(text: &str, style: Style) -> String {
+    match style.sgr() {
+        Some(sgr) if COLOR.load(Ordering::Relaxed) => format!("\x1b[{}m{}\x1b[0m", sgr, text),
+        _ => text.to_string(),
+    }
+}
+
+/// Print a banner, progress or summary line (normal verbosity only).
+pub fn info(text: &str) {
+    if verbosity() == Verbosity::Normal {
+        emit(text);
+    }
+}
+
+/// Print a finding: the full `line` in `style`, or only `bare` (its machine-readable form)
+/// with `--silent`. Findings without a machine-readable form pass `None`.
+pub fn finding(line: &str, style: Style, bare: Option<&str>) {
+    match verbosity() {
+        Verbosity::Silent => {
+            if let Some(bare) = bare {
+                emit(bare);
+            }
+        }
+        Verbosity::Quiet | Verbosity::Normal => emit(&paint(line, style)),
+    }
+}
+
+/// Print an annotation of a finding in `style` (hidden with `--silent`).
+pub fn print_styled(text: &str, style: Style) {
+    if verbosity() != Verbosity::Silent {
+        emit(&paint(text, style));
+    }
+}
+
+/// Write one line to stdout (credentials masked, see `redact.rs`), erasing the live status
+/// line first if it is shown.
+fn emit(text: &str) {
+    let text: &str = &redact::text(text);
+    if !LIVE_LINE.load(Ordering::Relaxed) {
+        println!("{}", text);
+        return;
+    }
+    let _terminal = match TERMINAL.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    eprint!("{}", CLEAR_LINE);
+    println!("{}", text);
+}
+
+/// Whether a live status line may be shown: normal verbosity and stderr is a terminal.
+pub fn live_line_supported() -> bool {
+    verbosity() == Verbosity::Normal && std::io::stderr().is_terminal()
+}
+
+/// Draw (or redraw) the live status line on stderr.
+pub fn draw_live_line(text: &str) {
+    let _terminal = match TERMINAL.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    LIVE_LINE.store(true, Ordering::Relaxed);
+    let mut stderr = std::io::stderr();
+    let _ = write!(stderr, "{}{}", CLEAR_LINE, text);
+    let _ = stderr.flush();
+}
+
+/// Erase the live status line for good (the scan is over).
+pub fn clear_live_line() {
+    let _terminal = match TERMINAL.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if LIVE_LINE.swap(false, Ordering::Relaxed) {
+        eprint!("{}", CLEAR_LINE);
+    }
+}
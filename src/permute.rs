@@ -0,0 +1,193 @@
+//! src/permute.rs
+//!
+//! Purpose:
+//!   `--permute` (scans and `dirust dns`): look for assets next to the ones already found,
+//!   the way altdns does for subdomains. A hit on `/api-v1/` or `vpn.example.com` suggests
+//!   `/api-v2/`, `/api-dev/`, `vpn-stage.example.com` or `vpn2.example.com`: names that are
+//!   in no wordlist, but follow the naming of the target.
+//!
+//! How:
+//!   Every found name (the last path segment of a directory finding, the first label of a
+//!   resolved name) is varied:
+//!     - a word attached, with and without a separator: `admin-dev`, `dev-admin`,
+//!       `admin_dev`, `admindev`, ... (DNS names also get the word as a label: `dev.vpn`);
+//!     - every number swapped for its neighbours, keeping zero padding: `v2` → `v1`, `v3`,
+//!       `node01` → `node00`, `node02`; a name without numbers gets a `2`.
+//!   Variants that are found are varied again, up to `--permute-depth` generations away
+//!   from a wordlist hit. Every variant is tried once, however many finds suggest it.
+//!
+//! Notes:
+//!   - Files (`login.php`) are not varied: variants of a directory are far more likely to
+//!     exist than siblings of a file.
+//!   - `--permute-words <PATH>` replaces the built-in words (one per line).
+//!   - A scan resumed from `--checkpoint` no longer knows which queued variants came from
+//!     which find; their finds count as first generation.
+//!
+//! Output:
+//!       ↳ 200 https://example.com/api-v2/  (permutation of https://example.com/api-v1/)
+//!   [2024-04-05T19:21:18.042Z] vpn-stage.example.com                    203.0.113.9  (permutation of vpn.example.com)
+
+use crate::{args::PermuteArgs, error::DirustError, output, scanner::wordlist};
+use std::collections::HashMap;
+
+/// Words attached to found names when `--permute-words` is not given.
+const WORDS: &[&str] = &[
+    "dev", "test", "stage", "staging", "qa", "uat", "prod", "beta", "old", "new", "backup", "internal", "v2",
+];
+
+/// Separators between a found name and an attached word in URL paths.
+const PATH_SEPARATORS: &[&str] = &["-", "_", ""];
+
+/// Separators between a found name and an attached word in DNS labels (no `_` in hostnames).
+const LABEL_SEPARATORS: &[&str] = &["-", ""];
+
+/// Variant generation for one scan or DNS run, with the generation of every variant.
+#[derive(Debug)]
+pub struct Permuter {
+    words: Vec<String>,
+    depth: usize,
+    /// Every variant handed out: its generation and the find it was derived from.
+    origins: HashMap<String, (usize, String)>,
+}
+
+impl Permuter {
+    /// The permuter for `args`, or `None` without `--permute`.
+    pub fn from_args(args: &PermuteArgs) -> Result<Option<Permuter>, DirustError> {
+        if !args.permute {
+            return Ok(None);
+        }
+        let words: Vec<String> = match &args.permute_words {
+            Some(path) => wordlist::read_wordlist(path)?
+                .into_iter()
+                .map(|w| w.trim().to_ascii_lowercase())
+                .filter(|w| !w.is_empty())
+                .collect(),
+            None => WORDS.iter().map(|w| w.to_string()).collect(),
+        };
+        Ok(Some(Permuter {
+            words,
+            depth: args.permute_depth as usize,
+            origins: HashMap::new(),
+        }))
+    }
+
+    /// The find `target` is a variant of (`None`: not a variant).
+    pub fn origin(&self, target: &str) -> Option<&str> {
+        self.origins.get(target).map(|(_, from)| from.as_str())
+    }
+
+    /// Variants of the directory finding `url`: its last path segment varied, the rest kept.
+    /// Empty for files, for the base itself, and once `url` is `--permute-depth` generations
+    /// deep.
+    pub fn path_variants(&mut self, url: &str) -> Vec<String> {
+        let slash = url.ends_with('/');
+        let trimmed = url.trim_end_matches('/');
+        let Some((parent, segment)) = trimmed.rsplit_once('/') else {
+            return Vec::new();
+        };
+        // `https://host` splits at the `//` of the scheme: that is the base, not a directory.
+        if segment.is_empty() || segment.contains('.') || parent.ends_with('/') {
+            return Vec::new();
+        }
+        let candidates: Vec<String> = variants(segment, &self.words, PATH_SEPARATORS, false)
+            .into_iter()
+            .map(|v| format!("{}/{}{}", parent, v, if slash { "/" } else { "" }))
+            .collect();
+        self.record(url, candidates)
+    }
+
+    /// Variants of the resolved name `name` under `domain`: its first label varied (and a
+    /// label inserted before it). Empty once `name` is `--permute-depth` generations deep.
+    pub fn name_variants(&mut self, name: &str, domain: &str) -> Vec<String> {
+        let Some(sub) = name.strip_suffix(domain).and_then(|s| s.strip_suffix('.')) else {
+            return Vec::new();
+        };
+        let (label, rest) = match sub.split_once('.') {
+            Some((label, rest)) => (label, format!(".{}", rest)),
+            None => (sub, String::new()),
+        };
+        let candidates: Vec<String> = variants(label, &self.words, LABEL_SEPARATORS, true)
+            .into_iter()
+            .map(|v| format!("{}{}.{}", v, rest, domain))
+            .collect();
+        self.record(name, candidates)
+    }
+
+    /// Keep the `candidates` derived from `found` that were not handed out before (none when
+    /// `found` is already `depth` generations deep).
+    fn record(&mut self, found: &str, candidates: Vec<String>) -> Vec<String> {
+        let generation = match self.origins.get(found) {
+            Some((generation, _)) => generation + 1,
+            None => 1,
+        };
+        if generation > self.depth {
+            return Vec::new();
+        }
+        let mut fresh: Vec<String> = Vec::new();
+        for candidate in candidates {
+            if candidate != found && !self.origins.contains_key(&candidate) {
+                self.origins.insert(candidate.clone(), (generation, found.to_string()));
+                fresh.push(candidate);
+            }
+        }
+        fresh
+    }
+}
+
+/// Print a find that came from `--permute`, below its result line.
+pub fn print_found(url: &str, status: u16, origin: &str) {
+    output::info(&format!("    ↳ {} {}  (permutation of {})", status, url, origin));
+}
+
+/// The variants of one name: `words` attached with each of `separators` on either side (as
+/// an extra label too, with `dotted`), and its numbers swapped.
+fn variants(name: &str, words: &[String], separators: &[&str], dotted: bool) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut push = |candidate: String| {
+        if candidate != name && !out.contains(&candidate) {
+            out.push(candidate);
+        }
+    };
+    for word in words {
+        if word == name {
+            continue;
+        }
+        for separator in separators {
+            push(format!("{}{}{}", name, separator, word));
+            push(format!("{}{}{}", word, separator, name));
+        }
+        if dotted {
+            push(format!("{}.{}", word, name));
+        }
+    }
+    let swapped = swap_numbers(name);
+    match swapped.is_empty() {
+        true => push(format!("{}2", name)),
+        false => swapped.into_iter().for_each(push),
+    }
+    out
+}
+
+/// `name` with one of its numbers replaced by the number before or after it (zero padding
+/// kept: `node01` → `node00`, `node02`). Empty when `name` has no digits.
+fn swap_numbers(name: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let bytes = name.as_bytes();
+    let mut start: usize = 0;
+    while start < bytes.len() {
+        if !bytes[start].is_ascii_digit() {
+            start += 1;
+            continue;
+        }
+        let end = start + bytes[start..].iter().take_while(|b| b.is_ascii_digit()).count();
+        let digits = &name[start..end];
+        if let Ok(number) = digits.parse::<u64>() {
+            let neighbours = [number.checked_sub(1), number.checked_add(1)];
+            for n in neighbours.into_iter().flatten() {
+                out.push(format!("{}{:0width$}{}", &name[..start], n, &name[end..], width = digits.len()));
+            }
+        }
+        start = end;
+    }
+    out
+}
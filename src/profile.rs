@@ -0,0 +1,159 @@
+//! src/profile.rs
+//!
+//! Purpose:
+//!   Per-host calibration profiles (`--profile-cache <PATH>`). What a scan measures about a
+//!   base URL before its first probe is stored, so the next scan of the same base starts
+//!   with it instead of measuring again:
+//!     - the random-path baseline (status, size and tolerance of a "not found" answer,
+//!       which gives away wildcard / soft-404 responses)
+//!     - whether HEAD is allowed (if not, probes use GET from the start instead of
+//!       retrying every 405)
+//!     - the technologies `--smart-exts` recognized
+//!
+//! File format (JSON, rewritten at the end of every scan):
+//!   { "version": 1,
+//!     "profiles": { "https://example.com/": {
+//!         "updated_at": 1712345678,
+//!         "not_found": { "status": 404, "length": 335, "tolerance": 16 },
+//!         "head_supported": true,
+//!         "technologies": [ { "name": "PHP", "evidence": "x-powered-by: PHP/8.1.2" } ] } } }
+//!
+//! Notes:
+//!   - Profiles are keyed by base URL (scheme, host, port and path): a host can answer
+//!     differently under `/` and `/app/`.
+//!   - Profiles older than `MAX_AGE` are measured again; `--recalibrate` always measures
+//!     again (and refreshes the profile).
+//!   - A missing file is an empty cache.
+
+use crate::error::DirustError;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Version of the profile file. Bump on any incompatible change.
+const PROFILE_VERSION: u32 = 1;
+
+/// Profiles older than this (seconds) are measured again: one week.
+const MAX_AGE: u64 = 7 * 24 * 3600;
+
+/// What is known about one base URL.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HostProfile {
+    /// When the profile was last written (UNIX seconds).
+    pub updated_at: u64,
+    /// The random-path baseline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_found: Option<BaselineRecord>,
+    /// Whether HEAD requests are answered (not 405).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head_supported: Option<bool>,
+    /// Technologies recognized by `--smart-exts` (`None`: never fingerprinted).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub technologies: Option<Vec<TechRecord>>,
+}
+
+/// A stored baseline (see `fuzz::baseline::Baseline`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BaselineRecord {
+    pub status: u16,
+    pub length: u64,
+    pub tolerance: u64,
+}
+
+/// A stored technology detection (see `scanner::fingerprint::Detection`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TechRecord {
+    pub name: String,
+    pub evidence: String,
+}
+
+/// The whole file.
+#[derive(Debug, Serialize, Deserialize)]
+struct ProfileFile {
+    version: u32,
+    profiles: BTreeMap<String, HostProfile>,
+}
+
+/// Profiles loaded from `--profile-cache`, shared by every scan of the run.
+pub struct ProfileCache {
+    path: String,
+    profiles: Mutex<BTreeMap<String, HostProfile>>,
+}
+
+impl ProfileCache {
+    /// Load the cache at `path` (missing file: empty cache).
+    ///
+    /// Errors:
+    ///   - `DirustError::Io` / `DirustError::Json` if the file cannot be read or parsed
+    ///   - `DirustError::UnsupportedSchema` if it was written by a newer version
+    pub fn load(path: &str) -> Result<ProfileCache, DirustError> {
+        let profiles = match File::open(path) {
+            Ok(file) => {
+                let parsed: ProfileFile = serde_json::from_reader(BufReader::new(file))?;
+                if parsed.version > PROFILE_VERSION {
+                    return Err(DirustError::UnsupportedSchema(parsed.version));
+                }
+                parsed.profiles
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(DirustError::Io(e)),
+        };
+        Ok(ProfileCache {
+            path: path.to_string(),
+            profiles: Mutex::new(profiles),
+        })
+    }
+
+    /// The profile of `base`, unless there is none or it is older than `MAX_AGE`.
+    pub fn fresh(&self, base: &str) -> Option<HostProfile> {
+        let profiles = match self.profiles.lock() {
+            Ok(g) => g,
+            Err(p) => p.into_inner(),
+        };
+        profiles
+            .get(base)
+            .filter(|profile| unix_now().saturating_sub(profile.updated_at) <= MAX_AGE)
+            .cloned()
+    }
+
+    /// Change (or create) the profile of `base` and stamp it with the current time.
+    pub fn update<F>(&self, base: &str, change: F)
+    where
+        F: FnOnce(&mut HostProfile),
+    {
+        let mut profiles = match self.profiles.lock() {
+            Ok(g) => g,
+            Err(p) => p.into_inner(),
+        };
+        let profile = profiles.entry(base.to_string()).or_default();
+        change(profile);
+        profile.updated_at = unix_now();
+    }
+
+    /// Write every profile back to the file.
+    pub fn save(&self) -> Result<(), DirustError> {
+        let profiles = match self.profiles.lock() {
+            Ok(g) => g.clone(),
+            Err(p) => p.into_inner().clone(),
+        };
+        let file = ProfileFile {
+            version: PROFILE_VERSION,
+            profiles,
+        };
+        serde_json::to_writer_pretty(BufWriter::new(File::create(&self.path)?), &file)?;
+        Ok(())
+    }
+}
+
+/// Seconds since the UNIX epoch (0 for a clock set before 1970).
+fn unix_now() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs(),
+        Err(_) => 0,
+    }
+}
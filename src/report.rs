@@ -0,0 +1,229 @@
+//! src/report.rs
+//!
+//! Purpose:
+//!   `dirust report <FILE>`: print the findings stored in a results file (e.g., a
+//!   `--results-db` file) in the same line format the scanner uses, so a past scan can be
+//!   reviewed without re-running it.
+//!
+//! Format:
+//!   [*] report: <target> (scanned <YYYY-MM-DD>, <n> findings)
+//!   <status> len=<Content-Length or "-">  <url> [-> <Location>][ [SEVERITY tags]][ [verdict: note]]
+//!
+//! Findings reviewed with `dirust triage` carry their verdict and note; `--hide-false-positives`
+//! leaves out the ones marked false positive.
+//!
+//! Sorting and grouping (for write-ups):
+//!   dirust report results.json --sort status,length --group-by dir
+//!
+//!   [*] dir https://example.com/ (1 finding)
+//!   200 len=1024  https://example.com/index.html
+//!   [*] dir https://example.com/admin/ (2 findings)
+//!   200 len=512  https://example.com/admin/login.php
+//!   403 len=199  https://example.com/admin/config/
+//!
+//!   - `--sort` takes keys (url, host, dir, ext, status, length, severity); each one breaks
+//!     the ties of the previous, and the URL breaks the remaining ones.
+//!   - `--group-by` prints the findings sharing a key value together under a header; groups
+//!     are ordered by that key, the findings inside them by `--sort`.
+
+use crate::{
+    args::{ReportArgs, ReportKey},
+    db,
+    error::DirustError,
+    export,
+    output::{self, Style},
+    scanner,
+    schema::{ResultEntry, Verdict},
+};
+use reqwest::Url;
+use std::cmp::Ordering;
+
+/// Print every finding of `args.file`, sorted by `--sort` (default: URL), grouped by
+/// `--group-by` if given.
+pub fn run(args: &ReportArgs) -> Result<(), DirustError> {
+    let result = db::read_result_file(&args.file)?;
+
+    let mut entries: Vec<ResultEntry> = result.results;
+    entries.sort_by(|a, b| {
+        args.sort
+            .iter()
+            .fold(Ordering::Equal, |order, key| order.then_with(|| compare(*key, a, b)))
+            .then_with(|| a.url.cmp(&b.url))
+    });
+    if args.hide_false_positives {
+        entries.retain(|entry| !is_false_positive(entry));
+    }
+
+    output::info(&format!(
+        "[*] report: {} (scanned {}, {} findings)",
+        result.scan.target,
+        export::iso_date(result.scan.started_at),
+        entries.len()
+    ));
+    // Scan settings, when the file records them (files from older versions do not).
+    if let Some(settings) = result.scan.header_lines().get(1) {
+        output::info(settings);
+    }
+
+    let key = match args.group_by {
+        Some(key) => key,
+        None => {
+            for entry in &entries {
+                print_entry(entry);
+            }
+            return Ok(());
+        }
+    };
+    // Findings keep their sorted order inside a group; groups are ordered by the key.
+    let mut groups: Vec<(String, Vec<&ResultEntry>)> = Vec::new();
+    for entry in &entries {
+        let value = key_label(key, entry);
+        match groups.iter_mut().find(|(label, _)| *label == value) {
+            Some((_, members)) => members.push(entry),
+            None => groups.push((value, vec![entry])),
+        }
+    }
+    groups.sort_by(|(_, a), (_, b)| compare(key, a[0], b[0]));
+    for (label, members) in &groups {
+        output::info(&format!(
+            "[*] {} {} ({} finding{})",
+            key_name(key),
+            label,
+            members.len(),
+            if members.len() == 1 { "" } else { "s" }
+        ));
+        for entry in members {
+            print_entry(entry);
+        }
+    }
+
+    Ok(())
+}
+
+/// Print one finding as a result line.
+fn print_entry(entry: &ResultEntry) {
+    output::finding(&format_entry(entry), Style::for_status(entry.status), Some(&entry.url));
+}
+
+/// Order of two findings by one `--sort` key.
+fn compare(key: ReportKey, a: &ResultEntry, b: &ResultEntry) -> Ordering {
+    match key {
+        ReportKey::Url => a.url.cmp(&b.url),
+        ReportKey::Host => host_of(&a.url).cmp(&host_of(&b.url)),
+        ReportKey::Dir => dir_of(&a.url).cmp(&dir_of(&b.url)),
+        // Findings without an extension (directories) go last.
+        ReportKey::Ext => {
+            let (ext_a, ext_b) = (ext_of(&a.url), ext_of(&b.url));
+            (ext_a.is_none(), ext_a).cmp(&(ext_b.is_none(), ext_b))
+        }
+        ReportKey::Status => a.status.cmp(&b.status),
+        ReportKey::Length => {
+            (a.content_length.is_none(), a.content_length).cmp(&(b.content_length.is_none(), b.content_length))
+        }
+        // Most severe first, unclassified last.
+        ReportKey::Severity => b.severity.cmp(&a.severity),
+    }
+}
+
+/// A key's name in group headers.
+fn key_name(key: ReportKey) -> &'static str {
+    match key {
+        ReportKey::Url => "url",
+        ReportKey::Host => "host",
+        ReportKey::Dir => "dir",
+        ReportKey::Ext => "ext",
+        ReportKey::Status => "status",
+        ReportKey::Length => "length",
+        ReportKey::Severity => "severity",
+    }
+}
+
+/// The value of `key` for `entry`, as shown in its group header.
+fn key_label(key: ReportKey, entry: &ResultEntry) -> String {
+    match key {
+        ReportKey::Url => entry.url.clone(),
+        ReportKey::Host => host_of(&entry.url),
+        ReportKey::Dir => dir_of(&entry.url),
+        ReportKey::Ext => ext_of(&entry.url).unwrap_or_else(|| "(none)".to_string()),
+        ReportKey::Status => entry.status.to_string(),
+        ReportKey::Length => match entry.content_length {
+            Some(len) => len.to_string(),
+            None => "-".to_string(),
+        },
+        ReportKey::Severity => match entry.severity {
+            Some(severity) => severity.to_string(),
+            None => "unclassified".to_string(),
+        },
+    }
+}
+
+/// `https://example.com:8443` from a finding URL (the URL itself if it does not parse).
+fn host_of(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(parsed) => parsed.origin().ascii_serialization(),
+        Err(_) => url.to_string(),
+    }
+}
+
+/// The directory a finding is in: `https://example.com/admin/` for both
+/// `https://example.com/admin/login.php` and `https://example.com/admin/config/`.
+fn dir_of(url: &str) -> String {
+    let parsed = match Url::parse(url) {
+        Ok(p) => p,
+        Err(_) => return url.to_string(),
+    };
+    let path = parsed.path().trim_end_matches('/');
+    let dir = match path.rfind('/') {
+        Some(slash) => &path[..=slash],
+        None => "/",
+    };
+    format!("{}{}", parsed.origin().ascii_serialization(), dir)
+}
+
+/// Lowercase extension of the last path segment (`None` for directories and dotless names).
+fn ext_of(url: &str) -> Option<String> {
+    let path = match Url::parse(url) {
+        Ok(parsed) => parsed.path().to_string(),
+        Err(_) => url.to_string(),
+    };
+    let name = path.rsplit('/').next()?;
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !ext.is_empty() => Some(ext.to_ascii_lowercase()),
+        _ => None,
+    }
+}
+
+/// One finding as a result line (without the timestamp, which the file does not keep).
+pub fn format_entry(entry: &ResultEntry) -> String {
+    let len_str = match entry.content_length {
+        Some(len) => len.to_string(),
+        None => "-".to_string(),
+    };
+    let label = scanner::severity_label(entry);
+
+    let mut line = match &entry.location {
+        Some(loc) => format!("{:>3} len={}  {} -> {}{}", entry.status, len_str, entry.url, loc, label),
+        None => format!("{:>3} len={}  {}{}", entry.status, len_str, entry.url, label),
+    };
+    if let Some(audit) = &entry.headers {
+        let missing = audit.missing(entry.is_sensitive());
+        if !missing.is_empty() {
+            line.push_str(&format!(" [missing: {}]", missing.join(",")));
+        }
+    }
+    if let Some(triage) = &entry.triage {
+        match &triage.note {
+            Some(note) => line.push_str(&format!(" [{}: {}]", triage.verdict.label(), note)),
+            None => line.push_str(&format!(" [{}]", triage.verdict.label())),
+        }
+    }
+    line
+}
+
+/// Whether a reviewer marked this finding as a false positive.
+pub fn is_false_positive(entry: &ResultEntry) -> bool {
+    match &entry.triage {
+        Some(triage) => triage.verdict == Verdict::FalsePositive,
+        None => false,
+    }
+}
@@ -0,0 +1,158 @@
+//! src/resume.rs
+//!
+//! Purpose:
+//!   Scan state files for `dirust scan --state <PATH>` and `dirust resume <PATH>`.
+//!   A long scan that is interrupted (Ctrl-C, crash, reboot) can be continued later without
+//!   probing the same URLs again.
+//!
+//! File format (plain text, appended while the scan runs):
+//!   line 1 : JSON header `{"version":1,"argv":["dirust","scan",...]}` — the command line
+//!   line 2+: one probed URL per line
+//!
+//! Resuming parses the stored command line again, so relative paths (wordlist, results
+//! database) must be resolved from the same working directory as the original scan.
+//! Writes are buffered and flushed every `FLUSH_EVERY` URLs; after a hard crash the last
+//! few probes may simply be repeated.
+
+use crate::{
+    args::{Cli, Command, ScanArgs},
+    error::DirustError,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    sync::Mutex,
+};
+
+/// Version of the state header. Bump on any incompatible change.
+const STATE_VERSION: u32 = 1;
+
+/// Flush the state file after this many recorded URLs.
+const FLUSH_EVERY: usize = 64;
+
+/// First line of a state file.
+#[derive(Debug, Serialize, Deserialize)]
+struct StateHeader {
+    version: u32,
+    argv: Vec<String>,
+}
+
+/// Writer half of the journal: buffered output plus the number of unflushed lines.
+struct Journal {
+    out: BufWriter<File>,
+    pending: usize,
+}
+
+/// Progress of one scan: the URLs already probed, and the open state file.
+///
+/// Shared by all probe tasks (`mark_done` takes `&self`), typically behind an `Arc`.
+pub struct ScanState {
+    done: HashSet<String>,
+    journal: Mutex<Journal>,
+}
+
+impl ScanState {
+    /// Start a new state file at `path` for the command line `argv` (truncates any old file).
+    pub fn create(path: &str, argv: &[String]) -> Result<ScanState, DirustError> {
+        let mut out = BufWriter::new(File::create(path)?);
+        let header = StateHeader {
+            version: STATE_VERSION,
+            argv: argv.to_vec(),
+        };
+        serde_json::to_writer(&mut out, &header)?;
+        writeln!(out)?;
+        out.flush()?;
+
+        Ok(ScanState {
+            done: HashSet::new(),
+            journal: Mutex::new(Journal { out, pending: 0 }),
+        })
+    }
+
+    /// Open an existing state file: returns the stored scan arguments and the state, ready
+    /// to record further progress (new URLs are appended).
+    ///
+    /// Errors:
+    ///   - `DirustError::Io` if the file cannot be read or reopened
+    ///   - `DirustError::InvalidState` if the header is missing, from a newer version, or
+    ///     does not describe a `scan` command line
+    pub fn open(path: &str) -> Result<(ScanArgs, ScanState), DirustError> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut lines = reader.lines();
+
+        let first = match lines.next() {
+            Some(line) => line?,
+            None => return Err(DirustError::InvalidState("empty file".to_string())),
+        };
+        let header: StateHeader = match serde_json::from_str(&first) {
+            Ok(h) => h,
+            Err(_) => return Err(DirustError::InvalidState("missing header line".to_string())),
+        };
+        if header.version > STATE_VERSION {
+            return Err(DirustError::InvalidState(format!("unsupported version {}", header.version)));
+        }
+
+        let args: ScanArgs = match Cli::try_parse_with_presets(&header.argv) {
+            Ok(Cli { command: Command::Scan(scan), .. }) => *scan,
+            Ok(_) => return Err(DirustError::InvalidState("not a scan command line".to_string())),
+            Err(e) => return Err(DirustError::InvalidState(e.to_string())),
+        };
+
+        let mut done: HashSet<String> = HashSet::new();
+        for line in lines {
+            let url = line?;
+            if !url.trim().is_empty() {
+                done.insert(url.trim().to_string());
+            }
+        }
+
+        let file = OpenOptions::new().append(true).open(path)?;
+        let state = ScanState {
+            done,
+            journal: Mutex::new(Journal {
+                out: BufWriter::new(file),
+                pending: 0,
+            }),
+        };
+        Ok((args, state))
+    }
+
+    /// Number of URLs already probed by earlier runs.
+    pub fn done_count(&self) -> usize {
+        self.done.len()
+    }
+
+    /// Whether `url` was probed by an earlier run.
+    pub fn is_done(&self, url: &str) -> bool {
+        self.done.contains(url)
+    }
+
+    /// Record that `url` has been probed.
+    pub fn mark_done(&self, url: &str) -> Result<(), DirustError> {
+        // A poisoned lock only means another task panicked mid-write; keep recording.
+        let mut journal = match self.journal.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        writeln!(journal.out, "{}", url)?;
+        journal.pending += 1;
+        if journal.pending >= FLUSH_EVERY {
+            journal.out.flush()?;
+            journal.pending = 0;
+        }
+        Ok(())
+    }
+
+    /// Write out any buffered URLs.
+    pub fn flush(&self) -> Result<(), DirustError> {
+        let mut journal = match self.journal.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        journal.out.flush()?;
+        journal.pending = 0;
+        Ok(())
+    }
+}
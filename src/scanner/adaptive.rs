@@ -0,0 +1,108 @@
+//! src/scanner/adaptive.rs
+//!
+//! Purpose:
+//!   `--adaptive-exts`: learn from the first few hundred probes which extensions produce
+//!   findings on this target. On a PHP site, `.asp`, `.aspx` and `.jsp` never hit, yet with
+//!   `--exts php,asp,aspx,jsp` they are 3/4 of the scan. Once `SAMPLE_PROBES` extension
+//!   targets were probed, extensions without a single finding are dropped (`drop`) or
+//!   pushed to the end of the scan (`defer`), so the useful ones finish first.
+//!
+//! Notes:
+//!   - Only targets built with an extension count; the as-is word is always probed.
+//!   - A finding is any "interesting" status (see `util::is_interesting_status`).
+//!   - The decision is made once per base URL and printed.
+
+use crate::args::AdaptiveExts;
+use std::collections::HashMap;
+
+/// Extension targets probed before extensions are judged.
+const SAMPLE_PROBES: usize = 300;
+
+/// What `--adaptive-exts` decided.
+pub struct Decision {
+    /// Extensions with at least one finding, with their finding counts.
+    pub keep: Vec<(String, usize)>,
+    /// Extensions that never produced a finding.
+    pub unmatched: Vec<String>,
+    /// Extension targets the decision is based on.
+    pub probes: usize,
+}
+
+/// Finding counts per extension while the sample is being collected.
+pub struct ExtensionTracker {
+    mode: AdaptiveExts,
+    extensions: Vec<String>,
+    probes: usize,
+    hits: HashMap<String, usize>,
+    decided: bool,
+}
+
+impl ExtensionTracker {
+    /// Track `extensions` (as from `ScanArgs::parse_exts`).
+    pub fn new(mode: AdaptiveExts, extensions: &[String]) -> ExtensionTracker {
+        ExtensionTracker {
+            mode,
+            extensions: extensions.to_vec(),
+            probes: 0,
+            hits: HashMap::new(),
+            decided: false,
+        }
+    }
+
+    /// `drop` or `defer`.
+    pub fn mode(&self) -> AdaptiveExts {
+        self.mode
+    }
+
+    /// Whether the sample is still being collected.
+    pub fn sampling(&self) -> bool {
+        !self.decided
+    }
+
+    /// Words that make about one sample's worth of extension targets, so the first chunks
+    /// stay small and the decision comes early.
+    pub fn sample_words(&self) -> usize {
+        (SAMPLE_PROBES / self.extensions.len().max(1)).max(1)
+    }
+
+    /// Count one probed URL (ignored unless it ends with a tracked extension).
+    pub fn record(&mut self, url: &str, interesting: bool) {
+        if self.decided {
+            return;
+        }
+        // The longest matching extension wins (`.php` vs `.inc.php`).
+        let matched = self
+            .extensions
+            .iter()
+            .filter(|ext| url.ends_with(ext.as_str()))
+            .max_by_key(|ext| ext.len());
+        if let Some(ext) = matched {
+            self.probes += 1;
+            if interesting {
+                *self.hits.entry(ext.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Judge the extensions once the sample is complete (`None` before that, and after).
+    pub fn decide(&mut self) -> Option<Decision> {
+        if self.decided || self.probes < SAMPLE_PROBES {
+            return None;
+        }
+        self.decided = true;
+
+        let mut keep: Vec<(String, usize)> = Vec::new();
+        let mut unmatched: Vec<String> = Vec::new();
+        for ext in &self.extensions {
+            match self.hits.get(ext) {
+                Some(&count) => keep.push((ext.clone(), count)),
+                None => unmatched.push(ext.clone()),
+            }
+        }
+        Some(Decision {
+            keep,
+            unmatched,
+            probes: self.probes,
+        })
+    }
+}
@@ -0,0 +1,109 @@
+//! src/scanner/auth.rs
+//!
+//! Purpose:
+//!   Recognize findings that are authentication entry points — HTTP auth prompts, login
+//!   forms, hand-offs to a single sign-on provider — and tag them `[AUTH]`, so a tester can
+//!   list a target's entry points after a scan (`auth` tag in result files).
+//!
+//! Signals:
+//!   - a `WWW-Authenticate` header (Basic, Digest, NTLM, Negotiate, Bearer, ...)
+//!   - a redirect to a well-known identity provider host (Entra ID, Google, Okta, Auth0, ...)
+//!     or to an SSO endpoint path (`/adfs/ls`, `/cas/login`, `/saml`, OpenID Connect `auth`)
+//!   - a password field in the page (`<input type="password">`)
+//!
+//! Notes:
+//!   - Headers are checked on every finding; bodies only when they are downloaded anyway
+//!     (`--forms`, `--secrets`), so `[AUTH]` costs no extra request.
+//!   - The signal is kept as a second tag: `auth:www-authenticate`, `auth:sso-redirect` or
+//!     `auth:password-form`.
+
+use super::http::HttpSummary;
+use reqwest::Url;
+
+/// Identity provider hosts (exact, or as a domain suffix).
+const SSO_HOSTS: &[&str] = &[
+    "login.microsoftonline.com",
+    "login.live.com",
+    "login.windows.net",
+    "accounts.google.com",
+    "okta.com",
+    "oktapreview.com",
+    "auth0.com",
+    "onelogin.com",
+    "pingidentity.com",
+    "pingone.com",
+    "duosecurity.com",
+    "amazoncognito.com",
+];
+
+/// Lower-case path fragments of SSO endpoints, on any host.
+const SSO_PATHS: &[&str] = &[
+    "/adfs/ls",
+    "/cas/login",
+    "/saml",
+    "/sso/",
+    "/oauth2/authorize",
+    "/oauth/authorize",
+    "/protocol/openid-connect/auth",
+];
+
+/// Why a finding counts as an authentication entry point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthSignal {
+    WwwAuthenticate,
+    SsoRedirect,
+    PasswordForm,
+}
+
+impl AuthSignal {
+    /// The detail tag stored next to `auth`.
+    pub fn tag(self) -> &'static str {
+        match self {
+            AuthSignal::WwwAuthenticate => "auth:www-authenticate",
+            AuthSignal::SsoRedirect => "auth:sso-redirect",
+            AuthSignal::PasswordForm => "auth:password-form",
+        }
+    }
+}
+
+/// Check one response (and its body, when it was downloaded) for authentication signals.
+pub fn detect(summary: &HttpSummary, body: Option<&str>) -> Option<AuthSignal> {
+    if summary.www_authenticate.is_some() {
+        return Some(AuthSignal::WwwAuthenticate);
+    }
+    if let Some(location) = &summary.location
+        && is_sso_location(location)
+    {
+        return Some(AuthSignal::SsoRedirect);
+    }
+    if let Some(body) = body
+        && has_password_field(body)
+    {
+        return Some(AuthSignal::PasswordForm);
+    }
+    None
+}
+
+/// A redirect target on an identity provider, or on an SSO endpoint path.
+fn is_sso_location(location: &str) -> bool {
+    let lower = location.to_ascii_lowercase();
+    if SSO_PATHS.iter().any(|path| lower.contains(path)) {
+        return true;
+    }
+    // Relative locations have no host to check.
+    let host = match Url::parse(&lower) {
+        Ok(url) => url.host_str().unwrap_or_default().to_string(),
+        Err(_) => return false,
+    };
+    SSO_HOSTS
+        .iter()
+        .any(|sso| host == *sso || host.ends_with(&format!(".{}", sso)))
+}
+
+/// An `<input type="password">` anywhere in the page (any quoting, any case).
+fn has_password_field(body: &str) -> bool {
+    let lower = body.to_ascii_lowercase();
+    ["type=\"password\"", "type='password'", "type=password"]
+        .iter()
+        .any(|marker| lower.contains(marker))
+}
@@ -0,0 +1,112 @@
+//! src/scanner/binary.rs
+//!
+//! Purpose:
+//!   Tell binary bodies (images, archives, executables, databases) apart from text, so the
+//!   text analyses (`--forms`, `--secrets`, `[AUTH]` password fields) do not run over bytes
+//!   that only produce garbage matches. Binary findings are recorded by content type and
+//!   size only (tagged `binary`).
+//!
+//! How a 200 finding is judged:
+//!   1) A `Content-Type` that can only be binary (images except SVG, audio, video, fonts,
+//!      archives, PDF, ...) → binary; the body is not downloaded at all.
+//!   2) Otherwise, once the body was downloaded: a known file signature ("PK\x03\x04",
+//!      "\x1f\x8b", "%PDF-", ...) or a NUL byte in the first `SNIFF_LIMIT` bytes → binary.
+//!      `application/octet-stream` alone decides nothing: text dumps are often served so.
+//!
+//! Large archives (`--flag-archives <MIN_BYTES>`):
+//!   - A 200 finding directly under the site root whose name ends in an archive or dump
+//!     suffix (`ARCHIVE_SUFFIXES`: .zip, .sql, .tar.gz, ...) and whose size is at least
+//!     `MIN_BYTES` is raised to high severity and tagged `large-archive` / `[ARCHIVE]`.
+//!   - The size is the `Content-Length` (or the `--measure-chunked` size); findings of
+//!     unknown size are not flagged.
+//!
+//! Notes:
+//!   - UTF-16 text starts with a byte order mark and has NUL bytes; a BOM means text.
+
+use reqwest::Url;
+
+/// How much of a body is looked at for signatures and NUL bytes.
+const SNIFF_LIMIT: usize = 1024;
+
+/// Content types (lower-case, without parameters) that are binary whatever the body.
+const BINARY_TYPES: &[&str] = &[
+    "application/zip",
+    "application/gzip",
+    "application/x-gzip",
+    "application/x-tar",
+    "application/x-bzip2",
+    "application/x-xz",
+    "application/zstd",
+    "application/x-7z-compressed",
+    "application/x-rar-compressed",
+    "application/vnd.rar",
+    "application/java-archive",
+    "application/pdf",
+    "application/wasm",
+    "application/x-sqlite3",
+    "application/vnd.sqlite3",
+    "application/x-msdownload",
+    "application/x-executable",
+];
+
+/// Content type prefixes that are binary (except `image/svg+xml`).
+const BINARY_PREFIXES: &[&str] = &["image/", "audio/", "video/", "font/"];
+
+/// File signatures at the start of common binary formats.
+const SIGNATURES: &[&[u8]] = &[
+    b"PK\x03\x04",           // zip, jar, docx, xlsx, apk
+    b"\x1f\x8b",             // gzip
+    b"BZh",                  // bzip2
+    b"\xfd7zXZ\x00",         // xz
+    b"\x28\xb5\x2f\xfd",     // zstd
+    b"7z\xbc\xaf\x27\x1c",   // 7-Zip
+    b"Rar!\x1a\x07",         // RAR
+    b"%PDF-",                // PDF
+    b"\x89PNG\r\n\x1a\n",    // PNG
+    b"\xff\xd8\xff",         // JPEG
+    b"GIF8",                 // GIF
+    b"\x7fELF",              // ELF executable
+    b"MZ",                   // Windows executable
+    b"SQLite format 3\x00",  // SQLite database
+    b"\x00asm",              // WebAssembly
+];
+
+/// Name suffixes of downloadable archives and dumps (`--flag-archives`).
+pub const ARCHIVE_SUFFIXES: &[&str] = &[
+    ".zip", ".tar", ".tar.gz", ".tgz", ".tar.bz2", ".tar.xz", ".gz", ".7z", ".rar", ".sql", ".sql.gz", ".dump",
+];
+
+/// Whether `content_type` (a header value) can only describe a binary body.
+pub fn is_binary_type(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    if essence == "image/svg+xml" {
+        return false;
+    }
+    BINARY_TYPES.contains(&essence.as_str()) || BINARY_PREFIXES.iter().any(|prefix| essence.starts_with(prefix))
+}
+
+/// Whether `body` looks binary: a known file signature, or a NUL byte near the start (see
+/// the module docs).
+pub fn looks_binary(body: &[u8]) -> bool {
+    if body.starts_with(b"\xef\xbb\xbf") || body.starts_with(b"\xff\xfe") || body.starts_with(b"\xfe\xff") {
+        return false;
+    }
+    if SIGNATURES.iter().any(|signature| body.starts_with(signature)) {
+        return true;
+    }
+    body[..body.len().min(SNIFF_LIMIT)].contains(&0)
+}
+
+/// Whether `url` names an archive or dump (`ARCHIVE_SUFFIXES`) directly under the site root,
+/// e.g. `https://example.com/backup.zip`.
+pub fn is_root_archive(url: &str) -> bool {
+    let path = match Url::parse(url) {
+        Ok(parsed) => parsed.path().to_ascii_lowercase(),
+        Err(_) => return false,
+    };
+    let name = match path.strip_prefix('/') {
+        Some(name) if !name.is_empty() && !name.contains('/') => name,
+        _ => return false,
+    };
+    ARCHIVE_SUFFIXES.iter().any(|suffix| name.len() > suffix.len() && name.ends_with(suffix))
+}
@@ -0,0 +1,111 @@
+//! src/scanner/charset.rs
+//!
+//! Purpose:
+//!   Turn response bodies into text using the charset they were written in, before any
+//!   regex or string matching (forms, secrets, login detection, technology fingerprints,
+//!   reflected parameters, crawled links). Reading everything as UTF-8 garbles legacy pages
+//!   (ISO-8859-x, Windows-125x, GBK, Shift_JIS, ...) and makes patterns miss.
+//!
+//! Detection order (the first one that names a known charset wins, as in browsers):
+//!   1) a byte order mark (UTF-8, UTF-16LE, UTF-16BE)
+//!   2) the `charset` parameter of the `Content-Type` header
+//!   3) `<meta charset="...">`, `<meta http-equiv="Content-Type" content="...; charset=...">`
+//!      or `<?xml ... encoding="..."?>` in the first `SNIFF_LIMIT` bytes of the body
+//!   4) UTF-8 if the body is valid UTF-8, windows-1252 otherwise
+//!
+//! Notes:
+//!   - Labels are resolved as the WHATWG Encoding Standard says (through `encoding_rs`):
+//!     `iso-8859-1` and `latin1` mean windows-1252, `gb2312` means GBK, and so on. Unknown
+//!     labels are skipped.
+//!   - A UTF-16 label in a meta tag means UTF-8 (the tag itself could not have been read
+//!     otherwise).
+//!   - Byte sequences that are invalid in the detected charset become U+FFFD.
+
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+
+/// How much of a body is searched for a meta tag or XML declaration.
+const SNIFF_LIMIT: usize = 1024;
+
+/// The charset of a body with `content_type` (the header value, if any).
+pub fn detect(content_type: Option<&str>, body: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _bom_length)) = Encoding::for_bom(body) {
+        return encoding;
+    }
+    let content_type = content_type.map(|value| value.to_ascii_lowercase());
+    if let Some(label) = content_type.as_ref().and_then(|value| label_after(value.as_bytes(), b"charset"))
+        && let Some(encoding) = Encoding::for_label(label)
+    {
+        return encoding;
+    }
+    if let Some(encoding) = sniff_document(&body[..body.len().min(SNIFF_LIMIT)]) {
+        return encoding;
+    }
+    match std::str::from_utf8(body) {
+        Ok(_) => UTF_8,
+        Err(_) => WINDOWS_1252,
+    }
+}
+
+/// `body` as text, decoded with the charset `detect` finds.
+pub fn decode(content_type: Option<&str>, body: &[u8]) -> String {
+    let (text, _encoding, _had_errors) = detect(content_type, body).decode(body);
+    text.into_owned()
+}
+
+/// The charset declared by a meta tag or the XML declaration at the start of a document.
+fn sniff_document(head: &[u8]) -> Option<&'static Encoding> {
+    let lower = head.to_ascii_lowercase();
+    if lower.starts_with(b"<?xml")
+        && let Some(end) = find(&lower, b"?>")
+        && let Some(label) = label_after(&lower[..end], b"encoding")
+        && let Some(encoding) = Encoding::for_label(label)
+    {
+        return Some(encoding);
+    }
+
+    let mut rest: &[u8] = &lower;
+    while let Some(start) = find(rest, b"<meta") {
+        let tag = &rest[start..];
+        let end = find(tag, b">").unwrap_or(tag.len());
+        if let Some(label) = label_after(&tag[..end], b"charset")
+            && let Some(encoding) = Encoding::for_label(label)
+        {
+            return Some(encoding.output_encoding());
+        }
+        rest = &tag[end..];
+    }
+    None
+}
+
+/// The value after `name=` in lowercase `text` (optional spaces and quotes), e.g. `utf-8`
+/// from `text/html; charset="utf-8"`.
+fn label_after<'a>(text: &'a [u8], name: &[u8]) -> Option<&'a [u8]> {
+    let mut from = 0;
+    while let Some(at) = find(&text[from..], name) {
+        let mut i = from + at + name.len();
+        from = i;
+        while i < text.len() && text[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if text.get(i) != Some(&b'=') {
+            continue;
+        }
+        i += 1;
+        while i < text.len() && (text[i].is_ascii_whitespace() || text[i] == b'"' || text[i] == b'\'') {
+            i += 1;
+        }
+        let start = i;
+        while i < text.len() && (text[i].is_ascii_alphanumeric() || matches!(text[i], b'-' | b'_' | b'.' | b':')) {
+            i += 1;
+        }
+        if i > start {
+            return Some(&text[start..i]);
+        }
+    }
+    None
+}
+
+/// Position of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
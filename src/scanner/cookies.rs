@@ -0,0 +1,132 @@
+//! src/scanner/cookies.rs
+//!
+//! Purpose:
+//!   `Set-Cookie` handling for findings:
+//!   - every finding keeps the cookies it set (name and attributes, never the value) in
+//!     its `"cookies"` entry of the result file;
+//!   - on sensitive findings (medium severity or higher, `[AUTH]`), cookies without
+//!     `Secure` (https:// only) or `HttpOnly` are flagged under the finding line;
+//!   - with `--keep-cookies`, session cookies are kept in a jar and sent with every later
+//!     probe of the scan, so pages behind a session handed out by an earlier page are reached.
+//!
+//! Output:
+//!   [2024-08-12T09:59:49.310Z] 200 len=512  https://example.com/admin [HIGH admin-panel]
+//!       [cookie] PHPSESSID: missing Secure, HttpOnly
+//!
+//! Notes:
+//!   - A session cookie is one whose name looks like a session id (`PHPSESSID`, `JSESSIONID`,
+//!     `connect.sid`, `*session*`, `*token*`, `*auth*`, ...). Tracking or preference cookies
+//!     are not kept.
+//!   - A cookie set again replaces its old value; one that is expired (`Max-Age=0`, empty
+//!     value) is dropped from the jar.
+
+use crate::{
+    output::{self, Style},
+    schema::{CookieRecord, ResultEntry},
+};
+use std::{collections::BTreeMap, sync::Mutex};
+
+/// Lower-case name fragments of session cookies.
+const SESSION_MARKERS: &[&str] = &["sess", "sid", "token", "auth", "login", "jwt"];
+
+/// Parse one `Set-Cookie` header value into its name and attributes.
+pub fn parse(set_cookie: &str) -> Option<CookieRecord> {
+    let mut parts = set_cookie.split(';');
+    let (name, _value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut record = CookieRecord {
+        name: name.to_string(),
+        secure: false,
+        http_only: false,
+        same_site: None,
+    };
+    for attribute in parts {
+        let (key, value) = match attribute.split_once('=') {
+            Some((k, v)) => (k.trim(), Some(v.trim())),
+            None => (attribute.trim(), None),
+        };
+        match key.to_ascii_lowercase().as_str() {
+            "secure" => record.secure = true,
+            "httponly" => record.http_only = true,
+            "samesite" => record.same_site = value.map(|v| v.to_string()),
+            _ => {}
+        }
+    }
+    Some(record)
+}
+
+/// Whether a cookie name looks like a session identifier.
+pub fn is_session(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    SESSION_MARKERS.iter().any(|marker| lower.contains(marker))
+}
+
+/// Print a `[cookie]` line for every weak cookie of a sensitive finding.
+pub fn print_weak(entry: &ResultEntry) {
+    if !entry.is_sensitive() {
+        return;
+    }
+    let https = entry.url.starts_with("https://");
+    for cookie in &entry.cookies {
+        let missing = cookie.missing(https);
+        if !missing.is_empty() {
+            let line = format!("    [cookie] {}: missing {}", cookie.name, missing.join(", "));
+            output::print_styled(&line, Style::Denied);
+        }
+    }
+}
+
+/// Session cookies collected during a scan (`--keep-cookies`), sent with every probe.
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    cookies: Mutex<BTreeMap<String, String>>,
+}
+
+impl CookieJar {
+    /// Keep the session cookies among `set_cookies` (raw `Set-Cookie` values from `url`).
+    pub fn absorb(&self, url: &str, set_cookies: &[String]) {
+        if set_cookies.is_empty() {
+            return;
+        }
+        let mut cookies = match self.cookies.lock() {
+            Ok(g) => g,
+            Err(p) => p.into_inner(),
+        };
+        for set_cookie in set_cookies {
+            let pair = set_cookie.split(';').next().unwrap_or_default();
+            let (name, value) = match pair.split_once('=') {
+                Some((n, v)) => (n.trim(), v.trim()),
+                None => continue,
+            };
+            if name.is_empty() || !is_session(name) {
+                continue;
+            }
+            let expired = value.is_empty() || set_cookie.to_ascii_lowercase().contains("max-age=0");
+            if expired {
+                cookies.remove(name);
+                continue;
+            }
+            let previous = cookies.insert(name.to_string(), value.to_string());
+            if previous.is_none() {
+                output::info(&format!("[*] cookies: keeping session cookie {} (from {})", name, url));
+            }
+        }
+    }
+
+    /// The `Cookie` header for the next request (`None` while the jar is empty).
+    pub fn header(&self) -> Option<String> {
+        let cookies = match self.cookies.lock() {
+            Ok(g) => g,
+            Err(p) => p.into_inner(),
+        };
+        if cookies.is_empty() {
+            return None;
+        }
+        let pairs: Vec<String> = cookies.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+        Some(pairs.join("; "))
+    }
+}
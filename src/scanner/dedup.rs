@@ -0,0 +1,201 @@
+//! src/scanner/dedup.rs
+//!
+//! Purpose:
+//!   Make sure no URL is probed twice in one scan, however many times it is generated
+//!   (duplicate wordlist lines, several bases, follow-up waves from `--follow-forms`, ...).
+//!
+//! How:
+//!   - Every scheduled URL goes into a bloom filter: a fixed bit array per layer, a few bits
+//!     set per URL. Memory stays around 3.6 bytes per URL instead of a full `String` per URL.
+//!     When a layer is full, a new layer twice as large is added (a "scalable" bloom filter),
+//!     so the false-positive rate stays bounded without knowing the target count up front.
+//!   - The filter sits behind a lock, so probe tasks can schedule the `word/` of a directory
+//!     redirect in the same set as the scan's other targets.
+//!
+//! Trade-off:
+//!   A bloom filter can answer "seen" for a URL it never saw (about 1 in a million here).
+//!   Such a URL is skipped. It can never answer "new" for a URL it did see, so duplicates
+//!   are always caught.
+//!
+//! Why there is no exact set of hits next to the filter:
+//!   - An exact set would only change answers for URLs the filter wrongly calls "seen". A hit
+//!     cannot be one of them: it became a hit by being probed, so the filter answered "new"
+//!     for it once and recorded it; from then on "seen" is the true answer.
+//!   - The URLs a false positive costs are never-probed ones, which no set of hits contains,
+//!     so the set would not win them back either.
+//!   - The hits are kept exactly anyway, keyed by URL, in the results database (`db.rs`):
+//!     exports, `--new-only` and the final summary read them from there.
+//!
+//!   The test below schedules every target twice and checks that every finding was probed
+//!   exactly once.
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{Mutex, MutexGuard},
+};
+
+/// Bits per URL: ~28.8 bits with 20 hash functions gives a ~1e-6 false-positive rate.
+const BITS_PER_ITEM: usize = 29;
+const HASHES: u32 = 20;
+
+/// Capacity of the first layer; each further layer doubles it.
+const FIRST_LAYER_ITEMS: usize = 1 << 16;
+
+/// One bloom filter with a fixed capacity.
+struct Layer {
+    bits: Vec<u64>,
+    capacity: usize,
+    items: usize,
+}
+
+impl Layer {
+    fn new(capacity: usize) -> Layer {
+        let words = (capacity * BITS_PER_ITEM).div_ceil(64);
+        Layer {
+            bits: vec![0; words],
+            capacity,
+            items: 0,
+        }
+    }
+
+    /// Bit positions for a URL (double hashing: h1 + i*h2).
+    fn positions(&self, h1: u64, h2: u64) -> impl Iterator<Item = usize> + use<> {
+        let total = (self.bits.len() * 64) as u64;
+        (0..HASHES as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % total) as usize)
+    }
+
+    fn contains(&self, h1: u64, h2: u64) -> bool {
+        self.positions(h1, h2).all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    fn insert(&mut self, h1: u64, h2: u64) {
+        for bit in self.positions(h1, h2) {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+        self.items += 1;
+    }
+}
+
+/// The filter layers, oldest first.
+struct Filter {
+    layers: Vec<Layer>,
+}
+
+impl Filter {
+    fn insert(&mut self, url: &str) -> bool {
+        let (h1, h2) = hash_pair(url);
+        if self.layers.iter().any(|layer| layer.contains(h1, h2)) {
+            return false;
+        }
+
+        // Only the newest layer takes new URLs; grow when it is full.
+        let full = match self.layers.last() {
+            Some(layer) => layer.items >= layer.capacity,
+            None => true,
+        };
+        if full {
+            let capacity = match self.layers.last() {
+                Some(layer) => layer.capacity * 2,
+                None => FIRST_LAYER_ITEMS,
+            };
+            self.layers.push(Layer::new(capacity));
+        }
+        if let Some(layer) = self.layers.last_mut() {
+            layer.insert(h1, h2);
+        }
+        true
+    }
+}
+
+/// The set of URLs scheduled in this scan.
+pub struct TargetDedup {
+    filter: Mutex<Filter>,
+}
+
+impl TargetDedup {
+    /// An empty set.
+    pub fn new() -> TargetDedup {
+        TargetDedup {
+            filter: Mutex::new(Filter {
+                layers: vec![Layer::new(FIRST_LAYER_ITEMS)],
+            }),
+        }
+    }
+
+    /// Lock the set (a poisoned lock only means a probe task panicked; the bits are intact).
+    fn lock(&self) -> MutexGuard<'_, Filter> {
+        match self.filter.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Record `url` as scheduled. Returns `true` if it was not seen before (probe it),
+    /// `false` if it was (skip it).
+    pub fn insert(&self, url: &str) -> bool {
+        self.lock().insert(url)
+    }
+
+    /// Whether `url` was seen before (without recording it).
+    pub fn contains(&self, url: &str) -> bool {
+        let filter = self.lock();
+        let (h1, h2) = hash_pair(url);
+        filter.layers.iter().any(|layer| layer.contains(h1, h2))
+    }
+
+    /// Keep only the URLs of `targets` that were not seen before (first occurrence wins).
+    pub fn filter(&self, targets: Vec<String>) -> Vec<String> {
+        let mut filter = self.lock();
+        let mut fresh: Vec<String> = Vec::with_capacity(targets.len());
+        for url in targets {
+            if filter.insert(&url) {
+                fresh.push(url);
+            }
+        }
+        fresh
+    }
+}
+
+/// Two independent 64-bit hashes of `url` (the second forced odd so strides cover the array).
+fn hash_pair(url: &str) -> (u64, u64) {
+    let mut first = DefaultHasher::new();
+    url.hash(&mut first);
+    let mut second = DefaultHasher::new();
+    0x9e37_79b9_7f4a_7c15_u64.hash(&mut second);
+    url.hash(&mut second);
+    (first.finish(), second.finish() | 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TargetDedup, FIRST_LAYER_ITEMS};
+
+    /// Enough targets to grow the filter past its first two layers.
+    const TARGETS: usize = FIRST_LAYER_ITEMS * 4;
+
+    fn target(i: usize) -> String {
+        format!("https://example.com/dir{}/word{}", i % 97, i)
+    }
+
+    #[test]
+    fn findings_are_probed_once_and_never_lost() {
+        let dedup = TargetDedup::new();
+
+        // First wave: every target once; every 1000th probed one answers as a finding.
+        let probed = dedup.filter((0..TARGETS).map(target).collect());
+        let findings: Vec<String> = probed.iter().step_by(1000).cloned().collect();
+        assert!(!findings.is_empty());
+
+        // A follow-up wave (recursion, permutations, ...) generates everything again.
+        let again = dedup.filter((0..TARGETS).map(target).collect());
+        assert!(again.is_empty(), "re-probed {} target(s)", again.len());
+        for finding in &findings {
+            assert!(dedup.contains(finding), "{} forgotten", finding);
+            assert!(!dedup.insert(finding), "{} would be probed twice", finding);
+        }
+
+        // What the filter costs: never-probed targets it wrongly calls "seen" (about 1e-6 each).
+        let skipped = TARGETS - probed.len();
+        assert!(skipped * 10_000 < TARGETS, "{} of {} targets skipped", skipped, TARGETS);
+    }
+}
@@ -0,0 +1,739 @@
+//! src/scanner/distributed.rs
+//!
+//! Purpose:
+//!   Distributed scanning for large engagements: one coordinator, any number of workers.
+//!     - `--role coordinator`: builds the targets one wordlist chunk (`--chunk-size`
+//!       targets) at a time as the batches already cut run out, listens on `--listen`, hands
+//!       the targets out in batches of `BATCH_SIZE`, and aggregates what comes back exactly like
+//!       a local scan does (severity rules, `--new-only`, redirect collapsing, results
+//!       database, exports, final summary).
+//!     - `--role worker --coordinator HOST:PORT`: fetches batches, probes them with its own
+//!       concurrency (`-c`), and sends the raw results back. Workers may join or leave at any
+//!       time; a batch held by a worker that disconnects, or that is not back within
+//!       `--batch-timeout`, is handed to the next one.
+//!
+//! Protocol (newline-delimited JSON over TCP, one `Message` per line):
+//!   worker → hello {token}    coordinator → config (request settings: --get, --timeout, ...)
+//!                                          | refused {reason} (wrong `--worker-token`)
+//!   worker → request          coordinator → batch {id, urls} | wait | done
+//!   worker → results {id, outcomes}
+//!
+//! Notes:
+//!   - Workers use the coordinator's request settings, not their own.
+//!   - `--incremental`, `--forms`, `--secrets`, `--state`, `--adaptive-exts` and the other
+//!     flags of local scans only are rejected with `--role coordinator` (see
+//!     `ScanArgs::check_coordinator`).
+//!   - With `--worker-token`, the coordinator only serves workers sending the same token.
+//!     The token is sent in clear (the protocol has no TLS): it keeps stray or misconfigured
+//!     workers out, not someone who can read the traffic.
+
+use super::{
+    cookies,
+    dedup::TargetDedup,
+    encodings,
+    health::HostHealth,
+    hitrate::HitRateWatch,
+    http::{HttpSummary, ProbeEngine}, latency::LatencyBaseline, print_line, print_unreported, redirects, run_wave, stats::ScanStats, targets,
+    transcript::Transcript,
+    ramp::Ramp, scope::Scope, soft404::SoftNotFound, source::PayloadSource, util::is_interesting_status, RedirectCollector,
+    ScanHooks, Wave,
+};
+use crate::{
+    args::ScanArgs, client, db::ResultsDb, error::DirustError, output, schema::ResultEntry,
+    severity::Classifier,
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpListener, TcpStream,
+    },
+    sync::{mpsc, Notify, Semaphore},
+};
+
+/// Targets per batch: small enough to rebalance, large enough to keep workers busy.
+const BATCH_SIZE: usize = 200;
+
+/// How long a worker waits before asking again when every batch is taken.
+const WAIT_DELAY: Duration = Duration::from_millis(500);
+
+/// The coordinator cuts the next wordlist chunk into batches once fewer than this many are
+/// waiting to be handed out.
+const LOW_WATER: usize = 8;
+
+/// One line of the coordinator/worker protocol.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Message {
+    Hello {
+        /// The worker's `--worker-token`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
+    Refused {
+        reason: String,
+    },
+    Config {
+        get: bool,
+        timeout: u64,
+        compressed: bool,
+        no_compression: bool,
+        measure_chunked: Option<u64>,
+        flag_archives: Option<u64>,
+        no_dir_confirm: bool,
+        slow_retry_ms: Option<u64>,
+        evade: bool,
+        latency_sigma: f64,
+        add_slash: bool,
+        header_audit: bool,
+        keep_cookies: bool,
+        rate: Option<u32>,
+        max_attempts: u32,
+        user_agent: Option<String>,
+        from: Option<String>,
+        http2: bool,
+        http2_prior_knowledge: bool,
+    },
+    Request,
+    Batch { id: usize, urls: Vec<String> },
+    Wait,
+    Done,
+    Results { id: usize, outcomes: Vec<RemoteOutcome> },
+}
+
+/// What a worker reports for one probed URL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RemoteOutcome {
+    entry: ResultEntry,
+    /// `--measure-chunked` hit its cap (the length is a lower bound).
+    length_capped: bool,
+    latency_ms: f64,
+    /// Status and latency of the `word` → `word/` redirect a `[dir]` finding confirmed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    confirmed_redirect: Option<(u16, f64)>,
+}
+
+/// A slice of the target list.
+#[derive(Debug, Clone)]
+struct Batch {
+    id: usize,
+    urls: Vec<String>,
+}
+
+/// State shared by the coordinator's connection handlers.
+struct Shared {
+    pending: Mutex<VecDeque<Batch>>,
+    /// Batches handed out and not back yet, by id: the batch, its worker, its deadline.
+    out: Mutex<HashMap<usize, (Batch, String, Instant)>>,
+    /// `--batch-timeout`.
+    batch_timeout: Duration,
+    /// `--worker-token`.
+    token: Option<String>,
+    finished: AtomicBool,
+    /// A worker found no batch waiting: the coordinator should cut the next chunk.
+    starved: Notify,
+}
+
+/// The coordinator's target list, built one wordlist chunk at a time.
+struct TargetFeed<'a> {
+    source: Box<dyn PayloadSource>,
+    bases: &'a [String],
+    args: &'a ScanArgs,
+    extensions: Vec<String>,
+    scope: Option<Scope>,
+    dedup: TargetDedup,
+    words_per_chunk: usize,
+    /// Batches cut so far (the next batch id).
+    cut: usize,
+    targets: usize,
+    out_of_scope: usize,
+    /// The source has no more words.
+    exhausted: bool,
+}
+
+impl TargetFeed<'_> {
+    /// Cut chunks into batches until at least `LOW_WATER` are waiting or the words run out.
+    fn top_up(&mut self, shared: &Shared) -> Result<(), DirustError> {
+        while !self.exhausted && lock_pending(shared).len() < LOW_WATER {
+            let words: Vec<String> = self.source.next_chunk(self.words_per_chunk)?;
+            if words.is_empty() {
+                self.exhausted = true;
+                break;
+            }
+            let mut chunk_targets: Vec<String> = Vec::new();
+            for base in self.bases {
+                let base_targets = targets::build_targets(base, &words, &self.extensions, self.args.add_slash);
+                let variants: Vec<String> = match self.args.unsafe_encodings {
+                    true => base_targets.iter().flat_map(|t| encodings::variants(base, t)).collect(),
+                    false => Vec::new(),
+                };
+                chunk_targets.extend(base_targets);
+                chunk_targets.extend(variants);
+            }
+            // `--scope`: out-of-scope targets are never handed to a worker.
+            if let Some(scope) = &self.scope {
+                self.out_of_scope += scope.retain(&mut chunk_targets);
+            }
+            let chunk_targets = self.dedup.filter(chunk_targets);
+            self.targets += chunk_targets.len();
+            let mut pending = lock_pending(shared);
+            for urls in chunk_targets.chunks(BATCH_SIZE) {
+                pending.push_back(Batch {
+                    id: self.cut,
+                    urls: urls.to_vec(),
+                });
+                self.cut += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run the coordinator for `bases` with the words of `source` and fold every result into `db`.
+pub async fn coordinate(
+    bases: &[String],
+    args: &ScanArgs,
+    source: Box<dyn PayloadSource>,
+    db: &mut ResultsDb,
+    previous: Option<&ResultsDb>,
+) -> Result<(), DirustError> {
+    // Flags a coordinator cannot honour were rejected when parsing (`check_coordinator`).
+
+    // Targets are built a chunk at a time, as the batches already cut are handed out, so
+    // the coordinator's memory does not grow with the wordlist.
+    let extensions = args.parse_exts();
+    let words_per_chunk: usize = (args.chunk_size / (super::targets_per_word(args, extensions.len()) * bases.len().max(1))).max(1);
+    let mut feed = TargetFeed {
+        source,
+        bases,
+        args,
+        extensions,
+        scope: match &args.scope {
+            Some(path) => Some(Scope::load(path)?),
+            None => None,
+        },
+        dedup: TargetDedup::new(),
+        words_per_chunk,
+        cut: 0,
+        targets: 0,
+        out_of_scope: 0,
+        exhausted: false,
+    };
+    let shared = Arc::new(Shared {
+        pending: Mutex::new(VecDeque::new()),
+        out: Mutex::new(HashMap::new()),
+        batch_timeout: args.batch_timeout,
+        token: args.worker_token.clone(),
+        finished: AtomicBool::new(false),
+        starved: Notify::new(),
+    });
+    feed.top_up(&shared)?;
+    let config = Message::Config {
+        get: args.http.get,
+        timeout: args.http.timeout,
+        compressed: args.http.compressed,
+        no_compression: args.http.no_compression,
+        measure_chunked: args.measure_chunked,
+        flag_archives: args.flag_archives,
+        // Workers do not know the scope, so they cannot tell whether a `word/` may be probed.
+        no_dir_confirm: args.no_dir_confirm || args.scope.is_some(),
+        slow_retry_ms: args.slow_retry_timeout.map(|d| d.as_millis() as u64),
+        evade: args.evade,
+        latency_sigma: args.latency_sigma,
+        add_slash: args.add_slash,
+        header_audit: args.header_audit,
+        keep_cookies: args.keep_cookies,
+        rate: args.rate,
+        max_attempts: args.max_attempts,
+        user_agent: args.http.user_agent.clone(),
+        from: args.http.from.clone(),
+        http2: args.http.http2,
+        http2_prior_knowledge: args.http.http2_prior_knowledge,
+    };
+    let config_line = serde_json::to_string(&config)?;
+
+    let listener = TcpListener::bind(&args.listen).await?;
+    output::info(&format!(
+        "[*] coordinator: {} batches of up to {} targets ready, waiting for workers on {}",
+        lock_pending(&shared).len(),
+        BATCH_SIZE,
+        listener.local_addr()?
+    ));
+    if args.worker_token.is_none() {
+        eprintln!("[!] coordinator: no --worker-token; any host that can connect gets batches and its results are trusted");
+    }
+
+    // Accept workers in the background; their results arrive on `results_rx`.
+    let (results_tx, mut results_rx) = mpsc::unbounded_channel::<(usize, Vec<RemoteOutcome>)>();
+    let accept_shared = Arc::clone(&shared);
+    let accept_task = tokio::spawn(async move {
+        while let Ok((stream, peer)) = listener.accept().await {
+            output::info(&format!("[*] coordinator: worker {} connected", peer));
+            tokio::spawn(serve_worker(
+                stream,
+                peer.to_string(),
+                Arc::clone(&accept_shared),
+                results_tx.clone(),
+                config_line.clone(),
+            ));
+        }
+    });
+
+    // Aggregate results exactly like a local scan.
+    let classifier = Classifier::load(args.rules.as_deref())?;
+    let mut redirects_held = RedirectCollector::new(args.redirect_threshold);
+    let mut stats = ScanStats::new();
+    let mut completed: HashSet<usize> = HashSet::new();
+    let mut deadlines = tokio::time::interval(Duration::from_secs(1));
+
+    loop {
+        feed.top_up(&shared)?;
+        if feed.exhausted && completed.len() == feed.cut {
+            break;
+        }
+        let (id, outcomes) = tokio::select! {
+            received = results_rx.recv() => match received {
+                Some(r) => r,
+                None => break,
+            },
+            // A worker is waiting for a batch: top up right away.
+            _ = shared.starved.notified() => continue,
+            _ = deadlines.tick() => {
+                requeue_overdue(&shared);
+                continue;
+            }
+        };
+        // A batch can come back twice if it was requeued while its first worker was still
+        // finishing; count it once.
+        if !completed.insert(id) {
+            continue;
+        }
+        for outcome in outcomes {
+            absorb(outcome, args, db, previous, &classifier, &mut stats, &mut redirects_held);
+        }
+    }
+    if feed.out_of_scope > 0 {
+        output::info(&format!("[*] scope: dropped {} out-of-scope target(s)", feed.out_of_scope));
+    }
+    output::info(&format!("[*] coordinator: {} targets in {} batches", feed.targets, feed.cut));
+
+    // Workers asking for more from now on are told to stop.
+    shared.finished.store(true, Ordering::Relaxed);
+    accept_task.abort();
+
+    redirects_held.flush();
+    stats.print_summary();
+    Ok(())
+}
+
+/// Fold one worker result into the scan: classify, report, record.
+fn absorb(
+    outcome: RemoteOutcome,
+    args: &ScanArgs,
+    db: &mut ResultsDb,
+    previous: Option<&ResultsDb>,
+    classifier: &Classifier,
+    stats: &mut ScanStats,
+    redirects_held: &mut RedirectCollector,
+) {
+    let mut entry = outcome.entry;
+    let status = match StatusCode::from_u16(entry.status) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    if let Some((status, latency_ms)) = outcome.confirmed_redirect {
+        stats.record(status, Duration::from_secs_f64(latency_ms / 1000.0));
+    }
+    stats.record(entry.status, Duration::from_secs_f64(outcome.latency_ms / 1000.0));
+
+    let interesting = is_interesting_status(status);
+    let already_known = match previous {
+        Some(set) => set.get(&entry.url).map(|e| e.status) == Some(entry.status),
+        None => false,
+    };
+
+    // Classification happens here so workers need no copy of `--rules`; the worker's own
+    // `auth`, `slow-retry` (`--slow-retry-timeout`) and `slow` (latency outlier) tags are kept.
+    let auth_tags: Vec<String> = entry.tags.iter().filter(|tag| tag.starts_with("auth")).cloned().collect();
+    let slow = entry.tags.iter().any(|tag| tag == "slow-retry");
+    let latency_outlier = entry.tags.iter().any(|tag| tag == "slow");
+    let class = if interesting {
+        classifier.classify(&entry.url, entry.status)
+    } else {
+        None
+    };
+    let mut label = class.as_ref().map(|c| c.label()).unwrap_or_default();
+    if !auth_tags.is_empty() {
+        label.push_str(" [AUTH]");
+    }
+    if slow {
+        label.push_str(" [slow-retry]");
+    }
+    if latency_outlier {
+        label.push_str(" [SLOW]");
+    }
+    entry.severity = class.as_ref().map(|c| c.severity);
+    entry.tags = class.map(|c| c.tags).unwrap_or_default();
+    entry.tags.extend(auth_tags);
+    if slow {
+        entry.tags.push("slow-retry".to_string());
+    }
+    if latency_outlier {
+        entry.tags.push("slow".to_string());
+    }
+
+    let summary = HttpSummary {
+        status,
+        content_length: entry.content_length.map(|len| len.to_string()),
+        location: entry.location.clone(),
+        etag: entry.etag.clone(),
+        last_modified: entry.last_modified.clone(),
+        content_type: None,
+        www_authenticate: None,
+        security: Default::default(),
+        set_cookies: Vec::new(),
+        tracked_headers: entry.response_headers.clone().unwrap_or_default(),
+        remote_addr: None,
+        length_capped: outcome.length_capped,
+        slow,
+        elapsed: Duration::from_secs_f64(outcome.latency_ms / 1000.0),
+    };
+    if interesting && !already_known {
+        if redirects::should_defer(args.redirect_threshold, &summary) {
+            redirects_held.push(entry.url.clone(), summary, label);
+        } else {
+            print_line(&entry.url, &summary, &label);
+            cookies::print_weak(&entry);
+        }
+    } else if args.all_statuses {
+        print_unreported(&entry.url, &summary);
+    }
+
+    if interesting && !already_known {
+        db.mark_reported(&entry.url);
+    }
+    db.apply(entry, interesting);
+}
+
+/// Hand the batches whose worker missed `--batch-timeout` to the next worker (the late
+/// worker's results are still taken if they come first; the second copy is ignored).
+fn requeue_overdue(shared: &Shared) {
+    let now = Instant::now();
+    let overdue: Vec<(Batch, String)> = {
+        let mut out = lock_out(shared);
+        let ids: Vec<usize> = out.iter().filter(|(_, (_, _, deadline))| *deadline <= now).map(|(id, _)| *id).collect();
+        ids.into_iter().filter_map(|id| out.remove(&id)).map(|(batch, peer, _)| (batch, peer)).collect()
+    };
+    for (batch, peer) in overdue {
+        eprintln!(
+            "[!] coordinator: worker {} did not return batch {} within {:?}; requeued",
+            peer, batch.id, shared.batch_timeout
+        );
+        lock_pending(shared).push_front(batch);
+    }
+}
+
+/// Whether `given` is the coordinator's token (compared in constant time).
+fn token_matches(expected: &str, given: &str) -> bool {
+    let (expected, given) = (expected.as_bytes(), given.as_bytes());
+    let mut difference: u8 = u8::from(expected.len() != given.len());
+    for (i, byte) in expected.iter().enumerate() {
+        difference |= byte ^ given.get(i).copied().unwrap_or(0);
+    }
+    difference == 0
+}
+
+/// Talk to one worker until it leaves; requeue its batch if it leaves mid-batch.
+async fn serve_worker(
+    stream: TcpStream,
+    peer: String,
+    shared: Arc<Shared>,
+    results_tx: mpsc::UnboundedSender<(usize, Vec<RemoteOutcome>)>,
+    config_line: String,
+) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut current: Option<usize> = None;
+
+    // Handshake.
+    let token = match receive(&mut lines).await {
+        Ok(Some(Message::Hello { token })) => token,
+        _ => return,
+    };
+    if let Some(expected) = &shared.token
+        && !token_matches(expected, token.as_deref().unwrap_or(""))
+    {
+        eprintln!("[!] coordinator: worker {} refused (wrong or missing --worker-token)", peer);
+        let reason = "wrong or missing --worker-token".to_string();
+        let _ = send(&mut writer, &Message::Refused { reason }).await;
+        return;
+    }
+    if writer.write_all(format!("{}\n", config_line).as_bytes()).await.is_err() {
+        return;
+    }
+
+    loop {
+        let message = match receive(&mut lines).await {
+            Ok(Some(m)) => m,
+            _ => break,
+        };
+        let reply = match message {
+            Message::Request => {
+                if shared.finished.load(Ordering::Relaxed) {
+                    let _ = send(&mut writer, &Message::Done).await;
+                    break;
+                }
+                let next = lock_pending(&shared).pop_front();
+                match next {
+                    Some(batch) => {
+                        let reply = Message::Batch {
+                            id: batch.id,
+                            urls: batch.urls.clone(),
+                        };
+                        current = Some(batch.id);
+                        let deadline = Instant::now() + shared.batch_timeout;
+                        lock_out(&shared).insert(batch.id, (batch, peer.clone(), deadline));
+                        reply
+                    }
+                    None => {
+                        shared.starved.notify_one();
+                        Message::Wait
+                    }
+                }
+            }
+            Message::Results { id, outcomes } => {
+                current = None;
+                if matches!(lock_out(&shared).get(&id), Some((_, owner, _)) if *owner == peer) {
+                    lock_out(&shared).remove(&id);
+                }
+                let _ = results_tx.send((id, outcomes));
+                continue;
+            }
+            _ => break,
+        };
+        if send(&mut writer, &reply).await.is_err() {
+            break;
+        }
+    }
+
+    // Its batch, unless it was already requeued (deadline) and handed to another worker.
+    let left_with = current.and_then(|id| {
+        let mut out = lock_out(&shared);
+        match out.get(&id) {
+            Some((_, owner, _)) if *owner == peer => out.remove(&id).map(|(batch, _, _)| batch),
+            _ => None,
+        }
+    });
+    if let Some(batch) = left_with {
+        eprintln!("[!] coordinator: worker {} left during batch {}; requeued", peer, batch.id);
+        lock_pending(&shared).push_front(batch);
+    } else if current.is_some() {
+        output::info(&format!("[*] coordinator: worker {} left; its batch was already requeued", peer));
+    } else {
+        output::info(&format!("[*] coordinator: worker {} finished", peer));
+    }
+}
+
+/// Run as a worker until the coordinator says the scan is done.
+pub async fn work(args: &ScanArgs) -> Result<(), DirustError> {
+    let address = args.coordinator.clone().unwrap_or_default();
+    let stream = TcpStream::connect(&address).await?;
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    output::info(&format!("[*] worker: connected to coordinator {}", address));
+
+    send(
+        &mut writer,
+        &Message::Hello {
+            token: args.worker_token.clone(),
+        },
+    )
+    .await?;
+    let mut probe_args: ScanArgs = args.clone();
+    match receive(&mut lines).await? {
+        Some(Message::Refused { reason }) => {
+            return Err(DirustError::Protocol(format!("the coordinator refused this worker: {}", reason)));
+        }
+        Some(Message::Config {
+            get,
+            timeout,
+            compressed,
+            no_compression,
+            measure_chunked,
+            flag_archives,
+            no_dir_confirm,
+            slow_retry_ms,
+            evade,
+            latency_sigma,
+            add_slash,
+            header_audit,
+            keep_cookies,
+            rate,
+            max_attempts,
+            user_agent,
+            from,
+            http2,
+            http2_prior_knowledge,
+        }) => {
+            probe_args.http.get = get;
+            probe_args.http.timeout = timeout;
+            probe_args.http.compressed = compressed;
+            probe_args.http.no_compression = no_compression;
+            probe_args.measure_chunked = measure_chunked;
+            probe_args.flag_archives = flag_archives;
+            probe_args.no_dir_confirm = no_dir_confirm;
+            probe_args.slow_retry_timeout = slow_retry_ms.map(Duration::from_millis);
+            probe_args.evade = evade;
+            probe_args.latency_sigma = latency_sigma;
+            probe_args.add_slash = add_slash;
+            probe_args.header_audit = header_audit;
+            probe_args.keep_cookies = keep_cookies;
+            probe_args.rate = rate;
+            probe_args.max_attempts = max_attempts;
+            probe_args.http.user_agent = user_agent;
+            probe_args.http.from = from;
+            probe_args.http.http2 = http2;
+            probe_args.http.http2_prior_knowledge = http2_prior_knowledge;
+        }
+        _ => return Err(DirustError::Protocol("expected a config message".to_string())),
+    }
+
+    // The coordinator decides about redirects, forms and history; the worker only probes.
+    probe_args.redirect_threshold = 0;
+    probe_args.forms = false;
+    probe_args.follow_forms = false;
+    probe_args.incremental = false;
+
+    let client = client::build_client(&probe_args.http)?;
+    // A worker's `--transcript` records the requests this worker sends.
+    let transcript = match &args.transcript {
+        Some(path) => Some(Arc::new(Transcript::open(path, args.transcript_body, false)?)),
+        None => None,
+    };
+    let chain = ProbeEngine::chain_for_scan(&probe_args, None, None, &[]);
+    let engine = ProbeEngine::for_scan(&client, &probe_args, transcript.clone(), chain)?;
+    let semaphore = Arc::new(Semaphore::new(probe_args.http.concurrency));
+    // A worker's `--ramp` paces this worker (each worker ramps up on its own).
+    let _ramp: Option<Ramp> = match args.ramp.is_empty() {
+        true => None,
+        false => Some(Ramp::start(&semaphore, probe_args.http.concurrency, &args.ramp)),
+    };
+    let classifier = Arc::new(Classifier::load(None)?);
+    let hooks = ScanHooks {
+        transcript,
+        ..ScanHooks::default()
+    };
+    let empty = ResultsDb::default();
+    // Outliers are judged per worker: each one has its own network path to the target.
+    let latency_baseline = Arc::new(LatencyBaseline::new(probe_args.latency_sigma));
+    // Hit rate spikes are for whoever reads the findings: the coordinator.
+    let hit_rate = Arc::new(HitRateWatch::new(0, false));
+    // A worker that loses the target fails its slice; the coordinator hands it on.
+    let health = Arc::new(HostHealth::new(false));
+    let scheduled = Arc::new(TargetDedup::new());
+    let soft_404 = Arc::new(SoftNotFound::new());
+    let wave = Wave {
+        engine: &engine,
+        args: &probe_args,
+        previous: None,
+        hooks: &hooks,
+        semaphore: &semaphore,
+        classifier: &classifier,
+        secrets: None,
+        latency: &latency_baseline,
+        hit_rate: &hit_rate,
+        health: &health,
+        not_found: None,
+        // No calibration here: every directory (the bases too) is calibrated on its first finding.
+        soft_404: &soft_404,
+        use_get: probe_args.http.get,
+        print_findings: false,
+        // The coordinator applied `--scope` (and turned directory confirmation off with it).
+        scope: None,
+        // The coordinator hands out every target once; this worker's `word/` confirmations
+        // are kept out of its later batches.
+        scheduled: &scheduled,
+    };
+
+    let mut probed: usize = 0;
+    loop {
+        send(&mut writer, &Message::Request).await?;
+        match receive(&mut lines).await? {
+            Some(Message::Batch { id, urls }) => {
+                let count = urls.len();
+                let urls = scheduled.filter(urls);
+                let outcomes = run_wave(&wave, &empty, urls).await?;
+                let remote: Vec<RemoteOutcome> = outcomes
+                    .into_iter()
+                    .map(|o| RemoteOutcome {
+                        length_capped: o.length_capped,
+                        latency_ms: o.latency.as_secs_f64() * 1000.0,
+                        confirmed_redirect: o
+                            .confirmed_redirect
+                            .map(|(status, latency)| (status, latency.as_secs_f64() * 1000.0)),
+                        entry: o.entry,
+                    })
+                    .collect();
+                // A coordinator that got this batch from another worker (`--batch-timeout`)
+                // may have finished and closed the connection meanwhile.
+                if let Err(e) = send(&mut writer, &Message::Results { id, outcomes: remote }).await {
+                    eprintln!("[!] worker: could not send batch {} back ({}); the coordinator is gone", id, e);
+                    break;
+                }
+                probed += count;
+                output::info(&format!("[*] worker: batch {} done ({} URLs, {} total)", id, count, probed));
+            }
+            Some(Message::Wait) => tokio::time::sleep(WAIT_DELAY).await,
+            Some(Message::Done) | None => break,
+            Some(_) => continue,
+        }
+    }
+
+    if let Some(transcript) = &hooks.transcript {
+        transcript.flush()?;
+    }
+    output::info(&format!("[*] worker: scan finished, {} URLs probed", probed));
+    Ok(())
+}
+
+/// Write one protocol line.
+async fn send(writer: &mut OwnedWriteHalf, message: &Message) -> Result<(), DirustError> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    Ok(())
+}
+
+/// Read one protocol line (`None` when the peer closed the connection).
+async fn receive(lines: &mut Lines<BufReader<OwnedReadHalf>>) -> Result<Option<Message>, DirustError> {
+    match lines.next_line().await? {
+        Some(line) => Ok(Some(serde_json::from_str(&line)?)),
+        None => Ok(None),
+    }
+}
+
+/// Lock the batches handed out (a poisoned lock only means a handler panicked).
+fn lock_out(shared: &Shared) -> std::sync::MutexGuard<'_, HashMap<usize, (Batch, String, Instant)>> {
+    match shared.out.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Lock the pending batches (a poisoned lock only means a handler panicked).
+fn lock_pending(shared: &Shared) -> std::sync::MutexGuard<'_, VecDeque<Batch>> {
+    match shared.pending.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
@@ -0,0 +1,90 @@
+//! src/scanner/encodings.rs
+//!
+//! Purpose:
+//!   `--unsafe-encodings`: a mutation pass that adds encoding variants of every target's
+//!   path, to test path filters (WAF rules, reverse-proxy ACLs, `location` blocks) that
+//!   compare the raw path while the application behind them decodes or normalizes it.
+//!
+//! Variants of the path after the base URL (at most `MAX_VARIANTS` per target):
+//!   - overlong UTF-8 `/` as 2 and 3 bytes: `%c0%af`, `%e0%80%af`      (only if the path has a `/`)
+//!   - overlong UTF-8 `.`: `%c0%ae`                                  (only if the path has a `.`)
+//!   - overlong UTF-8 first letter: `admin` → `%c1%a1dmin`
+//!   - fullwidth forms, which NFKC normalization maps back to ASCII: `admin` → `ａｄｍｉｎ`
+//!   - a compatibility look-alike for the first letter that has one (KELVIN SIGN for `k`,
+//!     LONG S for `s`, ...), also undone by NFKC
+//!
+//! Overlong sequences are invalid UTF-8: strict decoders reject them, only old or lenient
+//! ones turn them back into ASCII. That is the point, and also why the pass is opt-in — such
+//! requests look like attacks, trip IDS rules and multiply the request count.
+
+/// Upper bound on variants per target (for chunk sizing).
+pub const MAX_VARIANTS: usize = 6;
+
+/// ASCII letters with a compatibility character that NFKC normalizes to them.
+const LOOKALIKES: [(char, char); 6] = [
+    ('k', '\u{212A}'), // KELVIN SIGN
+    ('s', '\u{017F}'), // LATIN SMALL LETTER LONG S
+    ('a', '\u{00AA}'), // FEMININE ORDINAL INDICATOR
+    ('o', '\u{00BA}'), // MASCULINE ORDINAL INDICATOR
+    ('i', '\u{2170}'), // SMALL ROMAN NUMERAL ONE
+    ('n', '\u{207F}'), // SUPERSCRIPT LATIN SMALL LETTER N
+];
+
+/// Encoding variants of `target`, a URL under `base` (only the part after `base` changes).
+pub fn variants(base: &str, target: &str) -> Vec<String> {
+    let path = match target.strip_prefix(base) {
+        Some(p) if !p.is_empty() => p,
+        _ => return Vec::new(),
+    };
+
+    let mut out: Vec<String> = Vec::with_capacity(MAX_VARIANTS);
+    if path.contains('/') {
+        out.push(path.replace('/', "%c0%af"));
+        out.push(path.replace('/', "%e0%80%af"));
+    }
+    if path.contains('.') {
+        out.push(path.replace('.', "%c0%ae"));
+    }
+
+    // Overlong first letter: the two-byte form of an ASCII byte b is C0|b>>6, 80|b&3F.
+    if let Some(first) = path.chars().next()
+        && first.is_ascii_alphanumeric()
+    {
+        let b = first as u8;
+        out.push(format!("%{:02x}%{:02x}{}", 0xC0 | (b >> 6), 0x80 | (b & 0x3F), &path[1..]));
+    }
+
+    // Fullwidth forms (U+FF01..U+FF5E mirror ASCII 0x21..0x7E); `/` stays a separator.
+    // Paths with escapes or a query are left alone (`%2F` would turn into three letters).
+    let fullwidth: String = match path.contains(['%', '?']) {
+        true => path.to_string(),
+        false => path
+            .chars()
+            .map(|c| match c {
+                '/' => "/".to_string(),
+                '!'..='~' => percent_utf8(char::from_u32(c as u32 - 0x21 + 0xFF01).unwrap_or(c)),
+                _ => c.to_string(),
+            })
+            .collect(),
+    };
+    if fullwidth != path {
+        out.push(fullwidth);
+    }
+
+    // First letter that has a compatibility look-alike.
+    let lowered = path.to_ascii_lowercase();
+    for (i, c) in lowered.char_indices() {
+        if let Some((_, twin)) = LOOKALIKES.iter().find(|(ascii, _)| *ascii == c) {
+            out.push(format!("{}{}{}", &path[..i], percent_utf8(*twin), &path[i + 1..]));
+            break;
+        }
+    }
+
+    out.into_iter().map(|p| format!("{}{}", base, p)).collect()
+}
+
+/// Percent-encode the UTF-8 bytes of `c` (lowercase hex, like the overlong forms).
+fn percent_utf8(c: char) -> String {
+    let mut buf = [0u8; 4];
+    c.encode_utf8(&mut buf).bytes().map(|b| format!("%{:02x}", b)).collect()
+}
@@ -0,0 +1,153 @@
+//! src/scanner/events.rs
+//!
+//! Purpose:
+//!   `--progress-ndjson[=PATH]`: the scan's progress as machine-readable events, one JSON
+//!   object per line every `--progress-interval`, so a GUI or an orchestrator wrapping dirust
+//!   can draw a progress bar without scraping the live status line.
+//!
+//!     {"schema_version":1,"event":"progress","time":"2024-08-12T09:31:05.120Z","elapsed":65.0,"completed":1520,
+//!      "total":4810,"scheduled":1600,"rps":412.3,"errors":2,"eta":8,"findings":11,
+//!      "discovered":40,"base":"https://example.com/"}
+//!
+//!   The last line has `"event":"finished"`, with the rate over the whole scan.
+//!
+//! Notes:
+//!   - The counters are the ones of the live status line (`ScanMonitor`): `total` is
+//!     `completed` + the estimate of what is left, so it can grow as targets are discovered;
+//!     `total` and `eta` are `null` while the wordlist size is unknown.
+//!   - Events go to stderr by default (stdout keeps the findings, e.g. with `--silent`); the
+//!     live status line is not drawn meanwhile. With a path, the file is created (or a named
+//!     pipe opened) before the scan starts.
+//!   - See `schema::ProgressEvent` for the fields.
+
+use crate::{
+    error::DirustError,
+    export::rfc3339_millis,
+    scanner::monitor::ScanMonitor,
+    schema::{ProgressEvent, SCHEMA_VERSION},
+};
+use std::{
+    fs::File,
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::task::JoinHandle;
+
+/// Shortest interval between two events.
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Where the events are written.
+type Sink = Arc<Mutex<Box<dyn Write + Send>>>;
+
+/// Running `--progress-ndjson` events; `stop` them when the scan is over.
+pub struct ProgressEvents {
+    ticker: JoinHandle<()>,
+    sink: Sink,
+    monitor: Arc<ScanMonitor>,
+    started: Instant,
+}
+
+impl ProgressEvents {
+    /// Write an event for `monitor` to `destination` (`-`: stderr) every `interval`.
+    ///
+    /// Errors:
+    ///   - `DirustError::Io` if the file cannot be created
+    pub fn start(destination: &str, interval: Duration, monitor: Arc<ScanMonitor>) -> Result<ProgressEvents, DirustError> {
+        let writer: Box<dyn Write + Send> = match destination {
+            "-" => Box::new(io::stderr()),
+            path => Box::new(File::create(path)?),
+        };
+        let sink: Sink = Arc::new(Mutex::new(writer));
+        let started = Instant::now();
+
+        let ticker = {
+            let sink = Arc::clone(&sink);
+            let monitor = Arc::clone(&monitor);
+            tokio::spawn(async move {
+                let mut ticks = tokio::time::interval(interval.max(MIN_INTERVAL));
+                // The first tick completes at once; the first event comes one interval in.
+                ticks.tick().await;
+                let mut previous: (Instant, usize) = (started, 0);
+                loop {
+                    ticks.tick().await;
+                    let now = Instant::now();
+                    let event = event("progress", &monitor, started, previous);
+                    previous = (now, event.completed);
+                    write(&sink, &event);
+                }
+            })
+        };
+        Ok(ProgressEvents {
+            ticker,
+            sink,
+            monitor,
+            started,
+        })
+    }
+
+    /// Stop the periodic events and write the `finished` one.
+    pub async fn stop(self) {
+        self.ticker.abort();
+        // An event being written when the ticker was aborted comes before the last one.
+        let _ = self.ticker.await;
+        let event = event("finished", &self.monitor, self.started, (self.started, 0));
+        write(&self.sink, &event);
+    }
+}
+
+/// The `kind` event for the counters of `monitor` now, with the rate since `previous`
+/// (when, probes done then).
+fn event(kind: &str, monitor: &ScanMonitor, started: Instant, previous: (Instant, usize)) -> ProgressEvent {
+    let progress = monitor.progress();
+    let now = Instant::now();
+    let secs = now.duration_since(previous.0).as_secs_f64();
+    let rps = match secs > 0.0 {
+        true => progress.done.saturating_sub(previous.1) as f64 / secs,
+        false => 0.0,
+    };
+    let eta = match (progress.remaining, kind) {
+        (_, "finished") => Some(0),
+        (Some(remaining), _) if rps > 0.0 => Some((remaining as f64 / rps).ceil() as u64),
+        (Some(0), _) => Some(0),
+        _ => None,
+    };
+    let since_epoch = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(d) => d,
+        Err(_) => Duration::ZERO,
+    };
+    ProgressEvent {
+        schema_version: SCHEMA_VERSION,
+        event: kind.to_string(),
+        time: rfc3339_millis(since_epoch),
+        elapsed: round_tenths(now.duration_since(started).as_secs_f64()),
+        completed: progress.done,
+        total: progress.remaining.map(|remaining| progress.done + remaining),
+        scheduled: progress.total,
+        rps: round_tenths(rps),
+        errors: progress.errors,
+        eta,
+        findings: progress.findings,
+        discovered: progress.discovered,
+        base: progress.directory,
+    }
+}
+
+/// Write `event` as one line (a failed write is reported and the event dropped).
+fn write(sink: &Sink, event: &ProgressEvent) {
+    let Ok(line) = serde_json::to_string(event) else {
+        return;
+    };
+    let mut sink = match sink.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if let Err(e) = writeln!(sink, "{}", line).and_then(|_| sink.flush()) {
+        eprintln!("[!] progress-ndjson: could not write an event: {}", e);
+    }
+}
+
+/// `value` rounded to one decimal.
+fn round_tenths(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
@@ -0,0 +1,300 @@
+//! src/scanner/fast.rs
+//!
+//! Purpose:
+//!   `--engine fast`: a lean probe client for very high concurrency (`-c 500` and up), built
+//!   directly on hyper instead of going through reqwest.
+//!
+//! What makes it cheaper per request:
+//!   - HTTP/1.1 only: no ALPN/HTTP2 negotiation, no h2 machinery.
+//!   - No reqwest layers (redirect policy, cookie store, proxy lookup, decompression):
+//!     a request is a method, a URL and the headers the probe middleware chain built.
+//!   - One keep-alive connection per concurrent probe, kept in the pool between probes
+//!     (the pool is sized to `-c`), with TCP_NODELAY. GET bodies are drained (up to
+//!     `DRAIN_LIMIT`) so their connection can be reused instead of torn down.
+//!   - TLS handshakes are paid once per connection; reconnects resume the TLS session.
+//!
+//! What it does not do (use the default engine when these matter):
+//!   - proxies, HTTP/2, system certificate stores (the bundled Mozilla roots are used).
+//!   - ALPN: with `--tls-impersonate` the suites and groups match the profile, but no ALPN
+//!     extension is sent (HTTP/1.1 only), which makes the ClientHello less browser-like.
+//!
+//! With `--pipeline N` (N > 1), probes skip hyper and are written N at a time on raw
+//! HTTP/1.1 connections, their responses read afterwards (see `pipeline.rs`).
+//!
+//! The scanner talks to either engine through `http::ProbeEngine` (this is a
+//! `transport::ProbeClient`); body downloads (`--forms`, `--measure-chunked`) are handed to
+//! the default reqwest client. `--transcript` records every request sent here too (bodies
+//! come from the drain, so at most `DRAIN_LIMIT` bytes).
+
+use super::{
+    http::{self, HttpSummary},
+    middleware::ProbeRequest,
+    pipeline::Pipeline,
+    raw::{RawConnector, RawResponse},
+    transcript::{Exchange, Received, Transcript},
+    transport::{FetchFuture, MeasureFuture, ProbeClient, ProbeFuture},
+};
+use crate::{args::HttpArgs, error::DirustError, tls};
+use bytes::Bytes;
+use http_body_util::{BodyExt, Empty};
+use hyper::{header, Method, Request, Response, Uri};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use hyper_util::{
+    client::legacy::{
+        connect::{HttpConnector, HttpInfo},
+        Client,
+    },
+    rt::TokioExecutor,
+};
+use std::{
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// GET bodies up to this size are read and discarded to keep the connection reusable;
+/// larger ones are dropped (closing the connection is cheaper than downloading them).
+const DRAIN_LIMIT: usize = 64 * 1024;
+
+/// How long an idle pooled connection is kept.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Head start of the first address family when a host has both (Happy Eyeballs).
+const HAPPY_EYEBALLS: Duration = Duration::from_millis(300);
+
+/// The hyper-based probe client.
+pub struct FastClient {
+    client: Client<HttpsConnector<HttpConnector>, Empty<Bytes>>,
+    timeout: Duration,
+    /// `--transcript`: every request and response is recorded here.
+    transcript: Option<Arc<Transcript>>,
+    /// The default client, for body downloads.
+    bodies: reqwest::Client,
+    /// `--pipeline N` (N > 1): probes go over pipelined raw connections instead of hyper.
+    pipeline: Option<Pipeline>,
+}
+
+/// A probe response: its summary, and what `--transcript` records of it.
+struct Answer {
+    summary: HttpSummary,
+    headers: header::HeaderMap,
+    body: Vec<u8>,
+    /// The body was cut off (at `DRAIN_LIMIT`, or `raw::MAX_BODY` when pipelined).
+    body_truncated: bool,
+}
+
+impl FastClient {
+    /// Build the client from the shared client flags (`-c`, `--timeout`, TLS), with
+    /// Title-Case header names for `--evade`, recording to `transcript`. Bodies are downloaded
+    /// with `bodies`. A `pipeline` depth above 1 sends the probes pipelined.
+    pub fn new(
+        args: &HttpArgs,
+        bodies: reqwest::Client,
+        evade: bool,
+        transcript: Option<Arc<Transcript>>,
+        pipeline: usize,
+    ) -> Result<FastClient, DirustError> {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        http.set_nodelay(true);
+        http.set_connect_timeout(Some(args.request_timeout()));
+        http.set_happy_eyeballs_timeout(Some(HAPPY_EYEBALLS));
+        http.set_local_address(args.local_address());
+
+        let builder = match args.tls_impersonate {
+            Some(profile) => HttpsConnectorBuilder::new().with_tls_config(tls::client_config(profile)?),
+            None => HttpsConnectorBuilder::new().with_webpki_roots(),
+        };
+        let connector = builder
+            .https_or_http()
+            .enable_http1()
+            .wrap_connector(http);
+
+        let client = Client::builder(TokioExecutor::new())
+            .pool_max_idle_per_host(args.concurrency)
+            .pool_idle_timeout(IDLE_TIMEOUT)
+            .http1_title_case_headers(evade)
+            .build(connector);
+
+        let pipeline = match pipeline > 1 {
+            true => Some(Pipeline::new(RawConnector::new(args)?, pipeline, args.concurrency, evade)),
+            false => None,
+        };
+
+        Ok(FastClient {
+            client,
+            timeout: args.request_timeout(),
+            transcript,
+            bodies,
+            pipeline,
+        })
+    }
+
+    /// Send one request, summarize and drain its response, and record both in the
+    /// transcript (if any).
+    async fn exchange(&self, request: &ProbeRequest) -> Result<HttpSummary, DirustError> {
+        let (url, use_get) = (request.url.as_str(), request.use_get);
+        let timeout = request.timeout.unwrap_or(self.timeout);
+        let headers = request.headers.clone();
+        let sent_at = SystemTime::now();
+        let started = Instant::now();
+        let result = match &self.pipeline {
+            Some(pipeline) => match pipeline.send(url, use_get, &headers, timeout).await {
+                Ok((response, peer)) => Ok(raw_answer(response, peer)),
+                Err(e) => Err(e),
+            },
+            None => {
+                let uri: Uri = match url.parse() {
+                    Ok(u) => u,
+                    Err(e) => return Err(DirustError::Engine(format!("{}: {}", url, e))),
+                };
+                match self.request(&uri, use_get, headers.clone(), timeout).await {
+                    Ok(response) => Ok(answer(response).await),
+                    Err(e) => Err(e),
+                }
+            }
+        };
+
+        let transcript = match &self.transcript {
+            Some(t) => t,
+            None => return result.map(|answer| answer.summary),
+        };
+        let method = request.method();
+        match result {
+            Ok(answer) => {
+                let keep = answer.body.len().min(transcript.body_limit());
+                transcript.record(Exchange {
+                    method,
+                    url,
+                    request_headers: &headers,
+                    sent_at,
+                    elapsed: started.elapsed(),
+                    outcome: Ok(Received {
+                        status: answer.summary.status,
+                        headers: &answer.headers,
+                        body: &answer.body[..keep],
+                        body_truncated: answer.body_truncated || keep < answer.body.len(),
+                    }),
+                })?;
+                Ok(answer.summary)
+            }
+            Err(error) => {
+                transcript.record(Exchange {
+                    method,
+                    url,
+                    request_headers: &headers,
+                    sent_at,
+                    elapsed: started.elapsed(),
+                    outcome: Err(&error),
+                })?;
+                Err(error)
+            }
+        }
+    }
+
+    /// Send one request with `headers`, bounded by `timeout`.
+    async fn request(
+        &self,
+        uri: &Uri,
+        use_get: bool,
+        headers: header::HeaderMap,
+        timeout: Duration,
+    ) -> Result<Response<hyper::body::Incoming>, DirustError> {
+        let method = if use_get { Method::GET } else { Method::HEAD };
+        let mut request = match Request::builder().method(method).uri(uri.clone()).body(Empty::new()) {
+            Ok(r) => r,
+            Err(e) => return Err(DirustError::Engine(format!("{}: {}", uri, e))),
+        };
+        *request.headers_mut() = headers;
+
+        match tokio::time::timeout(timeout, self.client.request(request)).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(e)) => Err(DirustError::Engine(format!("{}: {}", uri, describe(&e)))),
+            Err(_) => Err(DirustError::Timeout(uri.to_string())),
+        }
+    }
+}
+
+impl ProbeClient for FastClient {
+    fn send<'a>(&'a self, request: &'a ProbeRequest) -> ProbeFuture<'a> {
+        Box::pin(self.exchange(request))
+    }
+
+    fn fetch<'a>(&'a self, url: &'a str) -> FetchFuture<'a> {
+        Box::pin(http::fetch(&self.bodies, url))
+    }
+
+    fn measure<'a>(&'a self, url: &'a str, cap: u64) -> MeasureFuture<'a> {
+        Box::pin(http::measure_body(&self.bodies, url, cap))
+    }
+}
+
+/// Summarize a hyper response and drain its body.
+async fn answer(response: Response<hyper::body::Incoming>) -> Answer {
+    let summary = summarize(&response);
+    let headers = response.headers().clone();
+    let (body, body_truncated) = drain(response).await;
+    Answer {
+        summary,
+        headers,
+        body,
+        body_truncated,
+    }
+}
+
+/// Summarize a pipelined response from `peer`.
+fn raw_answer(response: RawResponse, peer: Option<SocketAddr>) -> Answer {
+    let mut summary = http::summarize_headers(response.status, &response.headers);
+    summary.remote_addr = peer;
+    Answer {
+        summary,
+        body_truncated: response.transferred > response.body.len() as u64,
+        headers: response.headers,
+        body: response.body,
+    }
+}
+
+/// Read the headers the scanner prints into an `HttpSummary`.
+fn summarize(response: &Response<hyper::body::Incoming>) -> HttpSummary {
+    // `hyper::StatusCode` and `reqwest::StatusCode` are the same type (`http` crate).
+    let mut summary = http::summarize_headers(response.status(), response.headers());
+    summary.remote_addr = response.extensions().get::<HttpInfo>().map(|info| info.remote_addr());
+    summary
+}
+
+/// Read a small body so the connection goes back to the pool.
+///
+/// Returns what was read (for `--transcript`) and whether the body was cut off at
+/// `DRAIN_LIMIT` (the connection is then dropped instead of reused).
+async fn drain(response: Response<hyper::body::Incoming>) -> (Vec<u8>, bool) {
+    let mut body = response.into_body();
+    let mut read: Vec<u8> = Vec::new();
+    while let Some(frame) = body.frame().await {
+        match frame {
+            Ok(frame) => {
+                if let Some(data) = frame.data_ref() {
+                    read.extend_from_slice(data);
+                }
+                if read.len() > DRAIN_LIMIT {
+                    read.truncate(DRAIN_LIMIT);
+                    return (read, true);
+                }
+            }
+            Err(_) => break,
+        }
+    }
+    (read, false)
+}
+
+/// Error text including its causes: hyper's own message ("client error (Connect)") only
+/// says which stage failed, the sources say why.
+fn describe(error: &dyn std::error::Error) -> String {
+    let mut text = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        text.push_str(": ");
+        text.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    text
+}
@@ -0,0 +1,183 @@
+//! src/scanner/fingerprint.rs
+//!
+//! Purpose:
+//!   `--smart-exts`: recognize the technology behind a target from one GET of its base URL
+//!   and pick the extensions worth probing. A PHP site gets `.php`, IIS/ASP.NET gets
+//!   `.aspx`/`.ashx`/`.asmx`/`.asp`, a Java stack gets `.jsp`/`.do`/`.action` — and none of
+//!   the others, which would only cost requests.
+//!
+//! Signals (headers first, then a few body markers):
+//!   - `Server`, `X-Powered-By`, `X-AspNet-Version` / `X-AspNetMvc-Version`
+//!   - session cookie names (`PHPSESSID`, `ASP.NET_SessionId`, `JSESSIONID`, `CFID`, ...)
+//!   - body markers (`wp-content/` for WordPress, `__VIEWSTATE` for ASP.NET WebForms)
+//!
+//! Selection (`select_extensions`):
+//!   - extensions of the detected technologies are added;
+//!   - `--exts` entries that belong to a technology that was *not* detected are dropped;
+//!   - other `--exts` entries (`.html`, `.txt`, `.bak`, ...) are kept.
+//!
+//!   Nothing recognized: `--exts` is used unchanged.
+
+use crate::{error::DirustError, scanner::http};
+use reqwest::{
+    header::{self, HeaderMap},
+    Client,
+};
+
+/// A technology we can recognize, with the extensions its pages use.
+struct Technology {
+    name: &'static str,
+    extensions: &'static [&'static str],
+    /// Lower-case substrings of `Server` / `X-Powered-By`.
+    server_markers: &'static [&'static str],
+    /// Lower-case session cookie names (prefixes).
+    cookies: &'static [&'static str],
+    /// Headers whose mere presence identifies the technology.
+    headers: &'static [&'static str],
+    /// Substrings of the HTML body.
+    body_markers: &'static [&'static str],
+}
+
+const TECHNOLOGIES: &[Technology] = &[
+    Technology {
+        name: "PHP",
+        extensions: &[".php"],
+        server_markers: &["php"],
+        cookies: &["phpsessid"],
+        headers: &[],
+        body_markers: &["wp-content/", "wp-includes/"],
+    },
+    Technology {
+        name: "ASP.NET/IIS",
+        extensions: &[".aspx", ".ashx", ".asmx", ".asp"],
+        server_markers: &["microsoft-iis", "asp.net"],
+        cookies: &["asp.net_sessionid", "aspsessionid", ".aspxauth"],
+        headers: &["x-aspnet-version", "x-aspnetmvc-version"],
+        body_markers: &["__viewstate"],
+    },
+    Technology {
+        name: "Java",
+        extensions: &[".jsp", ".do", ".action"],
+        server_markers: &["tomcat", "jetty", "jboss", "wildfly", "weblogic", "websphere", "glassfish", "servlet", "jsp"],
+        cookies: &["jsessionid"],
+        headers: &[],
+        body_markers: &[],
+    },
+    Technology {
+        name: "ColdFusion",
+        extensions: &[".cfm"],
+        server_markers: &["coldfusion"],
+        cookies: &["cfid", "cftoken"],
+        headers: &[],
+        body_markers: &[],
+    },
+];
+
+/// A recognized technology and what gave it away.
+#[derive(Debug, Clone)]
+pub struct Detection {
+    pub name: &'static str,
+    /// E.g. "x-powered-by: PHP/8.1.2" or "cookie JSESSIONID".
+    pub evidence: String,
+}
+
+/// Fetch `base` once and return the technologies it reveals (possibly none).
+///
+/// Errors:
+///   - `DirustError::Http` if the base URL cannot be fetched at all
+pub async fn detect(client: &Client, base: &str) -> Result<Vec<Detection>, DirustError> {
+    let response = client.get(base).send().await?;
+    let headers = response.headers().clone();
+    let body = http::read_text(response).await?.to_ascii_lowercase();
+    Ok(detect_in(&headers, &body))
+}
+
+/// A detection read back from a calibration profile (`None` for an unknown technology).
+pub fn restore(name: &str, evidence: &str) -> Option<Detection> {
+    TECHNOLOGIES.iter().find(|tech| tech.name == name).map(|tech| Detection {
+        name: tech.name,
+        evidence: evidence.to_string(),
+    })
+}
+
+/// Match the headers and (lower-cased) body of one response against `TECHNOLOGIES`.
+fn detect_in(headers: &HeaderMap, body: &str) -> Vec<Detection> {
+    let mut found: Vec<Detection> = Vec::new();
+
+    for tech in TECHNOLOGIES {
+        let evidence = server_evidence(headers, tech)
+            .or_else(|| header_evidence(headers, tech))
+            .or_else(|| cookie_evidence(headers, tech))
+            .or_else(|| {
+                tech.body_markers
+                    .iter()
+                    .find(|marker| body.contains(*marker))
+                    .map(|marker| format!("body contains {}", marker))
+            });
+        if let Some(evidence) = evidence {
+            found.push(Detection { name: tech.name, evidence });
+        }
+    }
+    found
+}
+
+/// `Server` / `X-Powered-By` values naming the technology.
+fn server_evidence(headers: &HeaderMap, tech: &Technology) -> Option<String> {
+    for name in [header::SERVER.as_str(), "x-powered-by"] {
+        for value in headers.get_all(name) {
+            let text = String::from_utf8_lossy(value.as_bytes());
+            let lower = text.to_ascii_lowercase();
+            if tech.server_markers.iter().any(|marker| lower.contains(marker)) {
+                return Some(format!("{}: {}", name, text));
+            }
+        }
+    }
+    None
+}
+
+/// Headers only this technology sends.
+fn header_evidence(headers: &HeaderMap, tech: &Technology) -> Option<String> {
+    tech.headers
+        .iter()
+        .find(|name| headers.contains_key(**name))
+        .map(|name| format!("header {}", name))
+}
+
+/// Session cookies set by this technology.
+fn cookie_evidence(headers: &HeaderMap, tech: &Technology) -> Option<String> {
+    for value in headers.get_all(header::SET_COOKIE) {
+        let text = String::from_utf8_lossy(value.as_bytes());
+        let cookie_name = text.split('=').next().unwrap_or_default().trim().to_string();
+        let lower = cookie_name.to_ascii_lowercase();
+        if tech.cookies.iter().any(|prefix| lower.starts_with(prefix)) {
+            return Some(format!("cookie {}", cookie_name));
+        }
+    }
+    None
+}
+
+/// The extensions to scan with, given what was detected and what `--exts` asked for.
+pub fn select_extensions(detected: &[Detection], requested: &[String]) -> Vec<String> {
+    if detected.is_empty() {
+        return requested.to_vec();
+    }
+    let any_tech = |ext: &str| TECHNOLOGIES.iter().any(|tech| tech.extensions.contains(&ext));
+
+    let mut selected: Vec<String> = Vec::new();
+    for detection in detected {
+        for tech in TECHNOLOGIES.iter().filter(|tech| tech.name == detection.name) {
+            for ext in tech.extensions {
+                if !selected.iter().any(|s| s == ext) {
+                    selected.push(ext.to_string());
+                }
+            }
+        }
+    }
+    // Extensions of detected technologies are in already; keep the technology-neutral rest.
+    for ext in requested {
+        if !any_tech(ext) && !selected.contains(ext) {
+            selected.push(ext.clone());
+        }
+    }
+    selected
+}
@@ -0,0 +1,175 @@
+//! src/scanner/forms.rs
+//!
+//! Purpose:
+//!   Extract HTML forms from a response body: where they submit (`action`), how (`method`),
+//!   and which parameter names they send (`<input>`, `<select>`, `<textarea>` names).
+//!
+//! Design choices:
+//!   - A small hand-written scanner instead of a full HTML parser: we only need a handful
+//!     of attributes, and real-world HTML is messy enough that "find the tag, read the
+//!     attribute" is more forgiving than strict parsing.
+//!   - Tag and attribute names are matched case-insensitively; values keep their case.
+//!   - Relative actions are resolved against the page URL (an empty/missing action means
+//!     "submit to this page", per the HTML spec).
+
+use reqwest::Url;
+
+/// One form found on a page.
+#[derive(Debug, Clone)]
+pub struct FormInfo {
+    /// URL of the page the form was found on.
+    pub page: String,
+    /// Absolute URL the form submits to.
+    pub action: String,
+    /// Upper-cased submit method ("GET" when absent, as browsers do).
+    pub method: String,
+    /// Parameter names, in document order, without duplicates.
+    pub inputs: Vec<String>,
+}
+
+/// Find every `<form>` in `html` and describe it.
+///
+/// Forms whose action cannot be resolved to an http(s) URL (e.g., `javascript:` actions)
+/// are skipped, since there is nothing to probe or report.
+pub fn extract_forms(page_url: &str, html: &str) -> Vec<FormInfo> {
+    let mut out: Vec<FormInfo> = Vec::new();
+
+    // `to_ascii_lowercase` keeps byte offsets identical, so positions found in `lower`
+    // are valid in `html` too.
+    let lower = html.to_ascii_lowercase();
+    let mut cursor: usize = 0;
+
+    while let Some(rel_start) = lower[cursor..].find("<form") {
+        let start = cursor + rel_start;
+
+        // End of the opening tag.
+        let tag_end = match lower[start..].find('>') {
+            Some(rel) => start + rel,
+            None => break, // truncated document
+        };
+        let open_tag = &html[start..tag_end];
+
+        // The form body runs until `</form` (or the end of the document if unclosed).
+        let body_end = match lower[tag_end..].find("</form") {
+            Some(rel) => tag_end + rel,
+            None => lower.len(),
+        };
+        let body = &html[tag_end..body_end];
+
+        let raw_action = attr_value(open_tag, "action").unwrap_or_default();
+        let method = match attr_value(open_tag, "method") {
+            Some(m) if !m.trim().is_empty() => m.trim().to_ascii_uppercase(),
+            _ => "GET".to_string(),
+        };
+
+        if let Some(action) = resolve(page_url, &raw_action) {
+            out.push(FormInfo {
+                page: page_url.to_string(),
+                action,
+                method,
+                inputs: field_names(body),
+            });
+        }
+
+        cursor = body_end.max(tag_end);
+    }
+
+    out
+}
+
+/// Collect `name` attributes of form fields inside a form body.
+fn field_names(body: &str) -> Vec<String> {
+    let lower = body.to_ascii_lowercase();
+    let mut names: Vec<String> = Vec::new();
+
+    for tag_name in ["<input", "<select", "<textarea"] {
+        let mut cursor: usize = 0;
+        while let Some(rel) = lower[cursor..].find(tag_name) {
+            let start = cursor + rel;
+            let end = match lower[start..].find('>') {
+                Some(r) => start + r,
+                None => lower.len(),
+            };
+
+            if let Some(name) = attr_value(&body[start..end], "name") {
+                let trimmed = name.trim().to_string();
+                if !trimmed.is_empty() && !names.contains(&trimmed) {
+                    names.push(trimmed);
+                }
+            }
+
+            cursor = end;
+        }
+    }
+
+    names
+}
+
+/// Read an attribute value from the text of one tag (e.g., `<form action="/x" method=post`).
+///
+/// Handles double-quoted, single-quoted and unquoted values. The attribute name must be
+/// preceded by whitespace so `data-name=` does not match `name`.
+///
+/// Also used by `dirust wordgen` to read link attributes.
+pub fn attr_value(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let bytes = lower.as_bytes();
+    let mut cursor: usize = 0;
+
+    while let Some(rel) = lower[cursor..].find(name) {
+        let pos = cursor + rel;
+        cursor = pos + name.len();
+
+        // Must start a new attribute.
+        if pos == 0 || !bytes[pos - 1].is_ascii_whitespace() {
+            continue;
+        }
+
+        // Skip spaces, then require '='.
+        let mut i = pos + name.len();
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i >= bytes.len() || bytes[i] != b'=' {
+            continue;
+        }
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i >= bytes.len() {
+            return Some(String::new());
+        }
+
+        // Quoted or unquoted value.
+        let quote = bytes[i];
+        let value = if quote == b'"' || quote == b'\'' {
+            let rest = &tag[i + 1..];
+            match rest.find(quote as char) {
+                Some(end) => &rest[..end],
+                None => rest,
+            }
+        } else {
+            let rest = &tag[i..];
+            let end = rest
+                .find(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+                .unwrap_or(rest.len());
+            &rest[..end]
+        };
+
+        return Some(value.replace("&amp;", "&"));
+    }
+
+    None
+}
+
+/// Resolve a form action against its page. Returns `None` for non-HTTP results.
+fn resolve(page_url: &str, action: &str) -> Option<String> {
+    let page = Url::parse(page_url).ok()?;
+    let joined = page.join(action.trim()).ok()?;
+
+    match joined.scheme() {
+        "http" | "https" => Some(joined.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,228 @@
+//! src/scanner/health.rs
+//!
+//! Purpose:
+//!   Keep a scan going when its host goes down halfway (a crashed backend, a restart, a
+//!   firewall that starts dropping us). Without this, the first refused connection ends the
+//!   whole scan, including the other bases of a multi-base scan that are still fine.
+//!
+//! How:
+//!   - Every probe that gets no answer at all (connection refused or reset, DNS failure,
+//!     timeout) extends the host's failure streak; any answer ends it. A probe that failed
+//!     is put back in the queue once; failing again while the host still answers others, it
+//!     is given up with a warning.
+//!   - `DOWN_STREAK` failures in a row quarantine the host: it gets no new probes, and the
+//!     ones that failed wait. The scan then re-checks the base after 1s, 2s, 4s, ... (at most
+//!     `MAX_RECHECK_DELAY` apart) until it answers again, and resumes with the waiting
+//!     probes. Other bases keep scanning meanwhile.
+//!   - A host still down after `--max-downtime` is given up: the rest of its scan is skipped
+//!     (with `--state` / `--checkpoint` it can be finished later).
+//!   - Each outage (start, end, duration) is listed in the host's summary section.
+//!
+//! Notes:
+//!   - HTTP errors (5xx, 429) are answers: they never count as the host being down.
+//!   - Distributed workers (`--role worker`) keep failing on the first error; their
+//!     coordinator hands the slice to another worker.
+//!
+//! Output:
+//!   [!] https://example.com is down (20 probes in a row failed: connection refused (Connection refused (os error 111))); re-checking in 1s
+//!   [*] https://example.com is back after 47s; resuming 23 probe(s)
+
+use crate::{
+    client,
+    error::DirustError,
+    output::{self, Style},
+    schema::HostOutage,
+};
+use std::{
+    collections::HashSet,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Probes in a row that got no answer before a host counts as down.
+const DOWN_STREAK: usize = 20;
+
+/// First re-check of a quarantined host; doubles after every failed one.
+const FIRST_RECHECK_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest wait between two re-checks.
+const MAX_RECHECK_DELAY: Duration = Duration::from_secs(60);
+
+/// Failure streak and quarantine state of one host.
+#[derive(Debug, Default)]
+struct Health {
+    /// Probes in a row that got no answer.
+    streak: usize,
+    /// The last such failure, for the quarantine message.
+    last_error: Option<String>,
+    /// When the current outage started (the host is quarantined while this is set).
+    down_since: Option<(Instant, u64)>,
+    /// URLs whose probe failed, waiting to be probed again.
+    deferred: Vec<String>,
+    /// URLs already put back once.
+    retried: HashSet<String>,
+    /// Outages that are over (or were given up on).
+    outages: Vec<HostOutage>,
+}
+
+/// The health of the host one scan probes, shared by all probe tasks.
+#[derive(Debug)]
+pub struct HostHealth {
+    /// Whether failed probes are deferred at all (workers fail on the first error).
+    enabled: bool,
+    health: Mutex<Health>,
+}
+
+impl HostHealth {
+    /// Health tracking for one scan; without `enabled`, every failure is handed back.
+    pub fn new(enabled: bool) -> HostHealth {
+        HostHealth {
+            enabled,
+            health: Mutex::new(Health::default()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Health> {
+        match self.health.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Whether the host is quarantined (no new probes until `wait_until_back`).
+    pub fn is_down(&self) -> bool {
+        self.lock().down_since.is_some()
+    }
+
+    /// A probe got an answer: the streak is over.
+    pub fn record_answer(&self) {
+        if self.enabled {
+            self.lock().streak = 0;
+        }
+    }
+
+    /// The probe of `url` failed with `error`. Returns the error back when it should end the
+    /// scan (not a connection failure, tracking disabled); `Ok(())` when the probe was
+    /// deferred or given up.
+    pub fn record_failure(&self, url: &str, error: DirustError) -> Result<(), DirustError> {
+        if !self.enabled || !error.is_connection_failure() {
+            return Err(error);
+        }
+        let mut health = self.lock();
+        health.streak += 1;
+        health.last_error = Some(match &error {
+            DirustError::Http(e) => client::describe_failure(e),
+            other => other.to_string(),
+        });
+        if health.down_since.is_none() && health.streak >= DOWN_STREAK {
+            health.down_since = Some((Instant::now(), unix_now()));
+        }
+        // While the host is down, every failure waits for it; otherwise a URL gets one retry.
+        if health.down_since.is_some() || health.retried.insert(url.to_string()) {
+            health.deferred.push(url.to_string());
+        } else {
+            eprintln!("[!] giving up on {}: {}", url, error);
+        }
+        Ok(())
+    }
+
+    /// Put `url` back without probing it (the host went down before its turn).
+    pub fn defer(&self, url: String) {
+        self.lock().deferred.push(url);
+    }
+
+    /// The URLs waiting to be probed again.
+    pub fn take_deferred(&self) -> Vec<String> {
+        std::mem::take(&mut self.lock().deferred)
+    }
+
+    /// The outages of this scan, oldest first.
+    pub fn outages(&self) -> Vec<HostOutage> {
+        self.lock().outages.clone()
+    }
+
+    /// The host is quarantined: re-check `base` with growing delays (through `recheck`)
+    /// until it answers, `max_downtime` passed since the outage started (zero: no limit),
+    /// or `cancelled` says stop.
+    ///
+    /// Returns whether the host is back; when it is not, the outage is recorded as open.
+    pub async fn wait_until_back<F, Fut, C>(&self, base: &str, max_downtime: Duration, recheck: F, cancelled: C) -> bool
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = bool>,
+        C: Fn() -> bool,
+    {
+        let (since, started_at, reason) = {
+            let health = self.lock();
+            let Some((since, started_at)) = health.down_since else {
+                return true;
+            };
+            (since, started_at, health.last_error.clone().unwrap_or_default())
+        };
+        let line = format!(
+            "[!] {} is down ({} probes in a row failed: {}); re-checking in {}s",
+            base,
+            DOWN_STREAK,
+            reason,
+            FIRST_RECHECK_DELAY.as_secs()
+        );
+        eprintln!("{}", output::paint(&line, Style::Alert));
+
+        let mut delay = FIRST_RECHECK_DELAY;
+        let back = loop {
+            if max_downtime > Duration::ZERO && since.elapsed() >= max_downtime {
+                break false;
+            }
+            let step = match max_downtime > Duration::ZERO {
+                true => delay.min(max_downtime.saturating_sub(since.elapsed())),
+                false => delay,
+            };
+            // Sleep in short slices so a cancelled scan does not wait out a long delay.
+            let wake = Instant::now() + step;
+            while Instant::now() < wake && !cancelled() {
+                tokio::time::sleep(wake.saturating_duration_since(Instant::now()).min(Duration::from_millis(250))).await;
+            }
+            if cancelled() {
+                break false;
+            }
+            if recheck().await {
+                break true;
+            }
+            delay = (delay * 2).min(MAX_RECHECK_DELAY);
+        };
+
+        let mut health = self.lock();
+        let seconds = since.elapsed().as_secs();
+        health.down_since = None;
+        health.streak = 0;
+        health.outages.push(HostOutage {
+            started_at,
+            ended_at: if back { Some(unix_now()) } else { None },
+            seconds,
+        });
+        match back {
+            true => output::info(&format!(
+                "[*] {} is back after {}s; resuming {} probe(s)",
+                base,
+                seconds,
+                health.deferred.len()
+            )),
+            false if !cancelled() => eprintln!(
+                "[!] {} still down after {}s; giving up on this host ({} probe(s) left waiting)",
+                base,
+                seconds,
+                health.deferred.len()
+            ),
+            false => {}
+        }
+        back
+    }
+}
+
+/// Current time as UNIX seconds (0 for a clock before 1970).
+fn unix_now() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs(),
+        Err(_) => 0,
+    }
+}
@@ -0,0 +1,180 @@
+//! src/scanner/hitrate.rs
+//!
+//! Purpose:
+//!   Notice when the share of findings suddenly jumps (`--hit-rate-alert`). A scan that
+//!   found 2 paths in 2000 and then gets 200 for everything has usually not struck gold: the
+//!   target started serving a captive portal, the session behind `--cookie` expired and every
+//!   path redirects to the login page, or a WAF answers with a block page. The findings from
+//!   then on are noise, and the sooner someone looks, the less of the scan is wasted.
+//!
+//! How:
+//!   - Every probe is counted as a hit (200/301/302/401/403) or not. The last `WINDOW`
+//!     probes form the current rate; the probes before them, the scan's usual rate.
+//!   - A spike is a current rate of at least `--hit-rate-alert` percent (50 by default) that
+//!     is also at least `SPIKE_FACTOR` times the usual rate. Nothing is judged before
+//!     `WARMUP` probes made the usual rate meaningful.
+//!   - A spike is reported once, loudly; another one is only reported after the current rate
+//!     fell below half the threshold. Findings reported during a spike are tagged
+//!     `hit-rate-spike`.
+//!
+//! Pausing (`--pause-on-spike`):
+//!   - No new probes are started. On a terminal the scan asks whether to continue (fix the
+//!     session or the network first); answering no, or running without a terminal, stops it
+//!     with an error. With `--state` the rest of the scan can be run later (`dirust resume`).
+//!
+//! Notes:
+//!   - A target that answers everything from the start (catch-all routes) has a high usual
+//!     rate and never spikes.
+//!   - Distributed scans (`--role coordinator|worker`) are not watched.
+//!
+//! Output:
+//!   [!!] hit rate spike: 94% of the last 100 responses are findings (was 1%): captive
+//!        portal, expired session or block page? Findings from here on are suspect.
+
+use crate::{
+    error::DirustError,
+    output::{self, Style},
+};
+use std::{
+    collections::VecDeque,
+    io::{IsTerminal, Write},
+    sync::Mutex,
+};
+
+/// Probes that make up the current rate.
+const WINDOW: usize = 100;
+
+/// Probes counted into the usual rate before spikes are judged.
+const WARMUP: u64 = 200;
+
+/// How many times the usual rate the current one must be to count as a spike.
+const SPIKE_FACTOR: f64 = 5.0;
+
+/// A jump of the hit rate, as percentages.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spike {
+    /// Hits in the last `WINDOW` probes.
+    pub current: f64,
+    /// Hits in the probes before them.
+    pub usual: f64,
+}
+
+/// Counters behind the watch.
+#[derive(Debug, Default)]
+struct Rates {
+    /// Outcomes of the last `WINDOW` probes (`true`: hit).
+    window: VecDeque<bool>,
+    window_hits: usize,
+    /// Probes (and hits) that left the window.
+    usual_total: u64,
+    usual_hits: u64,
+    /// A spike was reported and the rate has not come down since.
+    spiking: bool,
+    /// A spike waits for the scheduler to pause (`--pause-on-spike`).
+    pause: Option<Spike>,
+}
+
+/// The scan-wide hit rate, shared by all probe tasks.
+#[derive(Debug)]
+pub struct HitRateWatch {
+    /// Current rate (percent) that counts as a spike; 0 = off.
+    threshold: f64,
+    /// Hold new probes back after a spike (`--pause-on-spike`).
+    pause_on_spike: bool,
+    rates: Mutex<Rates>,
+}
+
+impl HitRateWatch {
+    /// A watch alerting at `threshold` percent (0 = never), pausing with `pause_on_spike`.
+    pub fn new(threshold: u8, pause_on_spike: bool) -> HitRateWatch {
+        HitRateWatch {
+            threshold: f64::from(threshold),
+            pause_on_spike,
+            rates: Mutex::new(Rates::default()),
+        }
+    }
+
+    /// Count one probe. Returns the spike it started, if any (printed by the caller with
+    /// `print_spike`), and whether the scan is in a spike now.
+    pub fn observe(&self, hit: bool) -> (Option<Spike>, bool) {
+        if self.threshold <= 0.0 {
+            return (None, false);
+        }
+        let mut rates = match self.rates.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        rates.window.push_back(hit);
+        if hit {
+            rates.window_hits += 1;
+        }
+        if rates.window.len() > WINDOW
+            && let Some(oldest) = rates.window.pop_front()
+        {
+            rates.usual_total += 1;
+            if oldest {
+                rates.window_hits -= 1;
+                rates.usual_hits += 1;
+            }
+        }
+        if rates.usual_total < WARMUP {
+            return (None, false);
+        }
+
+        let current = rates.window_hits as f64 * 100.0 / rates.window.len() as f64;
+        let usual = rates.usual_hits as f64 * 100.0 / rates.usual_total as f64;
+        if rates.spiking {
+            if current < self.threshold / 2.0 {
+                rates.spiking = false;
+            }
+            return (None, rates.spiking);
+        }
+        if current >= self.threshold && current >= usual * SPIKE_FACTOR {
+            let spike = Spike { current, usual };
+            rates.spiking = true;
+            if self.pause_on_spike {
+                rates.pause = Some(spike);
+            }
+            return (Some(spike), true);
+        }
+        (None, false)
+    }
+
+    /// The spike the scheduler should pause for (`--pause-on-spike`), once.
+    pub fn take_pause(&self) -> Option<Spike> {
+        let mut rates = match self.rates.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        rates.pause.take()
+    }
+}
+
+/// Print the warning for a spike that just started.
+pub fn print_spike(spike: &Spike) {
+    let line = format!(
+        "[!!] hit rate spike: {:.0}% of the last {} responses are findings (was {:.0}%): captive portal, expired session or block page? Findings from here on are suspect.",
+        spike.current, WINDOW, spike.usual
+    );
+    eprintln!("{}", output::paint(&line, Style::Alert));
+}
+
+/// `--pause-on-spike`: ask on the terminal whether to go on after `spike`.
+///
+/// Errors:
+///   - `DirustError::HitRateSpike` when the answer is no, or there is no terminal to ask on
+///     (`stdin_is_wordlist`: stdin carries the words, not answers).
+pub fn confirm_after_spike(spike: Spike, stdin_is_wordlist: bool) -> Result<(), DirustError> {
+    if !std::io::stdin().is_terminal() || stdin_is_wordlist {
+        return Err(DirustError::HitRateSpike(spike.current, spike.usual));
+    }
+    eprint!("[?] scan paused after the hit rate spike. Continue? [y/N] ");
+    let _ = std::io::stderr().flush();
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    match answer.trim().to_ascii_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => Err(DirustError::HitRateSpike(spike.current, spike.usual)),
+    }
+}
@@ -2,8 +2,11 @@
 //!
 //! Responsibilities of this module:
 //!   1) Send a single HTTP request to a target URL (HEAD by default).
-//!   2) Fall back to GET when HEAD is not allowed (405 Method Not Allowed).
+//!   2) Fall back to GET when HEAD is not allowed (405 Method Not Allowed), in `ProbeEngine`,
+//!      for whichever `ProbeClient` (see `transport.rs`) sends the requests.
 //!   3) Extract just the fields the scanner prints: status, Content-Length, Location.
+//!   4) Read and decode bodies for body-based features, measuring both transferred and
+//!      decompressed sizes, and turn them into text with their charset (`charset.rs`).
 //!
 //! Design choices (important for understanding):
 //!   - We do NOT follow redirects automatically. Seeing 30x + Location is useful during discovery.
@@ -12,8 +15,31 @@
 //!   - We keep error handling explicit and convert external errors into `DirustError`.
 //!   - We only include header values that are valid UTF-8; otherwise we treat them as missing.
 
-use crate::error::DirustError;
-use reqwest::{header, Client, Response, StatusCode};
+use super::{
+    charset,
+    fast::FastClient,
+    middleware::{AuthRefresh, Chain, MethodFallback, Middleware, ProbeRequest, RateLimit, SharedBudget, SlowRetry},
+    shape::RequestShape,
+    transcript::{Exchange, Received, Transcript},
+    transport::{FetchFuture, FetchedBody, MeasureFuture, ProbeClient, ProbeFuture},
+};
+use crate::{
+    args::{EngineKind, ScanArgs},
+    client,
+    error::DirustError,
+    headerdiff,
+    schema::{HeaderAudit, ResultEntry},
+};
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use reqwest::{header, Client, RequestBuilder, Response, StatusCode};
+use std::{
+    collections::BTreeMap,
+    io::Read,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+use tokio::sync::Semaphore;
 
 /// A minimal summary of an HTTP response that the scanner knows how to print.
 ///
@@ -21,13 +47,52 @@ use reqwest::{header, Client, Response, StatusCode};
 /// - `status`:           The HTTP status code (e.g., 200, 301, 403).
 /// - `content_length`:   `Some("<number>")` if the `Content-Length` header exists and is valid UTF-8; otherwise `None`.
 /// - `location`:         `Some("<url>")` if the `Location` header exists and is valid UTF-8; otherwise `None`.
+/// - `etag`:             `Some("<tag>")` if the `ETag` header exists and is valid UTF-8; otherwise `None`.
+/// - `last_modified`:    `Some("<http-date>")` if the `Last-Modified` header exists and is valid UTF-8; otherwise `None`.
+/// - `content_type`:     `Some("<mime>")` if the `Content-Type` header exists and is valid UTF-8; otherwise `None`.
+/// - `www_authenticate`: `Some("<challenge>")` if the `WWW-Authenticate` header exists and is valid UTF-8; otherwise `None`.
+/// - `security`:         which security headers were present (stored with `--header-audit`).
+/// - `set_cookies`:      every `Set-Cookie` value that is valid UTF-8.
+/// - `tracked_headers`:  the software and hardening headers compared between runs (see `headerdiff.rs`).
+/// - `remote_addr`:      the address that answered (its family is shown with `-v`), if known.
+/// - `length_capped`:    `true` when `content_length` was measured with `--measure-chunked` and the
+///   body was larger than the cap (the value is then a lower bound).
+/// - `slow`:             `true` when the response only arrived on the `--slow-retry-timeout` retry.
 ///
-/// Note: We intentionally keep this struct small—just enough for meaningful CLI output.
+/// Note: We intentionally keep this struct small—just enough for meaningful CLI output
+/// plus the cache validators needed by `--incremental`.
 #[derive(Debug)]
 pub struct HttpSummary {
     pub status: StatusCode,
     pub content_length: Option<String>,
     pub location: Option<String>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub content_type: Option<String>,
+    pub www_authenticate: Option<String>,
+    pub security: HeaderAudit,
+    pub set_cookies: Vec<String>,
+    pub tracked_headers: BTreeMap<String, String>,
+    pub remote_addr: Option<SocketAddr>,
+    pub length_capped: bool,
+    pub slow: bool,
+    /// Time the transport took to answer, over every attempt (set by `Chain::run`; waits for
+    /// a `--rate` slot or a budget permit are not included).
+    pub elapsed: Duration,
+}
+
+/// Read one header as an owned UTF-8 string.
+///
+/// Returns `None` if the header is absent or its value is not valid UTF-8
+/// (we prefer "missing" over printing garbage).
+fn header_string(headers: &header::HeaderMap, name: header::HeaderName) -> Option<String> {
+    match headers.get(name) {
+        Some(v) => match v.to_str() {
+            Ok(s) => Some(s.to_string()),
+            Err(_) => None, // Non-UTF8 header → treat as absent
+        },
+        None => None, // Header not present
+    }
 }
 
 /// Convert a full `reqwest::Response` into our compact `HttpSummary`.
@@ -36,86 +101,450 @@ pub struct HttpSummary {
 ///   - Status code
 ///   - `Content-Length` header (if present + valid UTF-8)
 ///   - `Location` header (if present + valid UTF-8)
+///   - `ETag` / `Last-Modified` validators (if present + valid UTF-8)
+///   - `Content-Type` (if present + valid UTF-8)
+///   - `WWW-Authenticate` (if present + valid UTF-8)
+///   - Presence of HSTS / X-Frame-Options / CSP and the `Cache-Control` value
+///   - `Set-Cookie` values (for the finding's cookie record and `--keep-cookies`)
+///   - `Server`, `X-Powered-By`, the security headers, ... (`headerdiff::capture`)
 ///
 /// What we ignore (on purpose):
 ///   - The response body (to keep scans fast)
 ///   - Other headers (not needed for basic directory busting)
-fn summarize_response(resp: Response) -> HttpSummary {
-    // Attempt to read Content-Length from headers.
-    // If the header value is not valid UTF-8, we ignore it to avoid printing garbage.
-    let len_opt: Option<String> = match resp.headers().get(header::CONTENT_LENGTH) {
-        Some(v) => match v.to_str() {
-            Ok(s) => Some(s.to_string()),
-            Err(_) => None, // Non-UTF8 header → treat as absent
-        },
-        None => None, // Header not present
-    };
+fn summarize_response(resp: &Response) -> HttpSummary {
+    let mut summary = summarize_headers(resp.status(), resp.headers());
+    summary.remote_addr = resp.remote_addr();
+    summary
+}
 
-    // Attempt to read Location from headers.
-    // This is typically present on 30x responses and is useful to show redirect targets.
-    let loc_opt: Option<String> = match resp.headers().get(header::LOCATION) {
-        Some(v) => match v.to_str() {
-            Ok(s) => Some(s.to_string()),
-            Err(_) => None, // Non-UTF8 header → treat as absent
-        },
-        None => None, // No Location header
-    };
+/// The `HttpSummary` of a response with `status` and `headers`, as every `ProbeClient`
+/// builds it (see `summarize_response` for the fields kept); `remote_addr` is left to the
+/// caller.
+pub fn summarize_headers(status: StatusCode, headers: &header::HeaderMap) -> HttpSummary {
+    // Content-Length is printed as-is; Location is typically present on 30x responses
+    // and is useful to show redirect targets.
+    let len_opt: Option<String> = header_string(headers, header::CONTENT_LENGTH);
+    let loc_opt: Option<String> = header_string(headers, header::LOCATION);
+
+    // Validators are stored in the results database for conditional requests on later runs.
+    let etag_opt: Option<String> = header_string(headers, header::ETAG);
+    let modified_opt: Option<String> = header_string(headers, header::LAST_MODIFIED);
+
+    // Content-Type tells body-based features (e.g., `--forms`) whether parsing is worthwhile.
+    let type_opt: Option<String> = header_string(headers, header::CONTENT_TYPE);
+
+    // An auth challenge marks the finding as an entry point (`[AUTH]`).
+    let auth_opt: Option<String> = header_string(headers, header::WWW_AUTHENTICATE);
 
     HttpSummary {
-        status: resp.status(),
+        status,
         content_length: len_opt,
         location: loc_opt,
+        etag: etag_opt,
+        last_modified: modified_opt,
+        content_type: type_opt,
+        www_authenticate: auth_opt,
+        security: audit_headers(headers),
+        set_cookies: set_cookies(headers),
+        tracked_headers: headerdiff::capture(headers),
+        remote_addr: None,
+        length_capped: false,
+        slow: false,
+        elapsed: Duration::ZERO,
+    }
+}
+
+/// All `Set-Cookie` values of a response (non-UTF-8 ones are skipped).
+pub fn set_cookies(headers: &header::HeaderMap) -> Vec<String> {
+    headers
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Record the security headers of a response (`--header-audit`). HSTS counts as present or
+/// absent here; the caller drops it for http:// URLs.
+pub fn audit_headers(headers: &header::HeaderMap) -> HeaderAudit {
+    HeaderAudit {
+        hsts: Some(headers.contains_key(header::STRICT_TRANSPORT_SECURITY)),
+        x_frame_options: headers.contains_key(header::X_FRAME_OPTIONS),
+        csp: headers.contains_key(header::CONTENT_SECURITY_POLICY),
+        cache_control: headers
+            .get(header::CACHE_CONTROL)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string()),
     }
 }
 
-/// Send one HTTP request and return a summarized response.
+/// Build a request with the chosen method and `headers`, the headers the middleware chain gave
+/// this request (see `shape.rs`), including conditional headers when we have validators:
+///
+/// - `If-None-Match` carries the stored `ETag`.
+/// - `If-Modified-Since` carries the stored `Last-Modified` date.
+///
+/// A server that still has the same content answers `304 Not Modified`.
+fn build_request(
+    client: &Client,
+    headers: header::HeaderMap,
+    url: &str,
+    use_get: bool,
+    timeout: Option<Duration>,
+) -> RequestBuilder {
+    let mut request = if use_get { client.get(url) } else { client.head(url) };
+    request = request.headers(headers);
+
+    // A per-request timeout replaces the client's `--timeout` (see `--slow-retry-timeout`).
+    if let Some(limit) = timeout {
+        request = request.timeout(limit);
+    }
+
+    request
+}
+
+/// Send one request of a probe and reduce the response to the key printable fields,
+/// recording the exchange in `transcript` (if any).
 ///
 /// Parameters:
 /// - `client`:  A pre-built `reqwest::Client` (shared across tasks to reuse connections).
-/// - `url`:     The absolute URL to probe.
-/// - `use_get`: If `true`, send a GET immediately. If `false`, try HEAD first for speed.
+/// - `request`: URL, method (HEAD unless `use_get`), headers and timeout, as the middleware
+///   chain left them.
+/// - `transcript`: Records every request sent and its response (`--transcript`).
 ///
-/// Behavior:
-/// - Default (HEAD first): We prefer HEAD because it typically avoids downloading bodies.
-/// - Fallback: If the server returns `405 Method Not Allowed` to HEAD, we retry the same URL with GET.
-/// - We do not follow redirects; we want to *see* them (status + Location).
-///
-/// Returns:
-/// - `Ok(HttpSummary)` on success, containing status/headers of interest.
-/// - `Err(DirustError)` on network/protocol errors (DNS, TLS, socket, etc.).
-pub async fn probe(client: &Client, url: &str, use_get: bool) -> Result<HttpSummary, DirustError> {
-    // Decide the initial method:
-    // - GET if the caller asked for it (some servers misbehave on HEAD).
-    // - Otherwise HEAD, which is faster and avoids body downloads where supported.
-    let mut response_result = if use_get {
-        client.get(url).send().await
-    } else {
-        client.head(url).send().await
+/// We do not follow redirects; we want to *see* them (status + Location).
+async fn send(client: &Client, request: &ProbeRequest, transcript: Option<&Transcript>) -> Result<HttpSummary, DirustError> {
+    let (url, use_get, timeout) = (request.url.as_str(), request.use_get, request.timeout);
+    let headers = request.headers.clone();
+    let transcript = match transcript {
+        Some(t) => t,
+        None => {
+            let response = build_request(client, headers, url, use_get, timeout).send().await?;
+            return Ok(summarize_response(&response));
+        }
     };
 
-    // If the first request succeeded but came back with 405 (Method Not Allowed),
-    // and we *did not* force GET, then retry with GET to be robust.
-    match &response_result {
-        Ok(resp) => {
-            if resp.status() == StatusCode::METHOD_NOT_ALLOWED && use_get == false {
-                // A number of servers or frameworks may not implement HEAD properly.
-                // Doing a second attempt with GET makes the tool more compatible.
-                response_result = client.get(url).send().await;
+    let sent_at = SystemTime::now();
+    let started = Instant::now();
+    let result = build_request(client, headers.clone(), url, use_get, timeout).send().await;
+    let method = request.method();
+    match result {
+        Ok(mut response) => {
+            let summary = summarize_response(&response);
+            // The start of the body, when the transcript keeps bodies.
+            let mut body: Vec<u8> = Vec::new();
+            let mut body_truncated = false;
+            while use_get && transcript.body_limit() > 0 {
+                match response.chunk().await {
+                    Ok(Some(chunk)) => {
+                        let room = transcript.body_limit() - body.len();
+                        if chunk.len() > room {
+                            body.extend_from_slice(&chunk[..room]);
+                            body_truncated = true;
+                            break;
+                        }
+                        body.extend_from_slice(&chunk);
+                    }
+                    Ok(None) | Err(_) => break,
+                }
             }
+            transcript.record(Exchange {
+                method,
+                url,
+                request_headers: &headers,
+                sent_at,
+                elapsed: started.elapsed(),
+                outcome: Ok(Received {
+                    status: summary.status,
+                    headers: response.headers(),
+                    body: &body,
+                    body_truncated,
+                }),
+            })?;
+            Ok(summary)
         }
-        Err(_) => {
-            // If the request failed (DNS/TLS/timeout/connection), we do nothing here;
-            // the error is handled below when we unwrap `response_result`.
+        Err(e) => {
+            let error = DirustError::from(e);
+            transcript.record(Exchange {
+                method,
+                url,
+                request_headers: &headers,
+                sent_at,
+                elapsed: started.elapsed(),
+                outcome: Err(&error),
+            })?;
+            Err(error)
         }
     }
+}
+
+/// The default probe client (reqwest): the same client every other mode uses (a Title-Case
+/// twin of it with `--evade`), recording to the `--transcript` (if any).
+pub struct ReqwestProbe {
+    client: Client,
+    transcript: Option<Arc<Transcript>>,
+}
+
+impl ReqwestProbe {
+    pub fn new(client: Client, transcript: Option<Arc<Transcript>>) -> ReqwestProbe {
+        ReqwestProbe { client, transcript }
+    }
+}
+
+impl ProbeClient for ReqwestProbe {
+    fn send<'a>(&'a self, request: &'a ProbeRequest) -> ProbeFuture<'a> {
+        Box::pin(send(&self.client, request, self.transcript.as_deref()))
+    }
+
+    fn fetch<'a>(&'a self, url: &'a str) -> FetchFuture<'a> {
+        Box::pin(fetch(&self.client, url))
+    }
+
+    fn measure<'a>(&'a self, url: &'a str, cap: u64) -> MeasureFuture<'a> {
+        Box::pin(measure_body(&self.client, url, cap))
+    }
+}
+
+/// The client that sends the requests of a scan (`--engine`, or a custom `ProbeClient`, see
+/// `transport.rs`), and the middleware chain every probe goes through (see `middleware.rs`).
+/// Cheap to clone: clones share the client and its connection pool.
+#[derive(Clone)]
+pub struct ProbeEngine {
+    client: Arc<dyn ProbeClient>,
+    chain: Chain,
+}
 
-    // Turn the `Result<Response, reqwest::Error>` into either a `Response` or our error type.
-    let response = match response_result {
-        Ok(r) => r,
-        Err(e) => return Err(DirustError::from(e)),
+impl ProbeEngine {
+    /// Pick the engine requested by `--engine`; `client` is the already-built reqwest client.
+    /// Every request the engine sends is recorded in `transcript` (if any).
+    pub fn for_scan(
+        client: &Client,
+        args: &ScanArgs,
+        transcript: Option<Arc<Transcript>>,
+        chain: Chain,
+    ) -> Result<ProbeEngine, DirustError> {
+        let probe_client: Arc<dyn ProbeClient> = match args.engine {
+            EngineKind::Reqwest if args.evade => {
+                Arc::new(ReqwestProbe::new(client::build_title_case_client(&args.http)?, transcript))
+            }
+            EngineKind::Reqwest => Arc::new(ReqwestProbe::new(client.clone(), transcript)),
+            EngineKind::Fast if args.http.proxy.is_some() => {
+                return Err(DirustError::InvalidProxy(
+                    "--engine fast connects directly; use the default engine with --proxy".to_string(),
+                ));
+            }
+            EngineKind::Fast => {
+                if let Some(name) = client::env_proxy_in_use(&args.http) {
+                    return Err(DirustError::InvalidProxy(format!(
+                        "--engine fast connects directly, but {} is set; use the default engine, or --no-env-proxy",
+                        name
+                    )));
+                }
+                Arc::new(FastClient::new(&args.http, client.clone(), args.evade, transcript, args.pipeline as usize)?)
+            }
+        };
+        Ok(ProbeEngine::new(probe_client, chain))
+    }
+
+    /// An engine sending through `client` (e.g., a `transport::FakeClient`).
+    pub fn new(client: Arc<dyn ProbeClient>, chain: Chain) -> ProbeEngine {
+        ProbeEngine { client, chain }
+    }
+
+    /// The built-in middleware of a scan with `args` (see `middleware.rs` for the order),
+    /// holding permits of `budget` (if any), followed by `extra`. `rate_limit` replaces the
+    /// scan's own `--rate` limiter when several scans share one (bases on the same host).
+    pub fn chain_for_scan(
+        args: &ScanArgs,
+        budget: Option<Arc<Semaphore>>,
+        rate_limit: Option<Arc<RateLimit>>,
+        extra: &[Arc<dyn Middleware>],
+    ) -> Chain {
+        let mut chain = Chain::new().with_max_attempts(args.max_attempts);
+        match (rate_limit, args.rate) {
+            (Some(shared), _) => chain = chain.with(shared),
+            (None, Some(rate)) => chain = chain.with(Arc::new(RateLimit::new(rate))),
+            (None, None) => {}
+        }
+        if let Some(budget) = budget {
+            chain = chain.with(Arc::new(SharedBudget(budget)));
+        }
+        chain = chain
+            .with(Arc::new(RequestShape::new(&args.http, args.evade, args.keep_cookies)))
+            .with(Arc::new(MethodFallback));
+        if let Some(command) = &args.auth_cmd {
+            chain = chain.with(Arc::new(AuthRefresh::command(command)));
+        }
+        if let Some(timeout) = args.slow_retry_timeout {
+            chain = chain.with(Arc::new(SlowRetry(timeout)));
+        }
+        for layer in extra {
+            chain = chain.with(Arc::clone(layer));
+        }
+        chain
+    }
+
+    /// Probe `url` through the middleware chain: HEAD by default (GET with `use_get`),
+    /// conditional headers from `previous`, GET again on 405, redirects not followed.
+    pub async fn probe(
+        &self,
+        url: &str,
+        use_get: bool,
+        previous: Option<&ResultEntry>,
+    ) -> Result<HttpSummary, DirustError> {
+        self.chain.run(self.client.as_ref(), ProbeRequest::new(url, use_get, previous)).await
+    }
+
+    /// Send exactly one request, with the headers the chain gives it but no retry (e.g., to
+    /// find out whether HEAD is allowed at all).
+    pub async fn send_once(&self, url: &str, use_get: bool) -> Result<HttpSummary, DirustError> {
+        let mut request = ProbeRequest::new(url, use_get, None);
+        self.chain.prepare(&mut request).await?;
+        self.client.send(&request).await
+    }
+
+    /// Download `url`'s whole body with a GET (see `transport::FetchedBody`).
+    pub async fn fetch(&self, url: &str) -> Result<FetchedBody, DirustError> {
+        self.client.fetch(url).await
+    }
+
+    /// Size of `url`'s body (see `measure_body`).
+    pub async fn measure(&self, url: &str, cap: u64) -> Result<(u64, bool), DirustError> {
+        self.client.measure(url, cap).await
+    }
+
+    /// Open up to `connections` keep-alive connections to `url`'s host before scanning
+    /// (`--prewarm`), by sending that many HEAD requests at once. Each request needs its own
+    /// HTTP/1.1 connection; when they finish, the connections wait in the pool for the scan.
+    ///
+    /// Returns how many requests succeeded (i.e., connections that are now open).
+    /// Note: over HTTP/2 (reqwest engine, TLS with h2) all requests share one connection.
+    pub async fn prewarm(&self, url: &str, connections: usize) -> usize {
+        let mut handles: Vec<tokio::task::JoinHandle<bool>> = Vec::with_capacity(connections);
+        for _ in 0..connections {
+            let engine = self.clone();
+            let target = url.to_string();
+            handles.push(tokio::spawn(async move {
+                engine.probe(&target, false, None).await.is_ok()
+            }));
+        }
+
+        let mut opened: usize = 0;
+        for handle in handles {
+            if let Ok(true) = handle.await {
+                opened += 1;
+            }
+        }
+        opened
+    }
+}
+
+/// Download `url`'s whole body with a plain GET and decode it (see `read_body`).
+pub async fn fetch(client: &Client, url: &str) -> Result<FetchedBody, DirustError> {
+    let response = client.get(url).send().await?;
+    let status = response.status();
+    let content_type = header_string(response.headers(), header::CONTENT_TYPE);
+    let (body, size) = read_body(response).await?;
+    Ok(FetchedBody {
+        status,
+        content_type,
+        body,
+        size,
+    })
+}
+
+/// Measure the size of `url`'s body with a GET, reading at most `cap` bytes.
+///
+/// Used for responses without `Content-Length` (chunked transfer, or a connection-close
+/// body), so they still get a size. The size is counted on the wire, like Content-Length
+/// would be (no decompression). Reading stops early once `cap` is reached.
+///
+/// Returns:
+///   - Ok((size, false)) when the whole body was read
+///   - Ok((cap, true))   when the body was larger than `cap`
+pub async fn measure_body(client: &Client, url: &str, cap: u64) -> Result<(u64, bool), DirustError> {
+    let mut response = client.get(url).send().await?;
+    let mut total: u64 = 0;
+
+    while let Some(chunk) = response.chunk().await? {
+        total += chunk.len() as u64;
+        if total >= cap {
+            return Ok((cap, true));
+        }
+    }
+
+    Ok((total, false))
+}
+
+/// Sizes of one downloaded body.
+///
+/// - `transferred`: bytes received on the wire (after de-chunking, before decompression)
+/// - `decoded`:     bytes after undoing `Content-Encoding` (equal to `transferred` when
+///   the body was not compressed or could not be decoded)
+///
+/// Size comparisons (e.g., the fuzzing baselines) use `decoded`, so results do not depend
+/// on which encoding the server happened to pick.
+#[derive(Debug, Clone, Copy)]
+pub struct BodySize {
+    pub transferred: u64,
+    pub decoded: u64,
+}
+
+/// Read a full response body and undo its `Content-Encoding` (gzip / deflate).
+///
+/// Unknown encodings (e.g., `br` when we did not ask for it) and corrupt data are kept
+/// as-is: we would rather measure the raw bytes than fail the whole probe.
+pub async fn read_body(response: Response) -> Result<(Vec<u8>, BodySize), DirustError> {
+    let encoding = header_string(response.headers(), header::CONTENT_ENCODING).unwrap_or_default();
+    let raw = response.bytes().await?;
+
+    let body: Vec<u8> = match decode(encoding.trim(), &raw) {
+        Some(decoded) => decoded,
+        None => raw.to_vec(),
+    };
+
+    let size = BodySize {
+        transferred: raw.len() as u64,
+        decoded: body.len() as u64,
+    };
+    Ok((body, size))
+}
+
+/// Decompress `raw` according to a `Content-Encoding` value.
+///
+/// Returns `None` for identity / unknown encodings and for data that fails to decode.
+pub fn decode(encoding: &str, raw: &[u8]) -> Option<Vec<u8>> {
+    let mut out: Vec<u8> = Vec::new();
+    let result = match encoding.to_ascii_lowercase().as_str() {
+        "gzip" | "x-gzip" => GzDecoder::new(raw).read_to_end(&mut out),
+        // "deflate" is zlib-wrapped per the RFC, but some servers send raw deflate.
+        "deflate" => match ZlibDecoder::new(raw).read_to_end(&mut out) {
+            Ok(n) => Ok(n),
+            Err(_) => {
+                out.clear();
+                DeflateDecoder::new(raw).read_to_end(&mut out)
+            }
+        },
+        _ => return None,
     };
 
-    // Reduce the response down to the key printable fields.
-    let summary = summarize_response(response);
-    Ok(summary)
+    result.ok().map(|_| out)
+}
+
+/// Read a full response body as text: `Content-Encoding` undone (see `read_body`), then
+/// decoded with the charset from the headers or the document itself (see `charset.rs`).
+/// Invalid sequences are replaced rather than rejected.
+pub async fn read_text(response: Response) -> Result<String, DirustError> {
+    let content_type = header_string(response.headers(), header::CONTENT_TYPE);
+    let (body, _size) = read_body(response).await?;
+    Ok(charset::decode(content_type.as_deref(), &body))
+}
+
+/// Fetch the full response body of `url` as text with a plain GET (see `read_text`).
+///
+/// Used only by body-based features (e.g., `--forms`) after a probe already found the URL
+/// interesting, so the fast HEAD-first path stays unchanged for everything else.
+pub async fn fetch_body(client: &Client, url: &str) -> Result<String, DirustError> {
+    let response = client.get(url).send().await?;
+    read_text(response).await
 }
@@ -0,0 +1,67 @@
+//! src/scanner/inject.rs
+//!
+//! Purpose:
+//!   `--inject-pipe <PATH>`: add words to a running scan through a named pipe, so a path
+//!   found by hand can be expanded without restarting a multi-hour job:
+//!
+//!     mkfifo /tmp/dirust.in
+//!     dirust scan https://example.com/ -w big.txt --inject-pipe /tmp/dirust.in
+//!     echo backup-2024 >> /tmp/dirust.in          (from another shell, at any time)
+//!
+//! How:
+//!   - Every line written to the pipe is one word (blank lines and `#` comments dropped, as
+//!     in wordlists). Words go into the scan monitor's queue (`ScanMonitor::inject`), the
+//!     same queue `dirust serve` fills from `POST /scans/<id>/words`.
+//!   - The scanner takes queued words before its next wordlist chunk and probes them with
+//!     the current extensions, scope and dedup rules.
+//!   - The pipe is opened read-write, so writers may come and go without ending the reader.
+//!
+//! Notes:
+//!   - The pipe must exist (`mkfifo`); Unix only.
+//!   - Words injected after the last wordlist chunk has been probed are not scanned.
+
+use super::{monitor::ScanMonitor, wordlist};
+use crate::{error::DirustError, output};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+/// Start reading words from the named pipe at `path` into `monitor` (abort the handle when
+/// the scan is over).
+#[cfg(unix)]
+pub fn spawn_pipe_reader(path: &str, monitor: Arc<ScanMonitor>) -> Result<JoinHandle<()>, DirustError> {
+    use tokio::{
+        io::{AsyncBufReadExt, BufReader},
+        net::unix::pipe,
+    };
+
+    let receiver = pipe::OpenOptions::new().read_write(true).open_receiver(path)?;
+    output::info(&format!("[*] inject: reading extra words from {}", path));
+
+    let path = path.to_string();
+    Ok(tokio::spawn(async move {
+        let mut lines = BufReader::new(receiver).lines();
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => {
+                    if let Some(word) = wordlist::entry(&line) {
+                        monitor.inject(vec![word.to_string()]);
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    eprintln!("[!] inject: stopped reading {}: {}", path, e);
+                    break;
+                }
+            }
+        }
+    }))
+}
+
+/// Named pipes are only supported on Unix.
+#[cfg(not(unix))]
+pub fn spawn_pipe_reader(_path: &str, _monitor: Arc<ScanMonitor>) -> Result<JoinHandle<()>, DirustError> {
+    Err(DirustError::Io(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "--inject-pipe needs a Unix named pipe",
+    )))
+}
@@ -0,0 +1,80 @@
+//! src/scanner/latency.rs
+//!
+//! Purpose:
+//!   Flag findings whose response took unusually long compared to the rest of the scan
+//!   (`[SLOW]`). A page that answers in 900ms while everything else answers in 40ms is
+//!   usually doing real work behind the scenes (an auth check, a database query, a
+//!   template render) and deserves a closer look.
+//!
+//! How:
+//!   - Every probe updates a rolling baseline: an exponentially weighted mean and variance
+//!     of the latency, so the baseline follows a target that slows down over the scan
+//!     instead of flagging everything after the first hiccup.
+//!   - A probe is an outlier when it is more than `--latency-sigma` standard deviations
+//!     above the mean *and* at least `MIN_EXCESS` slower than it (on a fast, steady target
+//!     σ is tiny and a 3ms wobble would otherwise count).
+//!   - Nothing is flagged during the first `WARMUP` probes, while the baseline settles.
+
+use std::{sync::Mutex, time::Duration};
+
+/// Weight of the newest sample in the rolling mean/variance (about the last 40 probes).
+const ALPHA: f64 = 0.05;
+
+/// Probes observed before outliers are flagged.
+const WARMUP: u64 = 30;
+
+/// An outlier must also be at least this much slower than the mean (milliseconds).
+const MIN_EXCESS_MS: f64 = 50.0;
+
+/// Rolling mean and variance in milliseconds.
+#[derive(Debug, Default)]
+struct Rolling {
+    count: u64,
+    mean: f64,
+    variance: f64,
+}
+
+/// The scan-wide latency baseline, shared by all probe tasks.
+#[derive(Debug)]
+pub struct LatencyBaseline {
+    /// Standard deviations above the mean that count as an outlier (0 = never flag).
+    sigma: f64,
+    rolling: Mutex<Rolling>,
+}
+
+impl LatencyBaseline {
+    /// A baseline flagging probes more than `sigma` standard deviations above the mean.
+    pub fn new(sigma: f64) -> LatencyBaseline {
+        LatencyBaseline {
+            sigma,
+            rolling: Mutex::new(Rolling::default()),
+        }
+    }
+
+    /// Fold `latency` into the baseline and tell whether it is an outlier (judged against
+    /// the baseline *before* this sample).
+    pub fn observe(&self, latency: Duration) -> bool {
+        let ms = latency.as_secs_f64() * 1000.0;
+        let mut rolling = match self.rolling.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let outlier = self.sigma > 0.0
+            && rolling.count >= WARMUP
+            && ms > rolling.mean + self.sigma * rolling.variance.sqrt()
+            && ms > rolling.mean + MIN_EXCESS_MS;
+
+        if rolling.count == 0 {
+            rolling.mean = ms;
+        } else {
+            let diff = ms - rolling.mean;
+            let increment = ALPHA * diff;
+            rolling.mean += increment;
+            rolling.variance = (1.0 - ALPHA) * (rolling.variance + diff * increment);
+        }
+        rolling.count += 1;
+
+        outlier
+    }
+}
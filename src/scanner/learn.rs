@@ -0,0 +1,79 @@
+//! src/scanner/learn.rs
+//!
+//! Purpose:
+//!   `--learn-exts`: pick up extensions from what the target itself shows. A finding like
+//!   `login.php` (an as-is wordlist entry, a form action, a follow-up URL) says the site runs
+//!   PHP, so the plain-name words after it are also tried with `.php`, even when `--exts`
+//!   was not given.
+//!
+//! How:
+//!   1) Every reported finding (status 2xx, 401 or 403) and every form action is looked at;
+//!      the extension of its last path segment is learned if it is one of `LEARNABLE`
+//!      (page and script extensions, not `.css` / `.png` / ...).
+//!   2) Learned extensions are added to the scan's extension list after the wave that found
+//!      them, and apply to the wordlist chunks after it (words already probed are not
+//!      probed again).
+//!
+//! Notes:
+//!   - At most `MAX_LEARNED` extensions are learned per base URL; each one is printed.
+//!   - Extensions already in `--exts` are not learned again.
+//!   - Wordlist directives (`#!exts`, `#!noext`) still decide for the entries after them.
+//!   - `--adaptive-exts` judges only the `--exts` extensions; learned ones are kept.
+
+use reqwest::Url;
+
+/// Most extensions learned per base URL.
+pub const MAX_LEARNED: usize = 5;
+
+/// Extensions worth trying on every plain-name word (lower-case, without the dot).
+const LEARNABLE: &[&str] = &[
+    "php", "php3", "php4", "php5", "php7", "phtml", "asp", "aspx", "ashx", "asmx", "axd", "jsp", "jspx", "do",
+    "action", "cfm", "cgi", "pl", "py", "rb", "html", "htm", "shtml", "xhtml", "json", "xml", "txt",
+];
+
+/// Extensions learned so far for one base URL.
+pub struct ExtensionLearner {
+    /// Extensions the scan started with (`--exts`), normalized (".php").
+    known: Vec<String>,
+    /// Learned extensions, in the order they were learned.
+    learned: Vec<String>,
+}
+
+impl ExtensionLearner {
+    /// A learner that skips the `known` extensions (as from `ScanArgs::parse_exts`).
+    pub fn new(known: &[String]) -> ExtensionLearner {
+        ExtensionLearner {
+            known: known.to_vec(),
+            learned: Vec::new(),
+        }
+    }
+
+    /// Look at a finding or form action; returns the extension if it was newly learned.
+    pub fn observe(&mut self, url: &str) -> Option<String> {
+        if self.learned.len() >= MAX_LEARNED {
+            return None;
+        }
+        let ext = extension_of(url)?;
+        if self.known.contains(&ext) || self.learned.contains(&ext) {
+            return None;
+        }
+        self.learned.push(ext.clone());
+        Some(ext)
+    }
+
+    /// Every extension learned so far.
+    pub fn learned(&self) -> &[String] {
+        &self.learned
+    }
+}
+
+/// The learnable extension of `url`'s last path segment (".php"), if any.
+fn extension_of(url: &str) -> Option<String> {
+    let parsed = Url::parse(url).ok()?;
+    let name = parsed.path().rsplit('/').next()?.to_ascii_lowercase();
+    let (stem, ext) = name.rsplit_once('.')?;
+    match !stem.is_empty() && LEARNABLE.contains(&ext) {
+        true => Some(format!(".{}", ext)),
+        false => None,
+    }
+}
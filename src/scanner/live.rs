@@ -0,0 +1,140 @@
+//! src/scanner/live.rs
+//!
+//! Purpose:
+//!   The live status line of terminal scans, redrawn on stderr every `TICK` while the
+//!   scan runs, so an operator can judge whether to let it continue:
+//!
+//!     [*] 1520/~4810 (31%)  412 req/s  ETA 8s  +40 discovered  200:3 301:1 302:0 401:2 403:5  in https://example.com/
+//!
+//! It reads the scan's `ScanMonitor` (probes done, the estimate of targets left, findings per
+//! status, the base URL being scanned). The total is `done + remaining`, so targets found on
+//! the way (`--follow-forms`, injected words, deferred extensions) grow it instead of pushing
+//! the percentage past 100; the rate and ETA use the last `RATE_WINDOW` ticks only. When the
+//! wordlist size is unknown (stdin, `url(...)`), it shows probes done vs scheduled, no ETA. Callers only start it when `output::live_line_supported()`
+//! (stderr is a terminal, neither `-q` nor `--silent`) and `--no-progress` is not set.
+//! Result lines erase it before they are printed (see `output.rs`), and it is erased for
+//! good when the scan ends.
+
+use crate::{
+    output,
+    scanner::monitor::{Progress, ScanMonitor, COUNTED_STATUSES},
+};
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::task::JoinHandle;
+
+/// Redraw interval.
+const TICK: Duration = Duration::from_millis(500);
+
+/// Ticks the request rate is measured over (10 s).
+const RATE_WINDOW: usize = 20;
+
+/// Longest base URL shown (longer ones keep their end); keeps the line from wrapping,
+/// which would break erasing it.
+const MAX_DIRECTORY: usize = 48;
+
+/// A running status line; `stop` it when the scan is over.
+pub struct LiveLine {
+    ticker: JoinHandle<()>,
+}
+
+impl LiveLine {
+    /// Start redrawing the status line for `monitor`.
+    pub fn start(monitor: Arc<ScanMonitor>) -> LiveLine {
+        let ticker = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(TICK);
+            // (when, probes done) of the last ticks, oldest first.
+            let mut samples: VecDeque<(Instant, usize)> = VecDeque::with_capacity(RATE_WINDOW + 1);
+            loop {
+                interval.tick().await;
+                let progress = monitor.progress();
+                samples.push_back((Instant::now(), progress.done));
+                if samples.len() > RATE_WINDOW {
+                    samples.pop_front();
+                }
+                output::draw_live_line(&status_line(&progress, rate(&samples)));
+            }
+        });
+        LiveLine { ticker }
+    }
+
+    /// Stop redrawing and erase the line.
+    pub async fn stop(self) {
+        self.ticker.abort();
+        // Wait until a redraw in progress (if any) is done, so it cannot follow the erase.
+        let _ = self.ticker.await;
+        output::clear_live_line();
+    }
+}
+
+/// Probes per second between the oldest and newest of `samples`.
+fn rate(samples: &VecDeque<(Instant, usize)>) -> f64 {
+    let ((first_at, first_done), (last_at, last_done)) = match (samples.front(), samples.back()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return 0.0,
+    };
+    let secs = last_at.duration_since(*first_at).as_secs_f64();
+    if secs > 0.0 { last_done.saturating_sub(*first_done) as f64 / secs } else { 0.0 }
+}
+
+/// `4m10s`, `1h05m`, `8s`.
+fn format_eta(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Format the status line for `progress` at `rate` probes per second.
+fn status_line(progress: &Progress, rate: f64) -> String {
+    // With an estimate, the total is what is done plus what is left; the percentage stays
+    // below 100 until nothing is left.
+    let (total, estimated) = match progress.remaining {
+        Some(remaining) => (progress.done + remaining, true),
+        None => (progress.total, false),
+    };
+    let mut percent = (progress.done * 100).checked_div(total).unwrap_or(0);
+    if progress.done < total {
+        percent = percent.min(99);
+    }
+
+    let counts: Vec<String> = COUNTED_STATUSES
+        .iter()
+        .zip(progress.by_status.iter())
+        .map(|(status, count)| format!("{}:{}", status, count))
+        .collect();
+
+    let mut line = format!(
+        "[*] {}/{}{} ({}%)  {:.0} req/s",
+        progress.done,
+        if estimated { "~" } else { "" },
+        total,
+        percent,
+        rate
+    );
+    if let Some(remaining) = progress.remaining
+        && remaining > 0
+        && rate >= 1.0
+    {
+        line.push_str(&format!("  ETA {}", format_eta((remaining as f64 / rate).ceil() as u64)));
+    }
+    if progress.discovered > 0 {
+        line.push_str(&format!("  +{} discovered", progress.discovered));
+    }
+    line.push_str(&format!("  {}", counts.join(" ")));
+    if let Some(directory) = &progress.directory {
+        let chars: Vec<char> = directory.chars().collect();
+        let shown: String = if chars.len() > MAX_DIRECTORY {
+            let tail: String = chars[chars.len() - (MAX_DIRECTORY - 1)..].iter().collect();
+            format!("…{}", tail)
+        } else {
+            directory.clone()
+        };
+        line.push_str(&format!("  in {}", shown));
+    }
+    line
+}
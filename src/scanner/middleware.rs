@@ -0,0 +1,416 @@
+//! src/scanner/middleware.rs
+//!
+//! Purpose:
+//!   Everything that happens around a probe request — shaping its headers, holding a share
+//!   of a concurrency budget, retrying it — is a layer of one `Chain`, instead of being
+//!   spread over the engines and the scan loop. The code starting a scan can add layers of
+//!   its own through `ScanHooks::middleware` (interleaved bases add their shared `-c`
+//!   budget that way), also from programs using the library (`src/lib.rs` re-exports the
+//!   types a layer needs).
+//!
+//! How a probe goes through the chain:
+//!   1) `before` of every layer, in order: layers edit the `ProbeRequest` (headers, method,
+//!      timeout) or stop it with an error.
+//!   2) The request is sent by the `ProbeClient` (see `transport.rs`).
+//!   3) `after` of every layer, in reverse order (the last layer added sees the response
+//!      first): layers may edit the result and ask for another attempt (`Next::Retry`), after
+//!      changing the request if they want it sent differently.
+//!   4) With a retry, the whole chain runs again (`attempt` + 1), at most `--max-attempts`
+//!      times in all (`MAX_ATTEMPTS` by default).
+//!
+//! Built-in layers, in chain order (only those the flags ask for):
+//!   - `RateLimit`         : `--rate`, requests spaced evenly, retries included
+//!   - `SharedBudget`      : `dirust run` concurrency budget, one permit per request in flight
+//!   - `shape::RequestShape`: headers (User-Agent, conditional headers, `--evade`
+//!     randomization) and the `--keep-cookies` jar
+//!   - `MethodFallback`    : HEAD answered with 405 → the same request as GET
+//!   - `AuthRefresh`       : `--auth-cmd`, an `Authorization` value renewed on a 401
+//!   - `SlowRetry`         : a timeout → one retry with `--slow-retry-timeout` (tagged `[slow-retry]`)
+//!
+//! Layers added by callers come after these. Calibration requests (`ProbeEngine::send_once`)
+//! run the `before` hooks only.
+
+use super::{http::HttpSummary, transport::ProbeClient};
+use crate::{error::DirustError, schema::ResultEntry};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION},
+    StatusCode,
+};
+use std::{
+    future::Future,
+    pin::Pin,
+    process::Stdio,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    process::Command,
+    sync::{OwnedSemaphorePermit, Semaphore},
+    time::Instant,
+};
+
+/// Attempts of one probe, retries included (default of `--max-attempts`).
+pub const MAX_ATTEMPTS: u32 = 4;
+
+/// What the hooks of a layer return.
+pub type LayerFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// One probe request on its way through the chain.
+pub struct ProbeRequest {
+    pub url: String,
+    /// GET instead of HEAD.
+    pub use_get: bool,
+    /// Sent as they are, in map order (filled by `RequestShape`).
+    pub headers: HeaderMap,
+    /// Replaces `--timeout` when set.
+    pub timeout: Option<Duration>,
+    /// Stored results-database entry (`--incremental`), for conditional headers.
+    pub previous: Option<ResultEntry>,
+    /// 0 for the first attempt, counting up with every retry.
+    pub attempt: u32,
+    /// Permits held while this attempt is in flight (see `hold`).
+    held: Vec<OwnedSemaphorePermit>,
+}
+
+impl ProbeRequest {
+    /// A HEAD (GET with `use_get`) request for `url`, without headers yet.
+    pub fn new(url: &str, use_get: bool, previous: Option<&ResultEntry>) -> ProbeRequest {
+        ProbeRequest {
+            url: url.to_string(),
+            use_get,
+            headers: HeaderMap::new(),
+            timeout: None,
+            previous: previous.cloned(),
+            attempt: 0,
+            held: Vec::new(),
+        }
+    }
+
+    /// "GET" or "HEAD".
+    pub fn method(&self) -> &'static str {
+        if self.use_get { "GET" } else { "HEAD" }
+    }
+
+    /// Keep `permit` until this attempt's response has been through every `after` hook.
+    pub fn hold(&mut self, permit: OwnedSemaphorePermit) {
+        self.held.push(permit);
+    }
+}
+
+/// What a layer wants after seeing a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Next {
+    /// Hand the result on.
+    Continue,
+    /// Send the request again (a retry is made if any layer asks for one).
+    Retry,
+}
+
+/// A layer of the chain. Both hooks default to doing nothing.
+pub trait Middleware: Send + Sync {
+    /// Called before every attempt; an error ends the probe with that error.
+    fn before<'a>(&'a self, _request: &'a mut ProbeRequest) -> LayerFuture<'a, Result<(), DirustError>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Called with every attempt's result.
+    fn after<'a>(
+        &'a self,
+        _request: &'a mut ProbeRequest,
+        _result: &'a mut Result<HttpSummary, DirustError>,
+    ) -> LayerFuture<'a, Next> {
+        Box::pin(async { Next::Continue })
+    }
+}
+
+/// The layers every probe of a scan goes through (see the module docs). Cheap to clone.
+#[derive(Clone)]
+pub struct Chain {
+    layers: Vec<Arc<dyn Middleware>>,
+    /// Attempts per probe, retries included (at least 1).
+    max_attempts: u32,
+}
+
+impl Default for Chain {
+    fn default() -> Chain {
+        Chain::new()
+    }
+}
+
+impl Chain {
+    /// An empty chain (requests are sent without any header), `MAX_ATTEMPTS` attempts.
+    pub fn new() -> Chain {
+        Chain {
+            layers: Vec::new(),
+            max_attempts: MAX_ATTEMPTS,
+        }
+    }
+
+    /// The chain with at most `attempts` attempts per probe (1: no retries).
+    pub fn with_max_attempts(mut self, attempts: u32) -> Chain {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// The chain with `layer` added at the end.
+    pub fn with(mut self, layer: Arc<dyn Middleware>) -> Chain {
+        self.layers.push(layer);
+        self
+    }
+
+    /// Send `request` through `client` and every layer (see the module docs). The summary's
+    /// `elapsed` is the time spent in `client.send`, summed over the attempts.
+    pub async fn run(&self, client: &dyn ProbeClient, mut request: ProbeRequest) -> Result<HttpSummary, DirustError> {
+        let mut elapsed = Duration::ZERO;
+        loop {
+            self.prepare(&mut request).await?;
+            let sent = Instant::now();
+            let mut result = client.send(&request).await;
+            elapsed += sent.elapsed();
+            if let Ok(summary) = &mut result {
+                summary.elapsed = elapsed;
+            }
+
+            let mut retry = false;
+            for layer in self.layers.iter().rev() {
+                if layer.after(&mut request, &mut result).await == Next::Retry {
+                    retry = true;
+                }
+            }
+            request.held.clear();
+            if !retry || request.attempt + 1 >= self.max_attempts {
+                return result;
+            }
+            request.attempt += 1;
+        }
+    }
+
+    /// Run the `before` hooks only (the request is then sent once, as is).
+    pub async fn prepare(&self, request: &mut ProbeRequest) -> Result<(), DirustError> {
+        for layer in &self.layers {
+            layer.before(request).await?;
+        }
+        Ok(())
+    }
+}
+
+/// `--rate`: requests leave at most `per_second` times a second, evenly spaced (a request
+/// waits for its slot before it is sent).
+pub struct RateLimit {
+    interval: Duration,
+    /// When the next request may leave.
+    next: Mutex<Instant>,
+}
+
+impl RateLimit {
+    pub fn new(per_second: u32) -> RateLimit {
+        RateLimit {
+            interval: Duration::from_secs_f64(1.0 / f64::from(per_second.max(1))),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl Middleware for RateLimit {
+    fn before<'a>(&'a self, _request: &'a mut ProbeRequest) -> LayerFuture<'a, Result<(), DirustError>> {
+        // Take the next slot now; wait for it outside the lock.
+        let slot = {
+            let mut next = match self.next.lock() {
+                Ok(g) => g,
+                Err(p) => p.into_inner(),
+            };
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        Box::pin(async move {
+            tokio::time::sleep_until(slot).await;
+            Ok(())
+        })
+    }
+}
+
+/// Throttling across scans: every request holds a permit of a semaphore shared with other
+/// scans (`dirust run` with `concurrency`) while it is in flight.
+pub struct SharedBudget(pub Arc<Semaphore>);
+
+impl Middleware for SharedBudget {
+    fn before<'a>(&'a self, request: &'a mut ProbeRequest) -> LayerFuture<'a, Result<(), DirustError>> {
+        Box::pin(async move {
+            match self.0.clone().acquire_owned().await {
+                Ok(permit) => {
+                    request.hold(permit);
+                    Ok(())
+                }
+                Err(_) => Err(DirustError::Engine("concurrency budget closed".to_string())),
+            }
+        })
+    }
+}
+
+/// HEAD answered with `405 Method Not Allowed` → send the same request as GET; a number of
+/// servers and frameworks do not implement HEAD properly.
+pub struct MethodFallback;
+
+impl Middleware for MethodFallback {
+    fn after<'a>(
+        &'a self,
+        request: &'a mut ProbeRequest,
+        result: &'a mut Result<HttpSummary, DirustError>,
+    ) -> LayerFuture<'a, Next> {
+        let next = match result {
+            Ok(summary) if summary.status == StatusCode::METHOD_NOT_ALLOWED && !request.use_get => {
+                request.use_get = true;
+                Next::Retry
+            }
+            _ => Next::Continue,
+        };
+        Box::pin(async move { next })
+    }
+}
+
+/// Where `AuthRefresh` gets a new credential: the value of the header it sets.
+pub type CredentialSource = Arc<dyn Fn() -> LayerFuture<'static, Result<HeaderValue, DirustError>> + Send + Sync>;
+
+/// A credential younger than this is not renewed on a 401: the 401 is the answer.
+pub const AUTH_MIN_AGE: Duration = Duration::from_secs(30);
+
+/// Keeps a credential header (`Authorization` unless `with_header` says otherwise) on every
+/// request and renews it when it expires: the credential is fetched before the first
+/// request, and a 401 to a request sent with a credential older than `AUTH_MIN_AGE` drops
+/// it, so the retry fetches a new one. Requests that were in flight with a credential
+/// renewed meanwhile are retried with the new one; only one fetch runs at a time.
+pub struct AuthRefresh {
+    header: HeaderName,
+    fetch: CredentialSource,
+    /// The credential in use and when it was fetched.
+    current: tokio::sync::Mutex<Option<(HeaderValue, Instant)>>,
+}
+
+impl AuthRefresh {
+    /// A layer setting `Authorization` to what `fetch` returns.
+    pub fn new(fetch: CredentialSource) -> AuthRefresh {
+        AuthRefresh {
+            header: AUTHORIZATION,
+            fetch,
+            current: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// The layer with `header` set instead of `Authorization` (e.g. `X-Api-Key`).
+    pub fn with_header(mut self, header: HeaderName) -> AuthRefresh {
+        self.header = header;
+        self
+    }
+
+    /// `--auth-cmd`: the credential is the first line `command` prints (run by the shell).
+    pub fn command(command: &str) -> AuthRefresh {
+        let command = command.to_string();
+        AuthRefresh::new(Arc::new(move || {
+            let command = command.clone();
+            Box::pin(async move { run_auth_command(&command).await })
+        }))
+    }
+
+    /// The credential in use, fetched first if there is none.
+    async fn credential(&self) -> Result<HeaderValue, DirustError> {
+        let mut current = self.current.lock().await;
+        if let Some((value, _)) = current.as_ref() {
+            return Ok(value.clone());
+        }
+        let mut value = (self.fetch)().await?;
+        value.set_sensitive(true);
+        *current = Some((value.clone(), Instant::now()));
+        Ok(value)
+    }
+}
+
+impl Middleware for AuthRefresh {
+    fn before<'a>(&'a self, request: &'a mut ProbeRequest) -> LayerFuture<'a, Result<(), DirustError>> {
+        Box::pin(async move {
+            let value = self.credential().await?;
+            request.headers.insert(self.header.clone(), value);
+            Ok(())
+        })
+    }
+
+    fn after<'a>(
+        &'a self,
+        request: &'a mut ProbeRequest,
+        result: &'a mut Result<HttpSummary, DirustError>,
+    ) -> LayerFuture<'a, Next> {
+        let rejected = matches!(result, Ok(summary) if summary.status == StatusCode::UNAUTHORIZED);
+        Box::pin(async move {
+            if !rejected {
+                return Next::Continue;
+            }
+            let mut current = self.current.lock().await;
+            match current.as_ref() {
+                // Renewed since this request left: send it again with the new credential.
+                Some((value, _)) if request.headers.get(&self.header) != Some(value) => Next::Retry,
+                Some((_, fetched)) if fetched.elapsed() < AUTH_MIN_AGE => Next::Continue,
+                _ => {
+                    *current = None;
+                    Next::Retry
+                }
+            }
+        })
+    }
+}
+
+/// Run `command` with the shell and read the credential from the first line of its output.
+async fn run_auth_command(command: &str) -> Result<HeaderValue, DirustError> {
+    let mut shell = match cfg!(windows) {
+        true => {
+            let mut shell = Command::new("cmd");
+            shell.arg("/C").arg(command);
+            shell
+        }
+        false => {
+            let mut shell = Command::new("sh");
+            shell.arg("-c").arg(command);
+            shell
+        }
+    };
+    shell.stdin(Stdio::null()).stderr(Stdio::inherit()).kill_on_drop(true);
+    let output = match tokio::time::timeout(AUTH_MIN_AGE, shell.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return Err(DirustError::AuthRefresh(format!("could not run the command: {}", e))),
+        Err(_) => return Err(DirustError::AuthRefresh(format!("the command took more than {:?}", AUTH_MIN_AGE))),
+    };
+    if !output.status.success() {
+        return Err(DirustError::AuthRefresh(format!("the command exited with {}", output.status)));
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let line = text.lines().next().unwrap_or("").trim();
+    match (line.is_empty(), HeaderValue::from_str(line)) {
+        (false, Ok(value)) => Ok(value),
+        _ => Err(DirustError::AuthRefresh("the command printed no usable header value".to_string())),
+    }
+}
+
+/// A timed-out request gets one more chance with a longer timeout (`--slow-retry-timeout`);
+/// what only arrives then is marked `slow`.
+pub struct SlowRetry(pub Duration);
+
+impl Middleware for SlowRetry {
+    fn after<'a>(
+        &'a self,
+        request: &'a mut ProbeRequest,
+        result: &'a mut Result<HttpSummary, DirustError>,
+    ) -> LayerFuture<'a, Next> {
+        let retried = request.timeout == Some(self.0);
+        let next = match result {
+            Err(e) if e.is_timeout() && !retried => {
+                request.timeout = Some(self.0);
+                Next::Retry
+            }
+            Ok(summary) if retried => {
+                summary.slow = true;
+                Next::Continue
+            }
+            _ => Next::Continue,
+        };
+        Box::pin(async move { next })
+    }
+}
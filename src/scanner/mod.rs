@@ -1,118 +1,1328 @@
 //! src/scanner/mod.rs
 //!
 //! This module coordinates the whole scanning process:
-//!   - Load the wordlist from disk
+//!   - Pull the words from the payload source (`-w`: a wordlist file, stdin, ...)
 //!   - Parse extra extensions from CLI flags
-//!   - Build absolute target URLs to probe
+//!   - Build absolute target URLs to probe, one chunk of the wordlist at a time
 //!   - Run HTTP probes with bounded concurrency (semaphore)
-//!   - Print only “interesting” responses (200/301/302/401/403)
+//!   - Print only “interesting” responses (200/301/302/401/403), tagged with a severity
+//!     from the classification rules (`--rules`)
+//!   - Collapse large groups of redirects to the same target into one summary line
+//!   - Print a final summary (status-class histogram, latency percentiles)
+//!   - Record every probe outcome in the results database (for `--incremental`)
+//!   - Optionally parse HTML forms on 200 pages and report discovered parameters (`--forms`),
+//!     feeding in-scope form actions back into the queue as a follow-up wave (`--follow-forms`)
+//!   - Optionally search the bodies of 200 findings for leaked secrets (`--secrets`)
+//!   - Drop every target outside the `--scope` file before it is scheduled
+//!   - Capture a baseline (the base and a random path) at start and record how each finding
+//!     differs from it (`diff_from_baseline`, same comparison as the fuzzing modes); check
+//!     whether HEAD is allowed. Both come from `--profile-cache` when an earlier scan stored them
+//!   - Drop soft 404s: findings answered like random paths in their directory (`soft404.rs`)
 //!
 //! The heavy I/O work is delegated to submodules:
 //!   - wordlist.rs : reading and filtering wordlist lines
+//!   - source.rs   : `PayloadSource` and the composable `-w` sources (file, stdin, url, range, mutate)
+//!   - priority.rs : weighted wordlists and `--priority` lists (high-value words first)
 //!   - targets.rs  : turning (base + words + exts) into absolute URLs
+//!   - dedup.rs    : scan-wide "already scheduled?" set (scalable bloom filter)
+//!   - encodings.rs: overlong UTF-8 / Unicode variants of targets (`--unsafe-encodings`)
+//!   - scope.rs    : allowed hosts / path prefixes and denied patterns (`--scope`)
+//!   - ramp.rs     : staged concurrency increase (`--ramp`)
+//!   - adaptive.rs : per-extension finding counts for `--adaptive-exts`
+//!   - learn.rs    : extensions learned from findings for `--learn-exts`
+//!   - variants.rs : one line per resource for `admin` / `admin.php` / `admin/` (`--group-variants`)
 //!   - http.rs     : performing one HTTP probe and summarizing the response
+//!   - charset.rs  : decoding bodies with their declared or sniffed charset before matching
+//!   - binary.rs   : binary bodies (size-only findings) and large archives at the site root
+//!   - verify.rs   : --verify: slow second GET of every finding (reproduced?, title, hash)
+//!   - fast.rs     : the lean hyper-based probe client (`--engine fast`)
+//!   - raw.rs      : byte-exact HTTP/1.1 sender (`dirust fuzz raw --raw-engine`)
+//!   - pipeline.rs : pipelined HTTP/1.1 probes over raw connections (`--engine fast --pipeline`)
+//!   - transport.rs: the `ProbeClient` trait every engine implements, and an in-memory fake
+//!   - middleware.rs: the layers around every probe (headers, retries, shared budget) and
+//!     caller-supplied ones
+//!   - shape.rs    : per-request header construction (`--evade` randomization)
+//!   - forms.rs    : extracting form actions and parameter names from HTML
+//!   - fingerprint.rs: technology detection and extension choice (`--smart-exts`)
+//!   - redirects.rs: grouping redirect findings by target (`--redirect-threshold`)
+//!   - soft404.rs  : per-directory random-path baselines; findings answered like them are dropped
+//!   - secrets.rs  : secret/entropy search in the bodies of findings (`--secrets`)
+//!   - auth.rs     : authentication entry point heuristics (`[AUTH]`)
+//!   - cookies.rs  : `Set-Cookie` records, weak-cookie flags and the `--keep-cookies` jar
+//!   - stats.rs    : per-probe statistics and the final summary
+//!   - hitrate.rs  : sudden jumps of the share of findings (`--hit-rate-alert`, `--pause-on-spike`)
+//!   - health.rs   : hosts that stop answering mid-scan: quarantine, re-checks (`--max-downtime`)
+//!   - monitor.rs  : progress, findings, cancellation token and injected words for callers watching a scan
+//!   - events.rs   : `--progress-ndjson`: periodic machine-readable progress events
+//!   - inject.rs   : words added to a running scan through a named pipe (`--inject-pipe`)
+//!   - distributed.rs: `--role coordinator|worker`, sharding targets over the network
 //!   - util.rs     : small helpers (timestamp, status filtering)
 
-use crate::{args::Args, error::DirustError};
-use reqwest::Client;
-use std::sync::Arc;
-use tokio::{sync::Semaphore, task::JoinHandle};
+use crate::{
+    args::{AdaptiveExts, ScanArgs},
+    checkpoint::Checkpoint,
+    db::ResultsDb,
+    error::DirustError,
+    export::iso_datetime,
+    fuzz::{
+        baseline::{self, Baseline, Fingerprint},
+        reflect,
+    },
+    headerdiff,
+    output::{self, Style},
+    permute::{self, Permuter},
+    profile::ProfileCache,
+    resume::ScanState,
+    schema::{BaseSummary, ResultEntry},
+    severity::{Classification, Classifier, Severity},
+    url::canonical_target,
+};
+use reqwest::{Client, StatusCode};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
 
 // Bring in submodules that this orchestrator relies on.
-mod wordlist;
+// `wordlist`, `http` and `util` are also used by the fuzzing modes in `crate::fuzz`;
+// `forms` (attribute parsing) by `crate::wordgen`.
+pub mod wordlist;
+pub mod source;
+mod priority;
 mod targets;
-mod http;
-mod util;
+mod dedup;
+mod encodings;
+mod scope;
+mod adaptive;
+mod learn;
+mod variants;
+mod ramp;
+pub mod http;
+pub mod charset;
+pub mod binary;
+pub mod verify;
+mod fast;
+mod pipeline;
+pub mod raw;
+pub mod transport;
+pub mod middleware;
+mod shape;
+pub mod forms;
+pub mod fingerprint;
+mod redirects;
+mod soft404;
+mod latency;
+mod hitrate;
+mod health;
+mod secrets;
+mod auth;
+mod cookies;
+mod stats;
+pub mod monitor;
+pub mod inject;
+pub mod live;
+pub mod events;
+pub mod distributed;
+pub mod util;
+pub mod transcript;
 
 // Types and helpers used locally from the submodules.
-use http::HttpSummary;
-use util::{is_interesting_status, timestamp_seconds};
+use forms::FormInfo;
+use adaptive::{Decision, ExtensionTracker};
+use learn::ExtensionLearner;
+use dedup::TargetDedup;
+use http::{HttpSummary, ProbeEngine};
+use latency::LatencyBaseline;
+use hitrate::HitRateWatch;
+use health::HostHealth;
+use monitor::ScanMonitor;
+use redirects::RedirectCollector;
+use soft404::SoftNotFound;
+use ramp::Ramp;
+use scope::Scope;
+use secrets::{SecretMatch, SecretScanner};
+use source::{ListSource, PayloadSource};
+use stats::ScanStats;
+use transcript::Transcript;
+use middleware::{Middleware, RateLimit};
+use transport::ProbeClient;
+use util::is_interesting_status;
+use variants::HeldFinding;
+
+/// Optional observers of a scan, shared with every probe task.
+#[derive(Clone, Default)]
+pub struct ScanHooks {
+    /// `--state`: skip URLs probed by an earlier run, record every probed URL.
+    pub state: Option<Arc<ScanState>>,
+    /// Progress counters, findings and cancellation (e.g., for `dirust serve`).
+    pub monitor: Option<Arc<ScanMonitor>>,
+    /// `--transcript`: every probe request and its response.
+    pub transcript: Option<Arc<Transcript>>,
+    /// `--profile-cache`: calibration of earlier scans, reused and refreshed.
+    pub profiles: Option<Arc<ProfileCache>>,
+    /// `--checkpoint`: wordlist position of an interrupted run, updated after every wave.
+    pub checkpoint: Option<Arc<Checkpoint>>,
+    /// `dirust run`: in-flight requests allowed across all the jobs of a job file (every
+    /// probe request holds one of these permits on top of its own scan's).
+    pub budget: Option<Arc<Semaphore>>,
+    /// Extra middleware layers, run after the built-in ones on every probe request (see
+    /// `middleware.rs`).
+    pub middleware: Vec<Arc<dyn Middleware>>,
+    /// `--rate` limiter shared with the other scans of the same host (interleaved bases);
+    /// without one, the scan builds its own from `--rate`.
+    pub rate_limit: Option<Arc<RateLimit>>,
+    /// Sends every request of the scan instead of the `--engine` client (a
+    /// `transport::FakeClient` for `--self-test --in-memory` and the unit tests).
+    pub transport: Option<Arc<dyn ProbeClient>>,
+}
+
+impl ScanHooks {
+    /// Whether the monitor (if any) asked the scan to stop.
+    pub fn is_cancelled(&self) -> bool {
+        match &self.monitor {
+            Some(monitor) => monitor.is_cancelled(),
+            None => false,
+        }
+    }
+}
 
 /// Run the full scan using a pre-built HTTP client, a normalized base URL,
-/// and the parsed CLI arguments.
+/// the parsed CLI arguments, the words to probe (`source`), and the results database.
+///
+/// With `--incremental`, validators stored in `db` are sent as conditional headers.
+/// Every probe outcome is folded back into `db` (the caller decides whether to save it).
+/// With `--new-only`, `previous` holds the earlier result set and known findings are not printed.
+/// With `--state`, URLs already recorded in `hooks.state` are skipped and every probed URL
+/// is recorded there as soon as its probe finishes. With `--checkpoint`, the wordlist position
+/// recorded in `hooks.checkpoint` is skipped to first and advanced after every wave.
+/// `hooks.monitor` sees progress and
+/// findings, and may cancel the scan through its `CancellationToken`: no new probes are
+/// started, the ones in flight are aborted, and `Ok(())` is returned with the outcomes of the
+/// finished probes folded into `db` (the summary still prints).
 ///
 /// Returns:
-///   - Ok(()) on success (including the case where zero targets were “interesting”)
+///   - Ok(summary) on success (including the case where zero targets were “interesting”):
+///     what was sent to and found on `base`, for the per-host section of the metadata
 ///   - Err(DirustError) if any fatal error occurs (file I/O, HTTP, or task join failure)
-pub async fn scan(client: &Client, base: &str, args: &Args) -> Result<(), DirustError> {
-    // 1) Read wordlist from disk and apply basic filtering (trim, skip empty/#comment).
-    //    Any I/O error (e.g., file not found, permission denied) is returned immediately.
-    let words = wordlist::read_wordlist(&args.wordlist)?;
+pub async fn scan(
+    client: &Client,
+    base: &str,
+    args: &ScanArgs,
+    source: Box<dyn PayloadSource>,
+    db: &mut ResultsDb,
+    previous: Option<&ResultsDb>,
+    hooks: &ScanHooks,
+) -> Result<BaseSummary, DirustError> {
+    // 1) Words are pulled from `source` one chunk at a time (trimmed, no empty/#comment
+    //    lines). Any I/O error (e.g., a file that cannot be read) is returned immediately.
+    let mut source = source;
+
+    if let Some(monitor) = &hooks.monitor {
+        monitor.set_directory(base);
+    }
 
     // 2) Parse the comma-separated extensions passed via CLI into a normalized Vec<String>.
     //    Example: "php,html,txt" -> [".php", ".html", ".txt"]
-    let extensions = args.parse_exts();
+    //    With `--adaptive-exts`, the list shrinks once the first probes show which ones hit.
+    let mut extensions = args.parse_exts();
+    let mut ext_tracker: Option<ExtensionTracker> = match args.adaptive_exts {
+        Some(mode) if !extensions.is_empty() => Some(ExtensionTracker::new(mode, &extensions)),
+        _ => None,
+    };
+    // With `--learn-exts`, extensions seen in findings are added as the scan goes.
+    let mut learner: Option<ExtensionLearner> = match args.learn_exts {
+        true => Some(ExtensionLearner::new(&extensions)),
+        false => None,
+    };
+
+    // Severity rules (built-in, or `--rules <PATH>`), shared read-only by all tasks.
+    let classifier = Arc::new(Classifier::load(args.rules.as_deref())?);
+
+    // Secret rules (built-in, or `--secret-rules <PATH>`), only with `--secrets`.
+    let secret_scanner: Option<Arc<SecretScanner>> = match args.secrets {
+        true => Some(Arc::new(SecretScanner::load(args.secret_rules.as_deref())?)),
+        false => None,
+    };
+
+    // Rolling latency baseline for `[SLOW]` outliers (`--latency-sigma`).
+    let latency_baseline = Arc::new(LatencyBaseline::new(args.latency_sigma));
 
-    // 3) Build the final list of absolute URLs to probe (base + word [+ ext]).
-    //    The target builder ensures we do not add extensions to directories (“admin/”)
-    //    or to words that already contain a dot (“readme.txt”).
-    let all_targets = targets::build_targets(base, &words, &extensions);
+    // Share of findings among recent probes (`--hit-rate-alert`, `--pause-on-spike`).
+    let hit_rate = Arc::new(HitRateWatch::new(args.hit_rate_alert, args.pause_on_spike));
 
-    // 4) Prepare bounded concurrency using a semaphore.
+    // Failure streak of the host: quarantine and re-check it when it stops answering.
+    let health = Arc::new(HostHealth::new(true));
+    let mut gave_up = false;
+
+    // Allowed hosts / path prefixes and denied patterns (`--scope`).
+    let scope: Option<Scope> = match &args.scope {
+        Some(path) => Some(Scope::load(path)?),
+        None => None,
+    };
+    let mut out_of_scope: usize = 0;
+
+    // 3) Prepare bounded concurrency using a semaphore.
     //    We acquire a permit BEFORE spawning each task, guaranteeing that the number of
-    //    in-flight requests never exceeds `args.concurrency`.
-    let semaphore = Arc::new(Semaphore::new(args.concurrency));
+    //    in-flight requests never exceeds `--concurrency`.
+    let semaphore = Arc::new(Semaphore::new(args.http.concurrency));
+
+    // `--ramp`: hold back part of the permits and hand them out stage by stage (released
+    // when `_ramp` is dropped at the end of this scan).
+    let _ramp: Option<Ramp> = match args.ramp.is_empty() {
+        true => None,
+        false => Some(Ramp::start(&semaphore, args.http.concurrency, &args.ramp)),
+    };
+
+    // The client that sends the probes (`--engine`, unless the caller brought its own).
+    // Every probe goes through the middleware chain (headers, retries, `dirust run` budget).
+    let chain = ProbeEngine::chain_for_scan(args, hooks.budget.clone(), hooks.rate_limit.clone(), &hooks.middleware);
+    let engine = match &hooks.transport {
+        Some(transport) => ProbeEngine::new(Arc::clone(transport), chain),
+        None => ProbeEngine::for_scan(client, args, hooks.transcript.clone(), chain)?,
+    };
+
+    // What "nothing here" looks like (every finding's `diff_from_baseline`), and whether
+    // HEAD is allowed at all.
+    let calibration = calibrate(&engine, base, args, hooks.profiles.as_deref()).await;
+
+    // Soft 404s: the base's random-path baseline is known, other directories are calibrated
+    // when they have a finding.
+    let soft_404 = Arc::new(SoftNotFound::new());
+    soft_404.seed(base, calibration.not_found);
+
+    // `--prewarm`: open keep-alive connections before the first wave needs them.
+    if args.prewarm > 0 {
+        let wanted = args.prewarm.min(args.http.concurrency);
+        let started = Instant::now();
+        let opened = engine.prewarm(base, wanted).await;
+        output::info(&format!(
+            "[*] prewarm: {} of {} connection(s) ready in {:.1}s",
+            opened,
+            wanted,
+            started.elapsed().as_secs_f64()
+        ));
+    }
+
+    // Every URL ever scheduled, so nothing is probed twice: not a duplicate wordlist line,
+    // not a follow-up wave target that was already probed.
+    // Shared with the probe tasks, which schedule the `word/` of directory redirects.
+    let scheduled = Arc::new(TargetDedup::new());
+
+    // Forms collected across all waves, reported in their own section at the end.
+    let mut discovered_forms: Vec<FormInfo> = Vec::new();
+
+    // Secrets found in bodies (`--secrets`), listed again at the end.
+    let mut discovered_secrets: Vec<(String, SecretMatch)> = Vec::new();
+
+    // Redirect findings counted by target; past `--redirect-threshold`, held back and
+    // summarized at the end.
+    let mut redirects = RedirectCollector::new(args.redirect_threshold);
+
+    // Status and latency statistics for the final summary.
+    let mut stats = ScanStats::new();
+
+    // Findings to request again after the scan (`--verify`), in the order they were reported.
+    let mut to_verify: Vec<String> = Vec::new();
+
+    // Redirect targets queued by `--harvest-redirects`: target → the redirect that led to it.
+    let mut harvested: HashMap<String, String> = HashMap::new();
+
+    // `--permute`: variants of directory findings, and how many were queued / found.
+    let mut permuter = Permuter::from_args(&args.permute)?;
+    let mut permuted: (usize, usize) = (0, 0);
+
+    // 4) Process the wordlist in chunks of about `--chunk-size` targets: build the targets
+    //    of one slice of words, probe them, fold the outcomes in, move on. Targets, task
+    //    handles and outcomes only ever exist for one chunk, so memory stays flat however
+    //    large the wordlist is. URLs discovered while probing (e.g., form actions with
+    //    `--follow-forms`) are queued and run as their own wave before the next chunk.
+    let mut follow_ups: Vec<String> = Vec::new();
+    // Probes that got no answer, tried again before anything else (see `health.rs`).
+    let mut retries: Vec<String> = Vec::new();
+    let mut resumed_skips: usize = 0;
+    let mut grouped_variants: usize = 0;
+    // `--adaptive-exts=defer`: (words read since the decision, extensions to probe them with).
+    let mut deferred: Option<(Vec<String>, Vec<String>)> = None;
+    // Entries taken from `source` so far (`--checkpoint`); not advanced by the deferred pass.
+    let mut consumed: usize = 0;
+    let mut replaying = false;
+    // Extensions set by the last wordlist directive (`#!exts php`, `#!noext`; `None`: ours).
+    let mut directive_exts: Option<Vec<String>> = None;
+    // Normalized words seen so far, and how many entries were dropped as duplicates of one.
+    let seen_words = TargetDedup::new();
+    let mut duplicate_words: usize = 0;
+
+    // `--checkpoint`: continue where an interrupted run of this base stopped.
+    if let Some(checkpoint) = &hooks.checkpoint
+        && let Some(progress) = checkpoint.resume_point(base)
+    {
+        // Directives among the skipped entries still apply to the ones after them.
+        while consumed < progress.words {
+            let skipped = source.next_chunk((progress.words - consumed).min(args.chunk_size.max(1)))?;
+            if skipped.is_empty() {
+                break;
+            }
+            consumed += skipped.len();
+            let mut words: usize = 0;
+            for payload in &skipped {
+                match wordlist::Directive::parse(payload) {
+                    Some(directive) => directive_exts = directive.extensions(),
+                    None => words += 1,
+                }
+            }
+            if let Some(monitor) = &hooks.monitor {
+                monitor.unplan_words(words);
+            }
+        }
+        for url in progress.follow_ups {
+            if scheduled.insert(&url) {
+                follow_ups.push(url);
+            }
+        }
+        output::info(&format!(
+            "[*] checkpoint: resuming {} after {} word(s), {} queued URL(s)",
+            base,
+            consumed,
+            follow_ups.len()
+        ));
+    }
+
+    while !hooks.is_cancelled() {
+        // Whether the wave is built from wordlist entries (the others count as discovered work).
+        let mut from_wordlist = false;
+        let retrying = !retries.is_empty();
+        let queue: Vec<String> = if retrying {
+            std::mem::take(&mut retries)
+        } else if !follow_ups.is_empty() {
+            std::mem::take(&mut follow_ups)
+        } else {
+            // Chunks follow the current extension count; while `--adaptive-exts` is still
+            // sampling, they are kept small so the decision comes early.
+            let mut words_per_chunk: usize = (args.chunk_size / targets_per_word(args, extensions.len())).max(1);
+            if let Some(tracker) = &ext_tracker
+                && tracker.sampling()
+            {
+                words_per_chunk = words_per_chunk.min(tracker.sample_words());
+            }
+            // Words injected into the running scan go before the next wordlist chunk.
+            let injected: Vec<String> = match &hooks.monitor {
+                Some(monitor) => monitor.take_injected(words_per_chunk),
+                None => Vec::new(),
+            };
+            let from_source = injected.is_empty();
+            let chunk: Vec<String> = if !from_source {
+                output::info(&format!("[*] inject: probing {} injected word(s)", injected.len()));
+                injected
+            } else {
+                let chunk = source.next_chunk(words_per_chunk)?;
+
+                // After the wordlist, a deferred pass probes the remaining words again with the
+                // extensions that had no hits (their as-is targets are skipped as duplicates).
+                if chunk.is_empty() {
+                    match deferred.take() {
+                        Some((replay, deferred_exts)) => {
+                            output::info(&format!("[*] adaptive-exts: probing deferred {}", deferred_exts.join(", ")));
+                            source = Box::new(ListSource::new(replay));
+                            extensions = deferred_exts;
+                            directive_exts = None;
+                            replaying = true;
+                            continue;
+                        }
+                        None => break,
+                    }
+                }
+                if !replaying {
+                    consumed += chunk.len();
+                    from_wordlist = true;
+                    if let Some(monitor) = &hooks.monitor {
+                        monitor.record_words(chunk.iter().filter(|p| wordlist::Directive::parse(p).is_none()).count());
+                    }
+                }
+                chunk
+            };
+            // Merged lists repeat words as `admin`, `/admin`, `admin//`: keep one of each (the
+            // deferred pass replays words that already went through this).
+            let chunk: Vec<String> = match replaying {
+                true => chunk,
+                false => unique_words(chunk, &seen_words, args.add_slash, &mut duplicate_words),
+            };
+
+            // Build the absolute URLs to probe (base + word [+ ext]).
+            // The target builder ensures we do not add extensions to directories (“admin/”)
+            // or to words that already contain a dot (“readme.txt”). Wordlist directives
+            // (`#!exts`, `#!noext`) choose the extensions of the entries after them; injected
+            // words always get ours.
+            let mut no_directive: Option<Vec<String>> = None;
+            let overrides = if from_source { &mut directive_exts } else { &mut no_directive };
+            let (mut chunk_targets, default_words) =
+                targets::build_chunk_targets(base, &chunk, &extensions, overrides, args.add_slash);
+            if let Some((replay, _)) = &mut deferred {
+                replay.extend(default_words);
+            }
+
+            // `--unsafe-encodings`: add overlong UTF-8 / Unicode variants of every target.
+            if args.unsafe_encodings {
+                let variants: Vec<String> = chunk_targets.iter().flat_map(|t| encodings::variants(base, t)).collect();
+                chunk_targets.extend(variants);
+            }
+
+            // When resuming, drop the targets an earlier run already probed.
+            if let Some(progress) = &hooks.state
+                && progress.done_count() > 0
+            {
+                let before = chunk_targets.len();
+                chunk_targets.retain(|url| !progress.is_done(url));
+                resumed_skips += before - chunk_targets.len();
+            }
+
+            // `--scope`: out-of-scope targets are never requested.
+            if let Some(scope) = &scope {
+                out_of_scope += scope.retain(&mut chunk_targets);
+            }
+
+            scheduled.filter(chunk_targets)
+        };
+        if queue.is_empty() {
+            continue;
+        }
+
+        if let Some(monitor) = &hooks.monitor
+            && !retrying
+        {
+            match from_wordlist {
+                true => monitor.add_total(queue.len()),
+                false => monitor.add_discovered(queue.len()),
+            }
+        }
+        let wave = Wave {
+            engine: &engine,
+            args,
+            previous,
+            hooks,
+            semaphore: &semaphore,
+            classifier: &classifier,
+            secrets: secret_scanner.as_ref(),
+            latency: &latency_baseline,
+            hit_rate: &hit_rate,
+            health: &health,
+            not_found: calibration.not_found,
+            soft_404: &soft_404,
+            use_get: args.http.get || !calibration.head_supported,
+            print_findings: true,
+            scope: scope.as_ref(),
+            scheduled: &scheduled,
+        };
+        let outcomes = run_wave(&wave, db, queue).await?;
+        let mut held_findings: Vec<HeldFinding> = Vec::new();
+
+        for outcome in outcomes {
+            // Remember what the task saw (a confirmed directory took two requests).
+            if let Some((status, latency)) = outcome.confirmed_redirect {
+                stats.record(status, latency);
+            }
+            stats.record(outcome.entry.status, outcome.latency);
+            if let Some(tracker) = &mut ext_tracker {
+                tracker.record(&outcome.entry.url, outcome.interesting);
+            }
+            if outcome.reported {
+                stats.record_finding(&outcome.entry);
+                if args.verify.is_some() {
+                    to_verify.push(outcome.entry.url.clone());
+                }
+            }
+            if let Some(learner) = &mut learner
+                && outcome.reported
+                && matches!(outcome.entry.status, 200..=299 | 401 | 403)
+                && let Some(ext) = learner.observe(&outcome.entry.url)
+            {
+                print_learned_ext(&ext, &outcome.entry.url);
+            }
+            if let Some(summary) = outcome.deferred {
+                let label = severity_label(&outcome.entry);
+                redirects.push(outcome.entry.url.clone(), summary, label);
+            }
+            if let Some(finding) = outcome.held {
+                held_findings.push(finding);
+            }
+            for secret in outcome.secrets {
+                discovered_secrets.push((outcome.entry.url.clone(), secret));
+            }
+
+            // `--harvest-redirects`: queue the in-scope target of a redirect finding, once;
+            // and remember where a harvested target came from.
+            let mut entry = outcome.entry;
+            if let Some(source) = harvested.get(&entry.url) {
+                if outcome.reported {
+                    redirects::print_harvested(&entry.url, entry.status, source);
+                }
+                entry.redirected_from = Some(source.clone());
+            }
+            if args.harvest_redirects
+                && outcome.reported
+                && let Some(target) = redirects::harvest_target(&entry.url, entry.status, entry.location.as_deref())
+                && target.starts_with(base)
+                && scheduled.insert(&target)
+            {
+                match &scope {
+                    Some(scope) if !scope.allows(&target) => out_of_scope += 1,
+                    _ => {
+                        harvested.insert(target.clone(), entry.url.clone());
+                        follow_ups.push(target);
+                    }
+                }
+            }
+            // `--permute`: mark finds that are variants, and queue the variants of this
+            // one (up to `--permute-depth` generations deep).
+            if let Some(permuter) = &mut permuter {
+                if let Some(origin) = permuter.origin(&entry.url) {
+                    if outcome.reported {
+                        permute::print_found(&entry.url, entry.status, origin);
+                        permuted.1 += 1;
+                    }
+                    entry.permuted_from = Some(origin.to_string());
+                }
+                if outcome.reported && matches!(entry.status, 200..=399 | 401 | 403) {
+                    for target in permuter.path_variants(&entry.url) {
+                        if !target.starts_with(base) || !scheduled.insert(&target) {
+                            continue;
+                        }
+                        match &scope {
+                            Some(scope) if !scope.allows(&target) => out_of_scope += 1,
+                            _ => {
+                                permuted.0 += 1;
+                                follow_ups.push(target);
+                            }
+                        }
+                    }
+                }
+            }
+            if outcome.reported {
+                db.mark_reported(&entry.url);
+            }
+            db.apply(entry, outcome.interesting);
+
+            for form in outcome.forms {
+                if let Some(learner) = &mut learner
+                    && let Some(ext) = learner.observe(&form.action)
+                {
+                    print_learned_ext(&ext, &form.action);
+                }
+                // Only follow actions under the scanned base, and only once.
+                let action = canonical_target(&form.action);
+                let in_scope = action.starts_with(base);
+                if args.follow_forms && in_scope && scheduled.insert(&action) {
+                    // `--scope` may still rule it out (counted once, like the targets).
+                    match &scope {
+                        Some(scope) if !scope.allows(&action) => out_of_scope += 1,
+                        _ => follow_ups.push(action),
+                    }
+                }
+                discovered_forms.push(form);
+            }
+        }
+
+        // `--group-variants`: print this wave's findings, one line per resource.
+        if !held_findings.is_empty() {
+            grouped_variants += variants::print_grouped(held_findings, &extensions);
+        }
+
+        // `--adaptive-exts`: once the sample is complete, drop or defer what never hit.
+        if let Some(tracker) = &mut ext_tracker
+            && let Some(decision) = tracker.decide()
+        {
+            print_ext_decision(&decision, tracker.mode());
+            if !decision.unmatched.is_empty() {
+                extensions = decision.keep.into_iter().map(|(ext, _)| ext).collect();
+                if tracker.mode() == AdaptiveExts::Defer {
+                    deferred = Some((Vec::new(), decision.unmatched));
+                }
+            }
+        }
+
+        // `--learn-exts`: the extensions learned so far apply to the chunks after this wave
+        // (also after `--adaptive-exts` rebuilt the list).
+        if let Some(learner) = &learner {
+            for ext in learner.learned() {
+                if !extensions.contains(ext) {
+                    extensions.push(ext.clone());
+                }
+            }
+        }
+
+        // The host stopped answering: wait for it (other bases keep scanning), then probe
+        // what failed again; give up on it after `--max-downtime`.
+        if health.is_down() {
+            let use_get = args.http.get || !calibration.head_supported;
+            let recheck = || async { engine.send_once(base, use_get).await.is_ok() };
+            let max_downtime = Duration::from_secs(args.max_downtime);
+            gave_up = !health.wait_until_back(base, max_downtime, recheck, || hooks.is_cancelled()).await;
+        }
+        retries.extend(health.take_deferred());
+
+        // `--checkpoint`: this wave is done (a cancelled one may have skipped probes).
+        if let Some(checkpoint) = &hooks.checkpoint
+            && !hooks.is_cancelled()
+        {
+            let pending: Vec<String> = retries.iter().chain(follow_ups.iter()).cloned().collect();
+            checkpoint.record(base, consumed, &pending)?;
+        }
+        if gave_up {
+            break;
+        }
+    }
+
+    if duplicate_words > 0 {
+        output::info(&format!(
+            "[*] wordlist: {} duplicate entr{} removed (same word after slash normalization)",
+            duplicate_words,
+            if duplicate_words == 1 { "y" } else { "ies" }
+        ));
+    }
+    if resumed_skips > 0 {
+        output::info(&format!("[*] resume: skipped {} already probed target(s)", resumed_skips));
+    }
+    if grouped_variants > 0 {
+        output::info(&format!("[*] variants: {} finding(s) folded into the line of their resource", grouped_variants));
+    }
+    if out_of_scope > 0 {
+        output::info(&format!("[*] scope: dropped {} out-of-scope target(s)", out_of_scope));
+    }
+    if permuted.0 > 0 {
+        output::info(&format!("[*] permute: {} variant(s) probed, {} found", permuted.0, permuted.1));
+    }
+
+    // Everything probed is on disk now.
+    if let Some(progress) = &hooks.state {
+        progress.flush()?;
+    }
+    if let Some(checkpoint) = &hooks.checkpoint
+        && !hooks.is_cancelled()
+        && !gave_up
+    {
+        checkpoint.finish(base)?;
+    }
+
+    // 5) One summary line per redirect target that had redirects held back.
+    redirects.flush();
+
+    // 6) Report discovered parameters separately from the status lines.
+    if args.forms {
+        print_forms(&discovered_forms);
+    }
+    if args.reflect {
+        reflect_forms(client, base, args, scope.as_ref(), &discovered_forms).await?;
+    }
+    if args.secrets {
+        print_secrets(&discovered_secrets);
+    }
+
+    // 7) Final summary: request count, status classes, latency percentiles.
+    stats.print_summary();
+    if soft_404.suppressed() > 0 {
+        output::info(&format!(
+            "[*] soft-404: {} finding(s) answered like random paths in their directory; not reported",
+            soft_404.suppressed()
+        ));
+    }
+    let outages = health.outages();
+    for outage in &outages {
+        output::info(&format!("    outage: {}", outage.describe()));
+    }
+
+    // 8) `--verify`: request every finding once more, slowly, and record what came back
+    //    (after the summary, so the slow pass does not count in the scan's throughput).
+    if let Some(rate) = args.verify
+        && !hooks.is_cancelled()
+        && !gave_up
+    {
+        let injection = match args.host_injection {
+            true => Some(client),
+            false => None,
+        };
+        verify::run(&engine, db, &to_verify, rate, injection).await;
+    }
+
+    // If we get here, all tasks finished and none reported an error.
+    let mut summary = stats.host_summary(base);
+    summary.outages = outages;
+    Ok(summary)
+}
+
+/// Everything one probe task reports back to the orchestrator.
+struct ProbeOutcome {
+    /// Probe result in schema form (also what the results database stores).
+    entry: ResultEntry,
+    /// Whether the status was "interesting" (decides if the entry is kept in the database).
+    interesting: bool,
+    /// Whether this is a finding reported to the user (interesting and not `--new-only` known).
+    reported: bool,
+    /// Forms found on the page (only with `--forms` and a 200 HTML response).
+    forms: Vec<FormInfo>,
+    /// Secrets found in the body (only with `--secrets` and a reported 200 response).
+    secrets: Vec<SecretMatch>,
+    /// A redirect finding that was not printed yet (see `redirects.rs`).
+    deferred: Option<HttpSummary>,
+    /// A finding held back for `--group-variants` (see `variants.rs`).
+    held: Option<HeldFinding>,
+    /// Time spent in the probe itself (see `stats.rs`).
+    latency: Duration,
+    /// Status and latency of the `word` → `word/` redirect a `[dir]` finding confirmed (one
+    /// more request for the stats).
+    confirmed_redirect: Option<(u16, Duration)>,
+    /// The length was measured with `--measure-chunked` and hit its cap.
+    length_capped: bool,
+}
+
+/// The scan-wide context every wave is run with.
+struct Wave<'a> {
+    /// Client for the probes and body downloads (forms, measured lengths).
+    engine: &'a ProbeEngine,
+    args: &'a ScanArgs,
+    /// Earlier result set for `--new-only`.
+    previous: Option<&'a ResultsDb>,
+    /// Observers (`--state` progress, monitor).
+    hooks: &'a ScanHooks,
+    semaphore: &'a Arc<Semaphore>,
+    classifier: &'a Arc<Classifier>,
+    /// Secret rules for `--secrets` (none: bodies are not searched).
+    secrets: Option<&'a Arc<SecretScanner>>,
+    /// Rolling latency baseline for `[SLOW]` outliers.
+    latency: &'a Arc<LatencyBaseline>,
+    /// Share of findings among recent probes, for spike alerts.
+    hit_rate: &'a Arc<HitRateWatch>,
+    /// Failure streak of the host (probes without an answer are deferred, not fatal).
+    health: &'a Arc<HostHealth>,
+    /// Random-path baseline findings are compared with (none: no `diff_from_baseline`).
+    not_found: Option<Baseline>,
+    /// Per-directory random-path baselines; findings that match theirs are soft 404s.
+    soft_404: &'a Arc<SoftNotFound>,
+    /// Probe with GET instead of HEAD (`--get`, or HEAD is not allowed on this base).
+    use_get: bool,
+    /// Print findings as they arrive (workers leave that to their coordinator).
+    print_findings: bool,
+    /// `--scope`: the `word/` of a `word` → `word/` redirect is only probed when allowed.
+    scope: Option<&'a Scope>,
+    /// Every URL scheduled in the scan; the `word/` of a redirect is probed only if new.
+    scheduled: &'a Arc<TargetDedup>,
+}
 
+/// Probe one batch of URLs with bounded concurrency and collect every outcome.
+///
+/// Returns:
+///   - Ok(outcomes) in the same order as `targets` (minus any that could not be scheduled)
+///   - Err(DirustError) on the first task failure (HTTP error, panic, cancellation)
+async fn run_wave(
+    wave: &Wave<'_>,
+    db: &ResultsDb,
+    targets: Vec<String>,
+) -> Result<Vec<ProbeOutcome>, DirustError> {
+    let args = wave.args;
     // We store the JoinHandle of each spawned task so we can await them and propagate errors.
-    let mut jobs: Vec<JoinHandle<Result<(), DirustError>>> = Vec::with_capacity(all_targets.len());
+    // A task without an outcome deferred its probe (see `health.rs`).
+    let mut jobs: Vec<JoinHandle<Result<Option<ProbeOutcome>, DirustError>>> =
+        Vec::with_capacity(targets.len());
+
+    let cancel: Option<CancellationToken> = wave.hooks.monitor.as_ref().map(|monitor| monitor.cancellation());
 
     // Iterate the full list of targets and schedule each probe as an async task.
-    for url in all_targets {
-        // Try to acquire a concurrency permit. If this fails (which is rare and indicates
-        // the semaphore was closed), we log and skip scheduling this target.
-        let permit = match semaphore.clone().acquire_owned().await {
-            Ok(p) => p,
-            Err(_) => {
-                eprintln!("[!] failed to acquire semaphore permit");
-                continue;
-            }
+    for url in targets {
+        // A cancelled scan starts no new probes (the ones in flight are aborted below).
+        if wave.hooks.is_cancelled() {
+            break;
+        }
+
+        // `--pause-on-spike`: hold new probes until the user says go on (see `hitrate.rs`).
+        if let Some(spike) = wave.hit_rate.take_pause() {
+            let stdin_is_wordlist = source::reads_stdin(args.wordlist_path());
+            tokio::task::spawn_blocking(move || hitrate::confirm_after_spike(spike, stdin_is_wordlist)).await??;
+        }
+
+        // A host that stopped answering gets no new probes; they wait for it to come back.
+        if wave.health.is_down() {
+            wave.health.defer(url);
+            continue;
+        }
+
+        // Acquire a concurrency permit (the `dirust run` budget is a middleware layer). Waiting
+        // for one ends early when the scan is cancelled.
+        let permit = match acquire_permit(wave.semaphore, cancel.as_ref()).await {
+            Some(p) => p,
+            None => break,
         };
 
-        // Clone the shared client for this task. `reqwest::Client` is cheap to clone:
+        // Clone the shared engine for this task. `ProbeEngine` is cheap to clone:
         // it shares connection pools and other internals under the hood.
-        let client_clone = client.clone();
+        let engine_clone = wave.engine.clone();
+
+        // Record whether we should use GET instead of HEAD (`--get`, or calibration found
+        // that HEAD is answered with 405).
+        let use_get = wave.use_get;
+
+        // Record whether HTML forms should be extracted from 200 pages.
+        let parse_forms = args.forms;
+
+        // Redirects are held back for grouping unless collapsing is disabled.
+        let redirect_threshold = args.redirect_threshold;
+
+        // Whether this task prints its own finding.
+        let print_findings = wave.print_findings;
+
+        // Whether findings are held back to group extension / slash variants (`--add-slash`
+        // folds `word` / `word/` pairs the same way).
+        let group_variants = args.group_variants || args.add_slash;
+
+        // With `--add-slash`, `word/` is probed on its own: a `word` → `word/` redirect needs
+        // no confirmation and is not reported twice.
+        let add_slash = args.add_slash;
+
+        // Whether 200 findings keep their security headers (`--header-audit`).
+        let header_audit = args.header_audit;
+
+        // Whether every response is printed, not only findings (`--all-statuses`).
+        let all_statuses = args.all_statuses;
+
+        // Whether `word` → `word/` redirects are confirmed with a probe of `word/` (not when
+        // `--scope` rules `word/` out: the redirect is reported as it is).
+        let confirm_dirs = !args.no_dir_confirm && wave.scope.is_none_or(|scope| scope.allows(&format!("{}/", url)));
+
+        // Severity rules for tagging findings.
+        let classifier_clone = Arc::clone(wave.classifier);
+
+        // Secret rules for searching the bodies of 200 findings.
+        let secrets_clone: Option<Arc<SecretScanner>> = wave.secrets.cloned();
+
+        // Latency baseline, updated by every probe.
+        let baseline_clone = Arc::clone(wave.latency);
+
+        // Hit rate, updated by every probe.
+        let hit_rate_clone = Arc::clone(wave.hit_rate);
+
+        // Failure streak of the host, updated by every probe.
+        let health_clone = Arc::clone(wave.health);
 
-        // Record whether we should use GET instead of HEAD, as requested by the CLI.
-        let use_get = args.get;
+        // Random-path baseline, compared with every finding.
+        let not_found: Option<Baseline> = wave.not_found;
+
+        // Directory baselines, to drop soft-404 findings.
+        let soft_404_clone = Arc::clone(wave.soft_404);
+
+        // Progress recording for `--state` and the monitor.
+        let hooks_clone: ScanHooks = wave.hooks.clone();
+
+        // The scan's scheduled URLs, for the `word/` of a directory redirect.
+        let scheduled_clone = Arc::clone(wave.scheduled);
+
+        // Cap for measuring bodies that come without Content-Length (`--measure-chunked`).
+        let measure_cap: Option<u64> = args.measure_chunked;
+
+        // Minimum size of archives at the site root flagged high-interest (`--flag-archives`).
+        let archive_min: Option<u64> = args.flag_archives;
+
+        // In incremental mode, hand the task a copy of what we stored last time so it can
+        // send `If-None-Match` / `If-Modified-Since`.
+        let stored: Option<ResultEntry> = if args.incremental {
+            db.get(&url).cloned()
+        } else {
+            None
+        };
+
+        // For `--new-only`, give the task its own copy of the earlier result set lookup.
+        // We only need to know which status (if any) was reported before for this URL.
+        let known_status: Option<u16> = match wave.previous {
+            Some(set) => set.get(&url).map(|entry| entry.status),
+            None => None,
+        };
+        // The same for the directory form, in case this probe turns out to be one.
+        let known_dir_status: Option<u16> = match wave.previous {
+            Some(set) => set.get(&format!("{}/", url)).map(|entry| entry.status),
+            None => None,
+        };
 
         // Spawn one asynchronous task per target.
         // The `_permit` binding is kept inside the task so the permit is released when
         // the task completes (drop semantics).
-        let handle: JoinHandle<Result<(), DirustError>> = tokio::spawn(async move {
+        let handle: JoinHandle<Result<Option<ProbeOutcome>, DirustError>> = tokio::spawn(async move {
             // Keep the permit alive for the lifetime of this task.
             let _permit = permit;
 
-            // Perform a single HTTP probe for the given URL.
+            // Perform a single HTTP probe for the given URL (through the middleware chain).
             // - Uses HEAD by default (fast, no body)
             // - Falls back to GET on 405 (Method Not Allowed), or always uses GET if requested
-            let probe_result = http::probe(&client_clone, &url, use_get).await?;
+            // - Sends conditional headers when `stored` is present (a 304 means "unchanged")
+            // - A timeout gets one more chance with `--slow-retry-timeout`; what only arrives
+            //   then is tagged `[slow-retry]`.
+            // A probe that got no answer at all is deferred (the host may be going down).
+            let mut probe_result = match engine_clone.probe(&url, use_get, stored.as_ref()).await {
+                Ok(result) => {
+                    health_clone.record_answer();
+                    result
+                }
+                Err(e) => {
+                    if let Some(monitor) = &hooks_clone.monitor {
+                        monitor.record_error();
+                    }
+                    health_clone.record_failure(&url, e)?;
+                    return Ok(None);
+                }
+            };
+            let slow = probe_result.slow;
+            // Time on the wire only: waiting for a `--rate` slot or a budget permit is not
+            // latency (it would skew the summary percentiles and the outlier baseline).
+            let mut latency = probe_result.elapsed;
+            // A probe that needed the slow retry is already tagged and would skew the baseline.
+            let latency_outlier = !slow && baseline_clone.observe(latency);
+            if let Some(progress) = &hooks_clone.state {
+                progress.mark_done(&url)?;
+            }
+            if let Some(monitor) = &hooks_clone.monitor {
+                monitor.record_probe();
+                let status = probe_result.status.as_u16();
+                if status >= 500 || status == 429 {
+                    monitor.record_error();
+                }
+            }
+
+            // `word` → `word/` only says "this is a directory". Probe `word/` right away and
+            // report that single result (tagged `[dir]`) instead of the redirect.
+            let mut url = url;
+            let mut known_status = known_status;
+            let mut is_dir = false;
+            let mut confirmed_redirect: Option<(u16, Duration)> = None;
+            let dir_redirect = redirects::is_directory_redirect(&url, &probe_result);
+            // (Only plain names, without a dot, got a `word/` target.)
+            let mut slash_covered =
+                add_slash && url.rsplit('/').next().is_some_and(|name| !name.contains('.')) && dir_redirect;
+            if confirm_dirs && !slash_covered && dir_redirect {
+                let dir_url = format!("{}/", url);
+                // A `word/` scheduled on its own (wordlist entry, earlier wave) reports the
+                // directory already; it is not probed twice.
+                match scheduled_clone.insert(&dir_url) {
+                    false => slash_covered = true,
+                    true => {
+                        if let Some(monitor) = &hooks_clone.monitor {
+                            monitor.add_discovered(1);
+                        }
+                        match engine_clone.probe(&dir_url, use_get, None).await {
+                            Ok(dir_result) => {
+                                confirmed_redirect = Some((probe_result.status.as_u16(), latency));
+                                latency = dir_result.elapsed;
+                                if let Some(progress) = &hooks_clone.state {
+                                    progress.mark_done(&dir_url)?;
+                                }
+                                if let Some(monitor) = &hooks_clone.monitor {
+                                    monitor.record_probe();
+                                    let status = dir_result.status.as_u16();
+                                    if status >= 500 || status == 429 {
+                                        monitor.record_error();
+                                    }
+                                }
+                                url = dir_url;
+                                probe_result = dir_result;
+                                known_status = known_dir_status;
+                                is_dir = true;
+                            }
+                            Err(e) => {
+                                if let Some(monitor) = &hooks_clone.monitor {
+                                    monitor.record_error();
+                                }
+                                eprintln!("[!] could not confirm directory {}/: {}", url, e);
+                            }
+                        }
+                    }
+                }
+            }
 
             // Decide whether to print this line based on the status code.
             // We only print “interesting” statuses: 200, 301, 302, 401, 403.
-            if is_interesting_status(probe_result.status) {
-                print_line(&url, &probe_result);
+            // A 304 is never printed: the content did not change since the last run.
+            // Neither is a redirect to a `word/` that is probed on its own (`--add-slash`, or
+            // already scheduled), or a page answered like random paths in its directory.
+            let interesting = is_interesting_status(probe_result.status)
+                && !slash_covered
+                && !soft_404_clone.is_soft(&engine_clone, &url, probe_result.status).await;
+
+            // A sudden run of findings is more likely a portal or an expired session.
+            let (spike, spiking) = hit_rate_clone.observe(interesting);
+            if let Some(spike) = &spike {
+                hitrate::print_spike(spike);
+            }
+
+            // With `--new-only`, the same URL + status seen in the previous scan is not news.
+            let already_known = known_status == Some(probe_result.status.as_u16());
+            let reported = interesting && !already_known;
+
+            // With `--measure-chunked`, give interesting responses without Content-Length a
+            // measured size (one extra capped GET) instead of "len=-".
+            if let Some(cap) = measure_cap
+                && interesting
+                && !already_known
+                && probe_result.content_length.is_none()
+            {
+                match engine_clone.measure(&url, cap).await {
+                    Ok((size, capped)) => {
+                        probe_result.content_length = Some(size.to_string());
+                        probe_result.length_capped = capped;
+                    }
+                    Err(e) => eprintln!("[!] could not measure body of {}: {}", url, e),
+                }
+            }
+
+            // Tag interesting findings with a severity from the rules.
+            let class = if interesting {
+                classifier_clone.classify(&url, probe_result.status.as_u16())
+            } else {
+                None
+            };
+
+            // With `--forms`, download HTML bodies of 200 responses and look for forms; with
+            // `--secrets`, download the bodies of 200 findings and search them (one download
+            // serves both, and lets `[AUTH]` look for password fields). A failed download only costs
+            // us this page's forms and secrets. Binary bodies are not searched: they are
+            // recorded by content type and size only (see `binary.rs`).
+            let mut page_forms: Vec<FormInfo> = Vec::new();
+            let mut page_secrets: Vec<SecretMatch> = Vec::new();
+            let mut page_body: Option<String> = None;
+            let is_ok = probe_result.status == StatusCode::OK;
+            let mut binary_type: Option<String> = match &probe_result.content_type {
+                Some(ct) if is_ok && binary::is_binary_type(ct) => Some(ct.clone()),
+                _ => None,
+            };
+            let wants_forms = parse_forms && is_ok && is_html(&probe_result);
+            let wants_secrets = secrets_clone.is_some() && is_ok && !already_known && binary_type.is_none();
+            if wants_forms || wants_secrets {
+                match engine_clone.fetch(&url).await {
+                    Ok(fetched) if binary::looks_binary(&fetched.body) => {
+                        if probe_result.content_length.is_none() {
+                            probe_result.content_length = Some(fetched.size.transferred.to_string());
+                        }
+                        binary_type = Some(fetched.content_type.unwrap_or_else(|| "application/octet-stream".to_string()));
+                    }
+                    Ok(fetched) => {
+                        let body = charset::decode(fetched.content_type.as_deref(), &fetched.body);
+                        if wants_forms {
+                            page_forms = forms::extract_forms(&url, &body);
+                        }
+                        if wants_secrets && let Some(scanner) = &secrets_clone {
+                            page_secrets = scanner.scan(&body);
+                        }
+                        page_body = Some(body);
+                    }
+                    Err(e) => eprintln!("[!] could not read body of {}: {}", url, e),
+                }
+            }
+
+            // Login pages, HTTP auth prompts and SSO hand-offs are tagged `[AUTH]`.
+            let auth_signal = match interesting {
+                true => auth::detect(&probe_result, page_body.as_deref()),
+                false => None,
+            };
+
+            // Redirects are handed back to the orchestrator instead, to be grouped by target;
+            // with `--group-variants`, so are all other findings (grouped per wave).
+            let defer = redirects::should_defer(redirect_threshold, &probe_result);
+            let hold = group_variants && !defer;
+            let mut label = class.as_ref().map(|c| c.label()).unwrap_or_default();
+            if is_dir {
+                label.insert_str(0, " [dir]");
+            }
+            if auth_signal.is_some() {
+                label.push_str(" [AUTH]");
+            }
+            if slow {
+                label.push_str(" [slow-retry]");
+            }
+            if latency_outlier {
+                label.push_str(" [SLOW]");
+            }
+            if binary_type.is_some() {
+                label.push_str(" [binary]");
+            }
+            // `--flag-archives`: a large archive or dump right at the site root.
+            let large_archive = match (archive_min, parse_length(&probe_result.content_length)) {
+                (Some(min), Some(size)) => is_ok && size >= min && binary::is_root_archive(&url),
+                _ => false,
+            };
+            if large_archive {
+                label.push_str(" [ARCHIVE]");
+            }
+            if all_statuses {
+                label.push_str(family_label(&probe_result));
+            }
+            if print_findings && interesting && !already_known && !defer && !hold {
+                print_line(&url, &probe_result, &label);
+            } else if print_findings && all_statuses && !reported {
+                print_unreported(&url, &probe_result);
+            }
+
+            if print_findings {
+                for secret in &page_secrets {
+                    let line = format!("    [!!] secret {} [{}]: {}", secret.rule, secret.severity, secret.excerpt);
+                    output::print_styled(&line, Style::Alert);
+                }
             }
 
-            // Task completed successfully.
-            Ok(())
+            // Task completed successfully; report the outcome for the results database.
+            let mut entry = ResultEntry {
+                url,
+                status: probe_result.status.as_u16(),
+                content_length: parse_length(&probe_result.content_length),
+                content_type: None,
+                location: probe_result.location.clone(),
+                etag: probe_result.etag.clone(),
+                last_modified: probe_result.last_modified.clone(),
+                severity: class.as_ref().map(|c| c.severity),
+                tags: class.map(|c| c.tags).unwrap_or_default(),
+                triage: None,
+                headers: None,
+                cookies: Vec::new(),
+                diff_from_baseline: None,
+                redirected_from: None,
+                permuted_from: None,
+                verification: None,
+                response_headers: None,
+            };
+            // Tracked headers are kept with the finding; a 304 brings the ones it carries.
+            let not_modified = probe_result.status == StatusCode::NOT_MODIFIED;
+            if interesting || not_modified {
+                entry.response_headers = Some(probe_result.tracked_headers.clone());
+            }
+            // `--incremental`: what changed since the stored run (see `headerdiff.rs`).
+            if print_findings
+                && (interesting || not_modified)
+                && let Some(before) = stored.as_ref().and_then(|s| s.response_headers.as_ref())
+            {
+                let changes = headerdiff::compare(before, &probe_result.tracked_headers, not_modified);
+                headerdiff::print_changes(&entry.url, &changes);
+            }
+            if interesting {
+                entry.cookies = probe_result.set_cookies.iter().filter_map(|c| cookies::parse(c)).collect();
+                entry.diff_from_baseline = not_found.map(|b| b.compare(probe_result.status, entry.content_length));
+            }
+            // `--header-audit`: keep the security headers of 200 findings.
+            if header_audit && interesting && probe_result.status == StatusCode::OK {
+                let mut audit = probe_result.security.clone();
+                if !entry.url.starts_with("https://") {
+                    audit.hsts = None;
+                }
+                entry.headers = Some(audit);
+            }
+            if let Some(signal) = auth_signal {
+                entry.tags.push("auth".to_string());
+                entry.tags.push(signal.tag().to_string());
+            }
+            if slow {
+                entry.tags.push("slow-retry".to_string());
+            }
+            if latency_outlier && interesting {
+                entry.tags.push("slow".to_string());
+            }
+            if spiking && interesting {
+                entry.tags.push("hit-rate-spike".to_string());
+            }
+            if let Some(content_type) = binary_type {
+                entry.content_type = Some(content_type);
+                entry.tags.push("binary".to_string());
+            }
+            if large_archive {
+                entry.severity = Some(entry.severity.map_or(Severity::High, |s| s.max(Severity::High)));
+                entry.tags.push("large-archive".to_string());
+            }
+            // Secrets raise the finding's severity and are kept as tags.
+            for secret in &page_secrets {
+                entry.severity = Some(entry.severity.map_or(secret.severity, |s| s.max(secret.severity)));
+                let tag = format!("secret:{}", secret.rule);
+                if !entry.tags.contains(&tag) {
+                    entry.tags.push(tag);
+                }
+            }
+            // Weak cookies of sensitive findings go under the finding line.
+            if print_findings && interesting && !already_known && !defer && !hold {
+                cookies::print_weak(&entry);
+            }
+            // The monitor hears about findings as they happen (live counters, `serve` events).
+            if reported && let Some(monitor) = &hooks_clone.monitor {
+                monitor.record_finding(&entry);
+            }
+            let length_capped = probe_result.length_capped;
+            let mut deferred: Option<HttpSummary> = None;
+            let mut held: Option<HeldFinding> = None;
+            if interesting && !already_known && defer {
+                deferred = Some(probe_result);
+            } else if print_findings && reported && hold {
+                held = Some(HeldFinding {
+                    url: entry.url.clone(),
+                    summary: probe_result,
+                    label,
+                });
+            }
+            Ok(Some(ProbeOutcome {
+                entry,
+                interesting,
+                reported,
+                forms: page_forms,
+                secrets: page_secrets,
+                deferred,
+                held,
+                latency,
+                confirmed_redirect,
+                length_capped,
+            }))
         });
 
         // Keep the task handle to await it later.
         jobs.push(handle);
     }
 
-    // 5) Await all spawned tasks and propagate the first error we encounter.
-    //    This ensures that if a task returns an error (e.g., HTTP client error),
-    //    we abort the scan with a clear message rather than silently ignoring it.
-    for handle in jobs {
-        // `handle.await` can fail if the task panicked or was cancelled.
-        match handle.await {
+    // Await all spawned tasks and propagate the first error we encounter.
+    // This ensures that if a task returns an error (e.g., HTTP client error),
+    // we abort the scan with a clear message rather than silently ignoring it.
+    // If the scan is cancelled meanwhile, the probes still in flight are aborted; the ones
+    // that already finished are kept.
+    let mut outcomes: Vec<ProbeOutcome> = Vec::with_capacity(jobs.len());
+    let mut aborted: usize = 0;
+    for mut handle in jobs {
+        let cancelled = match &cancel {
+            Some(token) => token.is_cancelled(),
+            None => false,
+        };
+        if cancelled && !handle.is_finished() {
+            handle.abort();
+            aborted += 1;
+            continue;
+        }
+        let joined = match &cancel {
+            Some(token) if !cancelled => tokio::select! {
+                joined = &mut handle => joined,
+                _ = token.cancelled() => {
+                    if !handle.is_finished() {
+                        handle.abort();
+                        aborted += 1;
+                        continue;
+                    }
+                    handle.await
+                }
+            },
+            _ => handle.await,
+        };
+        // `joined` is an error if the task panicked or was cancelled.
+        match joined {
             // The task ran to completion. Now inspect the inner Result.
             Ok(inner_result) => {
                 // We avoid the `if let` shortcut and use a full `match` for clarity.
                 match inner_result {
-                    Ok(()) => {
-                        // Task returned Ok — nothing to do.
+                    Ok(Some(outcome)) => {
+                        // Task returned Ok — hand its outcome to the orchestrator.
+                        outcomes.push(outcome);
+                    }
+                    Ok(None) => {
+                        // The probe got no answer and waits for another try.
                     }
                     Err(e) => {
                         // Task returned an application error (e.g., HTTP or I/O).
@@ -127,44 +1337,374 @@ pub async fn scan(client: &Client, base: &str, args: &Args) -> Result<(), Dirust
             }
         }
     }
+    if aborted > 0 {
+        output::info(&format!("[*] cancelled: {} probe(s) in flight aborted", aborted));
+    }
 
-    // If we get here, all tasks finished and none reported an error.
-    Ok(())
+    Ok(outcomes)
+}
+
+/// A permit of `semaphore`; `None` once `cancel` is cancelled (or if the semaphore was closed,
+/// which is logged).
+async fn acquire_permit(semaphore: &Arc<Semaphore>, cancel: Option<&CancellationToken>) -> Option<OwnedSemaphorePermit> {
+    let acquired = match cancel {
+        Some(token) => tokio::select! {
+            acquired = semaphore.clone().acquire_owned() => acquired,
+            _ = token.cancelled() => return None,
+        },
+        None => semaphore.clone().acquire_owned().await,
+    };
+    match acquired {
+        Ok(permit) => Some(permit),
+        Err(_) => {
+            eprintln!("[!] failed to acquire semaphore permit");
+            None
+        }
+    }
+}
+
+/// What a scan knows about its base before the first probe.
+struct Calibration {
+    /// The random-path baseline (none when it could not be captured).
+    not_found: Option<Baseline>,
+    /// HEAD is allowed (a random path did not answer it with 405).
+    head_supported: bool,
+}
+
+/// Measure the calibration of `base`, or take it from a fresh `--profile-cache` profile
+/// (unless `--recalibrate`). What is measured is stored for the next scan.
+async fn calibrate(engine: &ProbeEngine, base: &str, args: &ScanArgs, profiles: Option<&ProfileCache>) -> Calibration {
+    if let Some(cache) = profiles
+        && !args.recalibrate
+        && let Some(profile) = cache.fresh(base)
+        && let Some(not_found) = profile.not_found.as_ref().and_then(Baseline::from_record)
+        && let Some(head_supported) = profile.head_supported
+    {
+        output::info(&format!(
+            "[*] profile: calibration of {} from {} (random path {} len={} (±{}), HEAD {}); --recalibrate measures again",
+            base,
+            iso_datetime(profile.updated_at),
+            not_found.status.as_u16(),
+            not_found.length,
+            not_found.tolerance,
+            if head_supported { "allowed" } else { "not allowed" }
+        ));
+        return Calibration {
+            not_found: Some(not_found),
+            head_supported,
+        };
+    }
+
+    let not_found = capture_baseline(engine, base).await;
+    let head_supported = match engine.send_once(&format!("{}{}", base, baseline::random_token()), false).await {
+        Ok(summary) => summary.status != StatusCode::METHOD_NOT_ALLOWED,
+        // Unknown: keep HEAD, the per-probe fallback still applies.
+        Err(_) => true,
+    };
+    if !head_supported && !args.http.get {
+        output::info("[*] calibration: HEAD is answered with 405 here; probing with GET");
+    }
+    if let Some(cache) = profiles {
+        cache.update(base, |profile| {
+            profile.not_found = not_found.map(|b| b.record());
+            profile.head_supported = Some(head_supported);
+        });
+    }
+    Calibration {
+        not_found,
+        head_supported,
+    }
+}
+
+/// Capture what a random path under `base` looks like (two samples, see `fuzz::baseline`),
+/// and fetch `base` itself to tell a catch-all route apart.
+///
+/// Random paths that answer with a finding status, or just like a 2xx/3xx `base`, mean the
+/// server answers every path; the scan goes on with a warning, and findings answered like the
+/// random paths are dropped as soft 404s (see `soft404.rs`). A capture that fails only costs
+/// findings their `diff_from_baseline`, and the base its soft-404 check.
+async fn capture_baseline(engine: &ProbeEngine, base: &str) -> Option<Baseline> {
+    let root = match engine.fetch(base).await {
+        Ok(fetched) => Fingerprint::of(&fetched),
+        Err(e) => {
+            eprintln!("[!] baseline: could not fetch {}: {}", base, e);
+            return None;
+        }
+    };
+    let mut samples: Vec<Fingerprint> = Vec::with_capacity(2);
+    for _ in 0..2 {
+        match engine.fetch(&format!("{}{}", base, baseline::random_token())).await {
+            Ok(fetched) => samples.push(Fingerprint::of(&fetched)),
+            Err(e) => {
+                eprintln!("[!] baseline: could not fetch a random path under {}: {}", base, e);
+                return None;
+            }
+        }
+    }
+    let not_found = baseline::from_fingerprints(samples[0], samples[1]);
+    output::info(&format!(
+        "[*] baseline: {} len={}, random path {} len={} (±{})",
+        root.status.as_u16(),
+        root.length,
+        not_found.status.as_u16(),
+        not_found.length,
+        not_found.tolerance
+    ));
+
+    // A base that itself answers 404 (no index page) matching random paths is just a
+    // server that 404s; only a 2xx/3xx base answered again for every path is a catch-all.
+    let root_answers = root.status.is_success() || root.status.is_redirection();
+    if root_answers && not_found.diff(&root).is_none() {
+        eprintln!(
+            "[!] baseline: random paths answer like {} itself (catch-all route); findings answered the same are not reported",
+            base
+        );
+    } else if is_interesting_status(not_found.status) {
+        eprintln!(
+            "[!] baseline: random paths answer {} (wildcard responses); findings answered the same are not reported",
+            not_found.status.as_u16()
+        );
+    }
+    Some(not_found)
 }
 
 /// Print one result line in a consistent, grep-friendly format.
 ///
 /// Format:
-///   [<unix_ts>] <status> len=<Content-Length or "-">  <url> [-> <Location>]
+///   [<time>] <status> len=<Content-Length, measured size, or "-">  <url> [-> <Location>][ [SEVERITY tags]]
 ///
 /// Examples:
-///   [1712345678] 200 len=1234  https://example.com/admin
-///   [1712345679] 301 len=-     https://example.com/admin -> https://example.com/admin/
-fn print_line(url: &str, summary: &HttpSummary) {
+///   [2024-04-05T19:21:18.042Z] 200 len=1234  https://example.com/admin [HIGH admin-panel]
+///   [2024-04-05T19:21:19.107Z] 301 len=-     https://example.com/admin -> https://example.com/admin/
+///
+/// `[<time>] ` follows `--time-format` (see `output::timestamp_prefix`).
+///
+/// `label` is the severity suffix (see `Classification::label`), or "" when no rule matched.
+fn print_line(url: &str, summary: &HttpSummary, label: &str) {
+    let status = summary.status.as_u16();
+    output::finding(&format_line(url, summary, label), Style::for_status(status), Some(url));
+}
+
+/// Print a response that is not a finding, for `--all-statuses` (404s dimmed).
+///
+/// Same format as `print_line`; hidden with `--silent`, which prints findings only.
+fn print_unreported(url: &str, summary: &HttpSummary) {
+    let style = match summary.status.as_u16() {
+        404 => Style::Dim,
+        status => Style::for_status(status),
+    };
+    output::print_styled(&format_line(url, summary, family_label(summary)), style);
+}
+
+/// ` [IPv4]` / ` [IPv6]` for the address family that answered (`-v`), or "" when unknown.
+fn family_label(summary: &HttpSummary) -> &'static str {
+    match summary.remote_addr {
+        Some(SocketAddr::V4(_)) => " [IPv4]",
+        Some(SocketAddr::V6(_)) => " [IPv6]",
+        None => "",
+    }
+}
+
+/// The text of a result line (see `print_line`).
+fn format_line(url: &str, summary: &HttpSummary, label: &str) -> String {
     // Prepare values for printing:
-    // - UNIX timestamp (seconds) for easy chronological sorting
+    // - timestamp prefix in the `--time-format` chosen (RFC 3339 by default)
     // - status code as a u16 (e.g., 200, 301)
     // - content-length as a string, or "-" if unknown
-    let ts = timestamp_seconds();
+    let ts = output::timestamp_prefix();
     let status = summary.status.as_u16();
+    // A measured size that hit the `--measure-chunked` cap is a lower bound: "4096+".
     let len_str = match &summary.content_length {
-        Some(s) => s.as_str(),
-        None => "-",
+        Some(s) if summary.length_capped => format!("{}+", s),
+        Some(s) => s.clone(),
+        None => "-".to_string(),
     };
 
     // Print with or without the redirect target depending on whether Location is present.
     match &summary.location {
-        Some(loc) => {
-            println!(
-                "[{}] {:>3} len={}  {} -> {}",
-                ts, status, len_str, url, loc
-            );
+        Some(loc) => format!("{}{:>3} len={}  {} -> {}{}", ts, status, len_str, url, loc, label),
+        None => format!("{}{:>3} len={}  {}{}", ts, status, len_str, url, label),
+    }
+}
+
+/// Rebuild the severity suffix (and `[AUTH]`) of a result line from a stored entry ("" when
+/// untagged).
+pub fn severity_label(entry: &ResultEntry) -> String {
+    let mut label = match entry.severity {
+        Some(severity) => Classification {
+            severity,
+            tags: entry.tags.iter().filter(|tag| !tag.starts_with("auth")).cloned().collect(),
+        }
+        .label(),
+        None => String::new(),
+    };
+    if entry.tags.iter().any(|tag| tag == "auth") {
+        label.push_str(" [AUTH]");
+    }
+    label
+}
+
+/// Normalize a textual `Content-Length` into a number for machine-readable output.
+///
+/// Missing or non-numeric values become `None` (never a misleading 0).
+fn parse_length(raw: &Option<String>) -> Option<u64> {
+    match raw {
+        Some(s) => s.trim().parse::<u64>().ok(),
+        None => None,
+    }
+}
+
+/// Return `true` when the response declares an HTML (or XHTML) body, or declares nothing.
+///
+/// Servers that omit Content-Type are given the benefit of the doubt.
+fn is_html(summary: &HttpSummary) -> bool {
+    match &summary.content_type {
+        Some(ct) => {
+            let lower = ct.to_ascii_lowercase();
+            lower.contains("text/html") || lower.contains("xhtml")
+        }
+        None => true,
+    }
+}
+
+/// Targets one wordlist entry turns into at most, with `extensions` extensions: as-is, one per
+/// extension, the `--add-slash` form, each with its `--unsafe-encodings` variants.
+pub fn targets_per_word(args: &ScanArgs, extensions: usize) -> usize {
+    let variants_per_target: usize = if args.unsafe_encodings { 1 + encodings::MAX_VARIANTS } else { 1 };
+    (1 + extensions + usize::from(args.add_slash)) * variants_per_target
+}
+
+/// The entries of `chunk` in normalized form (`wordlist::normalize`), without the ones seen
+/// before; `duplicates` counts what was dropped. With `--add-slash`, `admin/` is a duplicate
+/// of an earlier `admin` (which already probes the slash form). Directives pass through.
+fn unique_words(chunk: Vec<String>, seen: &TargetDedup, add_slash: bool, duplicates: &mut usize) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::with_capacity(chunk.len());
+    for entry in chunk {
+        if entry.starts_with("#!") {
+            unique.push(entry);
+            continue;
+        }
+        let word = match wordlist::normalize(&entry) {
+            Some(w) => w,
+            None => continue,
+        };
+        let covered = add_slash && word.ends_with('/') && seen.contains(word.trim_end_matches('/'));
+        if covered || !seen.insert(&word) {
+            *duplicates += 1;
+            continue;
+        }
+        unique.push(word);
+    }
+    unique
+}
+
+/// Print what `--adaptive-exts` decided, e.g.:
+///   [*] adaptive-exts: 312 extension probes, hits: .php (4); dropping .asp, .jsp
+fn print_ext_decision(decision: &Decision, mode: AdaptiveExts) {
+    let hits: Vec<String> = decision.keep.iter().map(|(ext, count)| format!("{} ({})", ext, count)).collect();
+    let hits = if hits.is_empty() { "none".to_string() } else { hits.join(", ") };
+    let action = match (decision.unmatched.is_empty(), mode) {
+        (true, _) => "keeping all extensions".to_string(),
+        (false, AdaptiveExts::Drop) => format!("dropping {}", decision.unmatched.join(", ")),
+        (false, AdaptiveExts::Defer) => format!("deferring {} to the end", decision.unmatched.join(", ")),
+    };
+    output::info(&format!(
+        "[*] adaptive-exts: {} extension probes, hits: {}; {}",
+        decision.probes, hits, action
+    ));
+}
+
+/// Announce an extension `--learn-exts` learned from `url`.
+fn print_learned_ext(ext: &str, url: &str) {
+    output::info(&format!("[*] learn-exts: learned {} from {}, tried on the words from now on", ext, url));
+}
+
+/// Print the "secrets found" section for `--secrets`: every match again, most severe first,
+/// so they do not get lost between thousands of status lines.
+///
+/// Format:
+///   [!!] secrets found (<n>)
+///       <SEVERITY> <rule>  <excerpt>  (in <url>)
+fn print_secrets(secrets: &[(String, SecretMatch)]) {
+    let mut sorted: Vec<&(String, SecretMatch)> = secrets.iter().collect();
+    sorted.sort_by(|a, b| b.1.severity.cmp(&a.1.severity).then_with(|| a.0.cmp(&b.0)));
+
+    output::info(&output::paint(&format!("[!!] secrets found ({})", sorted.len()), Style::Alert));
+    for (url, secret) in sorted {
+        output::info(&format!("    {} {}  {}  (in {})", secret.severity, secret.rule, secret.excerpt, url));
+    }
+}
+
+/// Print the "parameters discovered" section for `--forms`.
+///
+/// The same form often appears on many pages (e.g., a login box in a shared header), so
+/// identical (method, action, inputs) combinations are printed once, with the first page
+/// they were seen on.
+///
+/// Format:
+///   [*] parameters discovered (<n> forms)
+///       <METHOD> <action>  params=<a,b,c>  (from <page>)
+fn print_forms(forms: &[FormInfo]) {
+    let unique = unique_forms(forms);
+
+    output::info(&format!("[*] parameters discovered ({} forms)", unique.len()));
+    for form in unique {
+        let params = if form.inputs.is_empty() {
+            "-".to_string()
+        } else {
+            form.inputs.join(",")
+        };
+        output::info(&format!(
+            "    {} {}  params={}  (from {})",
+            form.method, form.action, params, form.page
+        ));
+    }
+}
+
+/// Forms with distinct (method, action, inputs), in order of first appearance.
+fn unique_forms(forms: &[FormInfo]) -> Vec<&FormInfo> {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut unique: Vec<&FormInfo> = Vec::new();
+
+    for form in forms {
+        let key = format!("{} {} {}", form.method, form.action, form.inputs.join(","));
+        if seen.insert(key) {
+            unique.push(form);
         }
-        None => {
-            println!(
-                "[{}] {:>3} len={}  {}",
-                ts, status, len_str, url
-            );
+    }
+    unique
+}
+
+/// `--reflect`: send canary values to the inputs of every distinct form whose action is
+/// under `base` (and allowed by `--scope`), and print the parameters that are echoed back.
+async fn reflect_forms(
+    client: &Client,
+    base: &str,
+    args: &ScanArgs,
+    scope: Option<&Scope>,
+    forms: &[FormInfo],
+) -> Result<(), DirustError> {
+    let mut endpoints: Vec<reflect::Endpoint> = Vec::new();
+    for form in unique_forms(forms) {
+        let in_scope = form.action.starts_with(base) && scope.is_none_or(|scope| scope.allows(&form.action));
+        if in_scope && !form.inputs.is_empty() {
+            endpoints.push(reflect::Endpoint {
+                method: form.method.clone(),
+                url: form.action.clone(),
+                params: form.inputs.clone(),
+            });
         }
     }
+    if endpoints.is_empty() {
+        return Ok(());
+    }
+
+    let tested: usize = endpoints.iter().map(|e| e.params.len()).sum();
+    let forms_tested = endpoints.len();
+    let reflected = reflect::run(client, args.http.concurrency, endpoints).await?;
+    output::info(&format!(
+        "[*] reflect: {} of {} parameter(s) in {} form(s) reflected their value",
+        reflected, tested, forms_tested
+    ));
+    Ok(())
 }
@@ -0,0 +1,292 @@
+//! src/scanner/monitor.rs
+//!
+//! Purpose:
+//!   Let code outside the scanner watch and steer a running scan:
+//!     - progress counters (targets scheduled / probed, failed probes, findings reported per
+//!       status, the base URL being scanned)
+//!     - an estimate of the work left that accounts for work added on the way: targets still
+//!       queued, plus the wordlist entries not read yet times the targets each entry turned
+//!       into so far. Targets discovered during the scan (`--follow-forms` actions, injected
+//!       words, the `--adaptive-exts=defer` pass) are counted separately, so progress is
+//!       `done / (done + remaining)` and never goes past 100%
+//!     - every reported finding, kept in a list and broadcast to subscribers as it happens
+//!     - cancellation through a `CancellationToken`: once `cancel()` is called (e.g. by
+//!       `dirust serve` on `DELETE /scans/<id>`) or the token from `cancellation()` /
+//!       `with_cancellation()` is cancelled, no new probe is started, the probes in flight
+//!       are aborted, and the scan ends normally with the results it has (summary included; aborted probes are neither
+//!       reported nor recorded, so `--state` / `--checkpoint` runs probe them again)
+//!     - extra words for the running scan (`inject()`), probed before its next wordlist chunk
+//!
+//! Used by `dirust serve` (REST API): one monitor per submitted scan job, by the live
+//! status line of terminal scans (`scanner/live.rs`) and `--progress-ndjson` events
+//! (`scanner/events.rs`), and by programs using the library (`ScanHooks::monitor`).
+
+use crate::schema::ResultEntry;
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Mutex,
+};
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+/// How many undelivered events a slow subscriber may lag behind before it skips some.
+const EVENT_BUFFER: usize = 1024;
+
+/// Something that happened during a monitored scan.
+#[derive(Debug, Clone)]
+pub enum MonitorEvent {
+    /// A finding was reported (printed, or held back as a redirect to be grouped).
+    Finding(Box<ResultEntry>),
+    /// The scan is over (finished, failed or cancelled); no more events follow.
+    Finished,
+}
+
+/// Statuses counted separately in `Progress::by_status` (the "interesting" ones).
+pub const COUNTED_STATUSES: [u16; 5] = [200, 301, 302, 401, 403];
+
+/// Point-in-time copy of the counters.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    /// Targets scheduled so far (wordlist and discovered).
+    pub total: usize,
+    pub done: usize,
+    /// Of `total`, the targets discovered during the scan rather than built from the wordlist.
+    pub discovered: usize,
+    /// Estimated targets left (`None` while the wordlist size is unknown: stdin, `url(...)`).
+    pub remaining: Option<usize>,
+    pub findings: usize,
+    /// Probes answered with a server error (5xx) or 429, or not answered at all.
+    pub errors: usize,
+    /// Findings per status, in `COUNTED_STATUSES` order.
+    pub by_status: [usize; 5],
+    /// Base URL currently being scanned (`None` before the first one starts).
+    pub directory: Option<String>,
+}
+
+/// Shared view of one scan; cheap to query from any task (wrap it in an `Arc`).
+pub struct ScanMonitor {
+    total: AtomicUsize,
+    done: AtomicUsize,
+    discovered: AtomicUsize,
+    errors: AtomicUsize,
+    /// Wordlist entries the scan will read (0: unknown), entries read so far, and the
+    /// targets built from them.
+    words_planned: AtomicUsize,
+    words_read: AtomicUsize,
+    word_targets: AtomicUsize,
+    cancel: CancellationToken,
+    finished: AtomicBool,
+    findings: Mutex<Vec<ResultEntry>>,
+    directory: Mutex<Option<String>>,
+    injected: Mutex<Vec<String>>,
+    events: broadcast::Sender<MonitorEvent>,
+}
+
+impl Default for ScanMonitor {
+    fn default() -> ScanMonitor {
+        ScanMonitor::new()
+    }
+}
+
+impl ScanMonitor {
+    /// A monitor for a scan that has not scheduled anything yet.
+    pub fn new() -> ScanMonitor {
+        ScanMonitor::with_cancellation(CancellationToken::new())
+    }
+
+    /// A monitor whose scan stops when `cancel` is cancelled (pass a child token to stop
+    /// several scans together).
+    pub fn with_cancellation(cancel: CancellationToken) -> ScanMonitor {
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        ScanMonitor {
+            total: AtomicUsize::new(0),
+            done: AtomicUsize::new(0),
+            discovered: AtomicUsize::new(0),
+            errors: AtomicUsize::new(0),
+            words_planned: AtomicUsize::new(0),
+            words_read: AtomicUsize::new(0),
+            word_targets: AtomicUsize::new(0),
+            cancel,
+            finished: AtomicBool::new(false),
+            findings: Mutex::new(Vec::new()),
+            directory: Mutex::new(None),
+            injected: Mutex::new(Vec::new()),
+            events,
+        }
+    }
+
+    /// Count `n` more targets built from the wordlist as scheduled (each wave adds its own).
+    pub fn add_total(&self, n: usize) {
+        self.total.fetch_add(n, Ordering::Relaxed);
+        self.word_targets.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Count `n` more targets discovered during the scan as scheduled.
+    pub fn add_discovered(&self, n: usize) {
+        self.total.fetch_add(n, Ordering::Relaxed);
+        self.discovered.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Announce `n` more wordlist entries to be read (a known wordlist size, per base).
+    pub fn plan_words(&self, n: usize) {
+        self.words_planned.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Take back `n` planned entries that will not be read (skipped by `--checkpoint`).
+    pub fn unplan_words(&self, n: usize) {
+        let _ = self
+            .words_planned
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |planned| Some(planned.saturating_sub(n)));
+    }
+
+    /// Count `n` wordlist entries as read.
+    pub fn record_words(&self, n: usize) {
+        self.words_read.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Record the base URL the scan is working on now.
+    pub fn set_directory(&self, directory: &str) {
+        let mut current = match self.directory.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        *current = Some(directory.to_string());
+    }
+
+    /// Count one finished probe.
+    pub fn record_probe(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count one probe that failed: a 5xx / 429 answer, or none at all.
+    pub fn record_error(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Store and broadcast one reported finding.
+    pub fn record_finding(&self, entry: &ResultEntry) {
+        let mut findings = match self.findings.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        findings.push(entry.clone());
+        // No subscribers is fine: the list above keeps the finding anyway.
+        let _ = self.events.send(MonitorEvent::Finding(Box::new(entry.clone())));
+    }
+
+    /// Queue words for the scan to probe next (`--inject-pipe`, `POST /scans/<id>/words`).
+    pub fn inject(&self, words: Vec<String>) {
+        let mut injected = match self.injected.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        injected.extend(words);
+    }
+
+    /// Take up to `max` queued words, oldest first.
+    pub fn take_injected(&self, max: usize) -> Vec<String> {
+        let mut injected = match self.injected.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let count = max.min(injected.len());
+        injected.drain(..count).collect()
+    }
+
+    /// Mark the scan as over and tell subscribers.
+    pub fn finish(&self) {
+        self.finished.store(true, Ordering::Relaxed);
+        let _ = self.events.send(MonitorEvent::Finished);
+    }
+
+    /// Stop the scan: no new probes, the ones in flight are aborted.
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Whether the scan was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// The scan's token, for cancelling it from another task.
+    pub fn cancellation(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// Whether `finish()` was called.
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+
+    /// Current counters.
+    pub fn progress(&self) -> Progress {
+        let (findings, by_status) = {
+            let findings = match self.findings.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            let mut by_status = [0usize; 5];
+            for entry in findings.iter() {
+                if let Some(i) = COUNTED_STATUSES.iter().position(|&s| s == entry.status) {
+                    by_status[i] += 1;
+                }
+            }
+            (findings.len(), by_status)
+        };
+        let directory = match self.directory.lock() {
+            Ok(guard) => guard.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        };
+        let total = self.total.load(Ordering::Relaxed);
+        let done = self.done.load(Ordering::Relaxed);
+        Progress {
+            total,
+            done,
+            discovered: self.discovered.load(Ordering::Relaxed),
+            remaining: self.remaining(total, done),
+            findings,
+            errors: self.errors.load(Ordering::Relaxed),
+            by_status,
+            directory,
+        }
+    }
+
+    /// Targets left: the ones queued, plus the unread wordlist entries at the number of
+    /// targets per entry seen so far (`None` while the wordlist size is unknown).
+    fn remaining(&self, total: usize, done: usize) -> Option<usize> {
+        let planned = self.words_planned.load(Ordering::Relaxed);
+        if planned == 0 {
+            return None;
+        }
+        let read = self.words_read.load(Ordering::Relaxed);
+        let per_word = match read {
+            0 => 1.0,
+            _ => self.word_targets.load(Ordering::Relaxed) as f64 / read as f64,
+        };
+        let unread = planned.saturating_sub(read) as f64 * per_word;
+        Some(total.saturating_sub(done) + unread.round() as usize)
+    }
+
+    /// Copy of every finding reported so far, in report order.
+    pub fn findings(&self) -> Vec<ResultEntry> {
+        match self.findings.lock() {
+            Ok(guard) => guard.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Copy of the findings reported after the first `skip` ones, in report order.
+    pub fn findings_since(&self, skip: usize) -> Vec<ResultEntry> {
+        let findings = match self.findings.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        findings.iter().skip(skip).cloned().collect()
+    }
+
+    /// Receive events from now on (subscribe before reading `findings()` to miss nothing).
+    pub fn subscribe(&self) -> broadcast::Receiver<MonitorEvent> {
+        self.events.subscribe()
+    }
+}
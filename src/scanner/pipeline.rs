@@ -0,0 +1,287 @@
+//! src/scanner/pipeline.rs
+//!
+//! Purpose:
+//!   `--engine fast --pipeline N`: HTTP/1.1 pipelining for probes. Instead of one request
+//!   per connection round trip, up to N requests are written back to back on a connection and
+//!   their responses read afterwards, in order. Against a server far away, a connection then
+//!   moves N probes per round trip; with `-c 500 --pipeline 10`, 50 connections carry the load
+//!   of 500 keep-alive ones.
+//!
+//! How:
+//!   - Connections are opened with `raw::RawConnector` (TCP_NODELAY, TLS with `http/1.1`
+//!     ALPN, `--tls-impersonate`, `-4` / `-6`), and requests are written byte for byte: the
+//!     request line, `Host`, the headers the middleware chain built, and the empty line.
+//!   - Each origin (scheme, host, port) gets `ceil(-c / N)` lanes. A lane owns one connection
+//!     and a queue of probes: it takes up to N queued probes, writes them in one go, then
+//!     reads one response per probe and hands each to its caller.
+//!   - A server may answer part of a batch and close (`Connection: close`, a keep-alive
+//!     request limit). The probes it did not answer are written again on a new connection;
+//!     a probe that never gets an answer from `MAX_ATTEMPTS` fresh connections fails.
+//!   - The per-probe timeout (`--timeout`, `--slow-retry-timeout`) applies to reading each
+//!     response, counted from the end of the one before it. A probe that times out takes the
+//!     connection with it (later responses could no longer be matched to their requests).
+//!
+//! Notes:
+//!   - HEAD probes (the default) pipeline best: their responses have no body. GET responses
+//!     are read to their end to find where the next one starts.
+//!   - Servers must process pipelined requests in order (RFC 9112); ones that close after
+//!     every response make the lane fall back to one probe per connection.
+
+use super::raw::{RawConnection, RawConnector, RawResponse};
+use crate::error::DirustError;
+use reqwest::{header::HeaderMap, Url};
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::sync::{mpsc, oneshot};
+
+/// Fresh connections a probe may be written to without getting an answer.
+const MAX_ATTEMPTS: u32 = 2;
+
+/// A response, and the address of the server that sent it.
+pub type Reply = Result<(RawResponse, Option<SocketAddr>), DirustError>;
+
+/// One probe waiting in a lane.
+struct Job {
+    /// The request, ready to be written.
+    bytes: Vec<u8>,
+    /// A HEAD request (its response has no body).
+    head: bool,
+    url: String,
+    timeout: Duration,
+    /// Connections this probe was written to that closed or failed before answering anything.
+    attempts: u32,
+    reply: oneshot::Sender<Reply>,
+}
+
+/// The lanes of one origin; probes are spread over them in turn.
+struct Lanes {
+    senders: Vec<mpsc::Sender<Job>>,
+    next: AtomicUsize,
+}
+
+/// Pipelined HTTP/1.1 connections to every origin a scan probes.
+pub struct Pipeline {
+    connector: RawConnector,
+    /// Probes written per connection before their responses are read.
+    depth: usize,
+    /// Lanes per origin.
+    lanes: usize,
+    /// Send header names Title-Cased (`--evade`).
+    title_case: bool,
+    origins: Mutex<HashMap<String, Arc<Lanes>>>,
+}
+
+impl Pipeline {
+    /// Pipelines of `depth` probes, with enough lanes per origin for `concurrency` probes
+    /// in flight.
+    pub fn new(connector: RawConnector, depth: usize, concurrency: usize, title_case: bool) -> Pipeline {
+        Pipeline {
+            connector,
+            depth,
+            lanes: concurrency.div_ceil(depth).max(1),
+            title_case,
+            origins: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Queue a HEAD (GET with `use_get`) request for `url` with `headers` and wait for its
+    /// response.
+    pub async fn send(&self, url: &str, use_get: bool, headers: &HeaderMap, timeout: Duration) -> Reply {
+        let parsed = Url::parse(url).map_err(|e| DirustError::Engine(format!("{}: {}", url, e)))?;
+        let (reply, answer) = oneshot::channel();
+        let job = Job {
+            bytes: render(&parsed, use_get, headers, self.title_case),
+            head: !use_get,
+            url: url.to_string(),
+            timeout,
+            attempts: 0,
+            reply,
+        };
+        let lanes = self.lanes_for(&parsed);
+        let lane = lanes.next.fetch_add(1, Ordering::Relaxed) % lanes.senders.len();
+        if lanes.senders[lane].send(job).await.is_err() {
+            return Err(DirustError::Engine(format!("{}: pipeline lane stopped", url)));
+        }
+        match answer.await {
+            Ok(reply) => reply,
+            Err(_) => Err(DirustError::Engine(format!("{}: pipeline lane stopped", url))),
+        }
+    }
+
+    /// The lanes of `url`'s origin, started on first use.
+    fn lanes_for(&self, url: &Url) -> Arc<Lanes> {
+        let origin = url.origin().ascii_serialization();
+        let mut origins = match self.origins.lock() {
+            Ok(g) => g,
+            Err(p) => p.into_inner(),
+        };
+        let lanes = origins.entry(origin).or_insert_with(|| {
+            let mut senders = Vec::with_capacity(self.lanes);
+            for _ in 0..self.lanes {
+                let (sender, receiver) = mpsc::channel(self.depth);
+                tokio::spawn(run_lane(self.connector.clone(), url.clone(), self.depth, receiver));
+                senders.push(sender);
+            }
+            Arc::new(Lanes {
+                senders,
+                next: AtomicUsize::new(0),
+            })
+        });
+        Arc::clone(lanes)
+    }
+}
+
+/// Serve the probes of one lane until the pipeline is dropped (every sender gone).
+async fn run_lane(connector: RawConnector, origin: Url, depth: usize, mut jobs: mpsc::Receiver<Job>) {
+    let mut connection: Option<RawConnection> = None;
+    // Probes to write: new ones, and ones a closed connection left unanswered.
+    let mut pending: VecDeque<Job> = VecDeque::new();
+    loop {
+        if pending.is_empty() {
+            match jobs.recv().await {
+                Some(job) => pending.push_back(job),
+                None => return,
+            }
+        }
+        while pending.len() < depth {
+            match jobs.try_recv() {
+                Ok(job) => pending.push_back(job),
+                Err(_) => break,
+            }
+        }
+        // Callers that gave up (cancelled scan) need no answer.
+        pending.retain(|job| !job.reply.is_closed());
+        if pending.is_empty() {
+            continue;
+        }
+
+        let mut conn = match connection.take() {
+            Some(conn) => conn,
+            None => match connector.connect(&origin).await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    for job in pending.drain(..) {
+                        let error = DirustError::Engine(format!("{}: connect: {}", job.url, e));
+                        let _ = job.reply.send(Err(error));
+                    }
+                    continue;
+                }
+            },
+        };
+
+        let batch: Vec<Job> = pending.drain(..depth.min(pending.len())).collect();
+        let bytes: Vec<u8> = batch.iter().flat_map(|job| job.bytes.iter().copied()).collect();
+        if let Err(e) = conn.write(&bytes).await {
+            for job in batch {
+                retry(job, true, &e.to_string(), &mut pending);
+            }
+            continue;
+        }
+
+        let peer = conn.peer_addr();
+        let mut answered: usize = 0;
+        let mut reusable = true;
+        let mut batch = batch.into_iter();
+        for job in batch.by_ref() {
+            match tokio::time::timeout(job.timeout, conn.read_response(job.head)).await {
+                Ok(Ok(response)) => {
+                    answered += 1;
+                    let closed = response.closed;
+                    let _ = job.reply.send(Ok((response, peer)));
+                    if closed {
+                        reusable = false;
+                        break;
+                    }
+                }
+                Ok(Err(e)) => {
+                    reusable = false;
+                    retry(job, answered == 0, &e.to_string(), &mut pending);
+                    break;
+                }
+                Err(_) => {
+                    reusable = false;
+                    let _ = job.reply.send(Err(DirustError::Timeout(job.url.clone())));
+                    break;
+                }
+            }
+        }
+        // Written but not answered on this connection: write them again on the next one.
+        for job in batch {
+            retry(job, answered == 0, "connection closed before the response", &mut pending);
+        }
+        if reusable {
+            connection = Some(conn);
+        }
+    }
+}
+
+/// Queue `job` to be written again, or fail it once `MAX_ATTEMPTS` connections gave it
+/// nothing. `counts`: the connection answered none of its probes (a server that answers
+/// some and closes is making progress, and costs no attempt).
+fn retry(mut job: Job, counts: bool, error: &str, pending: &mut VecDeque<Job>) {
+    if counts {
+        job.attempts += 1;
+    }
+    match job.attempts >= MAX_ATTEMPTS {
+        true => {
+            let _ = job.reply.send(Err(DirustError::Engine(format!("{}: {}", job.url, error))));
+        }
+        false => pending.push_back(job),
+    }
+}
+
+/// The bytes of a HEAD / GET request for `url`: request line, `Host` (unless `headers` has
+/// one), `headers` in map order, empty line.
+///
+/// Example:
+///   HEAD /admin?x=1 HTTP/1.1\r\nHost: example.com:8080\r\nuser-agent: dirust/0.1\r\n\r\n
+fn render(url: &Url, use_get: bool, headers: &HeaderMap, title_case: bool) -> Vec<u8> {
+    let method = if use_get { "GET" } else { "HEAD" };
+    let mut target = url.path().to_string();
+    if let Some(query) = url.query() {
+        target.push('?');
+        target.push_str(query);
+    }
+    let mut out: Vec<u8> = format!("{} {} HTTP/1.1\r\n", method, target).into_bytes();
+    if !headers.contains_key("host") {
+        let host = url.host_str().unwrap_or("");
+        let authority = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        out.extend_from_slice(format!("Host: {}\r\n", authority).as_bytes());
+    }
+    for (name, value) in headers {
+        let name = match title_case {
+            true => title_cased(name.as_str()),
+            false => name.as_str().to_string(),
+        };
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(b": ");
+        out.extend_from_slice(value.as_bytes());
+        out.extend_from_slice(b"\r\n");
+    }
+    out.extend_from_slice(b"\r\n");
+    out
+}
+
+/// `user-agent` → `User-Agent`.
+fn title_cased(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = true;
+    for c in name.chars() {
+        match upper {
+            true => out.push(c.to_ascii_uppercase()),
+            false => out.push(c),
+        }
+        upper = c == '-';
+    }
+    out
+}
@@ -0,0 +1,188 @@
+//! src/scanner/priority.rs
+//!
+//! Purpose:
+//!   Weighted wordlists: high-value words are probed first, so likely hits show up early in
+//!   a long scan instead of wherever they happen to sit in the file.
+//!
+//! Weights:
+//!   - A wordlist line may end with a weight column: `admin 10`, `backup.zip 50`,
+//!     `static -5` (default 0). Higher weights are probed first; equal weights keep file order.
+//!   - `--priority <PATH>` names a separate list of words (one per line, optionally weighted
+//!     the same way) that go before every word not on it, in the list's weight order.
+//!
+//! How:
+//!   - `PrioritySource` wraps the `-w` source and keeps a window of up to `WINDOW` words in
+//!     a max-heap, refilled one word for every word taken. Lists shorter than the window are
+//!     probed in exact weight order; on longer ones, order is by weight within the window,
+//!     so memory stays flat.
+//!   - Wordlist directives (`#!exts php`, `#!noext`) keep applying to the words they came
+//!     before: every queued word remembers the directive in effect, and the directive is
+//!     emitted again in front of it when that changes.
+//!   - stdin is read as it comes (window of one word): weight columns are dropped, nothing is
+//!     reordered, and a slow producer is not waited on.
+
+use super::{
+    source::PayloadSource,
+    wordlist::{self, Directive},
+};
+use crate::error::DirustError;
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap},
+    sync::Arc,
+};
+
+/// Words held in the reordering window.
+pub const WINDOW: usize = 50_000;
+
+/// Weights of the words on a `--priority` list.
+pub type PriorityList = HashMap<String, i64>;
+
+/// Read a `--priority` list: word -> weight (entries without a weight column get 0).
+pub fn load_list(path: &str) -> Result<PriorityList, DirustError> {
+    let mut list = PriorityList::new();
+    for line in wordlist::read_wordlist(path)? {
+        let (word, weight) = split_weight(&line);
+        list.entry(word.to_string()).or_insert(weight.unwrap_or(0));
+    }
+    Ok(list)
+}
+
+/// `("admin", Some(10))` from `admin 10`; lines without a numeric last column are all word.
+pub fn split_weight(line: &str) -> (&str, Option<i64>) {
+    if let Some((word, column)) = line.trim().rsplit_once(char::is_whitespace)
+        && let Ok(weight) = column.parse::<i64>()
+        && !word.trim().is_empty()
+    {
+        return (word.trim(), Some(weight));
+    }
+    (line.trim(), None)
+}
+
+/// One word waiting in the window.
+struct Queued {
+    /// 1 for words on the `--priority` list, 0 otherwise.
+    tier: u8,
+    weight: i64,
+    /// Read order (earlier first among equals).
+    seq: Reverse<u64>,
+    word: String,
+    /// The directive in effect where the word was read (`None`: none).
+    directive: Option<Arc<str>>,
+}
+
+impl Queued {
+    fn key(&self) -> (u8, i64, Reverse<u64>) {
+        (self.tier, self.weight, self.seq)
+    }
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Queued) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Queued) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Queued) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Another source, reordered by weight (see the module docs).
+pub struct PrioritySource {
+    inner: Box<dyn PayloadSource>,
+    window: usize,
+    priority: Option<PriorityList>,
+    heap: BinaryHeap<Queued>,
+    seq: u64,
+    exhausted: bool,
+    /// Directive in effect for the words being read.
+    reading: Option<Arc<str>>,
+    /// Directive in effect for the words handed out.
+    emitted: Option<Arc<str>>,
+    /// A word waiting behind the directive emitted just before it.
+    held: Option<String>,
+}
+
+impl PrioritySource {
+    /// Reorder `inner` within a window of `window` words (at least one).
+    pub fn new(inner: Box<dyn PayloadSource>, window: usize, priority: Option<PriorityList>) -> PrioritySource {
+        PrioritySource {
+            inner,
+            window: window.max(1),
+            priority,
+            heap: BinaryHeap::new(),
+            seq: 0,
+            exhausted: false,
+            reading: None,
+            emitted: None,
+            held: None,
+        }
+    }
+
+    /// Read from `inner` until the window is full or `inner` is exhausted.
+    fn fill(&mut self) -> Result<(), DirustError> {
+        while !self.exhausted && self.heap.len() < self.window {
+            let payload = match self.inner.next_payload()? {
+                Some(p) => p,
+                None => {
+                    self.exhausted = true;
+                    break;
+                }
+            };
+            if let Some(directive) = Directive::parse(&payload) {
+                self.reading = match directive {
+                    Directive::Default => None,
+                    _ => Some(Arc::from(payload.as_str())),
+                };
+                continue;
+            }
+
+            let (word, weight) = split_weight(&payload);
+            let listed: Option<i64> = self.priority.as_ref().and_then(|list| list.get(word).copied());
+            let queued = Queued {
+                tier: u8::from(listed.is_some()),
+                weight: listed.or(weight).unwrap_or(0),
+                seq: Reverse(self.seq),
+                word: word.to_string(),
+                directive: self.reading.clone(),
+            };
+            self.seq += 1;
+            self.heap.push(queued);
+        }
+        Ok(())
+    }
+}
+
+impl PayloadSource for PrioritySource {
+    fn next_payload(&mut self) -> Result<Option<String>, DirustError> {
+        if let Some(word) = self.held.take() {
+            return Ok(Some(word));
+        }
+        self.fill()?;
+        let queued = match self.heap.pop() {
+            Some(q) => q,
+            None => return Ok(None),
+        };
+        if queued.directive == self.emitted {
+            return Ok(Some(queued.word));
+        }
+
+        // The word was read under another directive than the last word handed out.
+        self.emitted = queued.directive.clone();
+        self.held = Some(queued.word);
+        match queued.directive {
+            Some(directive) => Ok(Some(directive.to_string())),
+            None => Ok(Some("#!default".to_string())),
+        }
+    }
+}
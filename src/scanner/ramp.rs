@@ -0,0 +1,109 @@
+//! src/scanner/ramp.rs
+//!
+//! Purpose:
+//!   `--ramp 10:60s,50:300s,100:0`: start a scan slow and raise its concurrency in stages,
+//!   so the people watching the target see the load grow instead of an instant burst.
+//!
+//! How:
+//!   The scan's semaphore keeps its full `-c` permits; the ramp holds back the ones the
+//!   current stage does not allow. Moving to a higher stage releases some of them; moving
+//!   to a lower one takes them back as in-flight probes finish. After the last timed stage
+//!   (or when the ramp is dropped) every permit is released and the scan runs at `-c`.
+
+use crate::{args::RampStage, output};
+use std::sync::Arc;
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    task::JoinHandle,
+};
+
+/// A running ramp; dropping it releases all held-back permits.
+pub struct Ramp {
+    task: JoinHandle<()>,
+}
+
+impl Ramp {
+    /// Start ramping `semaphore` (sized `max`, i.e. `-c`) through `stages`.
+    ///
+    /// Call before the first probe is scheduled: the first stage takes effect immediately.
+    pub fn start(semaphore: &Arc<Semaphore>, max: usize, stages: &[RampStage]) -> Ramp {
+        let first = stages[0].concurrency.min(max);
+        let held = take(semaphore, None, max - first);
+        let semaphore = Arc::clone(semaphore);
+        let stages = stages.to_vec();
+
+        let task = tokio::spawn(async move {
+            let mut held = held;
+            for (index, stage) in stages.iter().enumerate() {
+                let level = stage.concurrency.min(max);
+                if index > 0 {
+                    held = adjust(&semaphore, held, max - level).await;
+                }
+                match stage.hold {
+                    Some(duration) => {
+                        output::info(&format!("[*] ramp: concurrency {} for {}s", level, duration.as_secs()));
+                        tokio::time::sleep(duration).await;
+                    }
+                    None => {
+                        output::info(&format!("[*] ramp: concurrency {} until the end", level));
+                        // Keep the permits held until the ramp is dropped.
+                        let _keep = held;
+                        std::future::pending::<()>().await;
+                        return;
+                    }
+                }
+            }
+            drop(held);
+            output::info(&format!("[*] ramp: done, concurrency {}", max));
+        });
+
+        Ramp { task }
+    }
+}
+
+impl Drop for Ramp {
+    fn drop(&mut self) {
+        // Aborting drops the task's held permits, which returns them to the semaphore.
+        self.task.abort();
+    }
+}
+
+/// Hold back exactly `count` permits, releasing or acquiring the difference.
+///
+/// Acquiring waits for in-flight probes to finish (that is how lowering takes effect).
+async fn adjust(
+    semaphore: &Arc<Semaphore>,
+    held: Option<OwnedSemaphorePermit>,
+    count: usize,
+) -> Option<OwnedSemaphorePermit> {
+    let current = held.as_ref().map_or(0, |permit| permit.num_permits());
+    if count <= current {
+        return take(semaphore, held, count);
+    }
+    let extra = match semaphore.clone().acquire_many_owned((count - current) as u32).await {
+        Ok(permit) => permit,
+        // The semaphore is never closed; without permits there is nothing to hold back.
+        Err(_) => return held,
+    };
+    match held {
+        Some(mut permit) => {
+            permit.merge(extra);
+            Some(permit)
+        }
+        None => Some(extra),
+    }
+}
+
+/// Shrink `held` to `count` permits, or take `count` free permits when nothing is held.
+fn take(semaphore: &Arc<Semaphore>, held: Option<OwnedSemaphorePermit>, count: usize) -> Option<OwnedSemaphorePermit> {
+    match held {
+        Some(mut permit) => {
+            let surplus = permit.num_permits() - count;
+            // Dropping the split-off part returns it to the semaphore.
+            drop(permit.split(surplus));
+            if permit.num_permits() == 0 { None } else { Some(permit) }
+        }
+        None if count == 0 => None,
+        None => semaphore.clone().try_acquire_many_owned(count as u32).ok(),
+    }
+}
@@ -0,0 +1,407 @@
+//! src/scanner/raw.rs
+//!
+//! Purpose:
+//!   A byte-exact HTTP/1.1 sender. reqwest and hyper build every request from a method, a
+//!   URL and a header map: header names are validated (and re-cased on HTTP/2), malformed or
+//!   conflicting headers are refused, `Content-Length` / `Transfer-Encoding` are recomputed
+//!   from the body. Request smuggling templates need the opposite — `Transfer-Encoding :
+//!   chunked`, two different `Content-Length` lines or a bare LF line ending must reach the
+//!   server exactly as written.
+//!
+//! What it does:
+//!   - Connect: TCP (TCP_NODELAY, `-4` / `-6` honoured), then TLS for https:// (rustls with
+//!     the Mozilla roots, or the `--tls-impersonate` profile; ALPN offers `http/1.1` only).
+//!   - Write the request bytes untouched; nothing is added, removed or reordered.
+//!   - Read one response at a time: status line, headers, and a body framed by
+//!     `Transfer-Encoding: chunked`, `Content-Length` or the end of the connection. Responses
+//!     to HEAD, 1xx, 204 and 304 have no body. Bytes read past a response stay buffered for
+//!     the next one, so several requests can be written before their responses are read.
+//!
+//! What it does not do: proxies, HTTP/2, redirects, cookies, decompression (the caller
+//! decodes `Content-Encoding` if it wants decoded sizes, see `http::decode`).
+//!
+//! Users: `dirust fuzz raw --raw-engine` (request templates sent as written), and
+//! `--engine fast --pipeline N` (several probes written before their responses are read,
+//! see `pipeline.rs`).
+
+use crate::{args::HttpArgs, error::DirustError, tls};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    StatusCode, Url,
+};
+use rustls::pki_types::ServerName;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_rustls::TlsConnector;
+
+/// Largest response head (status line + headers) accepted.
+const MAX_HEAD: usize = 64 * 1024;
+
+/// Bodies are kept up to this size; the rest is read (to find the end of the response)
+/// and counted, but not kept.
+pub const MAX_BODY: usize = 4 * 1024 * 1024;
+
+/// Read buffer size.
+const READ_CHUNK: usize = 16 * 1024;
+
+/// A TCP or TLS stream.
+trait Stream: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Stream for T {}
+
+/// How a raw connection is opened: TLS settings and timeouts from the client flags.
+#[derive(Clone)]
+pub struct RawConnector {
+    tls: TlsConnector,
+    timeout: Duration,
+    args: HttpArgs,
+}
+
+impl RawConnector {
+    /// A connector for the client flags `args` (`--timeout`, `-4` / `-6`, `--tls-impersonate`).
+    pub fn new(args: &HttpArgs) -> Result<RawConnector, DirustError> {
+        let mut config = match args.tls_impersonate {
+            Some(profile) => tls::client_config(profile)?,
+            None => tls::default_config()?,
+        };
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+        Ok(RawConnector {
+            tls: TlsConnector::from(Arc::new(config)),
+            timeout: args.request_timeout(),
+            args: args.clone(),
+        })
+    }
+
+    /// `--timeout`, the limit for connecting and for every response.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Open a connection to the host and port of `url` (TLS for https://).
+    pub async fn connect(&self, url: &Url) -> Result<RawConnection, DirustError> {
+        match tokio::time::timeout(self.timeout, self.open(url)).await {
+            Ok(result) => result,
+            Err(_) => Err(DirustError::Timeout(url.to_string())),
+        }
+    }
+
+    async fn open(&self, url: &Url) -> Result<RawConnection, DirustError> {
+        let host = match url.host_str() {
+            Some(host) => host.trim_start_matches('[').trim_end_matches(']').to_string(),
+            None => return Err(DirustError::Raw(format!("{}: no host", url))),
+        };
+        let port = url.port_or_known_default().unwrap_or(80);
+
+        // Every address of the host, in resolver order, restricted to the `-4` / `-6` family.
+        let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port))
+            .await
+            .map_err(|e| DirustError::Raw(format!("{}: cannot resolve: {}", host, e)))?
+            .filter(|address| match self.args.local_address() {
+                Some(local) => local.is_ipv4() == address.is_ipv4(),
+                None => true,
+            })
+            .collect();
+        let mut last_error = format!("{}: no address to connect to", host);
+        let mut tcp: Option<TcpStream> = None;
+        for address in addresses {
+            match TcpStream::connect(address).await {
+                Ok(stream) => {
+                    tcp = Some(stream);
+                    break;
+                }
+                Err(e) => last_error = format!("connect {}: {}", address, e),
+            }
+        }
+        let tcp = match tcp {
+            Some(stream) => stream,
+            None => return Err(DirustError::Raw(last_error)),
+        };
+        tcp.set_nodelay(true)?;
+        let peer = tcp.peer_addr().ok();
+
+        let stream: Box<dyn Stream> = match url.scheme() {
+            "https" => {
+                let name = ServerName::try_from(host.clone())
+                    .map_err(|e| DirustError::Raw(format!("{}: {}", host, e)))?;
+                let tls = self
+                    .tls
+                    .connect(name, tcp)
+                    .await
+                    .map_err(|e| DirustError::Raw(format!("tls handshake with {}: {}", host, e)))?;
+                Box::new(tls)
+            }
+            _ => Box::new(tcp),
+        };
+        Ok(RawConnection {
+            stream,
+            peer,
+            buffer: Vec::new(),
+            closed: false,
+        })
+    }
+}
+
+/// One response as read from the wire.
+#[derive(Debug)]
+pub struct RawResponse {
+    pub status: StatusCode,
+    /// Headers that are valid as names and values (the others are skipped).
+    pub headers: HeaderMap,
+    /// The body, de-chunked, at most `MAX_BODY` bytes.
+    pub body: Vec<u8>,
+    /// Body bytes received (de-chunked, before decompression), including the ones beyond
+    /// `MAX_BODY` that were not kept.
+    pub transferred: u64,
+    /// The server closed the connection, or said it would (`Connection: close`); no
+    /// further response can be read.
+    pub closed: bool,
+}
+
+/// An open HTTP/1.1 connection.
+pub struct RawConnection {
+    stream: Box<dyn Stream>,
+    /// Address of the server end.
+    peer: Option<SocketAddr>,
+    /// Bytes read but not consumed yet (the start of the next response).
+    buffer: Vec<u8>,
+    /// The peer closed its side.
+    closed: bool,
+}
+
+impl RawConnection {
+    /// The address this connection is connected to.
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer
+    }
+
+    /// Write `bytes` exactly as they are.
+    pub async fn write(&mut self, bytes: &[u8]) -> Result<(), DirustError> {
+        self.stream.write_all(bytes).await?;
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    /// Read the next response; `head` when it answers a HEAD request (no body follows).
+    pub async fn read_response(&mut self, head: bool) -> Result<RawResponse, DirustError> {
+        loop {
+            let (status, headers, length) = self.read_head().await?;
+            // Interim responses (100 Continue, 103 Early Hints) precede the real one.
+            if status.is_informational() && status != StatusCode::SWITCHING_PROTOCOLS {
+                self.buffer.drain(..length);
+                continue;
+            }
+            self.buffer.drain(..length);
+
+            let mut response = RawResponse {
+                status,
+                closed: header_has(&headers, "connection", "close"),
+                headers,
+                body: Vec::new(),
+                transferred: 0,
+            };
+            let no_body = head || status == StatusCode::NO_CONTENT || status == StatusCode::NOT_MODIFIED;
+            if no_body {
+                return Ok(response);
+            }
+            if header_has(&response.headers, "transfer-encoding", "chunked") {
+                self.read_chunked(&mut response).await?;
+            } else if let Some(length) = content_length(&response.headers)? {
+                self.read_exact_body(&mut response, length).await?;
+            } else {
+                // No framing: the body runs until the server closes the connection.
+                self.read_until_close(&mut response).await?;
+                response.closed = true;
+            }
+            return Ok(response);
+        }
+    }
+
+    /// Read more bytes into the buffer; `Ok(false)` at the end of the stream.
+    async fn fill(&mut self) -> Result<bool, DirustError> {
+        if self.closed {
+            return Ok(false);
+        }
+        let start = self.buffer.len();
+        self.buffer.resize(start + READ_CHUNK, 0);
+        let read = match self.stream.read(&mut self.buffer[start..]).await {
+            Ok(n) => n,
+            Err(e) => {
+                self.buffer.truncate(start);
+                return Err(e.into());
+            }
+        };
+        self.buffer.truncate(start + read);
+        if read == 0 {
+            self.closed = true;
+        }
+        Ok(read > 0)
+    }
+
+    /// Read up to the end of a response head; returns its status, headers and length in
+    /// the buffer.
+    async fn read_head(&mut self) -> Result<(StatusCode, HeaderMap, usize), DirustError> {
+        loop {
+            if let Some(end) = head_end(&self.buffer) {
+                let (status, headers) = parse_head(&self.buffer[..end])?;
+                return Ok((status, headers, end));
+            }
+            if self.buffer.len() > MAX_HEAD {
+                return Err(DirustError::Raw(format!("response head larger than {} bytes", MAX_HEAD)));
+            }
+            if !self.fill().await? {
+                return Err(DirustError::Raw(match self.buffer.is_empty() {
+                    true => "connection closed before a response".to_string(),
+                    false => "connection closed in the middle of a response head".to_string(),
+                }));
+            }
+        }
+    }
+
+    /// Take up to `wanted` buffered body bytes into `response`; returns how many were taken.
+    fn take_body(&mut self, response: &mut RawResponse, wanted: u64) -> u64 {
+        let take = (self.buffer.len() as u64).min(wanted) as usize;
+        let room = MAX_BODY.saturating_sub(response.body.len()).min(take);
+        response.body.extend_from_slice(&self.buffer[..room]);
+        response.transferred += take as u64;
+        self.buffer.drain(..take);
+        take as u64
+    }
+
+    async fn read_exact_body(&mut self, response: &mut RawResponse, length: u64) -> Result<(), DirustError> {
+        let mut left = length;
+        loop {
+            left -= self.take_body(response, left);
+            if left == 0 {
+                return Ok(());
+            }
+            if !self.fill().await? {
+                return Err(DirustError::Raw(format!("connection closed {} bytes before the end of the body", left)));
+            }
+        }
+    }
+
+    async fn read_until_close(&mut self, response: &mut RawResponse) -> Result<(), DirustError> {
+        loop {
+            self.take_body(response, u64::MAX);
+            if !self.fill().await? {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn read_chunked(&mut self, response: &mut RawResponse) -> Result<(), DirustError> {
+        loop {
+            let line = self.read_line().await?;
+            // `1a;ext=1` → 0x1a; chunk extensions are ignored.
+            let size_text = line.split(';').next().unwrap_or("").trim();
+            let size = u64::from_str_radix(size_text, 16)
+                .map_err(|_| DirustError::Raw(format!("bad chunk size {:?}", line)))?;
+            if size == 0 {
+                // Trailer fields up to the empty line.
+                while !self.read_line().await?.is_empty() {}
+                return Ok(());
+            }
+            let mut left = size;
+            while left > 0 {
+                left -= self.take_body(response, left);
+                if left > 0 && !self.fill().await? {
+                    return Err(DirustError::Raw("connection closed in the middle of a chunk".to_string()));
+                }
+            }
+            // The CRLF after the chunk data.
+            self.read_line().await?;
+        }
+    }
+
+    /// One line (without its line ending) from the buffer.
+    async fn read_line(&mut self) -> Result<String, DirustError> {
+        loop {
+            if let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+                let line = String::from_utf8_lossy(&self.buffer[..end]).trim_end_matches('\r').to_string();
+                self.buffer.drain(..=end);
+                return Ok(line);
+            }
+            if self.buffer.len() > MAX_HEAD {
+                return Err(DirustError::Raw("chunk line too long".to_string()));
+            }
+            if !self.fill().await? {
+                return Err(DirustError::Raw("connection closed in a chunked body".to_string()));
+            }
+        }
+    }
+}
+
+/// End of the head in `buffer` (just after the empty line), if it is all there.
+/// Bare LF line endings are accepted as well.
+fn head_end(buffer: &[u8]) -> Option<usize> {
+    let crlf = buffer.windows(4).position(|w| w == b"\r\n\r\n").map(|i| i + 4);
+    let lf = buffer.windows(2).position(|w| w == b"\n\n").map(|i| i + 2);
+    match (crlf, lf) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Parse `HTTP/1.1 200 OK` and the header lines after it.
+fn parse_head(head: &[u8]) -> Result<(StatusCode, HeaderMap), DirustError> {
+    let text = String::from_utf8_lossy(head);
+    let mut lines = text.lines();
+    let status_line = lines.next().unwrap_or("");
+    let mut parts = status_line.split_whitespace();
+    let status = match (parts.next(), parts.next()) {
+        (Some(version), Some(code)) if version.starts_with("HTTP/") => code
+            .parse::<u16>()
+            .ok()
+            .and_then(|code| StatusCode::from_u16(code).ok()),
+        _ => None,
+    };
+    let status = match status {
+        Some(status) => status,
+        None => return Err(DirustError::Raw(format!("not an HTTP/1.x status line: {:?}", status_line))),
+    };
+
+    let mut headers = HeaderMap::new();
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.trim().as_bytes()),
+            HeaderValue::from_str(value.trim()),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    Ok((status, headers))
+}
+
+/// Whether any `name` header lists `token` (case-insensitive, comma-separated values).
+fn header_has(headers: &HeaderMap, name: &str, token: &str) -> bool {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|v| v.trim().eq_ignore_ascii_case(token))
+}
+
+/// The `Content-Length` of a response; conflicting values are an error (a response that
+/// is itself ambiguous cannot be framed safely).
+fn content_length(headers: &HeaderMap) -> Result<Option<u64>, DirustError> {
+    let mut length: Option<u64> = None;
+    for value in headers.get_all("content-length") {
+        let parsed = value
+            .to_str()
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .ok_or_else(|| DirustError::Raw(format!("bad Content-Length {:?}", value)))?;
+        if length.is_some_and(|known| known != parsed) {
+            return Err(DirustError::Raw("conflicting Content-Length values in the response".to_string()));
+        }
+        length = Some(parsed);
+    }
+    Ok(length)
+}
@@ -0,0 +1,178 @@
+//! src/scanner/redirects.rs
+//!
+//! Purpose:
+//!   Collapse redirect noise. Many applications answer *every* unknown or protected path with
+//!   a redirect to the same place (a login page, the home page), which would otherwise print
+//!   hundreds of identical 302 lines. Redirect findings are counted by their (absolute)
+//!   target as they come in:
+//!     - the first `--redirect-threshold` (5) paths to a target are printed right away, as
+//!       normal result lines
+//!     - the ones after that are held back, and the scan ends with ONE summary line per such
+//!       target (how many paths in all, a few of the held-back ones as examples)
+//!     - a path that redirects to itself is flagged as a redirect loop
+//!   `--redirect-threshold 0` turns collapsing off: every redirect gets its own line.
+//!
+//! Design choices:
+//!   - Grouping uses the Location resolved against the requested URL, so `/login`,
+//!     `https://host/login` and `../login` all count as the same target.
+//!   - Canonicalization redirects (`/admin` → `/admin/`) have a different target per path,
+//!     so they never collapse and keep their per-path lines.
+//!
+//! Target harvesting (`--harvest-redirects`):
+//!   - The target of a redirect finding (see `harvest_target`) is queued as a follow-up
+//!     wave when it is under the scanned base, allowed by `--scope` and not probed yet. A
+//!     target many paths redirect to is probed once.
+//!   - Query and fragment are dropped (`/login?next=/admin` → `/login`), so per-path
+//!     `next=` parameters do not turn into one probe per redirecting path.
+//!   - A finding reached this way is printed with the redirect that led to it and keeps it
+//!     as `redirected_from`; a redirect chain is followed one hop per wave.
+
+use super::{http::HttpSummary, print_line};
+use crate::{
+    output::{self, Style},
+    url::canonical_target,
+};
+use reqwest::Url;
+use std::collections::HashMap;
+
+/// Number of example source paths shown on a collapsed summary line.
+const EXAMPLES: usize = 3;
+
+/// Return `true` when a finding should be held back instead of printed right away.
+///
+/// Only redirects that actually carry a `Location` are worth grouping.
+pub fn should_defer(threshold: usize, summary: &HttpSummary) -> bool {
+    threshold > 0 && summary.status.is_redirection() && summary.location.is_some()
+}
+
+/// Return `true` when `url` redirects to the same path plus a trailing slash
+/// (`/admin` → `/admin/`): the server's way of saying "this is a directory".
+///
+/// The Location is resolved against `url`, so relative and absolute forms both match.
+pub fn is_directory_redirect(url: &str, summary: &HttpSummary) -> bool {
+    if !summary.status.is_redirection() || url.ends_with('/') {
+        return false;
+    }
+    let location = match &summary.location {
+        Some(loc) => loc,
+        None => return false,
+    };
+    match Url::parse(url).and_then(|base| base.join(location)) {
+        Ok(target) => target.as_str() == format!("{}/", url),
+        Err(_) => false,
+    }
+}
+
+/// The target to probe for a redirect finding of `url` with `status` and `location` (see
+/// the module docs), or `None` when it is not a redirect with a Location.
+pub fn harvest_target(url: &str, status: u16, location: Option<&str>) -> Option<String> {
+    if !(300..400).contains(&status) {
+        return None;
+    }
+    let mut target = Url::parse(url).ok()?.join(location?.trim()).ok()?;
+    target.set_query(None);
+    target.set_fragment(None);
+    Some(canonical_target(target.as_str()))
+}
+
+/// Print a finding reached through a redirect (`--harvest-redirects`).
+///
+/// Format:
+///       ↳ <status> <url>  (redirect from <source>)
+pub fn print_harvested(url: &str, status: u16, source: &str) {
+    output::info(&format!("    ↳ {} {}  (redirect from {})", status, url, source));
+}
+
+/// Redirects to one target: how many were printed, and the ones held back after that.
+#[derive(Default)]
+struct Group {
+    printed: usize,
+    /// (requested URL, status) of the redirects that were not printed.
+    held: Vec<(String, u16)>,
+}
+
+/// Redirect findings counted by target (see the module docs).
+pub struct RedirectCollector {
+    /// Redirects printed per target before the rest is held back (0 = never collapse).
+    threshold: usize,
+    /// Targets in first-seen order, for a stable end-of-scan report.
+    order: Vec<String>,
+    groups: HashMap<String, Group>,
+}
+
+impl RedirectCollector {
+    pub fn new(threshold: usize) -> RedirectCollector {
+        RedirectCollector {
+            threshold,
+            order: Vec::new(),
+            groups: HashMap::new(),
+        }
+    }
+
+    /// Print one redirect finding now, or hold it back when its target already had
+    /// `threshold` lines.
+    pub fn push(&mut self, url: String, summary: HttpSummary, label: String) {
+        let raw = summary.location.clone().unwrap_or_default();
+        let target = resolve(&url, &raw);
+        if !self.groups.contains_key(&target) {
+            self.order.push(target.clone());
+        }
+        let group = self.groups.entry(target.clone()).or_default();
+        if self.threshold > 0 && group.printed >= self.threshold {
+            group.held.push((url, summary.status.as_u16()));
+            return;
+        }
+        group.printed += 1;
+        print_line(&url, &summary, &label);
+        if url == target {
+            output::print_styled(&format!("    [!] redirect loop: {} redirects to itself", url), Style::Plain);
+        }
+    }
+
+    /// Print one summary line per target that had redirects held back.
+    pub fn flush(mut self) {
+        for target in &self.order {
+            if let Some(group) = self.groups.remove(target)
+                && !group.held.is_empty()
+            {
+                print_collapsed(target, &group);
+            }
+        }
+    }
+}
+
+/// Print one summary line for a collapsed group.
+///
+/// Format:
+///   [<time>] <status> x<count>  -> <target>  (collapsed: <printed> shown above, <held> more, e.g. <url>, <url>, <url>)
+///
+/// Severity labels of the members are not repeated here: a redirect to a shared target
+/// says little about each individual path.
+fn print_collapsed(target: &str, group: &Group) {
+    let status = group.held[0].1;
+    let examples: Vec<&str> = group.held.iter().take(EXAMPLES).map(|(url, _)| url.as_str()).collect();
+
+    let line = format!(
+        "{}{:>3} x{}  -> {}  (collapsed: {} shown above, {} more, e.g. {})",
+        output::timestamp_prefix(),
+        status,
+        group.printed + group.held.len(),
+        target,
+        group.printed,
+        group.held.len(),
+        examples.join(", ")
+    );
+    let urls: Vec<&str> = group.held.iter().map(|(url, _)| url.as_str()).collect();
+    output::finding(&line, Style::Dim, Some(&urls.join("\n")));
+}
+
+/// Resolve a `Location` value against the URL that returned it.
+///
+/// Falls back to the raw value when either side does not parse, so grouping still works
+/// on exact matches.
+fn resolve(url: &str, location: &str) -> String {
+    match Url::parse(url).and_then(|base| base.join(location.trim())) {
+        Ok(joined) => joined.to_string(),
+        Err(_) => location.trim().to_string(),
+    }
+}
@@ -0,0 +1,250 @@
+//! src/scanner/scope.rs
+//!
+//! Purpose:
+//!   `--scope <PATH>`: keep a scan inside the engagement's scope. Every generated target
+//!   (wordlist targets, encoding variants, followed form actions) is checked before it is
+//!   scheduled; targets outside the scope are dropped and counted, never requested. The
+//!   `word/` that confirms a `word` → `word/` redirect is checked too; when it is out of
+//!   scope, the redirect is reported as it is.
+//!
+//! File format (one rule per line, `#` starts a comment):
+//!
+//!     example.com                 # allow this host (any scheme, any port)
+//!     *.example.com               # allow every subdomain of example.com
+//!     example.com/app/            # allow only paths under /app/ on example.com
+//!     https://example.com:8443/   # allow only this scheme and port
+//!     !/logout                    # deny every URL containing "/logout"
+//!     !/api/*/delete              # `*` matches any run of characters
+//!
+//! Rules:
+//!   - A target must match at least one allow rule (a file without allow rules allows
+//!     every host) and no deny rule.
+//!   - Allow rules compare the host exactly (case-insensitive); the path prefix, scheme and
+//!     port only when the rule names them.
+//!   - Deny patterns are matched anywhere in the full URL, case-insensitively, so `!/Logout`
+//!     also keeps `/logout` out.
+
+use crate::error::DirustError;
+use regex::Regex;
+use reqwest::Url;
+use std::fs;
+
+/// One allow rule: a host (or `*.domain`) with optional scheme, port and path prefix.
+#[derive(Debug)]
+struct Allow {
+    scheme: Option<String>,
+    /// Lower-cased host; for `*.example.com` rules, `.example.com`.
+    host: String,
+    wildcard: bool,
+    port: Option<u16>,
+    path_prefix: String,
+}
+
+impl Allow {
+    /// Parse an allow line (`[scheme://]host[:port][/path-prefix]`).
+    fn parse(line: &str) -> Result<Allow, String> {
+        let (scheme, rest) = match line.split_once("://") {
+            Some((scheme, rest)) => (Some(scheme.to_ascii_lowercase()), rest),
+            None => (None, line),
+        };
+        let (authority, path_prefix) = match rest.find('/') {
+            Some(i) => (&rest[..i], rest[i..].to_string()),
+            None => (rest, String::new()),
+        };
+        // `[::1]` is an IPv6 host without a port, `[::1]:8080` one with a port.
+        let (host, port) = match authority.rsplit_once(':') {
+            Some(_) if authority.ends_with(']') => (authority, None),
+            Some((host, port)) => match port.parse::<u16>() {
+                Ok(p) => (host, Some(p)),
+                Err(_) => return Err(format!("bad port in {:?}", line)),
+            },
+            None => (authority, None),
+        };
+        let (host, wildcard) = match host.strip_prefix("*.") {
+            Some(domain) => (format!(".{}", domain), true),
+            None => (host.to_string(), false),
+        };
+        if host.is_empty() || host == "." {
+            return Err(format!("no host in {:?}", line));
+        }
+        Ok(Allow {
+            scheme,
+            host: host.to_ascii_lowercase(),
+            wildcard,
+            port,
+            path_prefix,
+        })
+    }
+
+    /// Whether `url` falls under this rule.
+    fn matches(&self, url: &Url) -> bool {
+        let host = match url.host_str() {
+            Some(h) => h.to_ascii_lowercase(),
+            None => return false,
+        };
+        let host_ok = match self.wildcard {
+            true => host.ends_with(&self.host),
+            false => host == self.host,
+        };
+        let scheme_ok = match &self.scheme {
+            Some(scheme) => url.scheme() == scheme,
+            None => true,
+        };
+        let port_ok = match self.port {
+            Some(port) => url.port_or_known_default() == Some(port),
+            None => true,
+        };
+        host_ok && scheme_ok && port_ok && url.path().starts_with(&self.path_prefix)
+    }
+}
+
+/// The parsed scope file.
+#[derive(Debug)]
+pub struct Scope {
+    allow: Vec<Allow>,
+    deny: Vec<Regex>,
+}
+
+impl Scope {
+    /// Read and parse the scope file at `path`.
+    ///
+    /// Errors:
+    ///   - `DirustError::Io` if the file cannot be read
+    ///   - `DirustError::InvalidScope` for a rule that cannot be parsed
+    pub fn load(path: &str) -> Result<Scope, DirustError> {
+        let text = fs::read_to_string(path)?;
+        let mut allow: Vec<Allow> = Vec::new();
+        let mut deny: Vec<Regex> = Vec::new();
+
+        for (number, raw) in text.lines().enumerate() {
+            let line = match raw.split_once('#') {
+                Some((rule, _comment)) => rule.trim(),
+                None => raw.trim(),
+            };
+            if line.is_empty() {
+                continue;
+            }
+            match line.strip_prefix('!') {
+                Some(pattern) => deny.push(deny_pattern(pattern.trim())?),
+                None => match Allow::parse(line) {
+                    Ok(rule) => allow.push(rule),
+                    Err(reason) => {
+                        return Err(DirustError::InvalidScope(format!("line {}: {}", number + 1, reason)));
+                    }
+                },
+            }
+        }
+
+        Ok(Scope { allow, deny })
+    }
+
+    /// Whether `target` may be requested. URLs that do not parse are out of scope.
+    pub fn allows(&self, target: &str) -> bool {
+        let url = match Url::parse(target) {
+            Ok(u) => u,
+            Err(_) => return false,
+        };
+        let allowed = self.allow.is_empty() || self.allow.iter().any(|rule| rule.matches(&url));
+        allowed && !self.deny.iter().any(|pattern| pattern.is_match(target))
+    }
+
+    /// Drop the out-of-scope targets from `targets`; returns how many were dropped.
+    pub fn retain(&self, targets: &mut Vec<String>) -> usize {
+        let before = targets.len();
+        targets.retain(|url| self.allows(url));
+        before - targets.len()
+    }
+}
+
+/// Compile a deny pattern: literal text with `*` wildcards, matched anywhere.
+fn deny_pattern(pattern: &str) -> Result<Regex, DirustError> {
+    let parts: Vec<String> = pattern.split('*').map(regex::escape).collect();
+    Ok(Regex::new(&format!("(?i){}", parts.join(".*")))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scope;
+    use crate::error::DirustError;
+
+    /// `Scope::load` of a file holding `rules`, one per line.
+    fn load(name: &str, rules: &[&str]) -> Result<Scope, DirustError> {
+        let path = std::env::temp_dir().join(format!("dirust-scope-{}-{}.txt", name, std::process::id()));
+        std::fs::write(&path, rules.join("\n")).expect("write scope file");
+        let scope = Scope::load(path.to_str().expect("utf-8 path"));
+        let _ = std::fs::remove_file(&path);
+        scope
+    }
+
+    fn check(scope: &Scope, cases: &[(&str, bool)]) {
+        for &(url, allowed) in cases {
+            assert_eq!(scope.allows(url), allowed, "{}", url);
+        }
+    }
+
+    #[test]
+    fn wildcard_hosts() {
+        let scope = load("wildcard", &["*.example.com   # subdomains only"]).expect("valid scope");
+        check(
+            &scope,
+            &[
+                ("https://www.example.com/", true),
+                ("http://a.b.example.com:8080/x", true),
+                ("https://WWW.Example.COM/admin", true),
+                // The domain itself is not one of its subdomains.
+                ("https://example.com/", false),
+                ("https://badexample.com/", false),
+                ("https://example.com.evil.net/", false),
+            ],
+        );
+    }
+
+    #[test]
+    fn ports() {
+        let scope = load("ports", &["example.com:8443", "[::1]:8080", "plain.net"]).expect("valid scope");
+        check(
+            &scope,
+            &[
+                ("https://example.com:8443/", true),
+                ("http://example.com:8443/", true),
+                ("https://example.com/", false),
+                ("https://example.com:8444/", false),
+                ("http://[::1]:8080/", true),
+                ("http://[::1]/", false),
+                // No port in the rule: any port.
+                ("http://plain.net:1234/", true),
+            ],
+        );
+        // A default port named in the rule matches URLs that leave it out.
+        let scope = load("default-port", &["example.com:443"]).expect("valid scope");
+        check(&scope, &[("https://example.com/", true), ("http://example.com/", false)]);
+    }
+
+    #[test]
+    fn schemes_and_paths() {
+        let rules = ["https://example.com:8443/app/", "HTTP://legacy.example.com", "!/app/*/delete"];
+        let scope = load("schemes", &rules).expect("valid scope");
+        check(
+            &scope,
+            &[
+                ("https://example.com:8443/app/users", true),
+                ("http://example.com:8443/app/users", false),
+                ("https://example.com:8443/other", false),
+                ("https://example.com/app/", false),
+                ("https://example.com:8443/app/users/delete", false),
+                ("https://example.com:8443/APP/x/Delete", false),
+                // Scheme names are case-insensitive.
+                ("http://legacy.example.com/", true),
+                ("https://legacy.example.com/", false),
+                ("not a url", false),
+            ],
+        );
+    }
+
+    #[test]
+    fn invalid_rules() {
+        for rule in ["example.com:http", "*.", ":8080"] {
+            assert!(load("invalid", &[rule]).is_err(), "{:?} accepted", rule);
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! src/scanner/secrets.rs
+//!
+//! Purpose:
+//!   `--secrets`: search the bodies of 200 findings for leaked credentials, so an exposed
+//!   `config.js` or `.env` shows *what* leaked, not just that the file exists.
+//!
+//! Detection:
+//!   - Regex rules (AWS keys, JWTs, private key headers, API tokens, ...), built-in in
+//!     `scanner/secrets.toml` or replaced with `--secret-rules <PATH>`.
+//!   - Entropy: tokens of base64/hex-like characters at least `min_length` long whose
+//!     Shannon entropy is at least `threshold` bits per character, unless a rule already
+//!     matched them. A token of n characters can never exceed log2(n) bits per character
+//!     (a 20-character key tops out at 4.32), so for short tokens the threshold is lowered
+//!     to log2(n) - 0.6 (see `token_threshold`). Random base64 keys of any length clear
+//!     it; random hex (digests, ETags) tops out at 4 bits per character and almost never
+//!     does.
+//!
+//! Only the first `MAX_SCAN_BYTES` of a body are searched and at most `MAX_MATCHES` matches
+//! are kept per page. Matched values are redacted to their first and last characters
+//! before they are printed or stored.
+
+use crate::{error::DirustError, severity::Severity};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// The built-in rule set, used when `--secret-rules` is not given.
+const DEFAULT_RULES: &str = include_str!("secrets.toml");
+
+/// Bodies are searched up to this size.
+const MAX_SCAN_BYTES: usize = 1 << 20;
+
+/// Matches kept per page (a minified bundle full of keys does not flood the output).
+const MAX_MATCHES: usize = 20;
+
+/// Longer tokens are blobs (inline images, fonts), not keys.
+const MAX_TOKEN_LENGTH: usize = 256;
+
+/// How far below the maximum entropy of its length (log2(n)) a short token may fall.
+/// Random base64 tokens stay within 0.6 bits of it (95% of 20..32-character ones);
+/// random hex tokens of those lengths fall 0.7 bits or more below it.
+const SHORT_TOKEN_MARGIN: f64 = 0.6;
+
+/// One rule as written in the TOML file.
+#[derive(Debug, Deserialize)]
+struct RuleSpec {
+    name: String,
+    pattern: String,
+    severity: Severity,
+    #[serde(default = "default_redact")]
+    redact: bool,
+}
+
+fn default_redact() -> bool {
+    true
+}
+
+/// The `[entropy]` table.
+#[derive(Debug, Clone, Deserialize)]
+struct EntropySpec {
+    min_length: usize,
+    threshold: f64,
+    severity: Severity,
+}
+
+/// Top-level layout of a secret rules file.
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    #[serde(default)]
+    rule: Vec<RuleSpec>,
+    entropy: Option<EntropySpec>,
+}
+
+/// A compiled rule.
+#[derive(Debug)]
+struct Rule {
+    name: String,
+    regex: Regex,
+    severity: Severity,
+    redact: bool,
+}
+
+/// One secret found in a body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretMatch {
+    /// Rule name (`high-entropy` for entropy findings).
+    pub rule: String,
+    pub severity: Severity,
+    /// The matched text, redacted unless the rule says otherwise.
+    pub excerpt: String,
+}
+
+/// A loaded rule set.
+#[derive(Debug)]
+pub struct SecretScanner {
+    rules: Vec<Rule>,
+    entropy: Option<EntropySpec>,
+}
+
+impl SecretScanner {
+    /// Load rules from `path`, or the built-in rules when `path` is `None`.
+    ///
+    /// Errors:
+    ///   - `DirustError::Io` if the file cannot be read
+    ///   - `DirustError::Toml` if it is not a valid rules file
+    ///   - `DirustError::Regex` if a pattern does not compile
+    pub fn load(path: Option<&str>) -> Result<SecretScanner, DirustError> {
+        let text = match path {
+            Some(p) => std::fs::read_to_string(p)?,
+            None => DEFAULT_RULES.to_string(),
+        };
+        let file: RulesFile = toml::from_str(&text)?;
+
+        let mut rules: Vec<Rule> = Vec::with_capacity(file.rule.len());
+        for spec in file.rule {
+            rules.push(Rule {
+                regex: Regex::new(&spec.pattern)?,
+                name: spec.name,
+                severity: spec.severity,
+                redact: spec.redact,
+            });
+        }
+
+        Ok(SecretScanner {
+            rules,
+            entropy: file.entropy,
+        })
+    }
+
+    /// Search `body` for secrets.
+    pub fn scan(&self, body: &str) -> Vec<SecretMatch> {
+        let mut end = body.len().min(MAX_SCAN_BYTES);
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        let text = &body[..end];
+
+        let mut found: Vec<SecretMatch> = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        for rule in &self.rules {
+            for m in rule.regex.find_iter(text) {
+                if found.len() >= MAX_MATCHES {
+                    return found;
+                }
+                if !seen.insert(m.as_str().to_string()) {
+                    continue;
+                }
+                found.push(SecretMatch {
+                    rule: rule.name.clone(),
+                    severity: rule.severity,
+                    excerpt: if rule.redact { redact(m.as_str()) } else { m.as_str().to_string() },
+                });
+            }
+        }
+
+        if let Some(entropy) = &self.entropy {
+            for token in text.split(|c: char| !is_token_char(c)) {
+                if found.len() >= MAX_MATCHES {
+                    break;
+                }
+                if token.len() < entropy.min_length || token.len() > MAX_TOKEN_LENGTH {
+                    continue;
+                }
+                // Tokens inside an earlier match (or containing one) are already reported.
+                if seen.iter().any(|s| s.contains(token) || token.contains(s.as_str())) {
+                    continue;
+                }
+                let has_digit = token.bytes().any(|b| b.is_ascii_digit());
+                let has_letter = token.bytes().any(|b| b.is_ascii_alphabetic());
+                if has_digit
+                    && has_letter
+                    && shannon_entropy(token) >= token_threshold(entropy.threshold, token.len())
+                {
+                    seen.insert(token.to_string());
+                    found.push(SecretMatch {
+                        rule: "high-entropy".to_string(),
+                        severity: entropy.severity,
+                        excerpt: redact(token),
+                    });
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Characters of base64, base64url and hex tokens.
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=' | '_' | '-')
+}
+
+/// The entropy a token of `len` characters must reach: `threshold`, lowered for tokens too
+/// short to ever reach it.
+fn token_threshold(threshold: f64, len: usize) -> f64 {
+    threshold.min((len as f64).log2() - SHORT_TOKEN_MARGIN)
+}
+
+/// Shannon entropy of `token` in bits per character.
+fn shannon_entropy(token: &str) -> f64 {
+    let mut counts = [0usize; 256];
+    for b in token.bytes() {
+        counts[b as usize] += 1;
+    }
+    let len = token.len() as f64;
+    counts
+        .iter()
+        .filter(|&&n| n > 0)
+        .map(|&n| {
+            let p = n as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Keep the first and last 4 characters of `value` (enough to recognize and rotate a key,
+/// not enough to use it).
+fn redact(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 12 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shannon_entropy, token_threshold, SecretScanner};
+
+    /// The built-in `[entropy]` threshold (`secrets.toml`).
+    const THRESHOLD: f64 = 4.5;
+
+    #[test]
+    fn token_threshold_by_length() {
+        // (length, expected threshold): log2(n) - 0.6 until that reaches `THRESHOLD`.
+        let cases: [(usize, f64); 6] = [
+            (20, 20f64.log2() - 0.6),
+            (24, 24f64.log2() - 0.6),
+            (34, 34f64.log2() - 0.6),
+            (35, THRESHOLD),
+            (40, THRESHOLD),
+            (128, THRESHOLD),
+        ];
+        for (len, expected) in cases {
+            let got = token_threshold(THRESHOLD, len);
+            assert!((got - expected).abs() < 1e-9, "len {}: {} != {}", len, got, expected);
+            // Every length stays reachable: a token of n distinct characters has log2(n) bits.
+            assert!(got < (len as f64).log2(), "len {}: {} unreachable", len, got);
+        }
+    }
+
+    #[test]
+    fn entropy_tokens_short_borderline_and_long() {
+        let scanner = SecretScanner::load(None).expect("built-in rules");
+        // (token, flagged)
+        let cases = [
+            // Short: 20 distinct characters, 4.32 bits, above 20's lowered threshold (3.72).
+            ("Zq8Xv2Lm9Rt4Kp7Wn3Hs", true),
+            // Short but repetitive: 2 bits per character.
+            ("ab12ab12ab12ab12ab12", false),
+            // Borderline: 34 characters, all distinct (5.09 bits) against 4.49.
+            ("Ab3Cd5Ef7Gh9Jk2Lm4Np6Qr8St1Uv0WxYz", true),
+            // Long hex digest (SHA-1): at most 4 bits per character, below 4.5.
+            ("da39a3ee5e6b4b0d3255bfef95601890afd80709", false),
+            // Long random base64 key (64 characters, up to 6 bits each).
+            ("sZLBkYg6OlnLhdbjHuoAd77LMxiDZgLXB9zCejY8yI1p2d1ZaKIVeUT449VGB8J7", true),
+            // Split at '.' into two 10-character tokens, both under `min_length`.
+            ("Zq8Xv2Lm9R.t4Kp7Wn3Hs", false),
+        ];
+        for (token, flagged) in cases {
+            let body = format!("var key = {};", token);
+            let found = scanner.scan(&body).iter().any(|m| m.rule == "high-entropy");
+            assert_eq!(
+                found,
+                flagged,
+                "{} (len {}, {:.2} bits vs {:.2})",
+                token,
+                token.len(),
+                shannon_entropy(token),
+                token_threshold(THRESHOLD, token.len())
+            );
+        }
+    }
+}
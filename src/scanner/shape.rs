@@ -0,0 +1,177 @@
+//! src/scanner/shape.rs
+//!
+//! Purpose:
+//!   Build the headers of every probe request, one request at a time, for both engines.
+//!
+//! Default shape (unchanged from earlier versions):
+//!   User-Agent (`--user-agent`), `From` (with `--from`), the `Accept-Encoding` chosen by
+//!   `--compressed` / `--no-compression`, and the conditional headers from the results
+//!   database (`--incremental`).
+//!
+//! `--evade` shape (WAF evasion):
+//!   A scanner that sends the same few headers in the same order a million times is trivial to
+//!   fingerprint. With `--evade`, every request gets:
+//!   - a browser-like `Accept` and `Accept-Language`, picked per request from small lists
+//!   - a random subset of innocuous browser headers (`Cache-Control`, `Pragma`, `DNT`,
+//!     `Upgrade-Insecure-Requests`, `Sec-Fetch-*`)
+//!   - all of its headers in a random order
+//!
+//!   Header casing: hyper (under both engines) writes header names as stored, i.e. lowercase,
+//!   and cannot vary case per request. `--evade` switches the connection to Title-Case names
+//!   (`User-Agent`), which is what browsers send over HTTP/1.1. HTTP/2 names are always
+//!   lowercase by protocol.
+//!
+//! `--keep-cookies`:
+//!   Session cookies set by earlier responses (see `cookies.rs`) go out as one `Cookie` header.
+//!
+//! A `RequestShape` is the header layer of the probe middleware chain (`middleware.rs`): it
+//! fills in each request's headers before it is sent and keeps the cookies of its response.
+
+use super::{
+    cookies::CookieJar,
+    http::HttpSummary,
+    middleware::{LayerFuture, Middleware, Next, ProbeRequest},
+};
+use crate::{args::HttpArgs, error::DirustError, fuzz::baseline::{random_below, shuffle}, schema::ResultEntry};
+use reqwest::header::{self, HeaderMap, HeaderName, HeaderValue};
+use std::sync::Arc;
+
+/// `Accept` values of common browsers and HTTP libraries.
+const ACCEPT_VALUES: [&str; 5] = [
+    "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8",
+    "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+    "text/html,*/*;q=0.9",
+    "application/json, text/plain, */*",
+    "*/*",
+];
+
+/// `Accept-Language` values.
+const LANGUAGE_VALUES: [&str; 5] = [
+    "en-US,en;q=0.9",
+    "en-GB,en;q=0.8",
+    "en-US,en;q=0.5",
+    "de-DE,de;q=0.9,en;q=0.8",
+    "fr-FR,fr;q=0.9,en;q=0.7",
+];
+
+/// Innocuous headers; each is added to a request with probability 1/2.
+const OPTIONAL_HEADERS: [(&str, &str); 7] = [
+    ("cache-control", "max-age=0"),
+    ("pragma", "no-cache"),
+    ("dnt", "1"),
+    ("upgrade-insecure-requests", "1"),
+    ("sec-fetch-dest", "document"),
+    ("sec-fetch-mode", "navigate"),
+    ("sec-fetch-site", "none"),
+];
+
+/// How probe requests look (see the module docs).
+#[derive(Clone, Debug)]
+pub struct RequestShape {
+    evade: bool,
+    /// `--user-agent` (or the default).
+    user_agent: String,
+    /// `--from` contact address, if any.
+    from: Option<String>,
+    /// `Accept-Encoding` to send (`--compressed` / `--no-compression`), if any.
+    accept_encoding: Option<&'static str>,
+    /// Session cookies to send (`--keep-cookies`).
+    jar: Option<Arc<CookieJar>>,
+}
+
+impl RequestShape {
+    /// The shape for the client flags in `http`, randomized when `evade` is set, with a
+    /// cookie jar when `keep_cookies` is set.
+    pub fn new(http: &HttpArgs, evade: bool, keep_cookies: bool) -> RequestShape {
+        let accept_encoding = if http.compressed {
+            Some("gzip, deflate")
+        } else if http.no_compression {
+            Some("identity")
+        } else {
+            None
+        };
+        let jar = match keep_cookies {
+            true => Some(Arc::new(CookieJar::default())),
+            false => None,
+        };
+        RequestShape {
+            evade,
+            user_agent: http.user_agent().to_string(),
+            from: http.from.clone(),
+            accept_encoding,
+            jar,
+        }
+    }
+
+    /// Keep the session cookies a response from `url` set (only with `--keep-cookies`).
+    pub fn remember_cookies(&self, url: &str, set_cookies: &[String]) {
+        if let Some(jar) = &self.jar {
+            jar.absorb(url, set_cookies);
+        }
+    }
+
+    /// The headers for one request, in the order they should be sent.
+    pub fn headers(&self, previous: Option<&ResultEntry>) -> HeaderMap {
+        let mut fields: Vec<(HeaderName, String)> = Vec::new();
+        fields.push((header::USER_AGENT, self.user_agent.clone()));
+        if let Some(from) = &self.from {
+            fields.push((header::FROM, from.clone()));
+        }
+        if let Some(encoding) = self.accept_encoding {
+            fields.push((header::ACCEPT_ENCODING, encoding.to_string()));
+        }
+        if let Some(entry) = previous {
+            if let Some(tag) = &entry.etag {
+                fields.push((header::IF_NONE_MATCH, tag.clone()));
+            }
+            if let Some(date) = &entry.last_modified {
+                fields.push((header::IF_MODIFIED_SINCE, date.clone()));
+            }
+        }
+        if let Some(cookie) = self.jar.as_ref().and_then(|jar| jar.header()) {
+            fields.push((header::COOKIE, cookie));
+        }
+
+        if self.evade {
+            let accept = ACCEPT_VALUES[random_below(ACCEPT_VALUES.len())];
+            let language = LANGUAGE_VALUES[random_below(LANGUAGE_VALUES.len())];
+            fields.push((header::ACCEPT, accept.to_string()));
+            fields.push((header::ACCEPT_LANGUAGE, language.to_string()));
+            for (name, value) in OPTIONAL_HEADERS {
+                if random_below(2) == 0 {
+                    fields.push((HeaderName::from_static(name), value.to_string()));
+                }
+            }
+            shuffle(&mut fields);
+        }
+
+        // `HeaderMap` keeps insertion order, and both engines send headers in map order.
+        // Values that are not valid header text (a stored validator gone bad) are skipped.
+        let mut headers = HeaderMap::with_capacity(fields.len());
+        for (name, value) in fields {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                headers.insert(name, value);
+            }
+        }
+        headers
+    }
+}
+
+impl Middleware for RequestShape {
+    fn before<'a>(&'a self, request: &'a mut ProbeRequest) -> LayerFuture<'a, Result<(), DirustError>> {
+        request.headers = self.headers(request.previous.as_ref());
+        Box::pin(async { Ok(()) })
+    }
+
+    fn after<'a>(
+        &'a self,
+        request: &'a mut ProbeRequest,
+        result: &'a mut Result<HttpSummary, DirustError>,
+    ) -> LayerFuture<'a, Next> {
+        // `--keep-cookies`: later probes carry the session cookies this one was given.
+        if let Ok(summary) = result {
+            self.remember_cookies(&request.url, &summary.set_cookies);
+        }
+        Box::pin(async { Next::Continue })
+    }
+}
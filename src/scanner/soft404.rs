@@ -0,0 +1,121 @@
+//! src/scanner/soft404.rs
+//!
+//! Purpose:
+//!   Keep soft-404 pages out of the findings. Some applications answer unknown paths with a
+//!   200 "page not found" page (or a redirect to a landing page), often only below some
+//!   directories (`/blog/<anything>`, `/api/<anything>`). Every word probed there "exists".
+//!
+//! How:
+//!   - The random-path baseline of a directory (two random names in it, see
+//!     `fuzz::baseline`) says what "nothing here" looks like there. The base's one comes
+//!     from the scan's calibration; other directories are calibrated the first time one of
+//!     their paths turns up as a finding, once per directory.
+//!   - A finding whose status is the one random names in its directory get is downloaded
+//!     and compared with that baseline. Within the size tolerance, it is the not-found page
+//!     answered again: not reported (and counted for the end of scan summary).
+//!   - Findings with another status never cost an extra request.
+//!
+//! Notes:
+//!   - The directory of `/admin/` (a confirmed directory) is the base, not `/admin/` itself.
+//!   - A directory whose calibration fails suppresses nothing.
+//!
+//! Output (end of scan):
+//!   [*] soft-404: 12 finding(s) answered like random paths in their directory; not reported
+
+use super::http::ProbeEngine;
+use crate::fuzz::baseline::{self, Baseline, Fingerprint};
+use reqwest::StatusCode;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::sync::OnceCell;
+
+/// Random-path baselines per directory, shared by all probe tasks of a scan.
+#[derive(Debug, Default)]
+pub struct SoftNotFound {
+    /// Directory URL (with trailing slash) -> its baseline (none: calibration failed).
+    dirs: Mutex<HashMap<String, Arc<OnceCell<Option<Baseline>>>>>,
+    /// Findings suppressed so far.
+    suppressed: AtomicU64,
+}
+
+impl SoftNotFound {
+    pub fn new() -> SoftNotFound {
+        SoftNotFound::default()
+    }
+
+    /// Use an already captured baseline for `dir` (the scan's calibration of its base).
+    pub fn seed(&self, dir: &str, not_found: Option<Baseline>) {
+        let cell = Arc::new(OnceCell::new_with(Some(not_found)));
+        self.lock().insert(dir.to_string(), cell);
+    }
+
+    /// Whether the finding `url` (answered with `status`) is its directory's not-found page.
+    pub async fn is_soft(&self, engine: &ProbeEngine, url: &str, status: StatusCode) -> bool {
+        let dir = directory_of(url);
+        let cell = Arc::clone(self.lock().entry(dir.to_string()).or_default());
+        let not_found = *cell.get_or_init(|| calibrate(engine, dir)).await;
+        let not_found = match not_found {
+            Some(b) if b.status == status => b,
+            _ => return false,
+        };
+        let soft = match engine.fetch(url).await {
+            Ok(fetched) => not_found.diff(&Fingerprint::of(&fetched)).is_none(),
+            // Unknown: keep the finding.
+            Err(_) => false,
+        };
+        if soft {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+        }
+        soft
+    }
+
+    /// Findings suppressed so far.
+    pub fn suppressed(&self) -> u64 {
+        self.suppressed.load(Ordering::Relaxed)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<OnceCell<Option<Baseline>>>>> {
+        match self.dirs.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Capture the random-path baseline of `dir` (quietly: a failure only disables suppression).
+async fn calibrate(engine: &ProbeEngine, dir: &str) -> Option<Baseline> {
+    let mut samples: Vec<Fingerprint> = Vec::with_capacity(2);
+    for _ in 0..2 {
+        match engine.fetch(&format!("{}{}", dir, baseline::random_token())).await {
+            Ok(fetched) => samples.push(Fingerprint::of(&fetched)),
+            Err(_) => return None,
+        }
+    }
+    Some(Baseline::from_samples(samples[0], samples[1]))
+}
+
+/// The directory a URL is in, with trailing slash; a directory URL's is its parent.
+///
+/// Examples:
+///   "http://h/soft/page"  -> "http://h/soft/"
+///   "http://h/admin/"     -> "http://h/"
+///   "http://h/a.php?id=1" -> "http://h/"
+fn directory_of(url: &str) -> &str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let trimmed = path.trim_end_matches('/');
+    // Never above the host ("http://h/" is its own directory).
+    let floor = match path.find("://") {
+        Some(at) => path[at + 3..].find('/').map_or(path.len(), |slash| at + 3 + slash + 1),
+        None => 0,
+    };
+    match trimmed.rfind('/') {
+        Some(at) if at + 1 >= floor => &path[..at + 1],
+        _ => &path[..floor.min(path.len())],
+    }
+}
+
@@ -0,0 +1,437 @@
+//! src/scanner/source.rs
+//!
+//! Purpose:
+//!   Where the words of a scan come from. `-w` takes a wordlist path as before, or a source
+//!   expression; every source implements `PayloadSource`, so they compose:
+//!     -w common.txt                            a file (same as `file(common.txt)`); gzip and
+//!                                              zstd files are decompressed on the fly
+//!     -w -                                     stdin (same as `stdin()`)
+//!     -w 'url(https://example.com/list.txt)'   a wordlist downloaded once at start
+//!     -w 'range(1, 500)'                       numbers 1..=500; `range(001, 500)` pads to 3 digits
+//!     -w 'mutate(case, file("list.txt"))'      every word of the inner source, mutated
+//!
+//! Mutations:
+//!   case    as-is, lower case, UPPER CASE and Capitalized (each spelling once)
+//!   lower   lower case only
+//!   upper   upper case only
+//!   backup  as-is, then `.bak`, `.old`, `.orig` and `~` copies
+//!
+//! How:
+//!   - The scanner pulls words one chunk at a time (`next_chunk`), so files, stdin and
+//!     ranges are never held in memory as a whole.
+//!   - Every source drops blank lines and `#` comments and trims whitespace, like
+//!     `wordlist::read_wordlist`, and skips lines that are not valid UTF-8 (with a warning
+//!     and a count) instead of failing the scan.
+//!   - Lines may carry a weight column (`admin 10`); `open` reorders the words by weight
+//!     (and `--priority`) before the scanner sees them, see `priority.rs`.
+//!   - Directive lines (`#!exts php,inc`, `#!noext`, `#!default`, see `wordlist::Directive`)
+//!     are payloads too: they pass through every source in order, and the scanner applies
+//!     them to the entries that follow.
+//!
+//! Notes:
+//!   - Arguments may be quoted ("..." or '...'), which is needed for paths containing
+//!     `,` or `)`. A `-w` value that is not an expression is always a file path.
+//!   - stdin can only be read once: with several bases (`--both-schemes`, `--ports`), the
+//!     first base gets every word.
+
+use super::{
+    priority::{self, PriorityList, PrioritySource},
+    wordlist::{self, TolerantLines},
+};
+use crate::error::DirustError;
+use reqwest::Client;
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader, Stdin},
+    path::Path,
+};
+
+/// A stream of payloads (wordlist entries) for a scan.
+pub trait PayloadSource: Send {
+    /// The next payload, or `None` once the source is exhausted.
+    fn next_payload(&mut self) -> Result<Option<String>, DirustError>;
+
+    /// Up to `max` payloads; an empty chunk means the source is exhausted.
+    fn next_chunk(&mut self, max: usize) -> Result<Vec<String>, DirustError> {
+        let mut chunk: Vec<String> = Vec::new();
+        while chunk.len() < max {
+            match self.next_payload()? {
+                Some(payload) => chunk.push(payload),
+                None => break,
+            }
+        }
+        Ok(chunk)
+    }
+}
+
+/// Wordlist lines read lazily from any buffered reader (a file, stdin); lines that are not
+/// valid UTF-8 are skipped with a warning.
+pub struct LineSource<R> {
+    lines: TolerantLines<R>,
+}
+
+impl<R: BufRead + Send> PayloadSource for LineSource<R> {
+    fn next_payload(&mut self) -> Result<Option<String>, DirustError> {
+        for line_result in self.lines.by_ref() {
+            let line = line_result?;
+            if let Some(entry) = wordlist::scan_item(&line) {
+                return Ok(Some(entry.to_string()));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Payloads already in memory (a downloaded wordlist, words replayed by the scanner).
+pub struct ListSource {
+    items: VecDeque<String>,
+}
+
+impl ListSource {
+    pub fn new(items: Vec<String>) -> ListSource {
+        ListSource { items: items.into() }
+    }
+}
+
+impl PayloadSource for ListSource {
+    fn next_payload(&mut self) -> Result<Option<String>, DirustError> {
+        Ok(self.items.pop_front())
+    }
+}
+
+/// Decimal numbers from `next` to `end` (inclusive), zero-padded to `width` digits.
+pub struct RangeSource {
+    next: u64,
+    end: u64,
+    width: usize,
+    done: bool,
+}
+
+impl PayloadSource for RangeSource {
+    fn next_payload(&mut self) -> Result<Option<String>, DirustError> {
+        if self.done {
+            return Ok(None);
+        }
+        let payload = format!("{:0width$}", self.next, width = self.width);
+        // Stop at `end` instead of incrementing past it (`end` may be u64::MAX).
+        match self.next == self.end {
+            true => self.done = true,
+            false => self.next += 1,
+        }
+        Ok(Some(payload))
+    }
+}
+
+/// How `mutate(...)` rewrites each word of its inner source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutation {
+    Case,
+    Lower,
+    Upper,
+    Backup,
+}
+
+impl Mutation {
+    fn parse(name: &str) -> Result<Mutation, DirustError> {
+        match name {
+            "case" => Ok(Mutation::Case),
+            "lower" => Ok(Mutation::Lower),
+            "upper" => Ok(Mutation::Upper),
+            "backup" => Ok(Mutation::Backup),
+            other => Err(DirustError::InvalidSource(format!(
+                "unknown mutation '{}' (expected case, lower, upper or backup)",
+                other
+            ))),
+        }
+    }
+
+    /// Every form of `word` this mutation produces, without repeats.
+    fn apply(self, word: &str) -> Vec<String> {
+        let forms: Vec<String> = match self {
+            Mutation::Case => vec![
+                word.to_string(),
+                word.to_lowercase(),
+                word.to_uppercase(),
+                capitalize(word),
+            ],
+            Mutation::Lower => vec![word.to_lowercase()],
+            Mutation::Upper => vec![word.to_uppercase()],
+            Mutation::Backup => vec![
+                word.to_string(),
+                format!("{}.bak", word),
+                format!("{}.old", word),
+                format!("{}.orig", word),
+                format!("{}~", word),
+            ],
+        };
+        let mut unique: Vec<String> = Vec::with_capacity(forms.len());
+        for form in forms {
+            if !unique.contains(&form) {
+                unique.push(form);
+            }
+        }
+        unique
+    }
+}
+
+/// "admin" -> "Admin" (the rest lower-cased).
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+        None => String::new(),
+    }
+}
+
+/// Another source with every word mutated (`mutate(kind, source)`).
+pub struct MutateSource {
+    mutation: Mutation,
+    inner: Box<dyn PayloadSource>,
+    pending: VecDeque<String>,
+}
+
+impl PayloadSource for MutateSource {
+    fn next_payload(&mut self) -> Result<Option<String>, DirustError> {
+        while self.pending.is_empty() {
+            match self.inner.next_payload()? {
+                // Directives (`#!exts ...`) pass through unchanged, weight columns stay on
+                // every spelling.
+                Some(word) if word.starts_with("#!") => self.pending.push_back(word),
+                Some(line) => match priority::split_weight(&line) {
+                    (word, Some(weight)) => {
+                        let spellings = self.mutation.apply(word);
+                        self.pending.extend(spellings.into_iter().map(|s| format!("{} {}", s, weight)));
+                    }
+                    (word, None) => self.pending.extend(self.mutation.apply(word)),
+                },
+                None => return Ok(None),
+            }
+        }
+        Ok(self.pending.pop_front())
+    }
+}
+
+/// A parsed `-w` value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Spec {
+    File(String),
+    Stdin,
+    Url(String),
+    Range { start: u64, end: u64, width: usize },
+    Mutate(Mutation, Box<Spec>),
+}
+
+/// Open the source described by `-w` (a path, `-`, or a source expression), ordered by the
+/// weight column of its lines and the `--priority` list at `priority` (see `priority.rs`).
+///
+/// `client` downloads `url(...)` wordlists.
+pub async fn open(spec: &str, priority: Option<&str>, client: &Client) -> Result<Box<dyn PayloadSource>, DirustError> {
+    let parsed = parse(spec)?;
+    let list: Option<PriorityList> = match priority {
+        Some(path) => Some(priority::load_list(path)?),
+        None => None,
+    };
+    // stdin is streamed as it comes: no reordering window to fill first.
+    let window = match reads_stdin(spec) {
+        true => 1,
+        false => priority::WINDOW,
+    };
+    let inner = open_spec(&parsed, client).await?;
+    Ok(Box::new(PrioritySource::new(inner, window, list)))
+}
+
+/// Number of payloads `open(spec)` yields, when known without reading stdin or the network
+/// (files and ranges).
+pub fn count(spec: &str) -> Result<Option<usize>, DirustError> {
+    match parse(spec)? {
+        Spec::File(path) => Ok(Some(wordlist::count_entries(&path)?)),
+        Spec::Range { start, end, .. } => Ok(Some((end - start).saturating_add(1) as usize)),
+        _ => Ok(None),
+    }
+}
+
+/// Short name of the source for scan metadata: the file name of a wordlist, or the
+/// expression as given.
+pub fn label(spec: &str) -> Option<String> {
+    match parse(spec) {
+        Ok(Spec::File(path)) => Path::new(&path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+        Ok(_) => Some(spec.trim().to_string()),
+        Err(_) => None,
+    }
+}
+
+/// Whether the source reads stdin (which can only be consumed once).
+pub fn reads_stdin(spec: &str) -> bool {
+    fn uses_stdin(spec: &Spec) -> bool {
+        match spec {
+            Spec::Stdin => true,
+            Spec::Mutate(_, inner) => uses_stdin(inner),
+            _ => false,
+        }
+    }
+    parse(spec).is_ok_and(|parsed| uses_stdin(&parsed))
+}
+
+/// Build the source for a parsed spec (`mutate` opens its inner source recursively).
+async fn open_spec(spec: &Spec, client: &Client) -> Result<Box<dyn PayloadSource>, DirustError> {
+    let source: Box<dyn PayloadSource> = match spec {
+        Spec::File(path) => Box::new(LineSource {
+            lines: TolerantLines::new(wordlist::open(path)?, Some(path)),
+        }),
+        Spec::Stdin => {
+            let reader: BufReader<Stdin> = BufReader::new(std::io::stdin());
+            Box::new(LineSource {
+                lines: TolerantLines::new(reader, Some("stdin")),
+            })
+        }
+        Spec::Url(address) => {
+            let body = client.get(address).send().await?.error_for_status()?.bytes().await?;
+            let mut words: Vec<String> = Vec::new();
+            for line in TolerantLines::new(&body[..], Some(address)) {
+                if let Some(entry) = wordlist::scan_item(&line?) {
+                    words.push(entry.to_string());
+                }
+            }
+            Box::new(ListSource::new(words))
+        }
+        Spec::Range { start, end, width } => Box::new(RangeSource {
+            next: *start,
+            end: *end,
+            width: *width,
+            done: false,
+        }),
+        Spec::Mutate(mutation, inner) => Box::new(MutateSource {
+            mutation: *mutation,
+            inner: Box::pin(open_spec(inner, client)).await?,
+            pending: VecDeque::new(),
+        }),
+    };
+    Ok(source)
+}
+
+/// Parse a `-w` value: `-`, an expression such as `mutate(case, file(list.txt))`, or a path.
+fn parse(spec: &str) -> Result<Spec, DirustError> {
+    let spec = spec.trim();
+    if spec == "-" {
+        return Ok(Spec::Stdin);
+    }
+    if !is_expression(spec) {
+        return Ok(Spec::File(spec.to_string()));
+    }
+    let mut parser = Parser { rest: spec };
+    let parsed = parser.source()?;
+    if !parser.rest.trim().is_empty() {
+        return Err(DirustError::InvalidSource(format!("unexpected '{}' after the source", parser.rest.trim())));
+    }
+    Ok(parsed)
+}
+
+/// `name(...)` with a known source name; anything else is a file path.
+fn is_expression(spec: &str) -> bool {
+    match spec.split_once('(') {
+        Some((name, _)) => {
+            ["file", "stdin", "url", "range", "mutate"].contains(&name.trim()) && spec.ends_with(')')
+        }
+        None => false,
+    }
+}
+
+/// A small recursive-descent parser over the unparsed rest of the expression.
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+/// One argument of a source call.
+enum Arg {
+    Text(String),
+    Source(Spec),
+}
+
+impl Parser<'_> {
+    /// `name(args)`.
+    fn source(&mut self) -> Result<Spec, DirustError> {
+        let (name, rest) = match self.rest.trim_start().split_once('(') {
+            Some((name, rest)) => (name.trim().to_string(), rest),
+            None => return Err(DirustError::InvalidSource(format!("expected a source, found '{}'", self.rest.trim()))),
+        };
+        self.rest = rest;
+        let args = self.args()?;
+
+        let text = |arg: &Arg| -> Result<String, DirustError> {
+            match arg {
+                Arg::Text(text) => Ok(text.clone()),
+                Arg::Source(_) => Err(DirustError::InvalidSource(format!("{}(...) expects a value, not a source", name))),
+            }
+        };
+        match (name.as_str(), args.as_slice()) {
+            ("file", [path]) => Ok(Spec::File(text(path)?)),
+            ("stdin", []) => Ok(Spec::Stdin),
+            ("url", [address]) => Ok(Spec::Url(text(address)?)),
+            ("range", [start, end]) => {
+                let (start, end) = (text(start)?, text(end)?);
+                let number = |value: &str| -> Result<u64, DirustError> {
+                    value
+                        .parse::<u64>()
+                        .map_err(|_| DirustError::InvalidSource(format!("range bound '{}' is not a number", value)))
+                };
+                let (low, high) = (number(&start)?, number(&end)?);
+                if low > high {
+                    return Err(DirustError::InvalidSource(format!("range({}, {}) is empty", start, end)));
+                }
+                // A leading zero asks for fixed-width numbers: range(001, 100) -> 001 .. 100.
+                let width = if start.len() > 1 && start.starts_with('0') { start.len() } else { 0 };
+                Ok(Spec::Range { start: low, end: high, width })
+            }
+            ("mutate", [kind, Arg::Source(inner)]) => Ok(Spec::Mutate(Mutation::parse(&text(kind)?)?, Box::new(inner.clone()))),
+            (other, _) => Err(DirustError::InvalidSource(format!(
+                "bad source {}(...) (expected file(PATH), stdin(), url(URL), range(START, END) or mutate(KIND, SOURCE))",
+                other
+            ))),
+        }
+    }
+
+    /// Comma-separated arguments up to the closing `)`.
+    fn args(&mut self) -> Result<Vec<Arg>, DirustError> {
+        let mut args: Vec<Arg> = Vec::new();
+        loop {
+            self.rest = self.rest.trim_start();
+            if let Some(rest) = self.rest.strip_prefix(')') {
+                self.rest = rest;
+                return Ok(args);
+            }
+            if !args.is_empty() {
+                match self.rest.strip_prefix(',') {
+                    Some(rest) => self.rest = rest.trim_start(),
+                    None => return Err(DirustError::InvalidSource(format!("expected ',' or ')' at '{}'", self.rest))),
+                }
+            }
+            args.push(self.arg()?);
+        }
+    }
+
+    /// A quoted value, a nested source, or a bare value (up to `,` or `)`).
+    fn arg(&mut self) -> Result<Arg, DirustError> {
+        let rest = self.rest;
+        if let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') {
+            return match rest[1..].split_once(quote) {
+                Some((value, after)) => {
+                    self.rest = after;
+                    Ok(Arg::Text(value.to_string()))
+                }
+                None => Err(DirustError::InvalidSource(format!("unterminated quote in '{}'", rest))),
+            };
+        }
+        let end = rest.find([',', ')']).unwrap_or(rest.len());
+        if rest[..end].contains('(') {
+            return Ok(Arg::Source(self.source()?));
+        }
+        let value = rest[..end].trim();
+        if value.is_empty() {
+            return Err(DirustError::InvalidSource(format!("missing value at '{}'", rest)));
+        }
+        self.rest = &rest[end..];
+        Ok(Arg::Text(value.to_string()))
+    }
+}
@@ -0,0 +1,206 @@
+//! src/scanner/stats.rs
+//!
+//! Purpose:
+//!   Collect per-probe statistics during a scan and print the final summary:
+//!     - total requests, elapsed time and throughput
+//!     - a status-class histogram (1xx/2xx/3xx/4xx/5xx) with per-class average latency
+//!     - latency percentiles (p50/p95/p99) over all probes
+//!   and the per-base section of multi-base runs (`BaseSummary`: requests, findings, errors,
+//!   mean latency, `Server` / `X-Powered-By` banners of the findings).
+//!
+//! Why?
+//!   - Reports usually want "how many requests, how fast, what came back".
+//!   - Rising latencies or a sudden wall of 429/5xx answers are the typical signs of
+//!     throttling or a struggling target.
+//!
+//! Percentiles come from a fixed histogram (32 buckets per power of two of microseconds, so
+//! within ~3% of the exact value): memory does not grow with the number of probes.
+//!
+//! Latency is measured per probe as the time the transport took to answer, over all attempts
+//! (a HEAD→GET retry included), so it reflects the target. Waiting for a `--rate` slot or a
+//! `dirust run` budget permit is not latency and is left out.
+
+use crate::{
+    output,
+    schema::{BaseSummary, ResultEntry},
+};
+use std::{
+    collections::BTreeSet,
+    time::{Duration, Instant},
+};
+
+/// Headers whose values name the software of a host (see `BaseSummary::technologies`).
+const BANNER_HEADERS: &[&str] = &["Server", "X-Powered-By"];
+
+/// Status classes shown in the histogram, indexed by `status / 100 - 1`.
+const CLASSES: [&str; 5] = ["1xx", "2xx", "3xx", "4xx", "5xx"];
+
+/// Latency buckets per power of two of microseconds (and exact buckets below that many µs).
+const SUB_BUCKETS: u64 = 32;
+
+/// Buckets for every `u64` of microseconds: the exact ones, then `SUB_BUCKETS` per bit length
+/// from 6 to 64.
+const LATENCY_BUCKETS: usize = (SUB_BUCKETS + (64 - 5) * SUB_BUCKETS) as usize;
+
+/// Statistics for one scan.
+pub struct ScanStats {
+    /// When the scan started (for elapsed time and throughput).
+    started: Instant,
+    /// Number of responses per status class.
+    class_counts: [usize; 5],
+    /// Sum of latencies per status class (for the per-class average).
+    class_totals: [Duration; 5],
+    /// Probes per latency bucket (see `bucket`), for percentiles.
+    latency_buckets: Vec<u64>,
+    /// Probes recorded.
+    requests: u64,
+    /// Sum of every latency (for the mean).
+    latency_total: Duration,
+    /// Responses with a 5xx or 429 status.
+    errors: usize,
+    /// Findings reported.
+    findings: usize,
+    /// `Server` / `X-Powered-By` values of the findings.
+    banners: BTreeSet<String>,
+}
+
+impl ScanStats {
+    /// Start collecting; the elapsed-time clock starts now.
+    pub fn new() -> ScanStats {
+        ScanStats {
+            started: Instant::now(),
+            class_counts: [0; 5],
+            class_totals: [Duration::ZERO; 5],
+            latency_buckets: vec![0; LATENCY_BUCKETS],
+            requests: 0,
+            latency_total: Duration::ZERO,
+            errors: 0,
+            findings: 0,
+            banners: BTreeSet::new(),
+        }
+    }
+
+    /// Record one completed probe.
+    pub fn record(&mut self, status: u16, latency: Duration) {
+        // Out-of-range codes (e.g., 600+) are counted in the percentiles only.
+        if (100..600).contains(&status) {
+            let class = (status / 100 - 1) as usize;
+            self.class_counts[class] += 1;
+            self.class_totals[class] += latency;
+        }
+        self.latency_buckets[bucket(latency)] += 1;
+        self.requests += 1;
+        self.latency_total += latency;
+        if status >= 500 || status == 429 {
+            self.errors += 1;
+        }
+    }
+
+    /// Record one reported finding (counted, and its software banners kept).
+    pub fn record_finding(&mut self, entry: &ResultEntry) {
+        self.findings += 1;
+        if let Some(headers) = &entry.response_headers {
+            for &name in BANNER_HEADERS {
+                if let Some(value) = headers.get(name) {
+                    self.banners.insert(value.clone());
+                }
+            }
+        }
+    }
+
+    /// The per-base section for the scan of `base` (its outages are added by the scan).
+    pub fn host_summary(&self, base: &str) -> BaseSummary {
+        let mean_latency_ms = match self.requests {
+            0 => None,
+            n => Some(self.latency_total.as_secs_f64() * 1000.0 / n as f64),
+        };
+        BaseSummary {
+            base: base.to_string(),
+            requests: self.requests,
+            findings: self.findings as u64,
+            errors: self.errors as u64,
+            mean_latency_ms,
+            technologies: self.banners.iter().cloned().collect(),
+            outages: Vec::new(),
+        }
+    }
+
+    /// Print the summary block.
+    ///
+    /// Format:
+    ///   [*] summary: <n> requests in <secs>s (<rate> req/s)
+    ///       class   count   avg_ms
+    ///       2xx        12     35.1
+    ///       ...
+    ///       latency p50=<ms>ms p95=<ms>ms p99=<ms>ms
+    pub fn print_summary(&self) {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let total = self.requests;
+        let rate = if elapsed > 0.0 { total as f64 / elapsed } else { 0.0 };
+
+        output::info(&format!(
+            "[*] summary: {} requests in {:.1}s ({:.1} req/s)",
+            total, elapsed, rate
+        ));
+        if total == 0 {
+            return;
+        }
+
+        output::info(&format!("    {:<7} {:>7} {:>8}", "class", "count", "avg_ms"));
+        for (i, label) in CLASSES.iter().enumerate() {
+            let count = self.class_counts[i];
+            if count == 0 {
+                continue;
+            }
+            let avg_ms = self.class_totals[i].as_secs_f64() * 1000.0 / count as f64;
+            output::info(&format!("    {:<7} {:>7} {:>8.1}", label, count, avg_ms));
+        }
+
+        output::info(&format!(
+            "    latency p50={:.1}ms p95={:.1}ms p99={:.1}ms",
+            self.percentile_ms(50),
+            self.percentile_ms(95),
+            self.percentile_ms(99)
+        ));
+    }
+
+    /// Nearest-rank percentile of the recorded latencies (at least one), in milliseconds:
+    /// the middle of the bucket holding that rank.
+    fn percentile_ms(&self, pct: u64) -> f64 {
+        // Rank = ceil(pct/100 * n), 1-based.
+        let rank = (pct * self.requests).div_ceil(100).max(1);
+        let mut seen: u64 = 0;
+        for (index, count) in self.latency_buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return bucket_middle_micros(index) / 1000.0;
+            }
+        }
+        0.0
+    }
+}
+
+/// Histogram bucket of `latency`: exact below `SUB_BUCKETS` µs, then `SUB_BUCKETS` buckets
+/// per power of two (the 6 leading bits of the microsecond count).
+fn bucket(latency: Duration) -> usize {
+    let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+    let bits = 64 - u64::from(micros.leading_zeros());
+    if bits <= 5 {
+        return micros as usize;
+    }
+    let shift = bits - 6;
+    let sub = (micros >> shift) - SUB_BUCKETS;
+    (SUB_BUCKETS + shift * SUB_BUCKETS + sub) as usize
+}
+
+/// Middle of histogram bucket `index`, in microseconds.
+fn bucket_middle_micros(index: usize) -> f64 {
+    let index = index as u64;
+    if index < SUB_BUCKETS {
+        return index as f64;
+    }
+    let shift = (index - SUB_BUCKETS) / SUB_BUCKETS;
+    let sub = (index - SUB_BUCKETS) % SUB_BUCKETS;
+    let low = (sub + SUB_BUCKETS) << shift;
+    low as f64 + ((1u64 << shift) as f64 - 1.0) / 2.0
+}
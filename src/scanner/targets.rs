@@ -1,7 +1,11 @@
+use super::wordlist::Directive;
+use crate::url::canonical_target;
+
 /// Build a list of absolute URLs to probe, based on:
 ///   - `base`: normalized base URL (must end with '/')
 ///   - `words`: entries from the wordlist (e.g., "admin", "admin/", "readme.txt")
 ///   - `exts`: extra extensions to try (e.g., [".php", ".html", ".txt"])
+///   - `add_slash`: also try the directory form of plain names (`--add-slash`)
 ///
 /// Rules:
 ///   1) Always include the "as-is" path: base + cleaned word.
@@ -9,12 +13,16 @@
 ///      DO NOT append extra extensions.
 ///   3) If the word already has a dot (e.g., "readme.txt"), treat it as a file that
 ///      already has an extension — DO NOT append extra extensions.
-///   4) Only when the word is a "plain name" (no '/' and no '.'), append all extra extensions.
-pub fn build_targets(base: &str, words: &[String], exts: &[String]) -> Vec<String> {
+///   4) Only when the word is a "plain name" (no '/' and no '.'), append all extra extensions
+///      and, with `add_slash`, the trailing-slash form ("admin" -> ".../admin/").
+///   5) Every URL is put in canonical form (`url::canonical_target`): `admin//x`, `./admin`
+///      and `x/../admin` style entries do not become separate probes of the same path.
+pub fn build_targets(base: &str, words: &[String], exts: &[String], add_slash: bool) -> Vec<String> {
     // Pre-calculate capacity to reduce re-allocations:
     // - If there are no extensions, we add exactly 1 target per word (the as-is URL).
     // - If there are N extensions, we add up to (1 + N) targets per word (as-is + each ext).
-    let per_word_estimate: usize = if exts.is_empty() { 1 } else { 1 + exts.len() };
+    // - `add_slash` adds one more (the directory form).
+    let per_word_estimate: usize = 1 + exts.len() + usize::from(add_slash);
     let capacity: usize = words.len() * per_word_estimate;
 
     // Pre-allocate the output vector with the estimated capacity.
@@ -28,9 +36,6 @@ pub fn build_targets(base: &str, words: &[String], exts: &[String]) -> Vec<Strin
         let trimmed: &str = raw.trim();
         let cleaned: &str = trimmed.trim_start_matches('/');
 
-        // Optional: progress logging to understand what we are processing.
-        println!("Processing word: {}", cleaned);
-
         // Skip empty lines or lines that become empty after trimming.
         if cleaned.is_empty() {
             continue;
@@ -55,8 +60,7 @@ pub fn build_targets(base: &str, words: &[String], exts: &[String]) -> Vec<Strin
         //    - plain names ("admin" -> ".../admin")
         //    - directories ("admin/" -> ".../admin/")
         let as_is_url: String = format!("{}{}", base, cleaned);
-        println!("{}", as_is_url);
-        targets.push(as_is_url);
+        targets.push(canonical_target(&as_is_url));
 
         // 4) Only append extensions when the entry is a simple "name" (no slashes, no dots).
         //    Examples where we DO append:
@@ -70,8 +74,13 @@ pub fn build_targets(base: &str, words: &[String], exts: &[String]) -> Vec<Strin
             // Append each configured extension to the base + cleaned word.
             for ext in exts {
                 let with_ext_url: String = format!("{}{}{}", base, cleaned, ext);
-                println!("{}", with_ext_url);
-                targets.push(with_ext_url);
+                targets.push(canonical_target(&with_ext_url));
+            }
+
+            // Many servers only answer on the directory form ("admin/", not "admin").
+            if add_slash {
+                let slash_url: String = format!("{}{}/", base, cleaned);
+                targets.push(canonical_target(&slash_url));
             }
         }
     }
@@ -79,3 +88,59 @@ pub fn build_targets(base: &str, words: &[String], exts: &[String]) -> Vec<Strin
     // Return the complete list of targets to probe.
     targets
 }
+
+/// Build the targets of a chunk of payloads that may hold wordlist directives (`#!exts php`,
+/// `#!noext`, `#!default`, see `wordlist::Directive`).
+///
+/// Entries are expanded with the extensions of the last directive seen, kept in `overrides`
+/// from one chunk to the next (`None`: the scan's own `exts`).
+///
+/// Returns the targets, and the words expanded with `exts` (what `--adaptive-exts=defer`
+/// probes again with the deferred extensions).
+pub fn build_chunk_targets(
+    base: &str,
+    payloads: &[String],
+    exts: &[String],
+    overrides: &mut Option<Vec<String>>,
+    add_slash: bool,
+) -> (Vec<String>, Vec<String>) {
+    let mut targets: Vec<String> = Vec::new();
+    let mut default_words: Vec<String> = Vec::new();
+    let mut run: Vec<String> = Vec::new();
+
+    for payload in payloads {
+        match Directive::parse(payload) {
+            Some(directive) => {
+                flush_run(base, &mut run, exts, overrides, add_slash, &mut targets, &mut default_words);
+                *overrides = directive.extensions();
+            }
+            None => run.push(payload.clone()),
+        }
+    }
+    flush_run(base, &mut run, exts, overrides, add_slash, &mut targets, &mut default_words);
+
+    (targets, default_words)
+}
+
+/// Expand the words collected under one directive.
+fn flush_run(
+    base: &str,
+    run: &mut Vec<String>,
+    exts: &[String],
+    overrides: &Option<Vec<String>>,
+    add_slash: bool,
+    targets: &mut Vec<String>,
+    default_words: &mut Vec<String>,
+) {
+    if run.is_empty() {
+        return;
+    }
+    match overrides {
+        Some(chosen) => targets.extend(build_targets(base, run, chosen, add_slash)),
+        None => {
+            targets.extend(build_targets(base, run, exts, add_slash));
+            default_words.extend(run.iter().cloned());
+        }
+    }
+    run.clear();
+}
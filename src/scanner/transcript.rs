@@ -0,0 +1,180 @@
+//! src/scanner/transcript.rs
+//!
+//! Purpose:
+//!   `--transcript <PATH>`: an auditable record of exactly what a scan sent. Every probe
+//!   request (both engines, including HEAD→GET retries and directory confirmations) becomes
+//!   one JSON line with the request and what came back:
+//!
+//!     {"time":"2024-04-05T19:21:18.042Z","elapsed_ms":12.4,
+//!      "request":{"method":"HEAD","url":"https://example.com/admin",
+//!                 "headers":[["user-agent","dirust/0.1.1"]]},
+//!      "response":{"status":301,"headers":[["location","/admin/"],...]}}
+//!
+//!   A request that failed has `"error"` instead of `"response"`.
+//!
+//! Notes:
+//!   - Request headers are the ones dirust sets, in the order sent. Headers the HTTP
+//!     library adds on the wire (`Host`; reqwest also adds `Accept: */*` when no `Accept`
+//!     was set) are not listed.
+//!   - Bodies are only recorded with `--transcript-body <BYTES>`: the first BYTES bytes of
+//!     GET response bodies (HEAD responses have none), as lossy UTF-8.
+//!   - `Authorization` and cookie values (`--keep-cookies`, `Set-Cookie`) are masked unless
+//!     `--no-redact` is given.
+//!   - Body downloads done after a probe (`--forms`, `--secrets`, `--measure-chunked`) are
+//!     not part of the transcript.
+
+use crate::{error::DirustError, export, redact};
+use reqwest::{header::HeaderMap, StatusCode};
+use serde::Serialize;
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufWriter, Write},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// One request as recorded.
+#[derive(Serialize)]
+struct RequestRecord<'a> {
+    method: &'a str,
+    url: &'a str,
+    headers: Vec<(String, String)>,
+}
+
+/// One response as recorded.
+#[derive(Serialize)]
+struct ResponseRecord {
+    status: u16,
+    headers: Vec<(String, String)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    /// The body was longer than `--transcript-body`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    body_truncated: bool,
+}
+
+/// One line of the transcript.
+#[derive(Serialize)]
+struct Line<'a> {
+    time: String,
+    elapsed_ms: f64,
+    request: RequestRecord<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response: Option<ResponseRecord>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// What came back for one request.
+pub struct Received<'a> {
+    pub status: StatusCode,
+    pub headers: &'a HeaderMap,
+    /// The start of the body (at most `body_limit()` bytes).
+    pub body: &'a [u8],
+    pub body_truncated: bool,
+}
+
+/// One request/response pair, handed to `Transcript::record`.
+pub struct Exchange<'a> {
+    pub method: &'a str,
+    pub url: &'a str,
+    pub request_headers: &'a HeaderMap,
+    /// When the request was sent.
+    pub sent_at: SystemTime,
+    pub elapsed: Duration,
+    pub outcome: Result<Received<'a>, &'a DirustError>,
+}
+
+/// An open transcript file, shared by all probe tasks.
+pub struct Transcript {
+    out: Mutex<BufWriter<File>>,
+    body_limit: usize,
+}
+
+impl Transcript {
+    /// Open the transcript at `path`, keeping up to `body_limit` bytes of each GET response
+    /// body (0 = no bodies). The file is truncated, or appended to when `append` is set
+    /// (a resumed scan continues its transcript).
+    pub fn open(path: &str, body_limit: usize, append: bool) -> Result<Transcript, DirustError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)?;
+        Ok(Transcript {
+            out: Mutex::new(BufWriter::new(file)),
+            body_limit,
+        })
+    }
+
+    /// How many body bytes to keep per response (0 = none).
+    pub fn body_limit(&self) -> usize {
+        self.body_limit
+    }
+
+    /// Append one exchange.
+    pub fn record(&self, exchange: Exchange<'_>) -> Result<(), DirustError> {
+        let since_epoch = exchange.sent_at.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let (response, error) = match exchange.outcome {
+            Ok(received) => {
+                let body = match self.body_limit {
+                    0 => None,
+                    _ if received.body.is_empty() => None,
+                    _ => Some(String::from_utf8_lossy(received.body).into_owned()),
+                };
+                let record = ResponseRecord {
+                    status: received.status.as_u16(),
+                    headers: header_pairs(received.headers),
+                    body,
+                    body_truncated: received.body_truncated,
+                };
+                (Some(record), None)
+            }
+            Err(e) => (None, Some(redact::text(&e.to_string()).into_owned())),
+        };
+        let url = redact::text(exchange.url);
+        let line = Line {
+            time: export::rfc3339_millis(since_epoch),
+            elapsed_ms: exchange.elapsed.as_secs_f64() * 1000.0,
+            request: RequestRecord {
+                method: exchange.method,
+                url: &url,
+                headers: header_pairs(exchange.request_headers),
+            },
+            response,
+            error,
+        };
+        let text = serde_json::to_string(&line)?;
+
+        // A poisoned lock only means another task panicked mid-write; keep recording.
+        let mut out = match self.out.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        writeln!(out, "{}", text)?;
+        Ok(())
+    }
+
+    /// Write out buffered lines.
+    pub fn flush(&self) -> Result<(), DirustError> {
+        let mut out = match self.out.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Headers as (name, value) pairs in order; values that are not UTF-8 are converted lossily,
+/// credentials are masked (see `redact.rs`).
+fn header_pairs(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes());
+            (name.to_string(), redact::header_value(name.as_str(), &value).into_owned())
+        })
+        .collect()
+}
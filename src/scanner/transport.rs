@@ -0,0 +1,244 @@
+//! src/scanner/transport.rs
+//!
+//! Purpose:
+//!   The seam between the scanner and the network. Everything a scan sends (probes,
+//!   calibration, body downloads for `--forms` / `--secrets` / `--measure-chunked`) goes
+//!   through a `ProbeClient`, so the scanner core can run without sockets.
+//!
+//! Implementations:
+//!   - `http::ReqwestProbe` : the default engine (reqwest)
+//!   - `fast::FastClient`   : `--engine fast` (hyper)
+//!   - `FakeClient`         : in-memory and deterministic; answers from a handler function
+//!     and records every request (`--self-test --in-memory`, unit tests)
+//!
+//! Contract:
+//!   - `send` is ONE request, exactly as the `middleware::ProbeRequest` describes it: its
+//!     method, its headers in map order, redirects not followed, its `timeout` (if given)
+//!     instead of `--timeout`. Headers, the GET retry on 405, `--keep-cookies` and
+//!     `--slow-retry-timeout` are middleware layers (`middleware.rs`), the same for every
+//!     implementation.
+//!   - `fetch` downloads a whole body with a GET (decoded as `http::read_body` does).
+//!   - `measure` counts body bytes on the wire up to a cap; the default reads the body
+//!     through `fetch`.
+//!
+//! Notes:
+//!   - The futures are boxed so the trait stays object-safe (`Arc<dyn ProbeClient>`).
+//!   - A custom transport is set through `ScanHooks::transport`; it replaces `--engine`, and
+//!     `--transcript` only records what the transport itself records.
+
+use super::{
+    http::{self, BodySize, HttpSummary},
+    middleware::ProbeRequest,
+};
+use crate::error::DirustError;
+use reqwest::{header::HeaderMap, StatusCode};
+use std::{future::Future, pin::Pin, sync::Mutex};
+
+/// What `ProbeClient::send` returns.
+pub type ProbeFuture<'a> = Pin<Box<dyn Future<Output = Result<HttpSummary, DirustError>> + Send + 'a>>;
+
+/// What `ProbeClient::fetch` returns.
+pub type FetchFuture<'a> = Pin<Box<dyn Future<Output = Result<FetchedBody, DirustError>> + Send + 'a>>;
+
+/// What `ProbeClient::measure` returns: (size, capped).
+pub type MeasureFuture<'a> = Pin<Box<dyn Future<Output = Result<(u64, bool), DirustError>> + Send + 'a>>;
+
+/// A downloaded body.
+#[derive(Debug, Clone)]
+pub struct FetchedBody {
+    pub status: StatusCode,
+    /// The `Content-Type` header, if valid text (for `charset::decode`).
+    pub content_type: Option<String>,
+    /// The body, `Content-Encoding` undone.
+    pub body: Vec<u8>,
+    pub size: BodySize,
+}
+
+/// Something that can send the requests of a scan (see the module docs).
+pub trait ProbeClient: Send + Sync {
+    /// Send `request` and summarize the response.
+    fn send<'a>(&'a self, request: &'a ProbeRequest) -> ProbeFuture<'a>;
+
+    /// Download `url`'s whole body with a GET.
+    fn fetch<'a>(&'a self, url: &'a str) -> FetchFuture<'a>;
+
+    /// Size of `url`'s body on the wire, reading at most `cap` bytes (`(cap, true)` when the
+    /// body is larger).
+    fn measure<'a>(&'a self, url: &'a str, cap: u64) -> MeasureFuture<'a> {
+        Box::pin(async move {
+            let fetched = self.fetch(url).await?;
+            match fetched.size.transferred >= cap {
+                true => Ok((cap, true)),
+                false => Ok((fetched.size.transferred, false)),
+            }
+        })
+    }
+}
+
+/// A canned response of a `FakeClient`.
+#[derive(Debug, Clone)]
+pub struct FakeResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// The GET body (HEAD responses get the same headers and no body).
+    pub body: Vec<u8>,
+}
+
+impl FakeResponse {
+    /// A response with `status`, `body` and a matching `Content-Length`.
+    pub fn new(status: u16, body: &[u8]) -> FakeResponse {
+        let mut headers = HeaderMap::new();
+        headers.insert(reqwest::header::CONTENT_LENGTH, body.len().into());
+        FakeResponse {
+            status: StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            headers,
+            body: body.to_vec(),
+        }
+    }
+
+    /// Add a header (ignored if the name or value is not valid).
+    pub fn with_header(mut self, name: &str, value: &str) -> FakeResponse {
+        if let (Ok(name), Ok(value)) = (
+            reqwest::header::HeaderName::from_bytes(name.as_bytes()),
+            reqwest::header::HeaderValue::from_str(value),
+        ) {
+            self.headers.append(name, value);
+        }
+        self
+    }
+}
+
+/// The function a `FakeClient` answers with: (method, url) -> response.
+type Handler = Box<dyn Fn(&str, &str) -> FakeResponse + Send + Sync>;
+
+/// An in-memory transport: every request is answered by a handler, instantly and in the
+/// same way every time, and recorded.
+pub struct FakeClient {
+    handler: Handler,
+    /// "METHOD url" of every request, in the order they were made.
+    requests: Mutex<Vec<String>>,
+}
+
+impl FakeClient {
+    /// A client answering with `handler(method, url)`; the method is "HEAD" or "GET".
+    pub fn new<F>(handler: F) -> FakeClient
+    where
+        F: Fn(&str, &str) -> FakeResponse + Send + Sync + 'static,
+    {
+        FakeClient {
+            handler: Box::new(handler),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Every request made so far, as "METHOD url".
+    pub fn requests(&self) -> Vec<String> {
+        match self.requests.lock() {
+            Ok(g) => g.clone(),
+            Err(p) => p.into_inner().clone(),
+        }
+    }
+
+    /// Record the request and produce its response.
+    fn answer(&self, method: &str, url: &str) -> FakeResponse {
+        let mut requests = match self.requests.lock() {
+            Ok(g) => g,
+            Err(p) => p.into_inner(),
+        };
+        requests.push(format!("{} {}", method, url));
+        drop(requests);
+        (self.handler)(method, url)
+    }
+}
+
+impl ProbeClient for FakeClient {
+    fn send<'a>(&'a self, request: &'a ProbeRequest) -> ProbeFuture<'a> {
+        let response = self.answer(request.method(), &request.url);
+        Box::pin(async move { Ok(http::summarize_headers(response.status, &response.headers)) })
+    }
+
+    fn fetch<'a>(&'a self, url: &'a str) -> FetchFuture<'a> {
+        let response = self.answer("GET", url);
+        let size = BodySize {
+            transferred: response.body.len() as u64,
+            decoded: response.body.len() as u64,
+        };
+        let content_type = response
+            .headers
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+        Box::pin(async move {
+            Ok(FetchedBody {
+                status: response.status,
+                content_type,
+                body: response.body,
+                size,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FakeClient, FakeResponse};
+    use crate::{
+        args::{Cli, Command, ScanArgs},
+        client,
+        db::ResultsDb,
+        scanner::{self, source::ListSource, ScanHooks},
+    };
+    use std::sync::Arc;
+
+    const BASE: &str = "http://fake.invalid/";
+
+    /// `dirust scan <BASE> -w - <flags>` as the scanner sees it.
+    fn scan_args(flags: &[&str]) -> ScanArgs {
+        let mut argv: Vec<String> = ["dirust", "scan", BASE, "-w", "-", "-q"].iter().map(|s| s.to_string()).collect();
+        argv.extend(flags.iter().map(|s| s.to_string()));
+        match Cli::try_parse_with_presets(&argv) {
+            Ok(Cli { command: Command::Scan(args), .. }) => *args,
+            _ => panic!("not a scan command line: {:?}", argv),
+        }
+    }
+
+    /// `admin` refuses HEAD (405) and answers GET, `backup` is forbidden, the rest is missing.
+    fn answer(method: &str, url: &str) -> FakeResponse {
+        match (method, url.trim_start_matches(BASE)) {
+            ("HEAD", "admin") => FakeResponse::new(405, b""),
+            (_, "admin") => FakeResponse::new(200, b"admin panel"),
+            (_, "backup") => FakeResponse::new(403, b"forbidden"),
+            _ => FakeResponse::new(404, b"not found"),
+        }
+    }
+
+    #[tokio::test]
+    async fn scan_through_fake_client() {
+        let args = scan_args(&[]);
+        let fake = Arc::new(FakeClient::new(answer));
+        let hooks = ScanHooks {
+            transport: Some(fake.clone()),
+            ..ScanHooks::default()
+        };
+        let words: Vec<String> = ["admin", "backup", "missing", "admin", "/admin"].iter().map(|s| s.to_string()).collect();
+        let client = client::build_client(&args.http).expect("client");
+        let mut db = ResultsDb::default();
+        scanner::scan(&client, BASE, &args, Box::new(ListSource::new(words)), &mut db, None, &hooks)
+            .await
+            .expect("scan");
+
+        // Hits are reported with the status of the final answer.
+        assert_eq!(db.get(&format!("{}admin", BASE)).map(|e| e.status), Some(200));
+        assert_eq!(db.get(&format!("{}backup", BASE)).map(|e| e.status), Some(403));
+        assert!(db.get(&format!("{}missing", BASE)).is_none());
+
+        let requests = fake.requests();
+        let count = |request: &str| requests.iter().filter(|r| r.as_str() == request).count();
+        // The 405 on HEAD is retried once as GET.
+        assert_eq!(count(&format!("HEAD {}admin", BASE)), 1);
+        assert_eq!(count(&format!("GET {}admin", BASE)), 1);
+        // Duplicate words (`admin`, `/admin`) are probed once.
+        assert_eq!(count(&format!("HEAD {}backup", BASE)), 1);
+        assert_eq!(count(&format!("HEAD {}missing", BASE)), 1);
+    }
+}
@@ -1,34 +1,9 @@
 //! Small helper functions used across the scanner module:
-//!   - `timestamp_seconds()`: produce a UNIX timestamp string for log lines.
 //!   - `is_interesting_status()`: decide whether a given HTTP status code is worth printing.
 //!
 //! We keep these helpers here to avoid cluttering the main scanning logic.
 
 use reqwest::StatusCode;
-use std::time::{SystemTime, UNIX_EPOCH};
-
-/// Return the current UNIX timestamp (seconds since 1970-01-01 00:00:00 UTC) as a String.
-///
-/// Why a string?
-///   - Our printing function expects a string to plug into `println!`.
-///   - Keeping it as a string avoids repeated conversions at every call site.
-///
-/// Error handling:
-///   - `SystemTime::now().duration_since(UNIX_EPOCH)` can theoretically fail if the system clock
-///     is set *before* the epoch. That would indicate a broken clock/config.
-///   - We call `.expect(...)` here to crash early with a clear message in that (rare) situation,
-///     because continuing would make our output timestamps meaningless.
-pub fn timestamp_seconds() -> String {
-    // Get "now" according to the OS.
-    let now = SystemTime::now()
-        // Compute how much time has elapsed since the UNIX epoch.
-        .duration_since(UNIX_EPOCH)
-        // If the system time is earlier than the epoch, abort with a clear message.
-        .expect("system time before UNIX_EPOCH");
-
-    // Format the elapsed seconds as a decimal string, e.g., "1723456789".
-    format!("{}", now.as_secs())
-}
 
 /// Return `true` if this HTTP status code is considered "interesting" for directory discovery.
 ///
@@ -0,0 +1,85 @@
+//! src/scanner/variants.rs
+//!
+//! Purpose:
+//!   `--group-variants`: extension expansion turns one resource into several findings —
+//!   `admin`, `admin.php` and `admin/` often all answer with the very same response. Such
+//!   findings are printed as ONE line that lists the other variants:
+//!
+//!     200 len=1234  https://example.com/admin [HIGH admin-panel] (variants: admin.php, admin/)
+//!
+//! How:
+//!   - Findings of a wave are held back until the wave ends (all variants of a word are built
+//!     into the same wave), then grouped and printed.
+//!   - Variants share a stem: the URL without its trailing `/` and without one of the
+//!     `--exts` extensions (`admin`, `admin.php`, `admin/` → `admin`).
+//!   - They only group when the responses look identical: same status, Content-Length,
+//!     Location and ETag (a HEAD scan has no body to hash; the ETag is the server's hash).
+//!   - The shortest URL of a group is the one printed. Result files keep every URL.
+
+use super::{http::HttpSummary, print_line};
+use std::collections::HashMap;
+
+/// A finding held back for grouping: URL, response and its full label.
+pub struct HeldFinding {
+    pub url: String,
+    pub summary: HttpSummary,
+    pub label: String,
+}
+
+/// Group `held` findings by stem and identical response, print one line per group, and
+/// return how many lines were saved.
+pub fn print_grouped(held: Vec<HeldFinding>, extensions: &[String]) -> usize {
+    // Groups in arrival order of their first member.
+    let mut order: Vec<String> = Vec::new();
+    let mut groups: HashMap<String, Vec<HeldFinding>> = HashMap::new();
+    for finding in held {
+        let key = group_key(&finding, extensions);
+        if !groups.contains_key(&key) {
+            order.push(key.clone());
+        }
+        groups.entry(key).or_default().push(finding);
+    }
+
+    let mut saved: usize = 0;
+    for key in order {
+        let mut members = match groups.remove(&key) {
+            Some(m) => m,
+            None => continue,
+        };
+        members.sort_by(|a, b| a.url.len().cmp(&b.url.len()).then_with(|| a.url.cmp(&b.url)));
+        let first = members.remove(0);
+        let mut label = first.label.clone();
+        if !members.is_empty() {
+            let parent_end = stem(&first.url, extensions).rfind('/').map_or(0, |i| i + 1);
+            let names: Vec<&str> = members.iter().map(|m| m.url.get(parent_end..).unwrap_or(&m.url)).collect();
+            label.push_str(&format!(" (variants: {})", names.join(", ")));
+            saved += members.len();
+        }
+        print_line(&first.url, &first.summary, &label);
+    }
+    saved
+}
+
+/// Stem plus everything that must match for two findings to be the same resource.
+fn group_key(finding: &HeldFinding, extensions: &[String]) -> String {
+    let summary = &finding.summary;
+    format!(
+        "{}|{}|{}|{}|{}",
+        stem(&finding.url, extensions),
+        summary.status.as_u16(),
+        summary.content_length.as_deref().unwrap_or("-"),
+        summary.location.as_deref().unwrap_or("-"),
+        summary.etag.as_deref().unwrap_or("-")
+    )
+}
+
+/// The URL without a trailing `/` and without one `--exts` extension.
+fn stem<'a>(url: &'a str, extensions: &[String]) -> &'a str {
+    let trimmed = url.strip_suffix('/').unwrap_or(url);
+    for ext in extensions {
+        if let Some(base) = trimmed.strip_suffix(ext.as_str()) {
+            return base;
+        }
+    }
+    trimmed
+}
@@ -0,0 +1,170 @@
+//! src/scanner/verify.rs
+//!
+//! Purpose:
+//!   `--verify`: a second, slow pass over the findings of a scan. Every finding is requested
+//!   once more with GET, body included, to confirm that it is reproducible (a busy server,
+//!   a deploy in progress or a flaky WAF produce findings that are gone a minute later) and
+//!   to enrich it with what only the body tells: page title, SHA-256 hash and length.
+//!
+//! How:
+//!   1) The findings reported by the main scan are collected in order.
+//!   2) After the scan, they are fetched one at a time, `REQ_PER_SEC` requests per second at
+//!      most (2 by default), so the pass stays well below the scan's own rate.
+//!   3) A finding is reproduced when the GET returns the status the probe saw. Each one gets
+//!      a `verification` block in result files; the ones that were not reproduced are also
+//!      tagged `unverified`.
+//!
+//! Output:
+//!   [*] verify: re-checking 3 finding(s) at 2 req/s
+//!       200 len=5120  http://example.com/admin  "Admin login"
+//!       [!] not reproduced: http://example.com/tmp (200 → 404)
+//!   [*] verify: 2 of 3 finding(s) reproduced
+//!
+//! Host header injection (`--host-injection`):
+//!   - Every finding is also requested with a poisoned `Host` and `X-Forwarded-Host` (see
+//!     `fuzz/hostinject.rs`), at the same rate. Reflections are printed as `HOSTINJECT`
+//!     lines, listed under `host_injection`, tagged `host-injection` and raise the finding
+//!     to medium severity at least.
+//!
+//! Notes:
+//!   - Redirects are not followed (same as the probes), so a 301 finding compares as a 301.
+//!   - The title is taken from HTML bodies only (decoded with their charset, see `charset.rs`).
+//!   - A cancelled scan skips the pass; a request that fails counts as not reproduced.
+
+use super::{binary, charset, http::ProbeEngine};
+use crate::{
+    db::ResultsDb,
+    fuzz::hostinject,
+    output,
+    schema::{ResultEntry, Verification},
+    severity::Severity,
+};
+use reqwest::Client;
+use ring::digest::{digest, SHA256};
+use std::time::Duration;
+
+/// Longest title kept, in characters.
+const MAX_TITLE_CHARS: usize = 120;
+
+/// Re-request every finding of `urls` (stored in `db`) at `rate` requests per second and
+/// record the outcome on its entry. With `injection` (`--host-injection`), each finding is
+/// also sent through that client with poisoned host headers.
+pub async fn run(engine: &ProbeEngine, db: &mut ResultsDb, urls: &[String], rate: u32, injection: Option<&Client>) {
+    if urls.is_empty() {
+        return;
+    }
+    output::info(&format!("[*] verify: re-checking {} finding(s) at {} req/s", urls.len(), rate));
+    let interval = Duration::from_secs_f64(1.0 / f64::from(rate.max(1)));
+    let mut reproduced: usize = 0;
+    let mut injectable: usize = 0;
+
+    for (i, url) in urls.iter().enumerate() {
+        let mut entry: ResultEntry = match db.get(url) {
+            Some(entry) => entry.clone(),
+            None => continue,
+        };
+        if i > 0 {
+            tokio::time::sleep(interval).await;
+        }
+
+        let mut verification = match engine.fetch(url).await {
+            Ok(fetched) => {
+                let title = match binary::looks_binary(&fetched.body) {
+                    true => None,
+                    false => page_title(&charset::decode(fetched.content_type.as_deref(), &fetched.body)),
+                };
+                Verification {
+                    reproduced: fetched.status.as_u16() == entry.status,
+                    status: Some(fetched.status.as_u16()),
+                    length: Some(fetched.body.len() as u64),
+                    sha256: Some(hex(digest(&SHA256, &fetched.body).as_ref())),
+                    title,
+                    host_injection: Vec::new(),
+                }
+            }
+            Err(e) => {
+                eprintln!("[!] verify: could not fetch {}: {}", url, e);
+                Verification {
+                    reproduced: false,
+                    status: None,
+                    length: None,
+                    sha256: None,
+                    title: None,
+                    host_injection: Vec::new(),
+                }
+            }
+        };
+
+        match verification.reproduced {
+            true => {
+                reproduced += 1;
+                let title = match &verification.title {
+                    Some(title) => format!("  \"{}\"", title),
+                    None => String::new(),
+                };
+                output::info(&format!(
+                    "    {} len={}  {}{}",
+                    entry.status,
+                    verification.length.unwrap_or(0),
+                    url,
+                    title
+                ));
+            }
+            false => {
+                let now = match verification.status {
+                    Some(status) => status.to_string(),
+                    None => "error".to_string(),
+                };
+                output::info(&format!("    [!] not reproduced: {} ({} → {})", url, entry.status, now));
+                if !entry.tags.iter().any(|tag| tag == "unverified") {
+                    entry.tags.push("unverified".to_string());
+                }
+            }
+        }
+
+        if let Some(client) = injection {
+            match hostinject::check(client, url, interval).await {
+                Ok(found) if !found.is_empty() => {
+                    injectable += 1;
+                    verification.host_injection = found.iter().map(|i| i.describe()).collect();
+                    entry.severity = Some(entry.severity.map_or(Severity::Medium, |s| s.max(Severity::Medium)));
+                    if !entry.tags.iter().any(|tag| tag == "host-injection") {
+                        entry.tags.push("host-injection".to_string());
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("[!] HOSTINJECT {}: {}", url, e),
+            }
+        }
+        entry.verification = Some(verification);
+        db.apply(entry, true);
+    }
+
+    match injection {
+        Some(_) => output::info(&format!(
+            "[*] verify: {} of {} finding(s) reproduced, {} with host header injection",
+            reproduced,
+            urls.len(),
+            injectable
+        )),
+        None => output::info(&format!("[*] verify: {} of {} finding(s) reproduced", reproduced, urls.len())),
+    }
+}
+
+/// The `<title>` of an HTML page, whitespace collapsed and shortened to `MAX_TITLE_CHARS`.
+fn page_title(body: &str) -> Option<String> {
+    let lower = body.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    let title: String = body[start..end].split_whitespace().collect::<Vec<_>>().join(" ");
+    match title.is_empty() {
+        true => None,
+        false => Some(title.chars().take(MAX_TITLE_CHARS).collect()),
+    }
+}
+
+/// Lower-case hex of `bytes`.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -1,16 +1,221 @@
 use crate::error::DirustError;
+use flate2::bufread::MultiGzDecoder;
 use std::{
     fs::File,
-    io::{BufRead, BufReader},
+    io::{self, BufRead, BufReader},
 };
 
+/// First bytes of a gzip stream.
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// First bytes of a zstd frame.
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Open the wordlist at `path` for reading, decompressing it on the fly when it is gzip
+/// (`list.txt.gz`) or zstd (`list.txt.zst`) compressed. The format is recognized by its
+/// first bytes, not by the file name, so a renamed file still reads.
+pub fn open(path: &str) -> Result<Box<dyn BufRead + Send>, DirustError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let head = reader.fill_buf()?;
+    if head.starts_with(GZIP_MAGIC) {
+        return Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))));
+    }
+    if head.starts_with(ZSTD_MAGIC) {
+        return Ok(Box::new(BufReader::new(zstd::stream::read::Decoder::with_buffer(reader)?)));
+    }
+    Ok(Box::new(reader))
+}
+
+/// Undecodable lines reported one by one before only the final count is printed.
+const MAX_LINE_WARNINGS: usize = 3;
+
+/// Lines of a wordlist, like `BufRead::lines`, except that lines which are not valid UTF-8
+/// (binary junk in big community lists) are skipped instead of ending the read.
+///
+/// With a label, the first few skipped lines are reported by number and the total count is
+/// printed once the reader is exhausted; without one, they are skipped silently. Real I/O
+/// errors are still returned.
+pub struct TolerantLines<R> {
+    reader: R,
+    label: Option<String>,
+    buf: Vec<u8>,
+    line_no: usize,
+    skipped: usize,
+}
+
+impl<R: BufRead> TolerantLines<R> {
+    /// Read `reader`, reporting skipped lines under `label` (`None`: silently).
+    pub fn new(reader: R, label: Option<&str>) -> TolerantLines<R> {
+        TolerantLines {
+            reader,
+            label: label.map(str::to_string),
+            buf: Vec::new(),
+            line_no: 0,
+            skipped: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for TolerantLines<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<io::Result<String>> {
+        loop {
+            self.buf.clear();
+            match self.reader.read_until(b'\n', &mut self.buf) {
+                Ok(0) => {
+                    if let Some(label) = &self.label
+                        && self.skipped > 0
+                    {
+                        eprintln!("[!] wordlist: skipped {} line(s) of {} that are not valid UTF-8", self.skipped, label);
+                        self.skipped = 0;
+                    }
+                    return None;
+                }
+                Ok(_) => {}
+                Err(e) => return Some(Err(e)),
+            }
+            self.line_no += 1;
+
+            // Same line endings as `BufRead::lines`: "\n" or "\r\n".
+            if self.buf.last() == Some(&b'\n') {
+                self.buf.pop();
+                if self.buf.last() == Some(&b'\r') {
+                    self.buf.pop();
+                }
+            }
+            match std::str::from_utf8(&self.buf) {
+                Ok(line) => return Some(Ok(line.to_string())),
+                Err(_) => {
+                    self.skipped += 1;
+                    if let Some(label) = &self.label
+                        && self.skipped <= MAX_LINE_WARNINGS
+                    {
+                        eprintln!("[!] wordlist: skipping line {} of {} (not valid UTF-8)", self.line_no, label);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Count the entries `read_wordlist` would return (non-empty, non-comment lines) without
+/// keeping them in memory.
+pub fn count_entries(path: &str) -> Result<usize, DirustError> {
+    let mut count: usize = 0;
+    for line_result in TolerantLines::new(open(path)?, None) {
+        let line = line_result?;
+        let trimmed = line.trim();
+        if !trimmed.is_empty() && !trimmed.starts_with('#') {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// The entry a wordlist line holds: trimmed, `None` for blank lines and `#` comments.
+pub fn entry(line: &str) -> Option<&str> {
+    let trimmed = line.trim();
+    match trimmed.is_empty() || trimmed.starts_with('#') {
+        true => None,
+        false => Some(trimmed),
+    }
+}
+
+/// The canonical form of a wordlist entry, so entries that only differ by slashes become
+/// one: leading slashes stripped, runs of `/` collapsed (`api//v1` -> `api/v1`), at most one
+/// trailing slash (`admin//` -> `admin/`). `None` if nothing is left (`/`).
+pub fn normalize(word: &str) -> Option<String> {
+    let mut normalized = String::with_capacity(word.len());
+    let mut previous_slash = true;
+    for c in word.trim().chars() {
+        if c == '/' && previous_slash {
+            continue;
+        }
+        previous_slash = c == '/';
+        normalized.push(c);
+    }
+    match normalized.is_empty() {
+        true => None,
+        false => Some(normalized),
+    }
+}
+
+/// An expansion directive inside a wordlist, applying to the entries after it:
+///
+///     #!exts php,inc      append these extensions instead of `--exts`
+///     #!noext             append no extension
+///     #!default           back to `--exts`
+///
+/// Only the directory scan reads directives; every other mode sees them as comments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Directive {
+    Exts(Vec<String>),
+    NoExt,
+    Default,
+}
+
+impl Directive {
+    /// Parse a (trimmed) directive line; `None` for anything else, unknown directives included.
+    pub fn parse(line: &str) -> Option<Directive> {
+        let body = line.strip_prefix("#!")?.trim();
+        let (name, argument) = match body.split_once(char::is_whitespace) {
+            Some((n, a)) => (n, a.trim()),
+            None => (body, ""),
+        };
+        match name {
+            "exts" | "ext" => {
+                let mut exts: Vec<String> = Vec::new();
+                for token in argument.split(',') {
+                    let token = token.trim().trim_start_matches('.');
+                    let ext = format!(".{}", token);
+                    if !token.is_empty() && !exts.contains(&ext) {
+                        exts.push(ext);
+                    }
+                }
+                match exts.is_empty() {
+                    true => None,
+                    false => Some(Directive::Exts(exts)),
+                }
+            }
+            "noext" | "noexts" if argument.is_empty() => Some(Directive::NoExt),
+            "default" if argument.is_empty() => Some(Directive::Default),
+            _ => None,
+        }
+    }
+
+    /// The extensions for the entries under this directive (`None`: the scan's own).
+    pub fn extensions(self) -> Option<Vec<String>> {
+        match self {
+            Directive::Exts(exts) => Some(exts),
+            Directive::NoExt => Some(Vec::new()),
+            Directive::Default => None,
+        }
+    }
+}
+
+/// What a wordlist line gives a directory scan: its entry, or the line itself when it is a
+/// directive (see `Directive`). Unknown directives are reported and dropped like comments.
+pub fn scan_item(line: &str) -> Option<&str> {
+    let trimmed = line.trim();
+    if trimmed.starts_with("#!") {
+        if Directive::parse(trimmed).is_some() {
+            return Some(trimmed);
+        }
+        eprintln!("[!] wordlist: ignoring unknown directive {} (known: #!exts LIST, #!noext, #!default)", trimmed);
+        return None;
+    }
+    entry(trimmed)
+}
+
+/// Every entry of the wordlist at `path`, decompressed if needed (undecodable lines are
+/// skipped with a warning).
 pub fn read_wordlist(path: &str) -> Result<Vec<String>, DirustError> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
+    let reader = open(path)?;
 
     let mut out: Vec<String> = Vec::new();
 
-    for line_result in reader.lines() {
+    for line_result in TolerantLines::new(reader, Some(path)) {
         match line_result {
             Ok(line) => {
                 let trimmed = line.trim().to_string();
@@ -23,11 +228,95 @@ pub fn read_wordlist(path: &str) -> Result<Vec<String>, DirustError> {
                 out.push(trimmed);
             }
             Err(e) => {
-                // Stop on the first I/O error
+                // Stop on the first I/O error (undecodable lines are not errors here)
                 return Err(DirustError::from(e));
             }
         }
     }
 
     Ok(out)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::{entry, normalize, scan_item, Directive, TolerantLines};
+    use std::io::Cursor;
+
+    fn exts(list: &[&str]) -> Option<Directive> {
+        Some(Directive::Exts(list.iter().map(|e| e.to_string()).collect()))
+    }
+
+    #[test]
+    fn directives() {
+        // (line, parsed)
+        let cases = [
+            ("#!exts php,inc", exts(&[".php", ".inc"])),
+            ("#!ext .php, .php ,,asp", exts(&[".php", ".asp"])),
+            ("#!exts", None),
+            ("#!exts ,", None),
+            ("#!noext", Some(Directive::NoExt)),
+            ("#!noexts", Some(Directive::NoExt)),
+            ("#!noext php", None),
+            ("#!default", Some(Directive::Default)),
+            ("#!default now", None),
+            ("#!extensions php", None),
+            ("# exts php", None),
+            ("admin", None),
+        ];
+        for (line, parsed) in cases {
+            assert_eq!(Directive::parse(line), parsed, "{:?}", line);
+        }
+        assert_eq!(Directive::NoExt.extensions(), Some(Vec::new()));
+        assert_eq!(Directive::Default.extensions(), None);
+
+        // Directives reach the directory scan; unknown ones are dropped like comments.
+        assert_eq!(scan_item("  #!noext  "), Some("#!noext"));
+        assert_eq!(scan_item("#!bogus"), None);
+        assert_eq!(scan_item(" admin "), Some("admin"));
+    }
+
+    #[test]
+    fn entries() {
+        // (line, entry)
+        let cases = [
+            ("admin", Some("admin")),
+            ("  admin\t", Some("admin")),
+            ("api/v1", Some("api/v1")),
+            ("a b", Some("a b")),
+            ("", None),
+            ("   ", None),
+            ("# comment", None),
+            ("  #!exts php", None),
+            ("admin#top", Some("admin#top")),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(entry(line), expected, "{:?}", line);
+        }
+
+        // (entry, normalized)
+        let cases = [
+            ("/admin", Some("admin")),
+            ("api//v1", Some("api/v1")),
+            ("admin//", Some("admin/")),
+            ("//", None),
+            ("", None),
+        ];
+        for (word, expected) in cases {
+            assert_eq!(normalize(word).as_deref(), expected, "{:?}", word);
+        }
+    }
+
+    #[test]
+    fn tolerant_lines_skip_invalid_utf8() {
+        let input: &[u8] = b"admin\r\n\xff\xfe\nbackup\n\xc3\x28\n\xc3\xa9t\xc3\xa9\nlast";
+        let lines: Vec<String> = TolerantLines::new(Cursor::new(input), None)
+            .map(|line| line.expect("no I/O error"))
+            .collect();
+        assert_eq!(lines, vec!["admin", "backup", "été", "last"]);
+
+        let empty: Vec<_> = TolerantLines::new(Cursor::new(&b""[..]), None).collect();
+        assert!(empty.is_empty());
+        // A blank line is a line (`entry` drops it later).
+        let blank: Vec<String> = TolerantLines::new(Cursor::new(&b"\n\nx"[..]), None).map(Result::unwrap).collect();
+        assert_eq!(blank, vec!["", "", "x"]);
+    }
+}
@@ -0,0 +1,484 @@
+//! src/schema.rs
+//!
+//! Purpose:
+//!   Define the versioned, machine-readable result schema shared by every JSON output
+//!   Dirust writes: the `--results-db` / `--new-only` result files below, and the documents
+//!   and lines built from the same records (`--export-tree`, `--output-dir` `index.json`,
+//!   `--stream-to` and `dirust dns --ndjson` lines, `--progress-ndjson` events). Every one
+//!   of them carries `"schema_version"` as its first field.
+//!
+//! Layout (schema version 1):
+//!   {
+//!     "schema_version": 1,
+//!     "scan":    { "tool": "dirust", "tool_version": "0.1.0", "target": "...",
+//!                  "started_at": 1712345678, "finished_at": 1712345699,
+//!                  "wordlist": "common.txt", "wordlist_entries": 4614,
+//!                  "extensions": [".php"], "concurrency": 50,
+//!                  "bases": [ { "base": "...", "requests": 4614, "findings": 3, "errors": 0,
+//!                               "mean_latency_ms": 12.4, "technologies": ["nginx/1.25.3"] } ] },
+//!     "results": [ { "url": "...", "status": 200, "content_length": 1234,
+//!                    "location": null, "etag": "\"abc\"", "last_modified": null,
+//!                    "severity": "high", "tags": ["vcs"],
+//!                    "triage": { "verdict": "false_positive", "note": "default page" } } ]
+//!   }
+//!
+//! Compatibility rules (so downstream parsers don't break):
+//!   - Field names are snake_case and never renamed within a schema version.
+//!   - New fields may be added in the same version; they are always optional, so readers
+//!     must ignore unknown fields (we do: no `deny_unknown_fields`).
+//!   - Removing or changing the meaning of a field bumps `SCHEMA_VERSION`.
+//!   - Timestamps are UNIX seconds (UTC); sizes are plain numbers, not strings.
+
+use crate::{export::iso_datetime, severity::Severity};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Current version of the result schema. Bump on any breaking change.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Top-level document written by every machine-readable output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanResult {
+    /// Version of this schema (see `SCHEMA_VERSION`).
+    pub schema_version: u32,
+    /// Information about the scan that produced the results.
+    pub scan: ScanMetadata,
+    /// One record per finding.
+    #[serde(default)]
+    pub results: Vec<ResultEntry>,
+}
+
+/// Scan metadata block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanMetadata {
+    /// Always "dirust".
+    pub tool: String,
+    /// Version of the binary that wrote the file (from Cargo metadata).
+    pub tool_version: String,
+    /// Normalized base URL that was scanned.
+    pub target: String,
+    /// UNIX seconds when the scan started.
+    pub started_at: u64,
+    /// UNIX seconds when the scan finished (0 while still running).
+    #[serde(default)]
+    pub finished_at: u64,
+    /// File name of the wordlist (without its directory).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wordlist: Option<String>,
+    /// Number of wordlist entries (non-empty, non-comment lines).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wordlist_entries: Option<usize>,
+    /// Extra extensions tried for plain names (`--exts`), normalized (".php").
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+    /// Requests in flight (`-c`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<usize>,
+    /// The proxy requests went through, and where it was configured (`--proxy`, an
+    /// environment variable); credentials masked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// One section per scanned base URL, in scan order (several with `--ports` /
+    /// `--both-schemes`, all on the host of `<BASE>`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bases: Vec<BaseSummary>,
+}
+
+/// What the scan of one base URL sent and found.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaseSummary {
+    /// The base URL.
+    pub base: String,
+    /// Probes sent (retries not counted separately).
+    pub requests: u64,
+    /// Findings reported.
+    pub findings: u64,
+    /// Probes answered with a server error (5xx) or `429 Too Many Requests`.
+    pub errors: u64,
+    /// Mean probe latency in milliseconds (`None`: no probe completed).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mean_latency_ms: Option<f64>,
+    /// Technologies seen: `--smart-exts` detections and the `Server` / `X-Powered-By`
+    /// values of findings (e.g. "PHP", "nginx/1.25.3").
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub technologies: Vec<String>,
+    /// Times the host stopped answering mid-scan (see `scanner/health.rs`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outages: Vec<HostOutage>,
+}
+
+/// One name found by `dirust dns` (the `result` of its `--ndjson` lines).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsEntry {
+    /// The name that resolved, e.g. "vpn.example.com".
+    pub name: String,
+    /// Its A and AAAA addresses.
+    pub addresses: Vec<String>,
+    /// CNAME targets on the way to them (only known with `--resolver`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cnames: Vec<String>,
+    /// The resolver that answered (`None`: the system resolver).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolver: Option<String>,
+    /// The name this one is a variant of (`--permute`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permuted_from: Option<String>,
+    /// `wildcard-zone`: found in a zone with wildcard DNS, with an answer of its own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// One `--progress-ndjson` line (see `scanner/events.rs`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressEvent {
+    /// Version of this schema (see `SCHEMA_VERSION`); 0 in events written before it was added.
+    #[serde(default)]
+    pub schema_version: u32,
+    /// "progress", or "finished" for the last line of the scan.
+    pub event: String,
+    /// RFC 3339 UTC time of the event, with milliseconds.
+    pub time: String,
+    /// Seconds since the scan started.
+    pub elapsed: f64,
+    /// Probes done.
+    pub completed: usize,
+    /// Estimated probes in all (`completed` + what is left; `None` while the wordlist size is
+    /// unknown: stdin, `url(...)`).
+    pub total: Option<usize>,
+    /// Targets scheduled so far (wordlist and discovered).
+    pub scheduled: usize,
+    /// Probes per second since the previous event (the whole scan for "finished").
+    pub rps: f64,
+    /// Probes answered with 5xx / 429 or not answered at all.
+    pub errors: usize,
+    /// Estimated seconds left (`None` when unknown or nothing is probed).
+    pub eta: Option<u64>,
+    /// Findings reported.
+    pub findings: usize,
+    /// Of `scheduled`, the targets discovered during the scan (forms, redirects, permutations).
+    pub discovered: usize,
+    /// Base URL being scanned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+}
+
+/// A stretch of time a host gave no answers during the scan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostOutage {
+    /// UNIX seconds of the first failure that made the host count as down.
+    pub started_at: u64,
+    /// UNIX seconds it answered again (`None`: never did; the scan gave up on it).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<u64>,
+    /// How long it was down (until given up on, when it never came back).
+    pub seconds: u64,
+}
+
+/// One finding, with normalized field names and types.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultEntry {
+    /// Absolute URL that was probed.
+    pub url: String,
+    /// HTTP status code.
+    pub status: u16,
+    /// Parsed `Content-Length` header, if present and numeric.
+    #[serde(default)]
+    pub content_length: Option<u64>,
+    /// `Content-Type` of a binary finding (tagged `binary`; see `scanner/binary.rs`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// `Location` header (redirect target), if present.
+    #[serde(default)]
+    pub location: Option<String>,
+    /// `ETag` header, if present.
+    #[serde(default)]
+    pub etag: Option<String>,
+    /// `Last-Modified` header, if present.
+    #[serde(default)]
+    pub last_modified: Option<String>,
+    /// Severity assigned by the classification rules, if any matched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    /// Tags assigned by the classification rules.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// A reviewer's decision from `dirust triage`, if the finding was reviewed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub triage: Option<Triage>,
+    /// Security headers of a 200 finding (`--header-audit`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HeaderAudit>,
+    /// Cookies the response set (names and attributes only).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cookies: Vec<CookieRecord>,
+    /// How the finding differs from a random path under the scanned base.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_from_baseline: Option<BaselineDiff>,
+    /// The redirect finding whose Location led to this one (`--harvest-redirects`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirected_from: Option<String>,
+    /// The finding this one is a variant of (`--permute`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permuted_from: Option<String>,
+    /// What the `--verify` pass saw when it requested the finding again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification: Option<Verification>,
+    /// Software and hardening headers of the response (`Server`, `WWW-Authenticate`, ...;
+    /// see `headerdiff.rs`), compared by `--incremental` scans and `dirust diff`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_headers: Option<BTreeMap<String, String>>,
+}
+
+/// The second, slow GET of a finding (`--verify`; see `scanner/verify.rs`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Verification {
+    /// The GET returned the status the probe saw.
+    pub reproduced: bool,
+    /// Status of the GET (`None`: the request failed).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Body length in bytes, `Content-Encoding` undone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length: Option<u64>,
+    /// SHA-256 of the body, lower-case hex.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// `<title>` of an HTML body, whitespace collapsed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Poisoned headers reflected in the response (`--host-injection`), e.g.
+    /// "X-Forwarded-Host: 302, in header location".
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub host_injection: Vec<String>,
+}
+
+/// How a response compares with the scan's baseline (see `fuzz::baseline`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaselineDiff {
+    /// The status differs from the one a random path got.
+    pub status_changed: bool,
+    /// Size minus the baseline size, in bytes (`None` when the size is unknown).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_delta: Option<i64>,
+}
+
+/// One `Set-Cookie` of a finding, without its value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CookieRecord {
+    pub name: String,
+    pub secure: bool,
+    pub http_only: bool,
+    /// `SameSite` attribute as sent (`Strict`, `Lax`, `None`), if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub same_site: Option<String>,
+}
+
+/// Which security headers a response carried (`--header-audit`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeaderAudit {
+    /// `Strict-Transport-Security` present (`None` over http://, where it does not apply).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hsts: Option<bool>,
+    /// `X-Frame-Options` present.
+    pub x_frame_options: bool,
+    /// `Content-Security-Policy` present.
+    pub csp: bool,
+    /// `Cache-Control` value, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<String>,
+}
+
+/// A reviewer's decision about one finding (`dirust triage`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Triage {
+    pub verdict: Verdict,
+    /// Free-form note for the report.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Whether a reviewed finding deserves attention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Interesting,
+    FalsePositive,
+}
+
+impl Verdict {
+    /// Label used in result lines (`[interesting]`, `[false-positive]`).
+    pub fn label(self) -> &'static str {
+        match self {
+            Verdict::Interesting => "interesting",
+            Verdict::FalsePositive => "false-positive",
+        }
+    }
+}
+
+impl HeaderAudit {
+    /// Names of the missing headers, e.g. `["hsts", "csp"]`.
+    ///
+    /// `cache-control` only counts on `sensitive` findings (see `ResultEntry::is_sensitive`),
+    /// which should not be cached: it is missing without `no-store` or `private`.
+    pub fn missing(&self, sensitive: bool) -> Vec<&'static str> {
+        let mut missing: Vec<&'static str> = Vec::new();
+        if self.hsts == Some(false) {
+            missing.push("hsts");
+        }
+        if !self.x_frame_options {
+            missing.push("x-frame-options");
+        }
+        if !self.csp {
+            missing.push("csp");
+        }
+        let uncached = match &self.cache_control {
+            Some(value) => {
+                let lower = value.to_ascii_lowercase();
+                lower.contains("no-store") || lower.contains("private")
+            }
+            None => false,
+        };
+        if sensitive && !uncached {
+            missing.push("cache-control");
+        }
+        missing
+    }
+}
+
+impl CookieRecord {
+    /// Missing protection flags, e.g. `["Secure", "HttpOnly"]` (`Secure` only counts over
+    /// `https`, where the cookie could have it).
+    pub fn missing(&self, https: bool) -> Vec<&'static str> {
+        let mut missing: Vec<&'static str> = Vec::new();
+        if https && !self.secure {
+            missing.push("Secure");
+        }
+        if !self.http_only {
+            missing.push("HttpOnly");
+        }
+        missing
+    }
+}
+
+impl ResultEntry {
+    /// Whether the finding deserves `Cache-Control` protection: a medium or higher severity,
+    /// or an authentication entry point (`auth` tag).
+    pub fn is_sensitive(&self) -> bool {
+        self.severity.is_some_and(|s| s >= Severity::Medium) || self.tags.iter().any(|tag| tag == "auth")
+    }
+}
+
+impl ScanMetadata {
+    /// Start a metadata block for a scan of `target`, stamped with the current time.
+    pub fn start(target: &str) -> ScanMetadata {
+        ScanMetadata {
+            tool: "dirust".to_string(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            target: target.to_string(),
+            started_at: unix_now(),
+            finished_at: 0,
+            wordlist: None,
+            wordlist_entries: None,
+            extensions: Vec::new(),
+            concurrency: None,
+            proxy: None,
+            bases: Vec::new(),
+        }
+    }
+
+    /// The header block printed at the top of text output, e.g.
+    ///
+    ///   [*] dirust 0.1.0 scan of https://example.com/ started 2024-08-12 09:30:00 UTC
+    ///   [*] wordlist: common.txt (4614 entries), extensions: .php,.html, concurrency: 50
+    ///   [*] proxy: http://proxy.corp:3128 (HTTPS_PROXY)
+    pub fn header_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "[*] {} {} scan of {} started {}",
+            self.tool,
+            self.tool_version,
+            self.target,
+            iso_datetime(self.started_at)
+        )];
+        if let Some(name) = &self.wordlist {
+            let entries = match self.wordlist_entries {
+                Some(n) => format!(" ({} entries)", n),
+                None => String::new(),
+            };
+            let extensions = match self.extensions.is_empty() {
+                true => "none".to_string(),
+                false => self.extensions.join(","),
+            };
+            let concurrency = match self.concurrency {
+                Some(c) => format!(", concurrency: {}", c),
+                None => String::new(),
+            };
+            lines.push(format!(
+                "[*] wordlist: {}{}, extensions: {}{}",
+                name, entries, extensions, concurrency
+            ));
+        }
+        if let Some(proxy) = &self.proxy {
+            lines.push(format!("[*] proxy: {}", proxy));
+        }
+        lines
+    }
+
+    /// The per-base block printed after a scan of several bases, e.g.
+    ///
+    ///   [*] bases: 2 scanned
+    ///       requests  findings  errors  mean_ms  base
+    ///            940         3       0     12.4  https://example.com/  (nginx/1.25.3, PHP)
+    pub fn base_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("[*] bases: {} scanned", self.bases.len()),
+            format!("    {:>8}  {:>8}  {:>6}  {:>7}  base", "requests", "findings", "errors", "mean_ms"),
+        ];
+        for section in &self.bases {
+            let mean = match section.mean_latency_ms {
+                Some(ms) => format!("{:.1}", ms),
+                None => "-".to_string(),
+            };
+            let technologies = match section.technologies.is_empty() {
+                true => String::new(),
+                false => format!("  ({})", section.technologies.join(", ")),
+            };
+            lines.push(format!(
+                "    {:>8}  {:>8}  {:>6}  {:>7}  {}{}",
+                section.requests, section.findings, section.errors, mean, section.base, technologies
+            ));
+            for outage in &section.outages {
+                lines.push(format!("              {}", outage.describe()));
+            }
+        }
+        lines
+    }
+
+    /// Record the end of the scan.
+    pub fn finish(&mut self) {
+        self.finished_at = unix_now();
+    }
+}
+
+impl HostOutage {
+    /// One line of text, e.g. "down 2026-10-16 09:12:03 UTC for 47s".
+    pub fn describe(&self) -> String {
+        match self.ended_at {
+            Some(_) => format!("down {} for {}s", iso_datetime(self.started_at), self.seconds),
+            None => format!("down {} and not back after {}s (rest of the scan skipped)", iso_datetime(self.started_at), self.seconds),
+        }
+    }
+}
+
+/// Current time as UNIX seconds.
+///
+/// A clock set before 1970 is a broken configuration; we report 0 rather than panicking
+/// here because metadata is informational only.
+fn unix_now() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs(),
+        Err(_) => 0,
+    }
+}
@@ -0,0 +1,170 @@
+//! src/selftest/mock.rs
+//!
+//! Purpose:
+//!   A tiny embedded HTTP/1.1 server with a fixed set of routes, used by `--self-test`.
+//!
+//! Routes (method-independent unless noted):
+//!   /              → 200  index page
+//!   /robots.txt    → 200  plain file
+//!   /admin         → 301  Location: /admin/
+//!   /admin/        → 200  directory index
+//!   /secret        → 403  forbidden
+//!   /upload        → 405  for HEAD, 200 for GET (exercises the HEAD → GET fallback)
+//!   /soft/<any>    → 200  "page not found" body (a soft-404 catch-all)
+//!   anything else  → 404
+//!
+//! Notes:
+//!   - `route` is also what `--self-test --in-memory` answers with, without the server.
+//!   - We deliberately avoid a web framework: requests are tiny (no bodies), so reading the
+//!     header block and writing a fixed response is enough.
+//!   - Keep-alive is supported so the client can reuse connections like against a real server.
+
+use crate::error::DirustError;
+use std::net::SocketAddr;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+/// A running mock server. Dropping it does not stop the server; call `shutdown`.
+pub struct MockServer {
+    /// Address the server listens on (always 127.0.0.1 with an OS-assigned port).
+    pub addr: SocketAddr,
+    /// Accept loop task; aborted by `shutdown`.
+    accept_task: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Base URL of the server, with trailing slash (e.g., "http://127.0.0.1:40123/").
+    pub fn base_url(&self) -> String {
+        format!("http://{}/", self.addr)
+    }
+
+    /// Stop accepting new connections.
+    pub fn shutdown(self) {
+        self.accept_task.abort();
+    }
+}
+
+/// Bind to an ephemeral localhost port and start serving in the background.
+pub async fn start() -> Result<MockServer, DirustError> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+
+    let accept_task = tokio::spawn(async move {
+        // Accept failures (e.g., too many open files) end the server.
+        while let Ok((stream, _peer)) = listener.accept().await {
+            // One task per connection; errors only affect that connection.
+            tokio::spawn(serve_connection(stream));
+        }
+    });
+
+    Ok(MockServer { addr, accept_task })
+}
+
+/// Serve requests on one connection until the client closes it.
+async fn serve_connection(mut stream: TcpStream) {
+    let mut buffer: Vec<u8> = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+
+    loop {
+        // Process every complete request head already in the buffer.
+        while let Some(end) = find_header_end(&buffer) {
+            let head = String::from_utf8_lossy(&buffer[..end]).to_string();
+            buffer.drain(..end + 4);
+
+            let response = respond(&head);
+            if stream.write_all(&response).await.is_err() {
+                return;
+            }
+        }
+
+        // Need more bytes: read the next chunk (0 bytes = client closed the connection).
+        match stream.read(&mut chunk).await {
+            Ok(0) => return,
+            Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+            Err(_) => return,
+        }
+    }
+}
+
+/// Locate the "\r\n\r\n" terminating a request head.
+fn find_header_end(buffer: &[u8]) -> Option<usize> {
+    buffer.windows(4).position(|w| w == b"\r\n\r\n")
+}
+
+/// The response of one route (see the module docs).
+pub struct Route {
+    pub status: u16,
+    pub reason: &'static str,
+    pub location: Option<&'static str>,
+    /// The GET body; HEAD responses have the same headers and no body.
+    pub body: &'static str,
+}
+
+/// Produce the raw HTTP response bytes for a request head.
+fn respond(head: &str) -> Vec<u8> {
+    // Request line: "<METHOD> <PATH> HTTP/1.1"
+    let request_line = head.lines().next().unwrap_or("");
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("/");
+    let is_head = method == "HEAD";
+    let Route {
+        status,
+        reason,
+        location,
+        body,
+    } = route(method, path);
+
+    let mut out = format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nContent-Type: text/plain\r\n",
+        status,
+        reason,
+        body.len()
+    );
+    if let Some(loc) = location {
+        out.push_str(&format!("Location: {}\r\n", loc));
+    }
+    out.push_str("\r\n");
+
+    // HEAD responses carry the headers of the GET response but never a body.
+    if !is_head {
+        out.push_str(body);
+    }
+
+    out.into_bytes()
+}
+
+/// What the server answers to `method` on `path`.
+pub fn route(method: &str, path: &str) -> Route {
+    let is_head = method == "HEAD";
+    let (status, reason, location, body): (u16, &'static str, Option<&'static str>, &'static str) = match path {
+        "/" => (200, "OK", None, "dirust self-test index"),
+        "/robots.txt" => (200, "OK", None, "User-agent: *\n"),
+        "/admin" => (301, "Moved Permanently", Some("/admin/"), ""),
+        "/admin/" => (200, "OK", None, "admin home"),
+        "/secret" => (403, "Forbidden", None, "forbidden"),
+        "/upload" => {
+            if is_head {
+                (405, "Method Not Allowed", None, "")
+            } else {
+                (200, "OK", None, "upload form")
+            }
+        }
+        _ => {
+            if path.starts_with("/soft/") {
+                (200, "OK", None, "Sorry, page not found")
+            } else {
+                (404, "Not Found", None, "not found")
+            }
+        }
+    };
+    Route {
+        status,
+        reason,
+        location,
+        body,
+    }
+}
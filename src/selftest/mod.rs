@@ -0,0 +1,199 @@
+//! src/selftest/mod.rs
+//!
+//! Purpose:
+//!   Implement `--self-test`: start the embedded mock server, run a real scan against it,
+//!   and compare what the scanner reported with what it *should* have reported.
+//!
+//! What this validates end-to-end:
+//!   - The binary can bind, connect and speak HTTP (install/runtime sanity).
+//!   - HEAD by default, with the GET fallback on 405.
+//!   - Redirects are not followed (301 is reported with its Location), except that the
+//!     `/admin` → `/admin/` directory redirect is confirmed and reported as `/admin/ [dir]`
+//!     unless `--no-dir-confirm` is given.
+//!   - The "interesting status" filter (200/301/302/401/403 shown, 404 hidden).
+//!   - Soft-404 calibration: `/soft/<any>` answers 200 with the same "page not found" page
+//!     for every name, so its 200 must not be reported.
+//!
+//! The user's non-target flags (timeout, concurrency, --get, --exts, ...) are kept, so the
+//! self-test also exercises the exact configuration about to be used for real scans.
+//!
+//! With `--in-memory`, the same routes are answered by a `FakeClient` (see
+//! `scanner/transport.rs`) instead of the server: no socket is opened, `--engine` and the
+//! connection flags play no part, and the run is fully deterministic.
+
+pub mod mock; // also used by `dirust bench` when no target is given
+
+use crate::{
+    args::ScanArgs,
+    client,
+    db::ResultsDb,
+    error::DirustError,
+    scanner::{
+        self,
+        transport::{FakeClient, FakeResponse},
+        ScanHooks,
+    },
+};
+use std::{fs, path::PathBuf, sync::Arc};
+
+/// Base URL of the in-memory run (never resolved).
+const IN_MEMORY_BASE: &str = "http://self-test.invalid/";
+
+/// One expected outcome: probing `path` must (or must not) produce a finding with `status`.
+struct Expectation {
+    path: &'static str,
+    status: u16,
+    reported: bool,
+}
+
+/// The fixed expectation table, mirroring the routes in `mock.rs`.
+const EXPECTATIONS: &[Expectation] = &[
+    Expectation { path: "robots.txt", status: 200, reported: true },
+    Expectation { path: "admin", status: 301, reported: true },
+    Expectation { path: "admin/", status: 200, reported: true },
+    Expectation { path: "secret", status: 403, reported: true },
+    Expectation { path: "upload", status: 200, reported: true },
+    Expectation { path: "soft/does-not-exist", status: 200, reported: false },
+    Expectation { path: "missing", status: 404, reported: false },
+];
+
+/// The route that redirects to its own directory form (see `mock.rs`).
+const DIRECTORY_REDIRECT: &str = "admin";
+
+/// Run the self-test and print a PASS/FAIL line per expectation.
+///
+/// Returns:
+///   - `Ok(())` when every expectation holds.
+///   - `Err(DirustError::SelfTestFailed(n))` with the number of failed checks otherwise.
+pub async fn run(args: &ScanArgs) -> Result<(), DirustError> {
+    let mut hooks = ScanHooks::default();
+    let mut fake: Option<Arc<FakeClient>> = None;
+    let server = match args.in_memory {
+        true => None,
+        false => Some(mock::start().await?),
+    };
+    let base = match &server {
+        Some(server) => {
+            let base = server.base_url();
+            println!("[*] self-test: mock server listening on {}", base);
+            base
+        }
+        None => {
+            let client = Arc::new(FakeClient::new(answer));
+            hooks.transport = Some(client.clone());
+            fake = Some(client);
+            println!("[*] self-test: answering {} in memory", IN_MEMORY_BASE);
+            IN_MEMORY_BASE.to_string()
+        }
+    };
+
+    // Write the wordlist for this run next to other temporary files.
+    let wordlist_path: PathBuf =
+        std::env::temp_dir().join(format!("dirust-selftest-{}.txt", std::process::id()));
+    let mut words = String::new();
+    for expectation in EXPECTATIONS {
+        words.push_str(expectation.path);
+        words.push('\n');
+    }
+    fs::write(&wordlist_path, words)?;
+
+    // Point a copy of the user's arguments at the mock server; drop anything that would
+    // read or write result files, so the self-test never touches real scan state.
+    let mut test_args: ScanArgs = args.clone();
+    test_args.base = Some(base.clone());
+    test_args.wordlist = Some(wordlist_path.to_string_lossy().to_string());
+    test_args.results_db = None;
+    test_args.incremental = false;
+    test_args.new_only = None;
+    test_args.state = None;
+
+    let client = client::build_client(&test_args.http)?;
+    let mut db = ResultsDb::default();
+    let words = scanner::source::open(test_args.wordlist_path(), None, &client).await?;
+    let scan_result = scanner::scan(&client, &base, &test_args, words, &mut db, None, &hooks).await;
+
+    // Clean up before inspecting results so failures do not leak files or tasks.
+    if let Some(server) = server {
+        server.shutdown();
+    }
+    let _ = fs::remove_file(&wordlist_path);
+    scan_result?;
+    if let Some(fake) = &fake {
+        println!("[*] self-test: {} request(s) answered in memory", fake.requests().len());
+    }
+
+    // Compare the database (which holds exactly the reported findings) with expectations.
+    let mut failures: usize = 0;
+    for expectation in EXPECTATIONS {
+        let url = format!("{}{}", base, expectation.path);
+        let observed: Option<u16> = db.get(&url).map(|entry| entry.status);
+
+        // A confirmed directory redirect is merged into the `admin/` finding.
+        let reported = if expectation.path == DIRECTORY_REDIRECT {
+            expectation.reported && test_args.no_dir_confirm
+        } else {
+            expectation.reported
+        };
+
+        let ok = if reported {
+            observed == Some(expectation.status)
+        } else {
+            observed.is_none()
+        };
+
+        let verdict = if ok { "PASS" } else { "FAIL" };
+        let observed_str = match observed {
+            Some(code) => code.to_string(),
+            None => "not reported".to_string(),
+        };
+        println!(
+            "[{}] /{:<22} expected {} ({}), observed {}",
+            verdict,
+            expectation.path,
+            expectation.status,
+            if reported { "reported" } else { "hidden" },
+            observed_str
+        );
+
+        if !ok {
+            failures += 1;
+        }
+    }
+
+    if failures > 0 {
+        return Err(DirustError::SelfTestFailed(failures));
+    }
+
+    println!("[*] self-test passed ({} checks)", EXPECTATIONS.len());
+    Ok(())
+}
+
+/// The in-memory twin of the mock server: the response `mock::route` gives `url`'s path.
+fn answer(method: &str, url: &str) -> FakeResponse {
+    let path = match url.strip_prefix(IN_MEMORY_BASE) {
+        Some(rest) => format!("/{}", rest),
+        None => "/".to_string(),
+    };
+    let route = mock::route(method, &path);
+    let response = FakeResponse::new(route.status, route.body.as_bytes()).with_header("content-type", "text/plain");
+    match route.location {
+        Some(location) => response.with_header("location", location),
+        None => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::args::{Cli, Command};
+
+    /// `dirust scan --self-test --in-memory`: every expectation holds, without a socket.
+    #[tokio::test]
+    async fn in_memory_self_test_passes() {
+        let argv = ["dirust", "scan", "--self-test", "--in-memory", "-q"];
+        let args = match Cli::try_parse_with_presets(argv) {
+            Ok(Cli { command: Command::Scan(args), .. }) => *args,
+            _ => panic!("not a scan command line: {:?}", argv),
+        };
+        assert!(super::run(&args).await.is_ok());
+    }
+}
@@ -0,0 +1,592 @@
+//! src/serve.rs
+//!
+//! Purpose:
+//!   `dirust serve --listen 127.0.0.1:8787`: run scans as jobs behind a small HTTP/JSON API,
+//!   so other tooling can submit scans, watch their progress, stream findings and cancel them.
+//!
+//! Endpoints:
+//!   POST   /scans              submit a job; body `{"args": ["https://x/", "-w", "words.txt"]}`
+//!                              (the arguments of `dirust scan`) → 201 `{"id": 1}`
+//!   GET    /scans              every job's summary
+//!   GET    /scans/<id>         one job's summary:
+//!                              `{"id", "target", "status", "total", "done", "discovered",
+//!                              "remaining", "findings", "error"}` (`remaining`: estimated
+//!                              targets left, absent while the wordlist size is unknown)
+//!   GET    /scans/<id>/results findings so far → `{"results": [<result entry>, ...]}`
+//!   GET    /scans/<id>/events  Server-Sent Events: `finding` (one result entry), `progress`
+//!                              (counters, every second) and a final `done` (job summary)
+//!   POST   /scans/<id>/words   add words to the running job: body `{"words": ["backup-2024"]}`
+//!                              → 202 `{"queued": 1}` (409 once the job is over)
+//!   DELETE /scans/<id>         cancel: no new probes are started, the ones in flight are
+//!                              aborted; findings so far are kept → 202
+//!
+//! Job status: "running", "finished", "cancelled" or "failed" (with `error`).
+//!
+//! Notes:
+//!   - Like the self-test mock server, this is a minimal HTTP/1.1 implementation (one request
+//!     per connection, `Connection: close`) instead of a web framework.
+//!   - Findings use the same JSON shape as result files (see `schema.rs`).
+//!   - Scan output is still printed to the server's stdout.
+//!   - There is no authentication; the default listen address is loopback only.
+//!   - Web pages the operator has open can reach a loopback API too. Requests with an
+//!     `Origin` header (sent by browsers on cross-origin requests) are refused with 403, and
+//!     request bodies must be `Content-Type: application/json`, which a page cannot send
+//!     without a CORS preflight this server never answers.
+//!   - DNS rebinding (a page's own host name resolving to 127.0.0.1 after it loaded) makes
+//!     requests same-origin; their `Host` header still names the page's host. Only requests
+//!     whose `Host` is the address the connection came in on, the `--listen` address as
+//!     given, or `localhost`, with the server's port, are answered (403 otherwise).
+//!   - Jobs cannot run commands or write files on the host: `--on-hit`, `--results-db`,
+//!     `--export-*`, `--output-dir`, `--state`, `--checkpoint`, `--transcript`,
+//!     `--profile-cache` and `--progress-ndjson=<PATH>` are refused (400). Neither can they
+//!     open listeners or connect to other machines with `--role` (distributed scans).
+
+use crate::{
+    args::{Cli, Command, ScanArgs, ServeArgs},
+    error::DirustError,
+    scanner::{
+        monitor::{MonitorEvent, ScanMonitor, COUNTED_STATUSES},
+        wordlist, ScanHooks,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::broadcast::error::RecvError,
+};
+
+/// Largest request body accepted (job submissions are tiny).
+const MAX_BODY: usize = 1024 * 1024;
+
+/// How often `progress` events are sent on an event stream.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Lifecycle of a job.
+#[derive(Debug, Clone)]
+enum JobStatus {
+    Running,
+    Finished,
+    Cancelled,
+    Failed(String),
+}
+
+/// One submitted scan.
+struct Job {
+    id: usize,
+    /// The base URL as submitted.
+    target: String,
+    monitor: Arc<ScanMonitor>,
+    status: Mutex<JobStatus>,
+}
+
+/// JSON view of a job.
+#[derive(Debug, Serialize)]
+struct JobSummary {
+    id: usize,
+    target: String,
+    status: &'static str,
+    total: usize,
+    done: usize,
+    discovered: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remaining: Option<usize>,
+    findings: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Body of `POST /scans`.
+#[derive(Debug, Deserialize)]
+struct SubmitRequest {
+    args: Vec<String>,
+}
+
+/// Body of `POST /scans/<id>/words`.
+#[derive(Debug, Deserialize)]
+struct WordsRequest {
+    words: Vec<String>,
+}
+
+/// Every job submitted since the server started (ids are 1-based indexes).
+#[derive(Default)]
+struct Registry {
+    jobs: Mutex<Vec<Arc<Job>>>,
+}
+
+/// A parsed request.
+struct Request {
+    method: String,
+    path: String,
+    /// `Content-Type` without parameters (`application/json; charset=utf-8` → `application/json`).
+    content_type: Option<String>,
+    /// Set by browsers on cross-origin requests.
+    has_origin: bool,
+    /// `Host` header, lower-cased (`None` when missing).
+    host: Option<String>,
+    body: Vec<u8>,
+}
+
+/// Serve the API until the process is stopped.
+pub async fn run(args: &ServeArgs) -> Result<(), DirustError> {
+    let listener = TcpListener::bind(&args.listen).await?;
+    let addr = listener.local_addr()?;
+    println!("[*] serve: API listening on http://{}/", addr);
+    if !addr.ip().is_loopback() {
+        eprintln!("[!] serve: listening on a non-loopback address; the API has no authentication");
+    }
+
+    let registry = Arc::new(Registry::default());
+    let listen: Arc<str> = Arc::from(args.listen.to_ascii_lowercase());
+    loop {
+        let (stream, _peer) = listener.accept().await?;
+        // One task per connection; errors only affect that connection.
+        tokio::spawn(handle_connection(stream, Arc::clone(&registry), Arc::clone(&listen)));
+    }
+}
+
+/// Read one request, answer it, close the connection.
+async fn handle_connection(mut stream: TcpStream, registry: Arc<Registry>, listen: Arc<str>) {
+    let request = match read_request(&mut stream).await {
+        Some(r) => r,
+        None => {
+            let _ = stream.write_all(&json_response(400, r#"{"error":"bad request"}"#)).await;
+            return;
+        }
+    };
+
+    let local = match stream.local_addr() {
+        Ok(addr) => addr,
+        Err(_) => return,
+    };
+    if !host_allowed(request.host.as_deref(), local, &listen) {
+        let _ = stream
+            .write_all(&error_response(403, "the Host header does not name this server"))
+            .await;
+        return;
+    }
+
+    if request.has_origin {
+        let _ = stream
+            .write_all(&error_response(403, "cross-origin requests are not accepted"))
+            .await;
+        return;
+    }
+    if request.method == "POST" && request.content_type.as_deref() != Some("application/json") {
+        let _ = stream
+            .write_all(&error_response(415, "the body must be sent as Content-Type: application/json"))
+            .await;
+        return;
+    }
+
+    // Strip any query string and split "/scans/3/events" into ["scans", "3", "events"].
+    let path = request.path.split('?').next().unwrap_or("");
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    let response: Vec<u8> = match (request.method.as_str(), segments.as_slice()) {
+        ("POST", ["scans"]) => submit(&registry, &request.body),
+        ("GET", ["scans"]) => {
+            let summaries: Vec<JobSummary> = registry_jobs(&registry).iter().map(|j| summary(j)).collect();
+            json_body(200, &summaries)
+        }
+        ("GET", ["scans", id]) => match find_job(&registry, id) {
+            Some(job) => json_body(200, &summary(&job)),
+            None => not_found(),
+        },
+        ("GET", ["scans", id, "results"]) => match find_job(&registry, id) {
+            Some(job) => json_body(200, &serde_json::json!({ "results": job.monitor.findings() })),
+            None => not_found(),
+        },
+        ("GET", ["scans", id, "events"]) => match find_job(&registry, id) {
+            Some(job) => {
+                stream_events(&mut stream, &job).await;
+                return;
+            }
+            None => not_found(),
+        },
+        ("POST", ["scans", id, "words"]) => match find_job(&registry, id) {
+            Some(job) => add_words(&job, &request.body),
+            None => not_found(),
+        },
+        ("DELETE", ["scans", id]) => match find_job(&registry, id) {
+            Some(job) => {
+                job.monitor.cancel();
+                json_body(202, &summary(&job))
+            }
+            None => not_found(),
+        },
+        (_, ["scans", ..]) => json_response(405, r#"{"error":"method not allowed"}"#),
+        _ => not_found(),
+    };
+
+    let _ = stream.write_all(&response).await;
+}
+
+/// Handle `POST /scans`: parse the scan arguments and start the job.
+fn submit(registry: &Arc<Registry>, body: &[u8]) -> Vec<u8> {
+    let submitted: SubmitRequest = match serde_json::from_slice(body) {
+        Ok(s) => s,
+        Err(e) => return error_response(400, &format!("invalid JSON body: {}", e)),
+    };
+
+    // Parse exactly like `dirust scan <args...>`.
+    let mut argv: Vec<String> = vec!["dirust".to_string(), "scan".to_string()];
+    argv.extend(submitted.args);
+    let mut scan_args: ScanArgs = match Cli::try_parse_with_presets(&argv) {
+        Ok(Cli { command: Command::Scan(scan), .. }) => *scan,
+        Ok(_) => return error_response(400, "not a scan command line"),
+        Err(e) => return error_response(400, &e.to_string()),
+    };
+    if scan_args.self_test {
+        return error_response(400, "--self-test is not available as a job");
+    }
+    if let Some(flag) = host_access_flag(&scan_args) {
+        return error_response(400, &format!("{} is not available as a job", flag));
+    }
+
+    let mut hooks = ScanHooks::default();
+    let monitor = Arc::new(ScanMonitor::new());
+    hooks.monitor = Some(Arc::clone(&monitor));
+    // Submitting the job is the confirmation: there is no terminal to ask on. A job that
+    // found something has not failed.
+    scan_args.yes = true;
+    scan_args.fail_on_findings = None;
+
+    let job = {
+        let mut jobs = lock_jobs(registry);
+        let job = Arc::new(Job {
+            id: jobs.len() + 1,
+            target: scan_args.base_url().to_string(),
+            monitor,
+            status: Mutex::new(JobStatus::Running),
+        });
+        jobs.push(Arc::clone(&job));
+        job
+    };
+    println!("[*] serve: job {} started for {}", job.id, job.target);
+
+    let running = Arc::clone(&job);
+    tokio::spawn(async move {
+        let outcome = crate::run_scan(&scan_args, hooks).await;
+        let status = match outcome {
+            Ok(()) if running.monitor.is_cancelled() => JobStatus::Cancelled,
+            Ok(()) => JobStatus::Finished,
+            Err(e) => JobStatus::Failed(e.to_string()),
+        };
+        println!("[*] serve: job {} {}", running.id, status_label(&status));
+        set_status(&running, status);
+        running.monitor.finish();
+    });
+
+    json_body(201, &serde_json::json!({ "id": job.id }))
+}
+
+/// Whether a `Host` header names this server: `local` (the address the connection came in
+/// on), the `--listen` address as given, or `localhost`, on the server's port.
+///
+/// Examples (listening on 127.0.0.1:8787):
+///   "127.0.0.1:8787", "localhost:8787" → true
+///   "attacker.example:8787", "localhost:80", missing → false
+fn host_allowed(host: Option<&str>, local: SocketAddr, listen: &str) -> bool {
+    let Some(host) = host else {
+        return false;
+    };
+    // A Host without a port means the scheme's default one.
+    let has_port = host.rsplit_once(':').is_some_and(|(_, port)| !port.contains(']'));
+    let host = match has_port {
+        true => host.to_string(),
+        false => format!("{}:80", host),
+    };
+    host == local.to_string() || host == listen || host == format!("localhost:{}", local.port())
+}
+
+/// The first flag of `args` that would run a command, write a file, or open a listener
+/// (`--role coordinator`) or outgoing connection (`--role worker`) on the host.
+fn host_access_flag(args: &ScanArgs) -> Option<&'static str> {
+    let writes_events = args.progress_ndjson.as_deref().is_some_and(|destination| destination != "-");
+    let flags = [
+        ("--on-hit", args.on_hit.is_some()),
+        ("--results-db", args.results_db.is_some()),
+        ("--export-defectdojo", args.export_defectdojo.is_some()),
+        ("--export-urls", args.export_urls.is_some()),
+        ("--export-tree", args.export_tree.is_some()),
+        ("--output-dir", args.output_dir.is_some()),
+        ("--state", args.state.is_some()),
+        ("--checkpoint", args.checkpoint.is_some()),
+        ("--transcript", args.transcript.is_some()),
+        ("--profile-cache", args.profile_cache.is_some()),
+        ("--progress-ndjson=<PATH>", writes_events),
+        ("--role", args.role.is_some()),
+    ];
+    flags.into_iter().find(|(_, set)| *set).map(|(flag, _)| flag)
+}
+
+/// Handle `POST /scans/<id>/words`: queue words for the running job.
+fn add_words(job: &Job, body: &[u8]) -> Vec<u8> {
+    let submitted: WordsRequest = match serde_json::from_slice(body) {
+        Ok(w) => w,
+        Err(e) => return error_response(400, &format!("invalid JSON body: {}", e)),
+    };
+    if job.monitor.is_finished() {
+        return error_response(409, "the job is over");
+    }
+    // Same filtering as wordlist lines: trimmed, no blanks or `#` comments.
+    let words: Vec<String> = submitted
+        .words
+        .iter()
+        .filter_map(|w| wordlist::entry(w))
+        .map(str::to_string)
+        .collect();
+    let queued = words.len();
+    job.monitor.inject(words);
+    json_body(202, &serde_json::json!({ "queued": queued }))
+}
+
+/// Stream a job's findings as Server-Sent Events until it ends or the client goes away.
+async fn stream_events(stream: &mut TcpStream, job: &Arc<Job>) {
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n";
+    if stream.write_all(head.as_bytes()).await.is_err() {
+        return;
+    }
+
+    // Subscribe first, then replay what was already found, so nothing falls in between.
+    let mut events = job.monitor.subscribe();
+    for entry in job.monitor.findings() {
+        if !send_event(stream, "finding", &entry).await {
+            return;
+        }
+    }
+
+    let mut ticker = tokio::time::interval(PROGRESS_INTERVAL);
+    while !job.monitor.is_finished() {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(MonitorEvent::Finding(entry)) => {
+                    if !send_event(stream, "finding", &entry).await {
+                        return;
+                    }
+                }
+                Ok(MonitorEvent::Finished) | Err(RecvError::Closed) => break,
+                // A slow client skipped some events; they are still in `/results`.
+                Err(RecvError::Lagged(_)) => continue,
+            },
+            _ = ticker.tick() => {
+                let progress = job.monitor.progress();
+                let by_status: serde_json::Map<String, serde_json::Value> = COUNTED_STATUSES
+                    .iter()
+                    .zip(progress.by_status.iter())
+                    .map(|(status, count)| (status.to_string(), serde_json::json!(count)))
+                    .collect();
+                let counters = serde_json::json!({
+                    "total": progress.total,
+                    "done": progress.done,
+                    "discovered": progress.discovered,
+                    "remaining": progress.remaining,
+                    "findings": progress.findings,
+                    "by_status": by_status,
+                });
+                if !send_event(stream, "progress", &counters).await {
+                    return;
+                }
+            }
+        }
+    }
+
+    // The job status is set before `finish()`, so the summary is final here.
+    let _ = send_event(stream, "done", &summary(job)).await;
+}
+
+/// Write one SSE event; returns `false` when the client is gone.
+async fn send_event<T: Serialize>(stream: &mut TcpStream, name: &str, data: &T) -> bool {
+    let json = match serde_json::to_string(data) {
+        Ok(j) => j,
+        Err(_) => return false,
+    };
+    let frame = format!("event: {}\ndata: {}\n\n", name, json);
+    stream.write_all(frame.as_bytes()).await.is_ok()
+}
+
+/// Read the request head and (Content-Length) body. `None` for malformed requests.
+async fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    let mut buffer: Vec<u8> = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+
+    // Read until the end of the head.
+    let head_end = loop {
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end;
+        }
+        if buffer.len() > MAX_BODY {
+            return None;
+        }
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+
+    let mut content_length: usize = 0;
+    let mut content_type: Option<String> = None;
+    let mut has_origin = false;
+    let mut host: Option<String> = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.trim().to_ascii_lowercase();
+        match name.as_str() {
+            "content-length" => content_length = value.trim().parse().ok()?,
+            "content-type" => {
+                let media = value.split(';').next().unwrap_or("");
+                content_type = Some(media.trim().to_ascii_lowercase());
+            }
+            "origin" => has_origin = true,
+            "host" => host = Some(value.trim().to_ascii_lowercase()),
+            _ => {}
+        }
+    }
+    if content_length > MAX_BODY {
+        return None;
+    }
+
+    // Read the rest of the body.
+    let mut body: Vec<u8> = buffer[head_end + 4..].to_vec();
+    while body.len() < content_length {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => body.extend_from_slice(&chunk[..n]),
+        }
+    }
+    body.truncate(content_length);
+
+    Some(Request {
+        method,
+        path,
+        content_type,
+        has_origin,
+        host,
+        body,
+    })
+}
+
+/// Build a job's JSON summary.
+fn summary(job: &Job) -> JobSummary {
+    let progress = job.monitor.progress();
+    let status = job_status(job);
+    JobSummary {
+        id: job.id,
+        target: job.target.clone(),
+        status: status_label(&status),
+        total: progress.total,
+        done: progress.done,
+        discovered: progress.discovered,
+        remaining: progress.remaining,
+        findings: progress.findings,
+        error: match status {
+            JobStatus::Failed(reason) => Some(reason),
+            _ => None,
+        },
+    }
+}
+
+/// API name of a status.
+fn status_label(status: &JobStatus) -> &'static str {
+    match status {
+        JobStatus::Running => "running",
+        JobStatus::Finished => "finished",
+        JobStatus::Cancelled => "cancelled",
+        JobStatus::Failed(_) => "failed",
+    }
+}
+
+/// Current status of a job.
+fn job_status(job: &Job) -> JobStatus {
+    match job.status.lock() {
+        Ok(guard) => guard.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+/// Replace the status of a job.
+fn set_status(job: &Job, status: JobStatus) {
+    match job.status.lock() {
+        Ok(mut guard) => *guard = status,
+        Err(poisoned) => *poisoned.into_inner() = status,
+    }
+}
+
+/// Lock the job list (a poisoned lock only means a handler panicked; the list is intact).
+fn lock_jobs(registry: &Registry) -> std::sync::MutexGuard<'_, Vec<Arc<Job>>> {
+    match registry.jobs.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Copy of the job list.
+fn registry_jobs(registry: &Registry) -> Vec<Arc<Job>> {
+    lock_jobs(registry).clone()
+}
+
+/// Look up a job by its id segment.
+fn find_job(registry: &Registry, id: &str) -> Option<Arc<Job>> {
+    let index: usize = id.parse().ok()?;
+    let jobs = lock_jobs(registry);
+    if index == 0 {
+        return None;
+    }
+    jobs.get(index - 1).cloned()
+}
+
+/// A JSON response from a serializable value.
+fn json_body<T: Serialize>(status: u16, value: &T) -> Vec<u8> {
+    match serde_json::to_string(value) {
+        Ok(text) => json_response(status, &text),
+        Err(e) => error_response(500, &e.to_string()),
+    }
+}
+
+/// `{"error": "..."}` with the given status.
+fn error_response(status: u16, message: &str) -> Vec<u8> {
+    json_body(status, &serde_json::json!({ "error": message }))
+}
+
+/// 404 for unknown paths and job ids.
+fn not_found() -> Vec<u8> {
+    json_response(404, r#"{"error":"not found"}"#)
+}
+
+/// Raw HTTP response bytes with a JSON body.
+fn json_response(status: u16, body: &str) -> Vec<u8> {
+    let reason = match status {
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        415 => "Unsupported Media Type",
+        _ => "Internal Server Error",
+    };
+    format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )
+    .into_bytes()
+}
@@ -0,0 +1,173 @@
+//! src/severity.rs
+//!
+//! Purpose:
+//!   Rules-based severity classification of findings, for prioritized triage.
+//!   Each finding (URL + status) is matched against a list of rules; matching rules give it
+//!   a severity (info … critical) and tags (e.g., "vcs", "secrets"), which are printed on the
+//!   result line and stored in result files.
+//!
+//! Configuration:
+//!   - Built-in rules live in `src/severity.toml` (embedded in the binary).
+//!   - `--rules <PATH>` replaces them with a user-supplied TOML file of the same format.
+//!
+//! Matching:
+//!   - `contains`: any of these substrings occurs in the URL path (case-insensitive)
+//!   - `status`:   the finding's status is listed (an empty list matches every status)
+//!   - Several rules may match: tags are merged, the highest severity wins.
+//!
+//! Thresholds:
+//!   - `--fail-on-findings=high` and `--notify-severity high` compare against the final
+//!     severity of a finding (after `--secrets` raised it); unclassified findings count as info.
+
+use crate::error::DirustError;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The built-in rule set, used when `--rules` is not given.
+const DEFAULT_RULES: &str = include_str!("severity.toml");
+
+/// How urgent a finding is. Ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Whether a finding of `severity` (`None`: unclassified, counted as info) is at this
+    /// threshold or above.
+    pub fn is_met_by(self, severity: Option<Severity>) -> bool {
+        severity.unwrap_or(Severity::Info) >= self
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Severity::Info => "INFO",
+            Severity::Low => "LOW",
+            Severity::Medium => "MEDIUM",
+            Severity::High => "HIGH",
+            Severity::Critical => "CRITICAL",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// One classification rule, as written in the TOML file.
+///
+/// A `name = "..."` key is allowed in the file for readability; it is not used.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rule {
+    /// Path substrings (any-of, case-insensitive).
+    pub contains: Vec<String>,
+    /// Status codes this rule applies to (empty = all).
+    #[serde(default)]
+    pub status: Vec<u16>,
+    pub severity: Severity,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Top-level layout of a rules file: a list of `[[rule]]` tables.
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    #[serde(default)]
+    rule: Vec<Rule>,
+}
+
+/// The outcome of classifying one finding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Classification {
+    pub severity: Severity,
+    pub tags: Vec<String>,
+}
+
+impl Classification {
+    /// Text appended to result lines, e.g. " [HIGH vcs,source-leak]".
+    pub fn label(&self) -> String {
+        if self.tags.is_empty() {
+            format!(" [{}]", self.severity)
+        } else {
+            format!(" [{} {}]", self.severity, self.tags.join(","))
+        }
+    }
+}
+
+/// A loaded rule set.
+#[derive(Debug, Clone)]
+pub struct Classifier {
+    rules: Vec<Rule>,
+}
+
+impl Classifier {
+    /// Load rules from `path`, or the built-in rules when `path` is `None`.
+    ///
+    /// Errors:
+    ///   - `DirustError::Io` if the file cannot be read
+    ///   - `DirustError::Toml` if it is not a valid rules file
+    pub fn load(path: Option<&str>) -> Result<Classifier, DirustError> {
+        let text = match path {
+            Some(p) => std::fs::read_to_string(p)?,
+            None => DEFAULT_RULES.to_string(),
+        };
+        let file: RulesFile = toml::from_str(&text)?;
+
+        // Lower-case patterns once so matching is a plain substring search.
+        let mut rules = file.rule;
+        for rule in &mut rules {
+            for pattern in &mut rule.contains {
+                *pattern = pattern.to_ascii_lowercase();
+            }
+        }
+
+        Ok(Classifier { rules })
+    }
+
+    /// Classify one finding, or `None` if no rule matches.
+    pub fn classify(&self, url: &str, status: u16) -> Option<Classification> {
+        let path = url_path(url);
+        let mut result: Option<Classification> = None;
+
+        for rule in &self.rules {
+            let status_ok = rule.status.is_empty() || rule.status.contains(&status);
+            let path_ok = rule.contains.iter().any(|p| path.contains(p.as_str()));
+            if !status_ok || !path_ok {
+                continue;
+            }
+
+            let current = result.get_or_insert(Classification {
+                severity: rule.severity,
+                tags: Vec::new(),
+            });
+            current.severity = current.severity.max(rule.severity);
+            for tag in &rule.tags {
+                if !current.tags.contains(tag) {
+                    current.tags.push(tag.clone());
+                }
+            }
+        }
+
+        result
+    }
+}
+
+/// Lower-cased path (plus query) of `url`, so host names never match path rules.
+fn url_path(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(parsed) => {
+            let mut path = parsed.path().to_string();
+            if let Some(query) = parsed.query() {
+                path.push('?');
+                path.push_str(query);
+            }
+            path.to_ascii_lowercase()
+        }
+        Err(_) => url.to_ascii_lowercase(),
+    }
+}
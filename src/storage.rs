@@ -0,0 +1,220 @@
+//! src/storage.rs
+//!
+//! Purpose:
+//!   `dirust storage <PROVIDER>`: cloud storage bucket/container enumeration. Wordlist entries
+//!   are treated as bucket (or storage account) names instead of paths, probed against the
+//!   provider's public URL pattern, and classified from the provider's response.
+//!
+//! URL patterns:
+//!   - s3     → https://<name>.s3.amazonaws.com/
+//!   - gcs    → https://storage.googleapis.com/<name>/
+//!   - azure  → https://<name>.blob.core.windows.net/?comp=list   (storage account)
+//!
+//! Classification:
+//!   - Missing       : the provider says the bucket/account does not exist (not printed)
+//!   - AccessDenied  : exists, but anonymous access is refused
+//!   - Listable      : exists and anonymous listing works (high interest!)
+//!   - Redirect      : exists in another region/endpoint (S3 PermanentRedirect)
+//!   - Unknown       : any other answer, printed with its status for manual review
+//!
+//! Probes run through the shared bounded-concurrency engine (`engine::run_bounded`).
+
+use crate::{
+    args::{StorageArgs, StorageProvider},
+    engine,
+    error::DirustError,
+    output::{self, Style},
+    scanner::wordlist,
+};
+use reqwest::{Client, StatusCode};
+
+/// Result of classifying one provider response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum BucketState {
+    Missing,
+    AccessDenied,
+    Listable,
+    Redirect,
+    Unknown(u16),
+}
+
+/// One probe: which provider, which name, which URL.
+struct StorageProbe {
+    provider: StorageProvider,
+    name: String,
+    url: String,
+}
+
+/// Run storage enumeration for the selected provider(s).
+pub async fn run(client: &Client, args: &StorageArgs) -> Result<(), DirustError> {
+    let names = wordlist::read_wordlist(&args.wordlist)?;
+
+    let providers: Vec<StorageProvider> = match args.provider {
+        StorageProvider::All => vec![StorageProvider::S3, StorageProvider::Gcs, StorageProvider::Azure],
+        one => vec![one],
+    };
+
+    // Expand names × providers, skipping names the provider would reject anyway.
+    let mut probes: Vec<StorageProbe> = Vec::new();
+    for raw in &names {
+        let name = raw.trim().to_ascii_lowercase();
+        for &provider in &providers {
+            if !is_valid_name(provider, &name) {
+                continue;
+            }
+            probes.push(StorageProbe {
+                provider,
+                url: storage_url(provider, &name),
+                name: name.clone(),
+            });
+        }
+    }
+
+    output::info(&format!("[*] storage: {} probes across {} provider(s)", probes.len(), providers.len()));
+
+    let client_owned = client.clone();
+    let states: Vec<BucketState> = engine::run_bounded(probes, args.http.concurrency, |probe: StorageProbe| {
+        let client_clone = client_owned.clone();
+        async move {
+            let state = classify(&client_clone, &probe).await;
+            if state != BucketState::Missing {
+                let line = format!(
+                    "{}{:<5} {:<13} {:<24} {}",
+                    output::timestamp_prefix(),
+                    provider_label(probe.provider),
+                    state_label(&state),
+                    probe.name,
+                    probe.url
+                );
+                output::finding(&line, Style::Plain, Some(&probe.url));
+            }
+            state
+        }
+    })
+    .await?;
+
+    let mut found: usize = 0;
+    for state in states {
+        if state != BucketState::Missing {
+            found += 1;
+        }
+    }
+    output::info(&format!("[*] storage: {} existing bucket(s)/account(s) found", found));
+
+    Ok(())
+}
+
+/// Build the probe URL for `name` at `provider`.
+fn storage_url(provider: StorageProvider, name: &str) -> String {
+    match provider {
+        StorageProvider::S3 => format!("https://{}.s3.amazonaws.com/", name),
+        StorageProvider::Gcs => format!("https://storage.googleapis.com/{}/", name),
+        StorageProvider::Azure => format!("https://{}.blob.core.windows.net/?comp=list", name),
+        StorageProvider::All => String::new(), // never probed directly
+    }
+}
+
+/// Provider naming rules (simplified): skip names that cannot exist.
+///
+///   - S3 / GCS: 3–63 chars of [a-z0-9.-], starting and ending with a letter or digit.
+///   - Azure storage accounts: 3–24 chars of [a-z0-9].
+fn is_valid_name(provider: StorageProvider, name: &str) -> bool {
+    let len = name.len();
+    match provider {
+        StorageProvider::Azure => {
+            (3..=24).contains(&len) && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        }
+        _ => {
+            let edges_ok = match (name.chars().next(), name.chars().last()) {
+                (Some(first), Some(last)) => first.is_ascii_alphanumeric() && last.is_ascii_alphanumeric(),
+                _ => false,
+            };
+            (3..=63).contains(&len)
+                && edges_ok
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-')
+        }
+    }
+}
+
+/// Probe one URL and classify the provider's answer.
+///
+/// Network errors are classified as `Missing` for Azure (a nonexistent account has no DNS
+/// record) and reported as warnings for S3/GCS (whose endpoints always resolve).
+async fn classify(client: &Client, probe: &StorageProbe) -> BucketState {
+    let response = match client.get(&probe.url).send().await {
+        Ok(r) => r,
+        Err(e) => {
+            if probe.provider != StorageProvider::Azure {
+                eprintln!("[!] storage {}: {}", probe.url, e);
+            }
+            return BucketState::Missing;
+        }
+    };
+
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+
+    match probe.provider {
+        StorageProvider::Azure => classify_azure(status, &body),
+        _ => classify_bucket(status, &body),
+    }
+}
+
+/// S3 and GCS share the S3-style XML error format.
+fn classify_bucket(status: StatusCode, body: &str) -> BucketState {
+    if body.contains("NoSuchBucket") {
+        return BucketState::Missing;
+    }
+    if body.contains("<ListBucketResult") {
+        return BucketState::Listable;
+    }
+    if body.contains("PermanentRedirect") || status.is_redirection() {
+        return BucketState::Redirect;
+    }
+    if body.contains("AccessDenied") || status == StatusCode::FORBIDDEN {
+        return BucketState::AccessDenied;
+    }
+    if status == StatusCode::NOT_FOUND {
+        return BucketState::Missing;
+    }
+    BucketState::Unknown(status.as_u16())
+}
+
+/// Azure answers at the account level; any HTTP answer means the account exists.
+fn classify_azure(status: StatusCode, body: &str) -> BucketState {
+    if body.contains("<EnumerationResults") {
+        return BucketState::Listable;
+    }
+    if body.contains("AuthenticationFailed")
+        || body.contains("AuthorizationFailure")
+        || body.contains("PublicAccessNotPermitted")
+        || status == StatusCode::FORBIDDEN
+    {
+        return BucketState::AccessDenied;
+    }
+    if status == StatusCode::NOT_FOUND {
+        return BucketState::Missing;
+    }
+    BucketState::Unknown(status.as_u16())
+}
+
+fn provider_label(provider: StorageProvider) -> &'static str {
+    match provider {
+        StorageProvider::S3 => "S3",
+        StorageProvider::Gcs => "GCS",
+        StorageProvider::Azure => "AZURE",
+        StorageProvider::All => "ALL",
+    }
+}
+
+fn state_label(state: &BucketState) -> String {
+    match state {
+        BucketState::Missing => "Missing".to_string(),
+        BucketState::AccessDenied => "AccessDenied".to_string(),
+        BucketState::Listable => "LISTABLE".to_string(),
+        BucketState::Redirect => "Redirect".to_string(),
+        BucketState::Unknown(code) => format!("Unknown({})", code),
+    }
+}
@@ -0,0 +1,197 @@
+//! src/stream.rs
+//!
+//! Purpose:
+//!   `--stream-to <SINK>`: forward every finding, as it is reported, to an external collector
+//!   as NDJSON, so scans running on many jump hosts can be aggregated in one place.
+//!
+//! Sinks:
+//!   tcp://collector.example:9000     a TCP connection
+//!   unix:///run/dirust/results.sock  a Unix domain socket (Unix only)
+//!
+//! Lines (one JSON object per finding, newline-terminated):
+//!   {"schema_version":1,"target":"https://example.com/","result":{"url":"https://example.com/admin","status":200,...}}
+//!   `target` is the base URL being scanned, `result` a result entry as in result files
+//!   (see `schema.rs`).
+//!
+//! How:
+//!   - The sink is connected before the scan starts; an unreachable collector stops the run
+//!     right away instead of losing findings later.
+//!   - A task follows the scan monitor: on every finding it sends what has not been sent yet
+//!     (so a slow collector delays lines, it does not lose them).
+//!   - A connection that breaks mid-scan is reopened for the next line; a line that cannot be
+//!     delivered is reported on stderr and skipped. Everything is still in `--results-db`.
+//!   - `--notify-severity` leaves out findings below the given severity.
+
+use crate::{
+    error::DirustError,
+    redact,
+    scanner::monitor::{MonitorEvent, ScanMonitor},
+    schema::{ResultEntry, SCHEMA_VERSION},
+    severity::Severity,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+    sync::{broadcast::error::RecvError, oneshot},
+    task::JoinHandle,
+};
+
+/// One NDJSON line.
+#[derive(Serialize)]
+struct StreamLine<'a> {
+    /// See `schema::SCHEMA_VERSION`.
+    schema_version: u32,
+    target: &'a str,
+    result: &'a ResultEntry,
+}
+
+/// A connected sink.
+type Connection = Box<dyn AsyncWrite + Unpin + Send>;
+
+/// A running `--stream-to` forwarder; `close` it when the scan is over.
+pub struct ResultStream {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl ResultStream {
+    /// Connect to `sink` and forward the findings `monitor` reports from now on; `bases` are
+    /// the base URLs of the run (each line names the one its finding belongs to). With
+    /// `min_severity`, findings below it are not sent.
+    ///
+    /// Errors:
+    ///   - `DirustError::StreamSink` if `sink` is neither `tcp://HOST:PORT` nor `unix://PATH`,
+    ///     or the collector cannot be reached
+    pub async fn start(
+        sink: &str,
+        bases: &[String],
+        min_severity: Option<Severity>,
+        monitor: Arc<ScanMonitor>,
+    ) -> Result<ResultStream, DirustError> {
+        let connection = match connect(sink).await {
+            Ok(c) => c,
+            Err(DirustError::Io(e)) => return Err(DirustError::StreamSink(format!("cannot connect to {}: {}", sink, e))),
+            Err(e) => return Err(e),
+        };
+        let (stop, mut stopped) = oneshot::channel::<()>();
+        let sink = sink.to_string();
+        let bases = bases.to_vec();
+
+        let task = tokio::spawn(async move {
+            let mut forwarder = Forwarder {
+                sink,
+                bases,
+                min_severity,
+                connection: Some(connection),
+                sent: 0,
+            };
+            let mut events = monitor.subscribe();
+            loop {
+                tokio::select! {
+                    _ = &mut stopped => break,
+                    event = events.recv() => match event {
+                        Ok(MonitorEvent::Finding(_)) | Err(RecvError::Lagged(_)) => forwarder.send_new(&monitor).await,
+                        Ok(MonitorEvent::Finished) | Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+            // Whatever arrived since the last event.
+            forwarder.send_new(&monitor).await;
+            if let Some(connection) = &mut forwarder.connection {
+                let _ = connection.shutdown().await;
+            }
+        });
+
+        Ok(ResultStream { stop, task })
+    }
+
+    /// Send the findings not forwarded yet, then close the connection.
+    pub async fn close(self) {
+        let _ = self.stop.send(());
+        let _ = self.task.await;
+    }
+}
+
+/// The forwarding state: where lines go and how many findings went out.
+struct Forwarder {
+    sink: String,
+    bases: Vec<String>,
+    min_severity: Option<Severity>,
+    connection: Option<Connection>,
+    sent: usize,
+}
+
+impl Forwarder {
+    /// Send every finding of `monitor` after the first `sent` ones.
+    async fn send_new(&mut self, monitor: &ScanMonitor) {
+        for entry in monitor.findings_since(self.sent) {
+            self.sent += 1;
+            if let Some(threshold) = self.min_severity
+                && !threshold.is_met_by(entry.severity)
+            {
+                continue;
+            }
+            let target = self.target_of(&entry.url);
+            let mut line = match serde_json::to_string(&StreamLine { schema_version: SCHEMA_VERSION, target, result: &entry }) {
+                Ok(l) => redact::text(&l).into_owned(),
+                Err(_) => continue,
+            };
+            line.push('\n');
+            if let Err(e) = self.write(line.as_bytes()).await {
+                eprintln!("[!] stream-to: could not send {} to {}: {}", entry.url, self.sink, e);
+            }
+        }
+    }
+
+    /// The base URL `url` was found under (the longest matching one).
+    fn target_of(&self, url: &str) -> &str {
+        self.bases
+            .iter()
+            .filter(|base| url.starts_with(base.as_str()))
+            .max_by_key(|base| base.len())
+            .map(|base| base.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Write one line, reconnecting once if the connection broke.
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), DirustError> {
+        if let Some(connection) = &mut self.connection
+            && connection.write_all(bytes).await.is_ok()
+        {
+            return Ok(());
+        }
+        self.connection = None;
+        let mut connection = connect(&self.sink).await?;
+        connection.write_all(bytes).await?;
+        self.connection = Some(connection);
+        Ok(())
+    }
+}
+
+/// Open the sink named by `tcp://HOST:PORT` or `unix://PATH`.
+async fn connect(sink: &str) -> Result<Connection, DirustError> {
+    if let Some(address) = sink.strip_prefix("tcp://") {
+        let stream = TcpStream::connect(address.trim_end_matches('/')).await?;
+        stream.set_nodelay(true)?;
+        return Ok(Box::new(stream));
+    }
+    if let Some(path) = sink.strip_prefix("unix://") {
+        return connect_unix(path).await;
+    }
+    Err(DirustError::StreamSink(format!(
+        "{} is not a sink (expected tcp://HOST:PORT or unix://PATH)",
+        sink
+    )))
+}
+
+#[cfg(unix)]
+async fn connect_unix(path: &str) -> Result<Connection, DirustError> {
+    Ok(Box::new(tokio::net::UnixStream::connect(path).await?))
+}
+
+#[cfg(not(unix))]
+async fn connect_unix(path: &str) -> Result<Connection, DirustError> {
+    Err(DirustError::StreamSink(format!("unix://{} is not supported (Unix domain sockets need a Unix system)", path)))
+}
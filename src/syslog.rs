@@ -0,0 +1,370 @@
+//! src/syslog.rs
+//!
+//! Purpose:
+//!   `--syslog[=TARGET]`: send every finding, as it is reported, to syslog as an RFC 5424
+//!   message, so findings land in a SOC's log pipeline without a file collector.
+//!
+//! Targets:
+//!   local (default)        the local daemon, through /dev/log
+//!   unix:///path/to/sock   another local datagram socket
+//!   udp://siem:514         a remote collector over UDP (port 514 if omitted)
+//!   tcp://siem:601         a remote collector over TCP (RFC 6587 octet counting; port 601
+//!                          if omitted)
+//!
+//! Messages (facility `user`; one per finding):
+//!   <11>1 2024-08-12T09:59:49.310Z scanner01 dirust 4242 finding [finding@32473
+//!     url="https://example.com/admin" status="200" severity="high" tags="admin-panel"]
+//!     200 https://example.com/admin [HIGH admin-panel]
+//!
+//! Severity mapping (finding severity from `--rules` -> syslog severity):
+//!   critical -> crit (2), high -> err (3), medium -> warning (4), low -> notice (5),
+//!   info or unclassified -> info (6)
+//!
+//! Notes:
+//!   - The target is reached before the scan starts; an unreachable collector stops the run.
+//!   - A TCP connection that breaks mid-scan is reopened for the next message; a message that
+//!     cannot be sent is reported on stderr and skipped.
+//!   - `--notify-severity` leaves out findings below the given severity.
+//!   - Structured data uses the documentation enterprise number (32473) as its SD-ID suffix.
+
+use crate::{
+    error::DirustError,
+    export,
+    redact,
+    scanner::monitor::{MonitorEvent, ScanMonitor},
+    schema::ResultEntry,
+    severity::Severity,
+};
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    io::AsyncWriteExt,
+    net::{TcpStream, UdpSocket},
+    sync::{broadcast::error::RecvError, oneshot},
+    task::JoinHandle,
+};
+
+/// Facility `user` (RFC 5424 section 6.2.1).
+const FACILITY_USER: u8 = 1;
+
+/// The local daemon's socket.
+const LOCAL_SOCKET: &str = "/dev/log";
+
+/// Default ports of remote collectors.
+const UDP_PORT: u16 = 514;
+const TCP_PORT: u16 = 601;
+
+/// An open syslog transport.
+enum Transport {
+    #[cfg(unix)]
+    Local(tokio::net::UnixDatagram),
+    Udp(UdpSocket),
+    Tcp(Option<TcpStream>, String),
+}
+
+/// A running `--syslog` forwarder; `close` it when the scan is over.
+pub struct SyslogSink {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl SyslogSink {
+    /// Open `target` and send the findings `monitor` reports from now on (with `min_severity`,
+    /// only those at that severity or above).
+    ///
+    /// Errors:
+    ///   - `DirustError::Syslog` if `target` cannot be parsed or reached
+    pub async fn start(
+        target: &str,
+        min_severity: Option<Severity>,
+        monitor: Arc<ScanMonitor>,
+    ) -> Result<SyslogSink, DirustError> {
+        let mut transport = match open(target).await {
+            Ok(t) => t,
+            Err(DirustError::Io(e)) => {
+                let shown = if target == "local" { LOCAL_SOCKET } else { target };
+                return Err(DirustError::Syslog(format!("cannot reach {}: {}", shown, e)));
+            }
+            Err(e) => return Err(e),
+        };
+        let (stop, mut stopped) = oneshot::channel::<()>();
+        let hostname = hostname();
+
+        let task = tokio::spawn(async move {
+            let mut sent = 0;
+            let mut events = monitor.subscribe();
+            loop {
+                tokio::select! {
+                    _ = &mut stopped => break,
+                    event = events.recv() => match event {
+                        Ok(MonitorEvent::Finding(_)) | Err(RecvError::Lagged(_)) => {
+                            sent = send_new(&mut transport, &hostname, min_severity, &monitor, sent).await;
+                        }
+                        Ok(MonitorEvent::Finished) | Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+            // Whatever arrived since the last event.
+            send_new(&mut transport, &hostname, min_severity, &monitor, sent).await;
+        });
+
+        Ok(SyslogSink { stop, task })
+    }
+
+    /// Send the findings not sent yet, then stop.
+    pub async fn close(self) {
+        let _ = self.stop.send(());
+        let _ = self.task.await;
+    }
+}
+
+/// Send every finding of `monitor` after the first `sent` ones (below `min_severity`: skipped);
+/// returns the new count.
+async fn send_new(
+    transport: &mut Transport,
+    hostname: &str,
+    min_severity: Option<Severity>,
+    monitor: &ScanMonitor,
+    sent: usize,
+) -> usize {
+    let entries = monitor.findings_since(sent);
+    for entry in &entries {
+        if let Some(threshold) = min_severity
+            && !threshold.is_met_by(entry.severity)
+        {
+            continue;
+        }
+        let message = format_message(entry, hostname);
+        if let Err(e) = transport.send(&redact::text(&message)).await {
+            eprintln!("[!] syslog: could not send {}: {}", entry.url, e);
+        }
+    }
+    sent + entries.len()
+}
+
+impl Transport {
+    /// Send one message (TCP: framed, reconnecting once if the connection broke).
+    async fn send(&mut self, message: &str) -> Result<(), DirustError> {
+        match self {
+            #[cfg(unix)]
+            Transport::Local(socket) => {
+                socket.send(message.as_bytes()).await?;
+            }
+            Transport::Udp(socket) => {
+                socket.send(message.as_bytes()).await?;
+            }
+            Transport::Tcp(stream, address) => {
+                let frame = format!("{} {}", message.len(), message);
+                if let Some(connection) = stream
+                    && connection.write_all(frame.as_bytes()).await.is_ok()
+                {
+                    return Ok(());
+                }
+                *stream = None;
+                let mut connection = TcpStream::connect(address.as_str()).await?;
+                connection.write_all(frame.as_bytes()).await?;
+                *stream = Some(connection);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Open the transport named by `target` (see the module docs).
+async fn open(target: &str) -> Result<Transport, DirustError> {
+    if target == "local" {
+        return open_local(LOCAL_SOCKET).await;
+    }
+    if let Some(path) = target.strip_prefix("unix://") {
+        return open_local(path).await;
+    }
+    if let Some(rest) = target.strip_prefix("udp://") {
+        let address = with_port(rest, UDP_PORT);
+        let bind = if address.starts_with('[') { "[::]:0" } else { "0.0.0.0:0" };
+        let socket = UdpSocket::bind(bind).await?;
+        socket.connect(&address).await?;
+        return Ok(Transport::Udp(socket));
+    }
+    if let Some(rest) = target.strip_prefix("tcp://") {
+        let address = with_port(rest, TCP_PORT);
+        let stream = TcpStream::connect(&address).await?;
+        return Ok(Transport::Tcp(Some(stream), address));
+    }
+    Err(DirustError::Syslog(format!(
+        "{} is not a target (expected local, unix://PATH, udp://HOST[:PORT] or tcp://HOST[:PORT])",
+        target
+    )))
+}
+
+#[cfg(unix)]
+async fn open_local(path: &str) -> Result<Transport, DirustError> {
+    let socket = tokio::net::UnixDatagram::unbound()?;
+    socket.connect(path)?;
+    Ok(Transport::Local(socket))
+}
+
+#[cfg(not(unix))]
+async fn open_local(path: &str) -> Result<Transport, DirustError> {
+    Err(DirustError::Syslog(format!("{} is not supported here (use udp:// or tcp://)", path)))
+}
+
+/// `host:port` from `host[:port][/]`, adding `default` when no port is given.
+fn with_port(rest: &str, default: u16) -> String {
+    let host = rest.trim_end_matches('/');
+    let has_port = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.contains("]:"),
+        None => host.contains(':'),
+    };
+    if has_port {
+        host.to_string()
+    } else {
+        format!("{}:{}", host, default)
+    }
+}
+
+/// Syslog severity of a finding.
+fn syslog_severity(severity: Option<Severity>) -> u8 {
+    match severity {
+        Some(Severity::Critical) => 2,
+        Some(Severity::High) => 3,
+        Some(Severity::Medium) => 4,
+        Some(Severity::Low) => 5,
+        Some(Severity::Info) | None => 6,
+    }
+}
+
+/// The RFC 5424 message for one finding, sent now by this process.
+fn format_message(entry: &ResultEntry, hostname: &str) -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok();
+    render(entry, hostname, now, std::process::id())
+}
+
+/// The RFC 5424 message for one finding sent at `now` (time since the epoch) by process
+/// `pid`. An unknown time or an empty host name is sent as the NILVALUE (`-`).
+fn render(entry: &ResultEntry, hostname: &str, now: Option<Duration>, pid: u32) -> String {
+    let priority = FACILITY_USER * 8 + syslog_severity(entry.severity);
+    let timestamp = match now {
+        Some(now) => export::rfc3339_millis(now),
+        None => "-".to_string(),
+    };
+    let hostname = if hostname.is_empty() { "-" } else { hostname };
+
+    let mut data = format!("[finding@32473 url=\"{}\" status=\"{}\"", sd_escape(&entry.url), entry.status);
+    let mut text = format!("{} {}", entry.status, entry.url);
+    if let Some(severity) = entry.severity {
+        data.push_str(&format!(" severity=\"{}\"", severity.to_string().to_ascii_lowercase()));
+        text.push_str(&format!(" [{}", severity));
+        if !entry.tags.is_empty() {
+            text.push_str(&format!(" {}", entry.tags.join(",")));
+        }
+        text.push(']');
+    }
+    if !entry.tags.is_empty() {
+        data.push_str(&format!(" tags=\"{}\"", sd_escape(&entry.tags.join(","))));
+    }
+    if let Some(length) = entry.content_length {
+        data.push_str(&format!(" length=\"{}\"", length));
+    }
+    if let Some(location) = &entry.location {
+        data.push_str(&format!(" location=\"{}\"", sd_escape(location)));
+    }
+    data.push(']');
+
+    format!(
+        "<{}>1 {} {} dirust {} finding {} {}",
+        priority,
+        timestamp,
+        hostname,
+        pid,
+        data,
+        text
+    )
+}
+
+/// A structured-data parameter value with `"`, `\` and `]` escaped (RFC 5424 section 6.3.3).
+fn sd_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// This machine's host name for the HOSTNAME field (empty when unknown).
+fn hostname() -> String {
+    let name = std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .unwrap_or_default();
+    name.trim().chars().filter(|c| c.is_ascii_graphic()).take(255).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, sd_escape};
+    use crate::schema::ResultEntry;
+    use std::time::Duration;
+
+    /// 2024-08-12T09:59:49.310Z
+    const NOW: Duration = Duration::from_millis(1_723_456_789_310);
+
+    fn finding(json: &str) -> ResultEntry {
+        serde_json::from_str(json).expect("finding")
+    }
+
+    #[test]
+    fn sd_escape_values() {
+        // (value, escaped)
+        let cases = [
+            ("plain", "plain"),
+            ("", ""),
+            (r#"say "hi""#, r#"say \"hi\""#),
+            (r"C:\dir", r"C:\\dir"),
+            ("a]b", r"a\]b"),
+            (r#"]"\"#, r#"\]\"\\"#),
+            // `[` and `=` need no escape inside a quoted value.
+            ("q[0]=1", r"q[0\]=1"),
+            ("café", "café"),
+        ];
+        for (value, escaped) in cases {
+            assert_eq!(sd_escape(value), escaped, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn messages() {
+        let admin = finding(
+            r#"{"url": "https://example.com/admin", "status": 200, "content_length": 1234,
+                "severity": "high", "tags": ["admin-panel", "login"]}"#,
+        );
+        assert_eq!(
+            render(&admin, "scanner01", Some(NOW), 4242),
+            concat!(
+                "<11>1 2024-08-12T09:59:49.310Z scanner01 dirust 4242 finding [finding@32473 ",
+                r#"url="https://example.com/admin" status="200" severity="high" tags="admin-panel,login" length="1234"] "#,
+                "200 https://example.com/admin [HIGH admin-panel,login]"
+            )
+        );
+
+        // Unclassified (severity info), values escaped, unknown time and host as NILVALUE.
+        let redirect = finding(
+            r#"{"url": "https://example.com/a\"b]", "status": 302, "location": "/login?next=\\x]",
+                "tags": ["odd\"tag"]}"#,
+        );
+        assert_eq!(
+            render(&redirect, "", None, 7),
+            concat!(
+                "<14>1 - - dirust 7 finding [finding@32473 ",
+                r#"url="https://example.com/a\"b\]" status="302" tags="odd\"tag" location="/login?next=\\x\]"] "#,
+                r#"302 https://example.com/a"b]"#
+            )
+        );
+    }
+}
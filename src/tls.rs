@@ -0,0 +1,117 @@
+//! src/tls.rs
+//!
+//! Purpose:
+//!   `--tls-impersonate <chrome|firefox|random>`: shape the TLS ClientHello so it looks less
+//!   like a default Rust TLS stack. Some CDNs and bot filters fingerprint the ClientHello
+//!   (JA3/JA4: offered cipher suites, their order, key-exchange groups, ALPN) and block
+//!   clients whose fingerprint matches a known tool.
+//!
+//! What can and cannot be shaped (rustls):
+//!   - cipher suites and their order: the browser's order, restricted to the AEAD suites
+//!     rustls implements (browsers additionally offer legacy CBC suites)
+//!   - key-exchange groups and their order (X25519, P-256, P-384)
+//!   - ALPN: `h2`, `http/1.1` for the default engine, set by `client.rs` (reqwest keeps the
+//!     ALPN of a preconfigured TLS configuration as it is); `--engine fast` speaks HTTP/1.1
+//!     only and offers no ALPN, which browsers never do
+//!   - NOT: GREASE values, extension order, or extensions rustls does not implement.
+//!     The result is close to the browser's profile but not a byte-identical ClientHello,
+//!     so fingerprints that hash the full extension list will still differ.
+//!
+//! `random` shuffles suite and group order once per run, so repeated runs do not share one
+//! stable fingerprint.
+
+use crate::{args::TlsProfile, error::DirustError, fuzz::baseline::shuffle};
+use rustls::{
+    crypto::{
+        ring::{self, cipher_suite as suites, kx_group as groups},
+        CryptoProvider, SupportedKxGroup,
+    },
+    ClientConfig, RootCertStore, SupportedCipherSuite,
+};
+use std::sync::Arc;
+
+/// The TLS client configuration without impersonation: rustls defaults, Mozilla root
+/// certificates, no ALPN (the caller sets it).
+pub fn default_config() -> Result<ClientConfig, DirustError> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(config)
+}
+
+/// Build the TLS client configuration for `profile` (Mozilla root certificates).
+///
+/// ALPN is left empty: hyper-rustls fills it in with the protocols it speaks (and refuses a
+/// configuration that already has it); for reqwest the caller sets it (`client.rs`).
+pub fn client_config(profile: TlsProfile) -> Result<ClientConfig, DirustError> {
+    let (cipher_suites, kx_groups): (Vec<SupportedCipherSuite>, Vec<&'static dyn SupportedKxGroup>) =
+        match profile {
+            // Chrome: AES-128 first, ChaCha20 last in TLS 1.3; ECDSA before RSA in TLS 1.2.
+            TlsProfile::Chrome => (
+                vec![
+                    suites::TLS13_AES_128_GCM_SHA256,
+                    suites::TLS13_AES_256_GCM_SHA384,
+                    suites::TLS13_CHACHA20_POLY1305_SHA256,
+                    suites::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256,
+                    suites::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+                    suites::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+                    suites::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+                    suites::TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+                    suites::TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256,
+                ],
+                vec![groups::X25519, groups::SECP256R1, groups::SECP384R1],
+            ),
+            // Firefox: ChaCha20 second in TLS 1.3 and right after the AES-128 suites in 1.2.
+            TlsProfile::Firefox => (
+                vec![
+                    suites::TLS13_AES_128_GCM_SHA256,
+                    suites::TLS13_CHACHA20_POLY1305_SHA256,
+                    suites::TLS13_AES_256_GCM_SHA384,
+                    suites::TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256,
+                    suites::TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+                    suites::TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256,
+                    suites::TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256,
+                    suites::TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384,
+                    suites::TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384,
+                ],
+                vec![groups::X25519, groups::SECP256R1, groups::SECP384R1],
+            ),
+            // Random: every suite and group rustls has, in a per-run order (TLS 1.3 first,
+            // as every real client does).
+            TlsProfile::Random => {
+                let mut tls13: Vec<SupportedCipherSuite> = Vec::new();
+                let mut tls12: Vec<SupportedCipherSuite> = Vec::new();
+                for suite in ring::ALL_CIPHER_SUITES {
+                    match suite {
+                        SupportedCipherSuite::Tls13(_) => tls13.push(*suite),
+                        _ => tls12.push(*suite),
+                    }
+                }
+                shuffle(&mut tls13);
+                shuffle(&mut tls12);
+                tls13.extend(tls12);
+
+                let mut kx: Vec<&'static dyn SupportedKxGroup> = ring::ALL_KX_GROUPS.to_vec();
+                shuffle(&mut kx);
+                (tls13, kx)
+            }
+        };
+
+    let provider = CryptoProvider {
+        cipher_suites,
+        kx_groups,
+        ..ring::default_provider()
+    };
+
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+
+    let config = ClientConfig::builder_with_provider(Arc::new(provider))
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(config)
+}
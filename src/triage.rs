@@ -0,0 +1,103 @@
+//! src/triage.rs
+//!
+//! Purpose:
+//!   `dirust triage <FILE>`: review the findings of a results file one by one after a scan.
+//!   Each finding is marked interesting or false positive, optionally with a note, and the
+//!   decision is written back into the file (`"triage"` on the entry) so `dirust report`
+//!   can show it, or leave false positives out (`--hide-false-positives`).
+//!
+//! Session:
+//!   [*] triage: 3 of 12 findings to review (i = interesting, f = false positive, s = skip, q = quit)
+//!   [1/3] 200 len=1234  https://example.com/admin [HIGH admin-panel]
+//!       verdict [i/f/s/q]: f
+//!       note (empty for none): default nginx page
+//!
+//! Notes:
+//!   - Only findings without a decision are shown, unless `--all` is given.
+//!   - The file is saved after every decision, so quitting (or Ctrl-C) loses nothing.
+//!   - A later scan into the same `--results-db` keeps a decision while the finding keeps its
+//!     status (see `ResultsDb::apply`).
+//!   - Prompts go to stderr; answers are read line by line from stdin (end of input = quit).
+
+use crate::{
+    args::TriageArgs,
+    db,
+    error::DirustError,
+    output::{self, Style},
+    report,
+    schema::{Triage, Verdict},
+};
+use std::io::{self, BufRead, Lines, StdinLock, Write};
+
+/// What the reviewer answered for one finding.
+enum Answer {
+    Decide(Verdict),
+    Skip,
+    Quit,
+}
+
+/// Review the findings of `args.file` interactively and save the decisions into it.
+pub fn run(args: &TriageArgs) -> Result<(), DirustError> {
+    let mut result = db::read_result_file(&args.file)?;
+    result.results.sort_by(|a, b| a.url.cmp(&b.url));
+
+    let pending: Vec<usize> = (0..result.results.len())
+        .filter(|&i| args.all || result.results[i].triage.is_none())
+        .collect();
+    output::info(&format!(
+        "[*] triage: {} of {} findings to review (i = interesting, f = false positive, s = skip, q = quit)",
+        pending.len(),
+        result.results.len()
+    ));
+
+    let mut lines = io::stdin().lock().lines();
+    let mut decided: usize = 0;
+    for (position, &index) in pending.iter().enumerate() {
+        let entry = &result.results[index];
+        let line = format!("[{}/{}] {}", position + 1, pending.len(), report::format_entry(entry));
+        output::print_styled(&line, Style::for_status(entry.status));
+
+        let verdict = match ask_verdict(&mut lines)? {
+            Answer::Decide(verdict) => verdict,
+            Answer::Skip => continue,
+            Answer::Quit => break,
+        };
+        let note = match ask(&mut lines, "    note (empty for none): ")? {
+            Some(text) if !text.is_empty() => Some(text),
+            _ => None,
+        };
+        result.results[index].triage = Some(Triage { verdict, note });
+        db::write_result_file(&args.file, &result)?;
+        decided += 1;
+    }
+
+    output::info(&format!("[*] triage: {} decision(s) saved to {}", decided, args.file));
+    Ok(())
+}
+
+/// Ask for a verdict until the answer is one of i/f/s/q (empty = skip, end of input = quit).
+fn ask_verdict(lines: &mut Lines<StdinLock<'_>>) -> Result<Answer, DirustError> {
+    loop {
+        let answer = match ask(lines, "    verdict [i/f/s/q]: ")? {
+            Some(text) => text.to_ascii_lowercase(),
+            None => return Ok(Answer::Quit),
+        };
+        match answer.as_str() {
+            "i" | "interesting" => return Ok(Answer::Decide(Verdict::Interesting)),
+            "f" | "fp" | "false-positive" => return Ok(Answer::Decide(Verdict::FalsePositive)),
+            "" | "s" | "skip" => return Ok(Answer::Skip),
+            "q" | "quit" => return Ok(Answer::Quit),
+            _ => eprintln!("    please answer i, f, s or q"),
+        }
+    }
+}
+
+/// Print `prompt` on stderr and read one trimmed line (`None` at end of input).
+fn ask(lines: &mut Lines<StdinLock<'_>>, prompt: &str) -> Result<Option<String>, DirustError> {
+    eprint!("{}", prompt);
+    io::stderr().flush()?;
+    match lines.next() {
+        Some(line) => Ok(Some(line?.trim().to_string())),
+        None => Ok(None),
+    }
+}
@@ -1,45 +1,57 @@
 //! src/url.rs
 //!
 //! Purpose:
-//!   Validate and normalize the base URL string provided on the CLI.
+//!   Validate and normalize the base URL string provided on the CLI, and put generated
+//!   target URLs in canonical form before they are scheduled (`canonical_target`).
 //!
 //! Behavior:
 //!   - Accept only `http://` or `https://` schemes (reject anything else).
+//!   - Accept scheme-less input (`example.com`) and assume `https://`; the caller decides
+//!     whether to fall back to `http://` or scan both (`--both-schemes`).
 //!   - Ensure the base ends with a trailing slash `/` so later joins are predictable.
+//!   - Put the base in the same canonical form as generated targets (`canonical_target`).
 //!
 //! Notes / assumptions:
 //!   - We treat the input as an opaque string and do minimal checks:
 //!       * leading/trailing whitespace is trimmed
-//!       * scheme must be http or https
+//!       * scheme must be http or https (or absent, meaning https)
 //!       * add a trailing slash if missing
 //!   - We do NOT parse or validate hostnames, ports, query strings, or fragments here.
 //!     If you later need strict URL parsing/validation, consider the `url` crate.
 
 use crate::error::DirustError;
+use reqwest::Url;
 
 /// Ensure the base URL starts with http/https and ends with a trailing slash.
 ///
 /// Examples:
 ///   Input:  "http://example.com"   → Ok("http://example.com/")
 ///   Input:  "https://x/y/"         → Ok("https://x/y/")
+///   Input:  "example.com:8080/app" → Ok("https://example.com:8080/app/")
+///   Input:  "http://LOCALHOST:80//a" → Ok("http://localhost/a/")
 ///   Input:  "ftp://example.com"    → Err(InvalidBaseUrl)
 ///
 /// Errors:
-///   - Returns `DirustError::InvalidBaseUrl` if the scheme is not http/https.
+///   - Returns `DirustError::InvalidBaseUrl` if the input is empty or the scheme is not http/https.
 pub fn normalize_base(base: &str) -> Result<String, DirustError> {
     // Make a new owned String we can modify. We also trim any surrounding whitespace
     // so accidental spaces in the CLI do not break our checks.
     let mut b: String = base.trim().to_string();
 
-    // Explicit booleans for the two allowed schemes.
-    // Keeping these as named variables makes the condition below very readable.
-    let starts_http: bool = b.starts_with("http://");
-    let starts_https: bool = b.starts_with("https://");
+    // Nothing to scan.
+    if b.is_empty() {
+        return Err(DirustError::InvalidBaseUrl);
+    }
+
+    // Scheme-less input ("example.com") defaults to HTTPS.
+    if !has_explicit_scheme(&b) {
+        b = format!("https://{}", b);
+    }
 
     // Reject anything that does not start with `http://` or `https://`.
     // This keeps the tool focused on HTTP(S) and avoids surprising behavior
     // with unsupported schemes (ftp, file, data, etc.).
-    if !starts_http && !starts_https {
+    if !has_http_scheme(&b) {
         return Err(DirustError::InvalidBaseUrl);
     }
 
@@ -50,6 +62,171 @@ pub fn normalize_base(base: &str) -> Result<String, DirustError> {
         b.push('/');
     }
 
-    // Return the normalized base string.
-    Ok(b)
+    // Same canonical form as every generated target (host lower-cased, default port
+    // dropped, `//` collapsed), so `target.starts_with(base)` holds for the base's own
+    // targets however the base was typed.
+    Ok(canonical_target(&b))
+}
+
+/// Return `true` if `url` starts with `http://` or `https://`.
+///
+/// Shared by `normalize_base` and by modes that take a full URL as-is (e.g., `bench`).
+pub fn has_http_scheme(url: &str) -> bool {
+    // Explicit booleans for the two allowed schemes.
+    // Keeping these as named variables makes the condition below very readable.
+    let starts_http: bool = url.starts_with("http://");
+    let starts_https: bool = url.starts_with("https://");
+
+    starts_http || starts_https
+}
+
+/// Return `true` if `input` names a scheme at all (`<scheme>://...`), supported or not.
+///
+/// Used to tell "example.com" (scheme-less, eligible for fallback) from "ftp://example.com".
+pub fn has_explicit_scheme(input: &str) -> bool {
+    input.trim().contains("://")
+}
+
+/// Return the same normalized base with the other HTTP scheme (https ↔ http).
+///
+/// Example:
+///   Input:  "https://example.com/" → "http://example.com/"
+pub fn swap_scheme(base: &str) -> String {
+    match base.strip_prefix("https://") {
+        Some(rest) => format!("http://{}", rest),
+        None => match base.strip_prefix("http://") {
+            Some(rest) => format!("https://{}", rest),
+            None => base.to_string(),
+        },
+    }
+}
+
+/// Return the same normalized base with the other HTTP scheme, on the port it had.
+///
+/// Unlike `swap_scheme`, a default port is kept (made explicit): used for the `--ports`
+/// candidates, where the port is what the user asked for.
+///
+/// Examples:
+///   Input:  "http://example.com/"       → Ok("https://example.com:80/")
+///   Input:  "https://example.com:8443/" → Ok("http://example.com:8443/")
+///
+/// Errors:
+///   - Returns `DirustError::InvalidBaseUrl` if the base does not parse or has no host.
+pub fn swap_scheme_keep_port(base: &str) -> Result<String, DirustError> {
+    let mut url: Url = match Url::parse(base) {
+        Ok(u) => u,
+        Err(_) => return Err(DirustError::InvalidBaseUrl),
+    };
+    let port = url.port_or_known_default();
+    let other = match url.scheme() {
+        "https" => "http",
+        _ => "https",
+    };
+    if url.set_scheme(other).is_err() || url.set_port(port).is_err() {
+        return Err(DirustError::InvalidBaseUrl);
+    }
+
+    Ok(url.to_string())
+}
+
+/// Guess the scheme a port usually speaks: HTTPS for 443 / 8443 / 9443, HTTP otherwise.
+pub fn guess_scheme(port: u16) -> &'static str {
+    match port {
+        443 | 8443 | 9443 => "https",
+        _ => "http",
+    }
+}
+
+/// Return the normalized base moved to another port, with the scheme guessed for that port.
+///
+/// Default ports are left implicit, as browsers print them.
+///
+/// Examples:
+///   Input:  ("https://example.com/app/", 8080) → Ok("http://example.com:8080/app/")
+///   Input:  ("http://example.com/", 443)       → Ok("https://example.com/")
+///
+/// Errors:
+///   - Returns `DirustError::InvalidBaseUrl` if the base does not parse or has no host.
+pub fn with_port(base: &str, port: u16) -> Result<String, DirustError> {
+    let mut url: Url = match Url::parse(base) {
+        Ok(u) => u,
+        Err(_) => return Err(DirustError::InvalidBaseUrl),
+    };
+
+    // Both calls only fail for URLs without a host (e.g., "http:///"); http <-> https
+    // scheme changes are always allowed.
+    if url.set_scheme(guess_scheme(port)).is_err() || url.set_port(Some(port)).is_err() {
+        return Err(DirustError::InvalidBaseUrl);
+    }
+
+    Ok(url.to_string())
+}
+
+/// Canonical form of a generated target URL, so equivalent spellings are probed once.
+///
+/// Steps:
+///   - collapse runs of `/` in the path (`base//admin` → `base/admin`)
+///   - resolve `.` and `..` segments (`base/a/../admin` → `base/admin`; never above the root)
+///   - upper-case percent-encoding hex digits (`%2f` → `%2F`), lower-case the host
+///
+/// Examples:
+///   Input:  "https://Example.com//app/./x/../admin" → "https://example.com/app/admin"
+///   Input:  "http://x/a%2fb?q=%7e"                  → "http://x/a%2Fb?q=%7E"
+///
+/// Strings that do not parse as a URL are returned with only the slashes collapsed.
+/// Dot segments would be resolved by the HTTP client anyway (it parses every URL the same
+/// way), so this changes which URLs count as duplicates, not what goes on the wire.
+pub fn canonical_target(target: &str) -> String {
+    // Everything before the path ("scheme://host:port") is left as written.
+    let path_start = match target.find("://") {
+        Some(i) => match target[i + 3..].find('/') {
+            Some(j) => i + 3 + j,
+            None => return target.to_string(),
+        },
+        None => return target.to_string(),
+    };
+    let path_end = match target[path_start..].find(['?', '#']) {
+        Some(k) => path_start + k,
+        None => target.len(),
+    };
+
+    let mut collapsed: String = String::with_capacity(target.len());
+    collapsed.push_str(&target[..path_start]);
+    let mut previous_slash = false;
+    for c in target[path_start..path_end].chars() {
+        if c == '/' && previous_slash {
+            continue;
+        }
+        previous_slash = c == '/';
+        collapsed.push(c);
+    }
+    collapsed.push_str(&target[path_end..]);
+
+    // Parsing resolves dot segments and lower-cases the host.
+    let mut url: Url = match Url::parse(&collapsed) {
+        Ok(u) => u,
+        Err(_) => return collapsed,
+    };
+    let path = upper_percent_hex(url.path());
+    url.set_path(&path);
+    if let Some(query) = url.query().map(upper_percent_hex) {
+        url.set_query(Some(&query));
+    }
+    url.to_string()
+}
+
+/// Upper-case the two hex digits after every `%` (`%2f` → `%2F`).
+fn upper_percent_hex(input: &str) -> String {
+    let mut out: String = String::with_capacity(input.len());
+    let mut pending_hex: usize = 0;
+    for c in input.chars() {
+        if pending_hex > 0 && c.is_ascii_hexdigit() {
+            out.push(c.to_ascii_uppercase());
+            pending_hex -= 1;
+            continue;
+        }
+        pending_hex = if c == '%' { 2 } else { 0 };
+        out.push(c);
+    }
+    out
 }
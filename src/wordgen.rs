@@ -0,0 +1,261 @@
+//! src/wordgen.rs
+//!
+//! Purpose:
+//!   Implement `dirust wordgen <URL>`: crawl a live target and build a wordlist tailored to
+//!   it (CeWL-style, but for paths), which can then be fed back into a scan with `-w`.
+//!
+//! How it works:
+//!   - Breadth-first crawl from `<URL>`, staying on the same origin (scheme + host + port),
+//!     up to `--depth` link hops and `--max-pages` pages. `/robots.txt` paths are added
+//!     as extra seeds.
+//!   - Links are read from `href`, `src` and `action` attributes of HTML pages.
+//!   - Every in-scope URL path is tokenized:
+//!       * each path segment as-is        ("login.php", "user-profile")
+//!       * the segment without extension ("login")
+//!       * the stem split on - _ .       ("user", "profile")
+//!   - Words are printed most frequent first (ties alphabetically), to stdout or `-o <PATH>`.
+//!
+//! Pages of one depth level are fetched concurrently (bounded by `-c`) through the shared
+//! engine (`engine::run_bounded`).
+
+use crate::{
+    args::WordgenArgs,
+    client, engine,
+    error::DirustError,
+    scanner::{forms, http},
+    url,
+};
+use reqwest::{header, Client, Url};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+/// Run the crawler described by `wordgen`.
+pub async fn run(wordgen: &WordgenArgs) -> Result<(), DirustError> {
+    let start_url = url::normalize_base(&wordgen.url)?;
+    let start = match Url::parse(&start_url) {
+        Ok(u) => u,
+        Err(_) => return Err(DirustError::InvalidBaseUrl),
+    };
+    let client: Client = client::build_client(&wordgen.http)?;
+
+    // URLs ever queued (crawl dedup) and every in-scope URL seen (tokenized at the end).
+    let mut queued: HashSet<String> = HashSet::new();
+    let mut seen_paths: HashSet<String> = HashSet::new();
+
+    let mut frontier: Vec<String> = vec![start.to_string()];
+    queued.insert(start.to_string());
+    for path in robots_paths(&client, &start).await {
+        if let Ok(joined) = start.join(&path) {
+            seen_paths.insert(joined.path().to_string());
+            if queued.insert(joined.to_string()) {
+                frontier.push(joined.to_string());
+            }
+        }
+    }
+
+    let mut pages: usize = 0;
+    for depth in 0..=wordgen.depth {
+        if frontier.is_empty() || pages >= wordgen.max_pages {
+            break;
+        }
+
+        // Respect the page budget.
+        let budget = wordgen.max_pages - pages;
+        frontier.truncate(budget);
+        pages += frontier.len();
+        eprintln!("[*] wordgen: depth {} — fetching {} page(s)", depth, frontier.len());
+
+        let client_owned = client.clone();
+        let link_lists: Vec<Vec<String>> = engine::run_bounded(frontier, wordgen.http.concurrency, |page: String| {
+            let client_clone = client_owned.clone();
+            async move { page_links(&client_clone, &page).await }
+        })
+        .await?;
+
+        // Keep in-scope links; the next level is made of the ones not crawled yet.
+        let mut next: Vec<String> = Vec::new();
+        for link in link_lists.into_iter().flatten() {
+            let parsed = match Url::parse(&link) {
+                Ok(u) => u,
+                Err(_) => continue,
+            };
+            if parsed.origin() != start.origin() {
+                continue;
+            }
+            seen_paths.insert(parsed.path().to_string());
+
+            let mut crawl = parsed.clone();
+            crawl.set_fragment(None);
+            if queued.insert(crawl.to_string()) {
+                next.push(crawl.to_string());
+            }
+        }
+        frontier = next;
+    }
+
+    // Tokenize every seen path and rank the words.
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for path in &seen_paths {
+        for word in tokenize(path, wordgen.min_length) {
+            *counts.entry(word).or_insert(0) += 1;
+        }
+    }
+    let mut words: Vec<(String, usize)> = counts.into_iter().collect();
+    words.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    // Emit the list.
+    match &wordgen.output {
+        Some(path) => {
+            let mut out = BufWriter::new(File::create(path)?);
+            for (word, _) in &words {
+                writeln!(out, "{}", word)?;
+            }
+            out.flush()?;
+        }
+        None => {
+            for (word, _) in &words {
+                println!("{}", word);
+            }
+        }
+    }
+
+    eprintln!(
+        "[*] wordgen: {} page(s) crawled, {} path(s) seen, {} word(s) emitted",
+        pages,
+        seen_paths.len(),
+        words.len()
+    );
+    Ok(())
+}
+
+/// Fetch one page and return the absolute URLs it links to.
+///
+/// Non-HTML answers and errors yield no links (a dead link should not stop the crawl).
+async fn page_links(client: &Client, page: &str) -> Vec<String> {
+    let response = match client.get(page).send().await {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("[!] wordgen {}: {}", page, e);
+            return Vec::new();
+        }
+    };
+
+    // Redirects are links too (e.g., "/app" → "/app/login").
+    let mut links: Vec<String> = Vec::new();
+    if let Some(location) = response.headers().get(header::LOCATION)
+        && let Ok(loc) = location.to_str()
+    {
+        links.push(loc.to_string());
+    }
+
+    let is_html = match response.headers().get(header::CONTENT_TYPE) {
+        Some(ct) => ct.to_str().unwrap_or_default().to_ascii_lowercase().contains("html"),
+        None => true,
+    };
+    if is_html && let Ok(body) = http::read_text(response).await {
+        links.extend(extract_links(&body));
+    }
+
+    // Resolve everything against the page URL.
+    let base = match Url::parse(page) {
+        Ok(u) => u,
+        Err(_) => return Vec::new(),
+    };
+    links
+        .iter()
+        .filter_map(|l| base.join(l.trim()).ok())
+        .filter(|u| u.scheme() == "http" || u.scheme() == "https")
+        .map(|u| u.to_string())
+        .collect()
+}
+
+/// Collect `href`, `src` and `action` attribute values from every tag in `html`.
+fn extract_links(html: &str) -> Vec<String> {
+    let mut links: Vec<String> = Vec::new();
+    let mut cursor: usize = 0;
+
+    while let Some(rel_start) = html[cursor..].find('<') {
+        let start = cursor + rel_start;
+        let end = match html[start..].find('>') {
+            Some(rel_end) => start + rel_end,
+            None => break,
+        };
+        let tag = &html[start..end];
+
+        for attr in ["href", "src", "action"] {
+            if let Some(value) = forms::attr_value(tag, attr)
+                && !value.trim().is_empty()
+            {
+                links.push(value);
+            }
+        }
+
+        cursor = end;
+    }
+
+    links
+}
+
+/// Read `Allow:` / `Disallow:` paths from `/robots.txt` (wildcards dropped).
+async fn robots_paths(client: &Client, start: &Url) -> Vec<String> {
+    let robots = match start.join("/robots.txt") {
+        Ok(u) => u,
+        Err(_) => return Vec::new(),
+    };
+    let text = match http::fetch_body(client, robots.as_str()).await {
+        Ok(t) => t,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut paths: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let lower = line.trim().to_ascii_lowercase();
+        if !(lower.starts_with("allow:") || lower.starts_with("disallow:")) {
+            continue;
+        }
+        let value = match line.split_once(':') {
+            Some((_, v)) => v.trim(),
+            None => continue,
+        };
+        // Keep the literal prefix before any wildcard.
+        let literal = value.split(['*', '$']).next().unwrap_or_default();
+        if literal.starts_with('/') && literal.len() > 1 {
+            paths.push(literal.to_string());
+        }
+    }
+    paths
+}
+
+/// Split a URL path into candidate wordlist entries (see the module docs).
+fn tokenize(path: &str, min_length: usize) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+
+    for raw in path.split('/') {
+        let segment = raw.trim();
+        if segment.is_empty() {
+            continue;
+        }
+
+        // The extension itself ("php", "png") is not a useful path word.
+        let stem = match segment.rsplit_once('.') {
+            Some((stem, _ext)) if !stem.is_empty() => stem,
+            _ => segment,
+        };
+        let mut candidates: Vec<&str> = vec![segment, stem];
+        candidates.extend(stem.split(['-', '_', '.']));
+
+        for candidate in candidates {
+            // Skip tiny fragments and pure numbers (IDs, dates), which do not generalize.
+            let keep = candidate.len() >= min_length && !candidate.chars().all(|c| c.is_ascii_digit());
+            let word = candidate.to_string();
+            if keep && !words.contains(&word) {
+                words.push(word);
+            }
+        }
+    }
+
+    words
+}
@@ -0,0 +1,120 @@
+//! tests/library.rs
+//!
+//! Scans run through the library API (`src/lib.rs`) with an in-memory transport: the
+//! requests never leave the process, so the results are the same on every run.
+
+use dirust::{
+    Cli, Command, FakeClient, FakeResponse, HttpSummary, LayerFuture, Middleware, Next, ProbeRequest, ScanArgs,
+    ScanHooks, ScanMonitor,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+const BASE: &str = "http://fake.invalid/";
+
+/// `dirust scan <BASE> -w <words> -q` as a library caller builds it.
+fn scan_args(words: &str) -> ScanArgs {
+    let argv: Vec<String> = ["dirust", "scan", BASE, "-w", words, "-q"].iter().map(|s| s.to_string()).collect();
+    match Cli::try_parse_with_presets(&argv) {
+        Ok(Cli { command: Command::Scan(args), .. }) => *args,
+        _ => panic!("not a scan command line: {:?}", argv),
+    }
+}
+
+/// A wordlist file with `words`, removed when dropped.
+struct Wordlist(std::path::PathBuf);
+
+impl Wordlist {
+    fn new(name: &str, words: &[&str]) -> Wordlist {
+        let path = std::env::temp_dir().join(format!("dirust-{}-{}.txt", name, std::process::id()));
+        std::fs::write(&path, words.join("\n")).expect("write wordlist");
+        Wordlist(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().expect("utf-8 path")
+    }
+}
+
+impl Drop for Wordlist {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// `admin` and `backup` exist, the rest is missing.
+fn answer(_method: &str, url: &str) -> FakeResponse {
+    match url.trim_start_matches(BASE) {
+        "admin" => FakeResponse::new(200, b"admin panel"),
+        "backup" => FakeResponse::new(403, b"forbidden"),
+        _ => FakeResponse::new(404, b"not found"),
+    }
+}
+
+/// A caller's layer: counts the probe requests it sees and tags them with a header.
+struct Counter(AtomicUsize);
+
+impl Middleware for Counter {
+    fn before<'a>(&'a self, request: &'a mut ProbeRequest) -> LayerFuture<'a, Result<(), dirust::DirustError>> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        request.headers.insert("x-embedder", "1".parse().expect("header value"));
+        Box::pin(async { Ok(()) })
+    }
+
+    fn after<'a>(
+        &'a self,
+        _request: &'a mut ProbeRequest,
+        _result: &'a mut Result<HttpSummary, dirust::DirustError>,
+    ) -> LayerFuture<'a, Next> {
+        Box::pin(async { Next::Continue })
+    }
+}
+
+#[tokio::test]
+async fn run_scan_with_fake_transport_and_middleware() {
+    let words = Wordlist::new("library-scan", &["admin", "backup", "missing"]);
+    let args = scan_args(words.path());
+    let fake = Arc::new(FakeClient::new(answer));
+    let counter = Arc::new(Counter(AtomicUsize::new(0)));
+    let monitor = Arc::new(ScanMonitor::new());
+    let hooks = ScanHooks {
+        transport: Some(fake.clone()),
+        middleware: vec![counter.clone()],
+        monitor: Some(monitor.clone()),
+        ..ScanHooks::default()
+    };
+    dirust::run_scan(&args, hooks).await.expect("scan");
+
+    let mut found: Vec<(String, u16)> = monitor.findings().into_iter().map(|e| (e.url, e.status)).collect();
+    found.sort();
+    assert_eq!(
+        found,
+        vec![(format!("{}admin", BASE), 200), (format!("{}backup", BASE), 403)]
+    );
+    // Every word was probed through the caller's layer.
+    for word in ["admin", "backup", "missing"] {
+        assert!(fake.requests().contains(&format!("HEAD {}{}", BASE, word)), "{} not probed", word);
+    }
+    assert!(counter.0.load(Ordering::SeqCst) >= 3);
+}
+
+#[tokio::test]
+async fn cancelled_scan_returns_without_probing() {
+    let words = Wordlist::new("library-cancel", &["admin", "backup", "missing"]);
+    let args = scan_args(words.path());
+    let fake = Arc::new(FakeClient::new(answer));
+    let token = dirust::CancellationToken::new();
+    let monitor = Arc::new(ScanMonitor::with_cancellation(token.clone()));
+    token.cancel();
+    let hooks = ScanHooks {
+        transport: Some(fake.clone()),
+        monitor: Some(monitor.clone()),
+        ..ScanHooks::default()
+    };
+    dirust::run_scan(&args, hooks).await.expect("cancelled scan");
+
+    assert!(monitor.findings().is_empty());
+    assert!(!fake.requests().iter().any(|r| r.starts_with("HEAD ")), "probed after cancel: {:?}", fake.requests());
+}